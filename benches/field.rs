@@ -1,5 +1,5 @@
 use criterion::{ black_box, criterion_group, Criterion };
-use distaff::math::{ field, parallel };
+use distaff::math::{ field, parallel, f128::BaseElement, FieldElement };
 
 pub fn add128(c: &mut Criterion) {
     let x = field::rand();
@@ -17,11 +17,63 @@ pub fn mul128(c: &mut Criterion) {
     });
 }
 
-pub fn mul_parallel(c: &mut Criterion) {
+pub fn mul128_batch(c: &mut Criterion) {
 
     let n = (1 << 10) as usize;
     let x = field::rand_vector(n);
     let y = field::rand_vector(n);
+    let mut z = vec![field::ZERO; n];
+
+    let mut group = c.benchmark_group("mul128 (batch of 1024)");
+    group.bench_function("specialized", |bench| {
+        bench.iter(|| {
+            for i in 0..n {
+                z[i] = field::mul(black_box(x[i]), black_box(y[i]));
+            }
+        })
+    });
+    group.bench_function("generic", |bench| {
+        bench.iter(|| {
+            for i in 0..n {
+                z[i] = field::mul_generic(black_box(x[i]), black_box(y[i]));
+            }
+        })
+    });
+    group.finish();
+}
+
+pub fn mul128_chain(c: &mut Criterion) {
+
+    let n = (1 << 10) as usize;
+    let x = field::rand();
+
+    let mut group = c.benchmark_group("mul128 (chain of 1024)");
+    group.bench_function("specialized", |bench| {
+        bench.iter(|| {
+            let mut acc = black_box(x);
+            for _ in 0..n {
+                acc = field::mul(acc, x);
+            }
+            acc
+        })
+    });
+    group.bench_function("generic", |bench| {
+        bench.iter(|| {
+            let mut acc = black_box(x);
+            for _ in 0..n {
+                acc = field::mul_generic(acc, x);
+            }
+            acc
+        })
+    });
+    group.finish();
+}
+
+pub fn mul_parallel(c: &mut Criterion) {
+
+    let n = (1 << 10) as usize;
+    let x = BaseElement::rand_vector(n);
+    let y = BaseElement::rand_vector(n);
     let threads = 2;
 
     c.bench_function("mul (parallel)", |bench| {
@@ -32,8 +84,8 @@ pub fn mul_parallel(c: &mut Criterion) {
 pub fn mul_parallel_in_place(c: &mut Criterion) {
 
     let n = (1 << 10) as usize;
-    let x = field::rand_vector(n);
-    let mut y = field::rand_vector(n);
+    let x = BaseElement::rand_vector(n);
+    let mut y = BaseElement::rand_vector(n);
     let threads = 2;
 
     c.bench_function("mul (parallel, in place)", |bench| {
@@ -56,4 +108,4 @@ pub fn inv128(c: &mut Criterion) {
     });
 }
 
-criterion_group!(group, add128, mul128, mul_parallel, mul_parallel_in_place, exp128, inv128);
\ No newline at end of file
+criterion_group!(group, add128, mul128, mul128_batch, mul128_chain, mul_parallel, mul_parallel_in_place, exp128, inv128);
\ No newline at end of file
@@ -1,5 +1,7 @@
+use sp_std::{ slice, vec, vec::Vec };
 use crate::math::field;
 use crate::utils::{ as_bytes };
+use super::HashFunction;
 use sha3::Digest;
 
 // CONSTANTS
@@ -217,6 +219,125 @@ pub fn sha3(values: &[u8], result: &mut [u8]) {
     result.copy_from_slice(hash.as_ref());
 }
 
+// BATCH HASHING
+// ================================================================================================
+
+// BLAKE3 initialization vector and domain separation flags; hashing a 64-byte input with these
+// parameters is equivalent to calling `blake3::hash()` on the input
+const BLAKE3_IV: [u32; 8] = [
+    0x6A09E667, 0xBB67AE85, 0x3C6EF372, 0xA54FF53A, 0x510E527F, 0x9B05688C, 0x1F83D9AB, 0x5BE0CD19,
+];
+const BLAKE3_CHUNK_START: u8 = 1 << 0;
+const BLAKE3_CHUNK_END  : u8 = 1 << 1;
+const BLAKE3_ROOT       : u8 = 1 << 3;
+
+/// Hash functions which can be used to build Merkle trees for STARK proofs. Unlike
+/// `HashFunction`, an algorithm identifies the hash function explicitly, and so batched
+/// implementations can be selected for it.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum HashAlgorithm {
+    Blake3,
+    Sha3,
+    Rescue,
+    Poseidon,
+    Gmimc,
+}
+
+impl HashAlgorithm {
+
+    /// Returns the function which hashes a single input with this algorithm.
+    pub fn hash_fn(&self) -> HashFunction {
+        return match self {
+            HashAlgorithm::Blake3   => blake3,
+            HashAlgorithm::Sha3     => sha3,
+            HashAlgorithm::Rescue   => rescue,
+            HashAlgorithm::Poseidon => poseidon,
+            HashAlgorithm::Gmimc    => gmimc,
+        };
+    }
+
+    /// Hashes each of the provided 64-byte `inputs` and writes the results into the
+    /// corresponding elements of `results`. The results are the same as when calling
+    /// `hash_fn()` for each input separately, but for blake3 and sha3 inputs are processed in
+    /// batches (for blake3, using SIMD instructions available on the current platform).
+    pub fn hash_many(&self, inputs: &[[u8; 64]], results: &mut [[u8; 32]]) {
+        assert!(inputs.len() == results.len(),
+            "expected {} results, but received {}", inputs.len(), results.len());
+
+        match self {
+            HashAlgorithm::Blake3 => blake3_many(inputs, results),
+            HashAlgorithm::Sha3 => sha3_many(inputs, results),
+            _ => {
+                let hash = self.hash_fn();
+                for (input, result) in inputs.iter().zip(results.iter_mut()) {
+                    hash(input, result);
+                }
+            }
+        }
+    }
+
+    /// Hashes rows of the matrix defined by `columns`, starting with `first_row`, and writes
+    /// the results into `results`; the row at index i is hashed into `results[i - first_row]`.
+    /// For blake3 and sha3, values of a row are fed into the hasher directly from the columns;
+    /// for other algorithms, the row is first copied into a contiguous buffer.
+    pub fn hash_rows<T: Copy>(&self, columns: &[Vec<T>], first_row: usize, results: &mut [[u8; 32]]) {
+        match self {
+            HashAlgorithm::Blake3 => {
+                let mut hasher = blake3::Hasher::new();
+                for (i, result) in results.iter_mut().enumerate() {
+                    let row = first_row + i;
+                    hasher.reset();
+                    for column in columns.iter() {
+                        hasher.update(as_bytes(&column[row..row + 1]));
+                    }
+                    result.copy_from_slice(hasher.finalize().as_bytes());
+                }
+            },
+            HashAlgorithm::Sha3 => {
+                let mut hasher = sha3::Sha3_256::new();
+                for (i, result) in results.iter_mut().enumerate() {
+                    let row = first_row + i;
+                    for column in columns.iter() {
+                        hasher.input(as_bytes(&column[row..row + 1]));
+                    }
+                    result.copy_from_slice(hasher.result_reset().as_ref());
+                }
+            },
+            _ => {
+                if columns.is_empty() { return; }
+                let hash = self.hash_fn();
+                let mut row_values = vec![columns[0][first_row]; columns.len()];
+                for (i, result) in results.iter_mut().enumerate() {
+                    for (value, column) in row_values.iter_mut().zip(columns.iter()) {
+                        *value = column[first_row + i];
+                    }
+                    hash(as_bytes(&row_values), result);
+                }
+            }
+        }
+    }
+}
+
+/// Hashes inputs with `hash_many()` of the platform backend of blake3; the backend is not a
+/// public API of blake3, and so the version of blake3 is pinned in Cargo.toml.
+fn blake3_many(inputs: &[[u8; 64]], results: &mut [[u8; 32]]) {
+    let platform = blake3::platform::Platform::detect();
+    let inputs: Vec<&[u8; 64]> = inputs.iter().collect();
+    let results = unsafe {
+        slice::from_raw_parts_mut(results.as_mut_ptr() as *mut u8, results.len() * 32)
+    };
+    platform.hash_many(&inputs, &BLAKE3_IV, 0, blake3::IncrementCounter::No,
+        BLAKE3_ROOT, BLAKE3_CHUNK_START, BLAKE3_CHUNK_END, results);
+}
+
+fn sha3_many(inputs: &[[u8; 64]], results: &mut [[u8; 32]]) {
+    let mut hasher = sha3::Sha3_256::new();
+    for (input, result) in inputs.iter().zip(results.iter_mut()) {
+        hasher.input(&input[..]);
+        result.copy_from_slice(hasher.result_reset().as_ref());
+    }
+}
+
 // HELPER FUNCTIONS
 // ================================================================================================
 fn add_constants(state: &mut[u128; 6], offset: usize) {
@@ -259,6 +380,7 @@ fn apply_mds(state: &mut[u128; 6]) {
 mod tests {
 
     use crate::utils::{ as_bytes };
+    use super::HashAlgorithm;
 
     #[test]
     fn poseidon() {
@@ -284,6 +406,41 @@ mod tests {
             result);
     }
 
+    #[test]
+    fn hash_many() {
+        let inputs: Vec<[u8; 64]> = (0..37u8).map(|i| {
+            let mut input = [0u8; 64];
+            for j in 0..64 { input[j] = i.wrapping_mul(j as u8).wrapping_add(7); }
+            input
+        }).collect();
+
+        for &algorithm in [HashAlgorithm::Blake3, HashAlgorithm::Sha3, HashAlgorithm::Rescue].iter() {
+            let mut results = vec![[0u8; 32]; inputs.len()];
+            algorithm.hash_many(&inputs, &mut results);
+            for (input, result) in inputs.iter().zip(results.iter()) {
+                let mut expected = [0u8; 32];
+                algorithm.hash_fn()(input, &mut expected);
+                assert_eq!(expected, *result);
+            }
+        }
+    }
+
+    #[test]
+    fn hash_rows() {
+        let columns: Vec<Vec<u128>> = (0..4u128).map(|i| (0..16u128).map(|j| i * 100 + j).collect()).collect();
+
+        for &algorithm in [HashAlgorithm::Blake3, HashAlgorithm::Sha3, HashAlgorithm::Rescue].iter() {
+            let mut results = vec![[0u8; 32]; 8];
+            algorithm.hash_rows(&columns, 4, &mut results);
+            for (i, result) in results.iter().enumerate() {
+                let row: Vec<u128> = columns.iter().map(|column| column[4 + i]).collect();
+                let mut expected = [0u8; 32];
+                algorithm.hash_fn()(as_bytes(&row), &mut expected);
+                assert_eq!(expected, *result);
+            }
+        }
+    }
+
     #[test]
     fn gmimc() {
         let value = [1u128, 2, 3, 4];
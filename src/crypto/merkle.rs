@@ -1,4 +1,5 @@
 use sp_std::{slice, vec};
+use crossbeam_utils::thread;
 use hashbrown::HashMap;
 use sp_std::vec::Vec;
 // use wasm_bindgen_test::console_log;
@@ -8,9 +9,16 @@ use alloc::{collections::BTreeSet as HashSet, vec::Vec};
 
 // use sp_std::collections::{ BTreeSet };
 use serde::{ Serialize, Deserialize };
-use crate::crypto::{ HashFunction };
+use crate::crypto::{ HashFunction, HashAlgorithm };
 use sp_std::collections::btree_set::BTreeSet;
 
+// CONSTANTS
+// ================================================================================================
+
+/// Levels of a Merkle tree with fewer than this many nodes per thread are built in a single
+/// thread.
+const MIN_CONCURRENT_LEVEL_SIZE: usize = 64;
+
 // TYPES AND INTERFACES
 // ================================================================================================
 pub struct MerkleTree {
@@ -29,8 +37,8 @@ pub struct BatchMerkleProof {
 // ================================================================================================
 impl MerkleTree {
 
-    /// Creates a new merkle tree from the provide leaves and using the provided hash function.
-    pub fn new(leaves: Vec<[u8; 32]>, hash: HashFunction) -> MerkleTree {
+    /// Creates a new merkle tree from the provide leaves and using the provided hash algorithm.
+    pub fn new(leaves: Vec<[u8; 32]>, hash: HashAlgorithm) -> MerkleTree {
         assert!(leaves.len().is_power_of_two(), "number of leaves must be a power of 2");
         assert!(leaves.len() >= 2, "a tree must contain at least 2 leaves");
 
@@ -41,6 +49,19 @@ impl MerkleTree {
         };
     }
 
+    /// Creates a new merkle tree from the provided leaves; nodes of the tree are hashed in
+    /// `num_threads` threads. The resulting tree is the same as the one built by `new()`.
+    pub fn new_concurrent(leaves: Vec<[u8; 32]>, hash: HashAlgorithm, num_threads: usize) -> MerkleTree {
+        assert!(leaves.len().is_power_of_two(), "number of leaves must be a power of 2");
+        assert!(leaves.len() >= 2, "a tree must contain at least 2 leaves");
+
+        let nodes = build_merkle_nodes_concurrent(&leaves, hash, num_threads);
+        return MerkleTree {
+            values  : leaves,
+            nodes   : nodes
+        };
+    }
+
     /// Returns the root of the tree
     pub fn root(&self) -> &[u8; 32] {
         return &self.nodes[1];
@@ -276,7 +297,7 @@ impl MerkleTree {
 // HELPER FUNCTIONS
 // ================================================================================================
 
-pub fn build_merkle_nodes(leaves: &[[u8; 32]], hash: HashFunction) -> Vec<[u8; 32]> {
+pub fn build_merkle_nodes(leaves: &[[u8; 32]], hash: HashAlgorithm) -> Vec<[u8; 32]> {
     let n = leaves.len() / 2;
 
     // create un-initialized array to hold all intermediate nodes
@@ -284,25 +305,72 @@ pub fn build_merkle_nodes(leaves: &[[u8; 32]], hash: HashFunction) -> Vec<[u8; 3
     unsafe { nodes.set_len(2 * n); }
     nodes[0] = [0u8; 32];
 
-    // re-interpret leaves as an array of two leaves fused together
-    let two_leaves = unsafe { slice::from_raw_parts(leaves.as_ptr() as *const [u8; 64], n) };
-
     // build first row of internal nodes (parents of leaves)
-    for (i, j) in (0..n).zip(n..nodes.len()) {
-        hash(&two_leaves[i], &mut nodes[j]);
+    hash.hash_many(as_pairs(leaves), &mut nodes[n..]);
+
+    // calculate all other tree nodes, one level at a time
+    let mut level_size = n / 2;
+    while level_size > 0 {
+        let (parents, children) = nodes.split_at_mut(2 * level_size);
+        hash.hash_many(as_pairs(&children[..2 * level_size]), &mut parents[level_size..]);
+        level_size = level_size / 2;
+    }
+
+    return nodes;
+}
+
+/// Same as `build_merkle_nodes()` but the work is split across `num_threads` threads; nodes
+/// are built one level at a time, and every level is split into `num_threads` contiguous
+/// batches which are hashed concurrently. Levels which are too small to be worth splitting
+/// are built in the calling thread.
+pub fn build_merkle_nodes_concurrent(leaves: &[[u8; 32]], hash: HashAlgorithm, num_threads: usize) -> Vec<[u8; 32]> {
+    assert!(num_threads.is_power_of_two(), "number of threads must be a power of 2");
+    let n = leaves.len() / 2;
+    if num_threads == 1 || n < num_threads * MIN_CONCURRENT_LEVEL_SIZE {
+        return build_merkle_nodes(leaves, hash);
     }
 
-    // re-interpret nodes as an array of two nodes fused together
-    let two_nodes = unsafe { slice::from_raw_parts(nodes.as_ptr() as *const [u8; 64], n) };
+    let mut nodes = vec![[0u8; 32]; 2 * n];
 
-    // calculate all other tree nodes
-    for i in (1..n).rev() {
-        hash(&two_nodes[i], &mut nodes[i]);
+    // build first row of internal nodes (parents of leaves)
+    hash_many_concurrent(hash, as_pairs(leaves), &mut nodes[n..], num_threads);
+
+    // calculate all other tree nodes, one level at a time; parents and children of every level
+    // are in disjoint parts of the array, so children can be read while parents are written
+    let mut level_size = n / 2;
+    while level_size > 0 {
+        let (parents, children) = nodes.split_at_mut(2 * level_size);
+        let children = as_pairs(&children[..2 * level_size]);
+        let parents = &mut parents[level_size..];
+        if level_size < num_threads * MIN_CONCURRENT_LEVEL_SIZE {
+            hash.hash_many(children, parents);
+        }
+        else {
+            hash_many_concurrent(hash, children, parents, num_threads);
+        }
+        level_size = level_size / 2;
     }
 
     return nodes;
 }
 
+/// Hashes `inputs` into `results` in the same way as `HashAlgorithm::hash_many()`, but splits
+/// the work into `num_threads` batches of equal size which are processed in separate threads.
+fn hash_many_concurrent(hash: HashAlgorithm, inputs: &[[u8; 64]], results: &mut [[u8; 32]], num_threads: usize) {
+    let batch_size = results.len() / num_threads;
+    thread::scope(|s| {
+        for (inputs, results) in inputs.chunks(batch_size).zip(results.chunks_mut(batch_size)) {
+            s.spawn(move |_| hash.hash_many(inputs, results));
+        }
+    }).unwrap();
+}
+
+/// Re-interprets a slice of nodes as a slice of pairs of nodes fused together; if the number
+/// of nodes is odd, the last node is ignored.
+fn as_pairs(nodes: &[[u8; 32]]) -> &[[u8; 64]] {
+    return unsafe { slice::from_raw_parts(nodes.as_ptr() as *const [u8; 64], nodes.len() / 2) };
+}
+
 fn map_indexes(indexes: &[usize], max_valid: usize) -> HashMap<usize, usize> {
     let mut map = HashMap::new();
     for (i, index) in indexes.iter().cloned().enumerate() {
@@ -326,7 +394,7 @@ fn normalize_indexes(indexes: &[usize]) -> Vec<usize> {
 #[cfg(test)]
 mod tests {
 
-    use crate::crypto::hash;
+    use crate::crypto::{ hash, HashAlgorithm };
 
     static LEAVES4: [[u8; 32]; 4] = [
         [166, 168,  47, 140, 153, 86, 156,  86, 226, 229, 149,  76,  70, 132, 209, 109, 166, 193, 113, 197,  42, 116, 170, 144,  74, 104,  29, 110, 220, 49, 224, 123],
@@ -349,7 +417,7 @@ mod tests {
     #[test]
     fn new_tree() {
         let leaves = LEAVES4.to_vec();
-        let tree = super::MerkleTree::new(leaves, hash::poseidon);
+        let tree = super::MerkleTree::new(leaves, HashAlgorithm::Poseidon);
         let root = hash_2x1(
             &hash_2x1(&LEAVES4[0], &LEAVES4[1]),
             &hash_2x1(&LEAVES4[2], &LEAVES4[3])
@@ -357,7 +425,7 @@ mod tests {
         assert_eq!(&root, tree.root());
 
         let leaves = LEAVES8.to_vec();
-        let tree = super::MerkleTree::new(leaves, hash::poseidon);
+        let tree = super::MerkleTree::new(leaves, HashAlgorithm::Poseidon);
         let root = hash_2x1(
             &hash_2x1(
                 &hash_2x1(&LEAVES8[0], &LEAVES8[1]),
@@ -371,11 +439,41 @@ mod tests {
         assert_eq!(&root, tree.root());
     }
 
+    #[test]
+    fn new_concurrent_tree() {
+        let mut leaves = vec![[0u8; 32]; 1024];
+        for (i, leaf) in leaves.iter_mut().enumerate() {
+            hash::blake3(&i.to_le_bytes(), leaf);
+        }
+
+        // nodes hashed in batches must be the same as nodes hashed one by one
+        let mut expected = vec![[0u8; 32]; leaves.len()];
+        for i in (1..leaves.len()).rev() {
+            let (left, right) = if 2 * i < leaves.len() {
+                (expected[2 * i], expected[2 * i + 1])
+            } else {
+                (leaves[2 * i - leaves.len()], leaves[2 * i + 1 - leaves.len()])
+            };
+            let mut buf = [0u8; 64];
+            buf[..32].copy_from_slice(&left);
+            buf[32..].copy_from_slice(&right);
+            hash::blake3(&buf, &mut expected[i]);
+        }
+        assert_eq!(expected, super::build_merkle_nodes(&leaves, HashAlgorithm::Blake3));
+
+        for &num_threads in [1, 2, 4, 8].iter() {
+            assert_eq!(expected, super::build_merkle_nodes_concurrent(&leaves, HashAlgorithm::Blake3, num_threads));
+        }
+
+        let tree = super::MerkleTree::new_concurrent(leaves.clone(), HashAlgorithm::Blake3, 4);
+        assert_eq!(super::MerkleTree::new(leaves, HashAlgorithm::Blake3).root(), tree.root());
+    }
+
     #[test]
     fn prove() {
         // depth 4
         let leaves = LEAVES4.to_vec();
-        let tree = super::MerkleTree::new(leaves, hash::poseidon);
+        let tree = super::MerkleTree::new(leaves, HashAlgorithm::Poseidon);
 
         let proof = vec![
             LEAVES4[1],
@@ -393,7 +491,7 @@ mod tests {
 
         // depth 5
         let leaves = LEAVES8.to_vec();
-        let tree = super::MerkleTree::new(leaves, hash::poseidon);
+        let tree = super::MerkleTree::new(leaves, HashAlgorithm::Poseidon);
 
         let proof = vec![
             LEAVES8[1],
@@ -416,7 +514,7 @@ mod tests {
     fn verify() {
         // depth 4
         let leaves = LEAVES4.to_vec();
-        let tree = super::MerkleTree::new(leaves, hash::poseidon);
+        let tree = super::MerkleTree::new(leaves, HashAlgorithm::Poseidon);
         let proof = tree.prove(1);
         assert_eq!(true, super::MerkleTree::verify(tree.root(), 1, &proof, hash::poseidon));
 
@@ -425,7 +523,7 @@ mod tests {
 
         // depth 5
         let leaves = LEAVES8.to_vec();
-        let tree = super::MerkleTree::new(leaves, hash::poseidon);
+        let tree = super::MerkleTree::new(leaves, HashAlgorithm::Poseidon);
         let proof = tree.prove(1);
         assert_eq!(true, super::MerkleTree::verify(tree.root(), 1, &proof, hash::poseidon));
 
@@ -436,7 +534,7 @@ mod tests {
     #[test]
     fn prove_batch() {
         let leaves = LEAVES8.to_vec();
-        let tree = super::MerkleTree::new(leaves, hash::poseidon);
+        let tree = super::MerkleTree::new(leaves, HashAlgorithm::Poseidon);
         
         // 1 index
         let proof = tree.prove_batch(&[1]);
@@ -505,7 +603,7 @@ mod tests {
     #[test]
     fn verify_batch() {
         let leaves = LEAVES8.to_vec();
-        let tree = super::MerkleTree::new(leaves, hash::poseidon);
+        let tree = super::MerkleTree::new(leaves, HashAlgorithm::Poseidon);
 
         let proof = tree.prove_batch(&[1]);
         assert_eq!(true, super::MerkleTree::verify_batch(tree.root(), &[1], &proof, hash::poseidon));
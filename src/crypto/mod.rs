@@ -1,6 +1,7 @@
 pub mod hash;
+pub use hash::HashAlgorithm;
 
 mod merkle;
-pub use merkle::{ MerkleTree, BatchMerkleProof, build_merkle_nodes };
+pub use merkle::{ MerkleTree, BatchMerkleProof, build_merkle_nodes, build_merkle_nodes_concurrent };
 
 pub type HashFunction = fn(&[u8], &mut [u8]);
\ No newline at end of file
@@ -30,5 +30,5 @@ pub fn parse_args(args: &[String]) -> (usize, ProofOptions) {
         grind_factor = args[4].parse().unwrap();
     }
 
-    return (n, ProofOptions::new(ext_factor, num_queries, grind_factor, default_options.hash_fn()));
+    return (n, ProofOptions::new(ext_factor, num_queries, grind_factor, default_options.hash_algorithm()));
 }
\ No newline at end of file
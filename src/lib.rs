@@ -1,4 +1,5 @@
 use log::debug;
+use crossbeam_utils::thread;
 use sp_std::{ops::Range, vec, vec::Vec};
 use serde::{Serialize, Deserialize};
 
@@ -18,19 +19,24 @@ pub mod math;
 pub mod utils;
 
 mod stark;
-pub use stark::{ StarkProof, ProofOptions, GenOutput, ProgramAssembly };
+pub use stark::{
+    StarkProof, SegmentProof, ProofOptions, ProverConfig, FieldExtension, GenOutput, ProgramAssembly, ConstraintFailure, CheckError, TraceOp,
+    ProverMetrics, PhaseMetrics, ProverPhase, ProverError, ProgressCallback, air };
 
 mod processor;
-pub use processor::{ OpCode, OpHint };
+pub use processor::{
+    OpCode, OpHint, ExecutionLimits, ExecutionError, VmState,
+    AdviceProvider, AdviceRequest, AdviceHandler, AdviceHandlers };
 
 mod programs;
-pub use programs::{ Program, ProgramInputs, assembly, blocks };
+pub use programs::{ Program, ProgramInputs, ProgramLibrary, ProgramCost, MerkleStore, LamportKey, LamportSignature, assembly, blocks };
 
 extern crate console_error_panic_hook;
 
 extern crate wasm_bindgen;
 use wasm_bindgen::prelude::*;
 use blocks::{ ProgramBlock, Span, Group, Switch, Loop };
+use processor::ExecutionSegment;
 
 extern crate web_sys;
 use wasm_bindgen_test::*;
@@ -46,12 +52,77 @@ use crate::alloc::string::ToString;
 /// * `inputs` specifies the initial stack state and provides secret input tapes;
 /// * `num_outputs` specifies the number of elements from the top of the stack to be returned;
 pub fn execute(program: &Program, inputs: &ProgramInputs, num_outputs: usize, options: &ProofOptions) -> String
+{
+    return execute_with_limits(program, inputs, num_outputs, options, &ExecutionLimits::default()).unwrap();
+}
+
+/// Same as `execute()` but aborts execution with an error as soon as the program exceeds any
+/// of the specified `limits`; no proof is generated in such a case.
+pub fn execute_with_limits(
+    program     : &Program,
+    inputs      : &ProgramInputs,
+    num_outputs : usize,
+    options     : &ProofOptions,
+    limits      : &ExecutionLimits) -> Result<String, ExecutionError>
+{
+    let config = ProverConfig::default();
+    return match execute_with_metrics(program, inputs, num_outputs, options, &config, limits, None) {
+        Ok((result, _)) => Ok(result),
+        Err(ProverError::Execution(err)) => Err(err),
+        Err(err) => panic!("{}", err),
+    };
+}
+
+/// Same as `execute()` but requests of the program for nondeterministic inputs are handled by
+/// the specified `advice` provider rather than only by built-in handlers; an error is returned
+/// if the provider fails to supply the requested values.
+pub fn execute_with_advice(
+    program     : &Program,
+    inputs      : &ProgramInputs,
+    num_outputs : usize,
+    options     : &ProofOptions,
+    advice      : &mut dyn AdviceProvider) -> Result<String, ExecutionError>
+{
+    let config = ProverConfig::default();
+    let limits = ExecutionLimits::default();
+    return match prove_execution(program, inputs, num_outputs, options, &config, &limits, None, advice) {
+        Ok((result, _)) => Ok(result),
+        Err(ProverError::Execution(err)) => Err(err),
+        Err(err) => panic!("{}", err),
+    };
+}
+
+/// Same as `execute_with_limits()` but also returns metrics for every phase of proof generation.
+/// If `progress` callback is provided, it is invoked at the start of every prover phase, and
+/// proof generation is aborted as soon as the callback returns `false`. Proof generation is
+/// split across the number of threads specified by `config`.
+pub fn execute_with_metrics(
+    program     : &Program,
+    inputs      : &ProgramInputs,
+    num_outputs : usize,
+    options     : &ProofOptions,
+    config      : &ProverConfig,
+    limits      : &ExecutionLimits,
+    progress    : Option<ProgressCallback>) -> Result<(String, ProverMetrics), ProverError>
+{
+    return prove_execution(program, inputs, num_outputs, options, config, limits, progress, &mut AdviceHandlers::new());
+}
+
+fn prove_execution(
+    program     : &Program,
+    inputs      : &ProgramInputs,
+    num_outputs : usize,
+    options     : &ProofOptions,
+    config      : &ProverConfig,
+    limits      : &ExecutionLimits,
+    progress    : Option<ProgressCallback>,
+    advice      : &mut dyn AdviceProvider) -> Result<(String, ProverMetrics), ProverError>
 {
     assert!(num_outputs <= MAX_OUTPUTS, 
         "cannot produce more than {} outputs, but requested {}", MAX_OUTPUTS, num_outputs);
 
     // execute the program to create an execution trace
-    let (trace, ctx_depth, loop_depth) = processor::execute(program, inputs);
+    let (trace, ctx_depth, loop_depth) = processor::execute_with_advice(program, inputs, limits, advice)?;
     let mut trace = stark::TraceTable::new(trace, ctx_depth, loop_depth, options.extension_factor());
     debug!("Generated execution trace of {} registers and {} steps ",
         trace.register_count(),
@@ -75,7 +146,12 @@ pub fn execute(program: &Program, inputs: &ProgramInputs, num_outputs: usize, op
         hex::encode(program_hash));
 
     // generate STARK proof
-    let proof = stark::prove(&mut trace, inputs.get_public_inputs(), &outputs, options);
+    let pub_inputs = stark::ProcessorPublicInputs {
+        program_hash: *program.hash(),
+        inputs      : inputs.get_public_inputs().to_vec(),
+        outputs     : outputs.clone(),
+    };
+    let (proof, metrics) = stark::prove_with_metrics::<stark::ProcessorAir>(&mut trace, &pub_inputs, options, config, progress)?;
     let proof_bytes = bincode::serialize(&proof).unwrap();
 
     let gen_output = GenOutput{
@@ -85,7 +161,75 @@ pub fn execute(program: &Program, inputs: &ProgramInputs, num_outputs: usize, op
     };
     let res = serde_json::to_string(&gen_output).unwrap();
 
-    return res;
+    return Ok((res, metrics));
+}
+
+/// Executes the specified `program` in segments and generates a separate proof for every segment
+/// as soon as the segment is executed. Segments are proved in batches of `num_threads` segments,
+/// and all segments of a batch are proved concurrently; thus, the prover keeps at most
+/// `num_threads` segments of the execution trace in memory at a time. The trace is cut at the
+/// first block which starts after a segment reached `segment_length` steps. Returns the outputs
+/// of the program and the proofs of all segments in the order of execution.
+pub fn execute_segments(
+    program         : &Program,
+    inputs          : &ProgramInputs,
+    num_outputs     : usize,
+    segment_length  : usize,
+    options         : &ProofOptions,
+    limits          : &ExecutionLimits,
+    num_threads     : usize) -> Result<(Vec<u128>, Vec<SegmentProof>), ExecutionError>
+{
+    assert!(num_outputs <= MAX_OUTPUTS, 
+        "cannot produce more than {} outputs, but requested {}", MAX_OUTPUTS, num_outputs);
+    assert!(num_threads > 0, "number of threads must be greater than 0");
+
+    // prove every batch of segments as soon as the last segment of the batch is executed
+    let mut proofs = Vec::new();
+    let mut batch = Vec::with_capacity(num_threads);
+    processor::execute_segments(program, inputs, limits, segment_length, &mut |segment| {
+        debug!("Generated execution segment of {} registers and {} steps starting at step {}",
+            segment.trace.len(),
+            segment.trace[0].len(),
+            segment.first_step);
+
+        batch.push(segment);
+        if batch.len() == num_threads {
+            proofs.append(&mut prove_segments(&mut batch, options));
+        }
+    })?;
+    proofs.append(&mut prove_segments(&mut batch, options));
+
+    // make sure number of executed operations was sufficient
+    let last_state = &proofs.last().unwrap().exit;
+    assert!(last_state.op_counter as usize >= MIN_TRACE_LENGTH,
+        "a program must consist of at least {} operation, but only {} were executed",
+        MIN_TRACE_LENGTH,
+        last_state.op_counter);
+
+    // make sure program hash generated by the VM matches the hash of the program
+    let program_hash = utils::as_bytes(&last_state.sponge[..PROGRAM_DIGEST_SIZE]);
+    assert!(program.hash() == program_hash,
+        "expected program hash {} does not match trace hash {}",
+        hex::encode(program.hash()),
+        hex::encode(program_hash));
+
+    let outputs = last_state.user_stack[..num_outputs].to_vec();
+    return Ok((outputs, proofs));
+}
+
+/// Executes the specified `program` and checks that the resulting execution trace satisfies all
+/// transition and boundary constraints, including the constraints which bind the stack to public
+/// inputs and to the expected `outputs`. Unlike `execute()`, this does not generate a proof, and
+/// reports every unsatisfied constraint together with the step and the operation at which it
+/// failed; this works in both debug and release builds. If the program cannot be executed, the
+/// execution error is returned instead.
+pub fn check_execution(program: &Program, inputs: &ProgramInputs, outputs: &[u128]) -> Result<(), CheckError>
+{
+    let limits = ExecutionLimits::default();
+    let (trace, ctx_depth, loop_depth) = processor::execute(program, inputs, &limits)?;
+    let trace = stark::TraceTable::new(trace, ctx_depth, loop_depth, MIN_EXTENSION_FACTOR);
+    stark::check_trace(&trace, inputs.get_public_inputs(), outputs)?;
+    return Ok(());
 }
 
 // VERIFIER
@@ -95,7 +239,115 @@ pub fn execute(program: &Program, inputs: &ProgramInputs, num_outputs: usize, op
 /// provided `public_inputs` and some secret inputs, the result is equal to the `outputs`.
 pub fn verify(program_hash: &[u8; 32], public_inputs: &[u128], outputs: &[u128], proof: &StarkProof) -> Result<bool, String>
 {
-    return stark::verify(program_hash, public_inputs, outputs, proof);
+    let pub_inputs = stark::ProcessorPublicInputs {
+        program_hash: *program_hash,
+        inputs      : public_inputs.to_vec(),
+        outputs     : outputs.to_vec(),
+    };
+    return stark::verify::<stark::ProcessorAir>(proof, &pub_inputs);
+}
+
+/// Verifies a batch of proofs; every entry of the batch consists of a program hash, public
+/// inputs, outputs, and a proof, and is verified in the same way as by `verify()`. Returns a
+/// result for every entry of the batch. Precomputed values are shared by proofs with the same
+/// trace shape and proof options, and the work is split across `num_threads` threads.
+pub fn verify_batch(batch: &[(&[u8; 32], &[u128], &[u128], &StarkProof)], num_threads: usize) -> Vec<Result<bool, String>>
+{
+    let proofs = build_proof_batch(batch);
+    return stark::verify_batch::<stark::ProcessorAir>(&proofs, num_threads);
+}
+
+/// Same as `verify_batch()` but returns a single answer for the entire batch: `Ok(true)` if all
+/// proofs are valid, or an error for an invalid proof; verification stops as soon as an invalid
+/// proof is found.
+pub fn verify_all(batch: &[(&[u8; 32], &[u128], &[u128], &StarkProof)], num_threads: usize) -> Result<bool, String>
+{
+    let proofs = build_proof_batch(batch);
+    return stark::verify_all::<stark::ProcessorAir>(&proofs, num_threads);
+}
+
+/// Verifies that if a program with the specified `program_hash` is executed with the provided
+/// `public_inputs` and some secret inputs, the result is equal to the `outputs`; the execution
+/// is attested to by a chain of segment proofs generated by `execute_segments()`. The chain is
+/// valid if the first segment starts in the initial state of the VM, every other segment starts
+/// in the state in which the previous segment ends, the last segment ends in a state which
+/// contains the program hash and the outputs, and the proofs of all segments are valid.
+pub fn verify_segments(program_hash: &[u8; 32], public_inputs: &[u128], outputs: &[u128], segments: &[SegmentProof]) -> Result<bool, String>
+{
+    if segments.is_empty() {
+        return Err(String::from("execution must consist of at least one segment"));
+    }
+    if public_inputs.len() > MAX_STACK_DEPTH || outputs.len() > MAX_STACK_DEPTH {
+        return Err(String::from("inputs and outputs must fit into the user stack"));
+    }
+
+    // check the states at which segments are chained together
+    if segments[0].entry.hash() != VmState::initial(public_inputs).hash() {
+        return Err(String::from("the first segment does not start in the initial state of the VM"));
+    }
+    for (i, pair) in segments.windows(2).enumerate() {
+        if pair[0].exit.hash() != pair[1].entry.hash() {
+            return Err(format!("segment {} does not start in the state in which segment {} ends", i + 1, i));
+        }
+    }
+
+    // check the state in which execution ends
+    let last_state = &segments[segments.len() - 1].exit;
+    if last_state.op_counter < MIN_TRACE_LENGTH as u128 {
+        return Err(String::from("Verification of minimum operation count failed"));
+    }
+    if utils::as_bytes(&last_state.sponge[..PROGRAM_DIGEST_SIZE]) != program_hash {
+        return Err(String::from("the last segment does not end with the specified program hash"));
+    }
+    if last_state.ctx_stack.iter().chain(last_state.loop_stack.iter()).any(|&value| value != 0) {
+        return Err(String::from("the last segment does not end with empty context and loop stacks"));
+    }
+    if !last_state.user_stack.starts_with(outputs) {
+        return Err(String::from("the last segment does not end with the specified outputs"));
+    }
+
+    // verify the proofs of all segments; proofs of segments with the same trace shape share
+    // precomputed verifier values
+    let proofs: Vec<_> = segments.iter().map(|segment| {
+        let pub_inputs = stark::SegmentPublicInputs { entry: segment.entry.clone(), exit: segment.exit.clone() };
+        (&segment.proof, pub_inputs)
+    }).collect();
+    return stark::verify_all::<stark::SegmentAir>(&proofs, 1);
+}
+
+/// Proves all segments of the `batch` concurrently, one segment per thread, and removes them
+/// from the batch; returns the proofs in the order of the segments.
+fn prove_segments(batch: &mut Vec<ExecutionSegment>, options: &ProofOptions) -> Vec<SegmentProof> {
+    let prove_segment = |segment: ExecutionSegment| {
+        let mut trace = stark::TraceTable::new(segment.trace, segment.ctx_depth, segment.loop_depth, options.extension_factor());
+        let pub_inputs = stark::SegmentPublicInputs { entry: segment.entry, exit: segment.exit };
+        let proof = stark::prove::<stark::SegmentAir>(&mut trace, &pub_inputs, options);
+        return SegmentProof { entry: pub_inputs.entry, exit: pub_inputs.exit, proof };
+    };
+
+    if batch.len() <= 1 {
+        return batch.drain(..).map(prove_segment).collect();
+    }
+
+    let prove_segment = &prove_segment;
+    return thread::scope(|s| {
+        let handles: Vec<_> = batch.drain(..)
+            .map(|segment| s.spawn(move |_| prove_segment(segment)))
+            .collect();
+        handles.into_iter().map(|handle| handle.join().unwrap()).collect()
+    }).unwrap();
+}
+
+fn build_proof_batch<'a>(batch: &[(&[u8; 32], &[u128], &[u128], &'a StarkProof)]) -> Vec<(&'a StarkProof, stark::ProcessorPublicInputs)>
+{
+    return batch.iter().map(|&(program_hash, public_inputs, outputs, proof)| {
+        let pub_inputs = stark::ProcessorPublicInputs {
+            program_hash: *program_hash,
+            inputs      : public_inputs.to_vec(),
+            outputs     : outputs.to_vec(),
+        };
+        (proof, pub_inputs)
+    }).collect();
 }
 
 // GLOBAL CONSTANTS
@@ -105,7 +357,7 @@ pub const MAX_CONTEXT_DEPTH : usize = 16;
 pub const MAX_LOOP_DEPTH    : usize = 8;
 const MIN_TRACE_LENGTH      : usize = 16;
 const MAX_REGISTER_COUNT    : usize = 128;
-const MIN_EXTENSION_FACTOR  : usize = 16;
+const MIN_EXTENSION_FACTOR  : usize = 8;
 const BASE_CYCLE_LENGTH     : usize = 16;
 
 const MIN_STACK_DEPTH       : usize = 8;
@@ -133,9 +385,14 @@ const HACC_NUM_ROUNDS       : usize = 14;
 // DECODER LAYOUT
 // ------------------------------------------------------------------------------------------------
 //
-//  ctr ╒═════ sponge ══════╕╒═══ cf_ops ══╕╒═══════ ld_ops ═══════╕╒═ hd_ops ╕╒═ ctx ══╕╒═ loop ═╕
-//   0    1    2    3    4    5    6    7    8    9    10   11   12   13   14   15   ..   ..   ..
-// ├────┴────┴────┴────┴────┴────┴────┴────┴────┴────┴────┴────┴────┴────┴────┴────┴────┴────┴────┤
+//  ctr ╒═════ sponge ══════╕╒═══ cf_ops ══╕╒═══════ ld_ops ═══════╕╒═ hd_ops ╕╒═ helpers ══╕╒═ ctx ══╕╒═ loop ═╕
+//   0    1    2    3    4    5    6    7    8    9    10   11   12   13   14   15   ..   34   ..   ..   ..   ..
+// ├────┴────┴────┴────┴────┴────┴────┴────┴────┴────┴────┴────┴────┴────┴────┴────┴────┴────┴────┴────┴────┴────┤
+//
+// op flag helpers hold degree 1 versions of frequently used op flags so that constraints which
+// depend on these flags have low degree; the helpers are: HACC, VOID, BEGIN, and TEND flags
+// (15 - 18), flags for all combinations of ld_op bits 0 and 1 (19 - 22), flags for all
+// combinations of ld_op bits 2, 3, and 4 (23 - 30), and flags for all hd_ops (31 - 34).
 
 const NUM_CF_OP_BITS        : usize = 3;
 const NUM_LD_OP_BITS        : usize = 5;
@@ -145,11 +402,16 @@ const NUM_CF_OPS            : usize = 8;
 const NUM_LD_OPS            : usize = 32;
 const NUM_HD_OPS            : usize = 4;
 
+const NUM_LD_LO_HELPERS     : usize = 4;    // one for each combination of ld_op bits 0 and 1
+const NUM_LD_HI_HELPERS     : usize = 8;    // one for each combination of ld_op bits 2, 3, and 4
+const NUM_OP_FLAG_HELPERS   : usize = 4 + NUM_LD_LO_HELPERS + NUM_LD_HI_HELPERS + NUM_HD_OPS;
+
 const OP_COUNTER_IDX        : usize = 0;
 const SPONGE_RANGE          : Range<usize> = Range { start:  1, end:  5 };
 const CF_OP_BITS_RANGE      : Range<usize> = Range { start:  5, end:  8 };
 const LD_OP_BITS_RANGE      : Range<usize> = Range { start:  8, end: 13 };
 const HD_OP_BITS_RANGE      : Range<usize> = Range { start: 13, end: 15 };
+const OP_FLAG_HELPERS_RANGE : Range<usize> = Range { start: 15, end: 35 };
 
 // STACK LAYOUT
 // ------------------------------------------------------------------------------------------------
@@ -160,4 +422,22 @@ const HD_OP_BITS_RANGE      : Range<usize> = Range { start: 13, end: 15 };
 
 pub const MAX_PUBLIC_INPUTS : usize = 8;
 pub const MAX_OUTPUTS       : usize = MAX_PUBLIC_INPUTS;
-pub const MAX_STACK_DEPTH   : usize = 32;
\ No newline at end of file
+pub const MAX_STACK_DEPTH   : usize = 32;
+
+// RANGE CHECKS
+// ------------------------------------------------------------------------------------------------
+//
+// the last register of the trace holds multiplicities of range check lookups: the value at step
+// i is the number of times byte value i was looked up by RCACC operations; lookups are checked
+// against a table of all byte values, and so, traces which use range checks must be long enough
+// for the table to fit before the last step of the execution
+//
+// the table holds bytes rather than 16-bit values, and every 16-bit limb is checked with two
+// lookups: the table is a periodic column, and a table of all 16-bit values would pad every
+// trace which performs a range check to at least 2^17 steps, while the byte table needs only
+// 512 steps; the second lookup is evaluated in the same aux constraint, and so it costs no
+// additional cycles
+
+const NUM_RANGE_CHECK_REGISTERS     : usize = 1;
+const RANGE_CHECK_TABLE_SIZE        : usize = 256;
+const MIN_RANGE_CHECK_TRACE_LENGTH  : usize = 2 * RANGE_CHECK_TABLE_SIZE;
\ No newline at end of file
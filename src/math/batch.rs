@@ -0,0 +1,182 @@
+use crate::math::{ polynom, FieldElement };
+use crate::utils::uninit_vector;
+use sp_std::vec::Vec;
+
+// POLYNOMIAL BATCH OPERATIONS
+// ================================================================================================
+
+/// Evaluates a batch of polynomials of degree N - 1 at the provided X coordinate.
+pub fn evaluate_batch<E: FieldElement, const N: usize>(polys: &[[E; N]], x: E) -> Vec<E> {
+    let mut result: Vec<E> = Vec::with_capacity(polys.len());
+    for poly in polys.iter() {
+        result.push(polynom::eval(poly, x));
+    }
+    return result;
+}
+
+/// Interpolates a set of X, Y coordinates into a batch of degree N - 1 polynomials.
+///
+/// Similarly to `quartic::interpolate_batch`, inversions are amortized over the entire batch;
+/// but the polynomials can be of any size.
+pub fn interpolate_batch<E: FieldElement, const N: usize>(xs: &[[E; N]], ys: &[[E; N]]) -> Vec<[E; N]> {
+    debug_assert!(xs.len() == ys.len(), "number of X coordinates must be equal to number of Y coordinates");
+
+    let n = xs.len();
+    let mut numerators: Vec<[E; N]> = Vec::with_capacity(n * N);
+    let mut denominators: Vec<E> = Vec::with_capacity(n * N);
+
+    // for each row, compute numerators of Lagrange basis polynomials by dividing the zero
+    // polynomial of the row by (x - x_j), and evaluate each numerator at x_j
+    for row in xs.iter() {
+        let zero_poly = get_zero_poly(row);
+        for &x in row.iter() {
+            let numerator = div_by_root(&zero_poly, x);
+            denominators.push(polynom::eval(&numerator, x));
+            numerators.push(numerator);
+        }
+    }
+
+    let inverses = E::inv_many(&denominators);
+
+    // combine basis polynomials weighted by y / denominator into the resulting polynomials
+    let mut result: Vec<[E; N]> = Vec::with_capacity(n);
+    for (i, ys) in ys.iter().enumerate() {
+        let mut poly = [E::ZERO; N];
+        for j in 0..N {
+            let inv_y = ys[j] * inverses[i * N + j];
+            let numerator = &numerators[i * N + j];
+            for k in 0..N {
+                poly[k] = poly[k] + inv_y * numerator[k];
+            }
+        }
+        result.push(poly);
+    }
+
+    return result;
+}
+
+// VECTOR TRANSFORMATIONS
+// ================================================================================================
+
+/// Transposes every `stride`-th element of the `vector` into a matrix with N columns, such that
+/// element i of column j is the element i + j * row_count of the strided vector.
+pub fn transpose<E: Copy, const N: usize>(vector: &[E], stride: usize) -> Vec<[E; N]> {
+    assert!(vector.len() % (N * stride) == 0, "vector length must be divisible by {}", N * stride);
+    let row_count = vector.len() / (N * stride);
+
+    let mut result = to_rows::<_, N>(uninit_vector(row_count * N));
+    for i in 0..row_count {
+        for j in 0..N {
+            result[i][j] = vector[(i + j * row_count) * stride];
+        }
+    }
+    return result;
+}
+
+/// Re-interprets a vector of integers as a vector of rows with N elements each.
+pub fn to_rows<E: Copy, const N: usize>(vector: Vec<E>) -> Vec<[E; N]> {
+    assert!(vector.len() % N == 0, "vector length must be divisible by {}", N);
+    let mut v = sp_std::mem::ManuallyDrop::new(vector);
+    let p = v.as_mut_ptr();
+    let len = v.len() / N;
+    let cap = v.capacity() / N;
+    return unsafe { Vec::from_raw_parts(p as *mut [E; N], len, cap) };
+}
+
+/// Re-interprets a vector of rows with N elements each as a vector of integers.
+pub fn flatten<E: Copy, const N: usize>(vector: Vec<[E; N]>) -> Vec<E> {
+    let mut v = sp_std::mem::ManuallyDrop::new(vector);
+    let p = v.as_mut_ptr();
+    let len = v.len() * N;
+    let cap = v.capacity() * N;
+    return unsafe { Vec::from_raw_parts(p as *mut E, len, cap) };
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Returns coefficients of the monic polynomial (x - xs_0) * ... * (x - xs_{N-1}); the leading
+/// coefficient is implied and is not included in the result.
+fn get_zero_poly<E: FieldElement, const N: usize>(xs: &[E; N]) -> [E; N] {
+    let mut result = [E::ZERO; N];
+    for (degree, &x) in xs.iter().enumerate() {
+        // multiply the polynomial of the current degree by (x - xs_i) in place
+        for i in (0..=degree).rev() {
+            let c = if i == degree { E::ONE } else { result[i] };
+            let c_prev = if i == 0 { E::ZERO } else { result[i - 1] };
+            result[i] = c_prev - x * c;
+        }
+    }
+    return result;
+}
+
+/// Divides a monic polynomial of degree N (with implied leading coefficient) by (x - root);
+/// the remainder of the division is discarded.
+fn div_by_root<E: FieldElement, const N: usize>(poly: &[E; N], root: E) -> [E; N] {
+    let mut result = [E::ZERO; N];
+    result[N - 1] = E::ONE;
+    for i in (1..N).rev() {
+        result[i - 1] = poly[i] + root * result[i];
+    }
+    return result;
+}
+
+// TESTS
+// ================================================================================================
+#[cfg(test)]
+mod tests {
+    use crate::math::{ f128::BaseElement, polynom, quartic, FieldElement, StarkField };
+
+    #[test]
+    fn interpolate_batch() {
+        check_interpolate_batch::<2>();
+        check_interpolate_batch::<4>();
+        check_interpolate_batch::<8>();
+        check_interpolate_batch::<16>();
+
+        // results should match the specialized quartic implementation
+        let r = BaseElement::get_root_of_unity(64);
+        let xs = quartic::to_quartic_vec(BaseElement::get_power_series(r, 64));
+        let ys = quartic::to_quartic_vec(BaseElement::rand_vector(64));
+        assert_eq!(quartic::interpolate_batch(&xs, &ys), super::interpolate_batch(&xs, &ys));
+    }
+
+    #[test]
+    fn evaluate_batch() {
+        let x = BaseElement::rand();
+        let polys = super::to_rows::<_, 8>(BaseElement::rand_vector(64));
+        let expected = polys.iter().map(|p| polynom::eval(p, x)).collect::<Vec<BaseElement>>();
+        assert_eq!(expected, super::evaluate_batch(&polys, x));
+    }
+
+    #[test]
+    fn transpose() {
+        let vector = (0..32).collect::<Vec<u128>>();
+
+        let expected = vec![[0, 8, 16, 24], [1, 9, 17, 25], [2, 10, 18, 26], [3, 11, 19, 27],
+            [4, 12, 20, 28], [5, 13, 21, 29], [6, 14, 22, 30], [7, 15, 23, 31]];
+        assert_eq!(expected, super::transpose::<_, 4>(&vector, 1));
+
+        let expected = vec![[0, 8, 16, 24], [2, 10, 18, 26], [4, 12, 20, 28], [6, 14, 22, 30]];
+        assert_eq!(expected, super::transpose::<_, 4>(&vector, 2));
+        assert_eq!(quartic::transpose(&vector, 2), super::transpose::<_, 4>(&vector, 2));
+
+        let expected = vec![[0, 16], [2, 18], [4, 20], [6, 22], [8, 24], [10, 26], [12, 28], [14, 30]];
+        assert_eq!(expected, super::transpose::<_, 2>(&vector, 2));
+    }
+
+    fn check_interpolate_batch<const N: usize>() {
+        let r = BaseElement::get_root_of_unity(N * 4);
+        let xs = super::to_rows::<_, N>(BaseElement::get_power_series(r, N * 4));
+        let ys = super::to_rows::<_, N>(BaseElement::rand_vector(N * 4));
+
+        let mut expected: Vec<[BaseElement; N]> = vec![];
+        for i in 0..xs.len() {
+            let mut row = [BaseElement::ZERO; N];
+            row.copy_from_slice(&polynom::interpolate(&xs[i], &ys[i]));
+            expected.push(row);
+        }
+
+        assert_eq!(expected, super::interpolate_batch(&xs, &ys));
+    }
+}
@@ -0,0 +1,349 @@
+use sp_std::{ fmt, mem, slice, ops::{ Add, Sub, Mul, Div, Neg, AddAssign, SubAssign, MulAssign, DivAssign } };
+use sp_std::vec::Vec;
+use serde::{ Serialize, Deserialize };
+use crate::math::{ FieldElement, StarkField, ExtensibleField };
+
+// QUADRATIC EXTENSION
+// ================================================================================================
+
+/// Element of a degree 2 extension of the prime field `B`; the element is represented as
+/// a0 + a1 * x, where arithmetic is defined by `ExtensibleField<2>` implementation of `B`.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(bound = "B: StarkField + ExtensibleField<2>")]
+pub struct QuadExtension<B: StarkField + ExtensibleField<2>>(B, B);
+
+impl<B: StarkField + ExtensibleField<2>> QuadExtension<B> {
+
+    /// Returns an element with the specified coefficients.
+    pub fn new(a0: B, a1: B) -> QuadExtension<B> {
+        return QuadExtension(a0, a1);
+    }
+
+    /// Returns coefficients of this element.
+    pub fn to_base_elements(self) -> [B; 2] {
+        return [self.0, self.1];
+    }
+}
+
+impl<B: StarkField + ExtensibleField<2>> FieldElement for QuadExtension<B> {
+    type BaseField = B;
+
+    const ZERO: QuadExtension<B> = QuadExtension(B::ZERO, B::ZERO);
+    const ONE: QuadExtension<B> = QuadExtension(B::ONE, B::ZERO);
+    const ELEMENT_BYTES: usize = B::ELEMENT_BYTES * 2;
+    const EXTENSION_DEGREE: usize = 2;
+
+    fn inv(self) -> QuadExtension<B> {
+        let [a0, a1] = <B as ExtensibleField<2>>::inv([self.0, self.1]);
+        return QuadExtension(a0, a1);
+    }
+
+    fn rand() -> QuadExtension<B> {
+        return QuadExtension(B::rand(), B::rand());
+    }
+
+    fn prng_vector(seed: [u8; 32], length: usize) -> Vec<QuadExtension<B>> {
+        let values = B::prng_vector(seed, length * 2);
+        return values.chunks(2).map(|v| QuadExtension(v[0], v[1])).collect();
+    }
+
+    fn from_bytes(bytes: &[u8]) -> QuadExtension<B> {
+        let (b0, b1) = bytes.split_at(B::ELEMENT_BYTES);
+        return QuadExtension(B::from_bytes(b0), B::from_bytes(b1));
+    }
+
+    #[inline(always)]
+    fn mul_base(self, other: B) -> QuadExtension<B> {
+        return QuadExtension(self.0 * other, self.1 * other);
+    }
+
+    fn as_base_elements(elements: &[QuadExtension<B>]) -> &[B] {
+        let len = elements.len() * 2;
+        return unsafe { slice::from_raw_parts(elements.as_ptr() as *const B, len) };
+    }
+
+    fn slice_from_base_elements(elements: &[B]) -> &[QuadExtension<B>] {
+        assert!(elements.len() % 2 == 0, "number of base elements must be divisible by 2");
+        debug_assert!(mem::size_of::<QuadExtension<B>>() == mem::size_of::<B>() * 2);
+        let len = elements.len() / 2;
+        return unsafe { slice::from_raw_parts(elements.as_ptr() as *const QuadExtension<B>, len) };
+    }
+}
+
+impl<B: StarkField + ExtensibleField<2>> Add for QuadExtension<B> {
+    type Output = QuadExtension<B>;
+
+    #[inline(always)]
+    fn add(self, rhs: QuadExtension<B>) -> QuadExtension<B> {
+        return QuadExtension(self.0 + rhs.0, self.1 + rhs.1);
+    }
+}
+
+impl<B: StarkField + ExtensibleField<2>> Sub for QuadExtension<B> {
+    type Output = QuadExtension<B>;
+
+    #[inline(always)]
+    fn sub(self, rhs: QuadExtension<B>) -> QuadExtension<B> {
+        return QuadExtension(self.0 - rhs.0, self.1 - rhs.1);
+    }
+}
+
+impl<B: StarkField + ExtensibleField<2>> Mul for QuadExtension<B> {
+    type Output = QuadExtension<B>;
+
+    #[inline(always)]
+    fn mul(self, rhs: QuadExtension<B>) -> QuadExtension<B> {
+        let [a0, a1] = <B as ExtensibleField<2>>::mul([self.0, self.1], [rhs.0, rhs.1]);
+        return QuadExtension(a0, a1);
+    }
+}
+
+impl<B: StarkField + ExtensibleField<2>> Div for QuadExtension<B> {
+    type Output = QuadExtension<B>;
+
+    fn div(self, rhs: QuadExtension<B>) -> QuadExtension<B> {
+        return self * rhs.inv();
+    }
+}
+
+impl<B: StarkField + ExtensibleField<2>> Neg for QuadExtension<B> {
+    type Output = QuadExtension<B>;
+
+    #[inline(always)]
+    fn neg(self) -> QuadExtension<B> {
+        return QuadExtension(-self.0, -self.1);
+    }
+}
+
+impl<B: StarkField + ExtensibleField<2>> From<B> for QuadExtension<B> {
+    fn from(value: B) -> QuadExtension<B> {
+        return QuadExtension(value, B::ZERO);
+    }
+}
+
+impl<B: StarkField + ExtensibleField<2>> From<u128> for QuadExtension<B> {
+    fn from(value: u128) -> QuadExtension<B> {
+        return QuadExtension(B::from(value), B::ZERO);
+    }
+}
+
+impl<B: StarkField + ExtensibleField<2>> fmt::Display for QuadExtension<B> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return write!(f, "({}, {})", self.0, self.1);
+    }
+}
+
+// CUBIC EXTENSION
+// ================================================================================================
+
+/// Element of a degree 3 extension of the prime field `B`; the element is represented as
+/// a0 + a1 * x + a2 * x^2, where arithmetic is defined by `ExtensibleField<3>` implementation
+/// of `B`.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(bound = "B: StarkField + ExtensibleField<3>")]
+pub struct CubeExtension<B: StarkField + ExtensibleField<3>>(B, B, B);
+
+impl<B: StarkField + ExtensibleField<3>> CubeExtension<B> {
+
+    /// Returns an element with the specified coefficients.
+    pub fn new(a0: B, a1: B, a2: B) -> CubeExtension<B> {
+        return CubeExtension(a0, a1, a2);
+    }
+
+    /// Returns coefficients of this element.
+    pub fn to_base_elements(self) -> [B; 3] {
+        return [self.0, self.1, self.2];
+    }
+}
+
+impl<B: StarkField + ExtensibleField<3>> FieldElement for CubeExtension<B> {
+    type BaseField = B;
+
+    const ZERO: CubeExtension<B> = CubeExtension(B::ZERO, B::ZERO, B::ZERO);
+    const ONE: CubeExtension<B> = CubeExtension(B::ONE, B::ZERO, B::ZERO);
+    const ELEMENT_BYTES: usize = B::ELEMENT_BYTES * 3;
+    const EXTENSION_DEGREE: usize = 3;
+
+    fn inv(self) -> CubeExtension<B> {
+        let [a0, a1, a2] = <B as ExtensibleField<3>>::inv([self.0, self.1, self.2]);
+        return CubeExtension(a0, a1, a2);
+    }
+
+    fn rand() -> CubeExtension<B> {
+        return CubeExtension(B::rand(), B::rand(), B::rand());
+    }
+
+    fn prng_vector(seed: [u8; 32], length: usize) -> Vec<CubeExtension<B>> {
+        let values = B::prng_vector(seed, length * 3);
+        return values.chunks(3).map(|v| CubeExtension(v[0], v[1], v[2])).collect();
+    }
+
+    fn from_bytes(bytes: &[u8]) -> CubeExtension<B> {
+        let (b0, rest) = bytes.split_at(B::ELEMENT_BYTES);
+        let (b1, b2) = rest.split_at(B::ELEMENT_BYTES);
+        return CubeExtension(B::from_bytes(b0), B::from_bytes(b1), B::from_bytes(b2));
+    }
+
+    #[inline(always)]
+    fn mul_base(self, other: B) -> CubeExtension<B> {
+        return CubeExtension(self.0 * other, self.1 * other, self.2 * other);
+    }
+
+    fn as_base_elements(elements: &[CubeExtension<B>]) -> &[B] {
+        let len = elements.len() * 3;
+        return unsafe { slice::from_raw_parts(elements.as_ptr() as *const B, len) };
+    }
+
+    fn slice_from_base_elements(elements: &[B]) -> &[CubeExtension<B>] {
+        assert!(elements.len() % 3 == 0, "number of base elements must be divisible by 3");
+        debug_assert!(mem::size_of::<CubeExtension<B>>() == mem::size_of::<B>() * 3);
+        let len = elements.len() / 3;
+        return unsafe { slice::from_raw_parts(elements.as_ptr() as *const CubeExtension<B>, len) };
+    }
+}
+
+impl<B: StarkField + ExtensibleField<3>> Add for CubeExtension<B> {
+    type Output = CubeExtension<B>;
+
+    #[inline(always)]
+    fn add(self, rhs: CubeExtension<B>) -> CubeExtension<B> {
+        return CubeExtension(self.0 + rhs.0, self.1 + rhs.1, self.2 + rhs.2);
+    }
+}
+
+impl<B: StarkField + ExtensibleField<3>> Sub for CubeExtension<B> {
+    type Output = CubeExtension<B>;
+
+    #[inline(always)]
+    fn sub(self, rhs: CubeExtension<B>) -> CubeExtension<B> {
+        return CubeExtension(self.0 - rhs.0, self.1 - rhs.1, self.2 - rhs.2);
+    }
+}
+
+impl<B: StarkField + ExtensibleField<3>> Mul for CubeExtension<B> {
+    type Output = CubeExtension<B>;
+
+    #[inline(always)]
+    fn mul(self, rhs: CubeExtension<B>) -> CubeExtension<B> {
+        let [a0, a1, a2] = <B as ExtensibleField<3>>::mul([self.0, self.1, self.2], [rhs.0, rhs.1, rhs.2]);
+        return CubeExtension(a0, a1, a2);
+    }
+}
+
+impl<B: StarkField + ExtensibleField<3>> Div for CubeExtension<B> {
+    type Output = CubeExtension<B>;
+
+    fn div(self, rhs: CubeExtension<B>) -> CubeExtension<B> {
+        return self * rhs.inv();
+    }
+}
+
+impl<B: StarkField + ExtensibleField<3>> Neg for CubeExtension<B> {
+    type Output = CubeExtension<B>;
+
+    #[inline(always)]
+    fn neg(self) -> CubeExtension<B> {
+        return CubeExtension(-self.0, -self.1, -self.2);
+    }
+}
+
+impl<B: StarkField + ExtensibleField<3>> From<B> for CubeExtension<B> {
+    fn from(value: B) -> CubeExtension<B> {
+        return CubeExtension(value, B::ZERO, B::ZERO);
+    }
+}
+
+impl<B: StarkField + ExtensibleField<3>> From<u128> for CubeExtension<B> {
+    fn from(value: u128) -> CubeExtension<B> {
+        return CubeExtension(B::from(value), B::ZERO, B::ZERO);
+    }
+}
+
+impl<B: StarkField + ExtensibleField<3>> fmt::Display for CubeExtension<B> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return write!(f, "({}, {}, {})", self.0, self.1, self.2);
+    }
+}
+
+// ASSIGNMENT OPERATORS
+// ================================================================================================
+
+macro_rules! impl_assign_ops {
+    ($ext:ident, $n:literal) => {
+        impl<B: StarkField + ExtensibleField<$n>> AddAssign for $ext<B> {
+            #[inline(always)]
+            fn add_assign(&mut self, rhs: $ext<B>) {
+                *self = *self + rhs;
+            }
+        }
+
+        impl<B: StarkField + ExtensibleField<$n>> SubAssign for $ext<B> {
+            #[inline(always)]
+            fn sub_assign(&mut self, rhs: $ext<B>) {
+                *self = *self - rhs;
+            }
+        }
+
+        impl<B: StarkField + ExtensibleField<$n>> MulAssign for $ext<B> {
+            #[inline(always)]
+            fn mul_assign(&mut self, rhs: $ext<B>) {
+                *self = *self * rhs;
+            }
+        }
+
+        impl<B: StarkField + ExtensibleField<$n>> DivAssign for $ext<B> {
+            fn div_assign(&mut self, rhs: $ext<B>) {
+                *self = *self / rhs;
+            }
+        }
+    };
+}
+
+impl_assign_ops!(QuadExtension, 2);
+impl_assign_ops!(CubeExtension, 3);
+
+// TESTS
+// ================================================================================================
+#[cfg(test)]
+mod tests {
+
+    use crate::math::{ f64::BaseElement, FieldElement };
+    use super::{ QuadExtension, CubeExtension };
+
+    #[test]
+    fn quad_arithmetic() {
+        let a = QuadExtension::<BaseElement>::rand();
+        let b = QuadExtension::<BaseElement>::rand();
+        let c = BaseElement::rand();
+
+        assert_eq!(QuadExtension::ONE, a * a.inv());
+        assert_eq!(a, (a / b) * b);
+        assert_eq!(a * QuadExtension::from(c), a.mul_base(c));
+        assert_eq!(a * (b + a), a * b + a * a);
+        assert_eq!(QuadExtension::ZERO, a + (-a));
+    }
+
+    #[test]
+    fn cube_arithmetic() {
+        let a = CubeExtension::<BaseElement>::rand();
+        let b = CubeExtension::<BaseElement>::rand();
+        let c = BaseElement::rand();
+
+        assert_eq!(CubeExtension::ONE, a * a.inv());
+        assert_eq!(a, (a / b) * b);
+        assert_eq!(a * CubeExtension::from(c), a.mul_base(c));
+        assert_eq!(a * (b + a), a * b + a * a);
+        assert_eq!(CubeExtension::ZERO, a + (-a));
+    }
+
+    #[test]
+    fn base_element_slices() {
+        let values = QuadExtension::<BaseElement>::prng_vector([1; 32], 4);
+        let base_values = QuadExtension::as_base_elements(&values);
+        assert_eq!(8, base_values.len());
+        assert_eq!(values[1].to_base_elements(), [base_values[2], base_values[3]]);
+        assert_eq!(values, QuadExtension::slice_from_base_elements(base_values));
+    }
+}
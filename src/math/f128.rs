@@ -0,0 +1,256 @@
+use sp_std::{ fmt, mem, ops::{ Add, Sub, Mul, Div, Neg, AddAssign, SubAssign, MulAssign, DivAssign } };
+use sp_std::vec::Vec;
+use serde::{ Serialize, Deserialize };
+use crate::math::{ field, FieldElement, StarkField };
+
+// TYPES AND INTERFACES
+// ================================================================================================
+
+/// Element of the 128-bit prime field with modulus 2^128 - 45 * 2^40 + 1; this is the field
+/// in which Distaff VM operates. All arithmetic is delegated to `math::field` functions, and
+/// the layout of the element is the same as the layout of its raw `u128` value.
+#[repr(transparent)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BaseElement(u128);
+
+// BASE ELEMENT IMPLEMENTATION
+// ================================================================================================
+impl BaseElement {
+
+    /// Returns a field element for the specified `value`; the value must be smaller than the
+    /// field modulus.
+    pub const fn new(value: u128) -> BaseElement {
+        return BaseElement(value);
+    }
+
+    /// Re-interprets a vector of raw field elements as a vector of `BaseElement`s without
+    /// copying the data.
+    pub fn from_raw_vec(vector: Vec<u128>) -> Vec<BaseElement> {
+        let mut v = mem::ManuallyDrop::new(vector);
+        let (p, len, cap) = (v.as_mut_ptr(), v.len(), v.capacity());
+        return unsafe { Vec::from_raw_parts(p as *mut BaseElement, len, cap) };
+    }
+
+    /// Re-interprets a vector of `BaseElement`s as a vector of raw field elements without
+    /// copying the data.
+    pub fn into_raw_vec(vector: Vec<BaseElement>) -> Vec<u128> {
+        let mut v = mem::ManuallyDrop::new(vector);
+        let (p, len, cap) = (v.as_mut_ptr(), v.len(), v.capacity());
+        return unsafe { Vec::from_raw_parts(p as *mut u128, len, cap) };
+    }
+
+    /// Re-interprets a slice of raw field elements as a slice of `BaseElement`s.
+    pub fn from_raw_slice(values: &[u128]) -> &[BaseElement] {
+        return unsafe { sp_std::slice::from_raw_parts(values.as_ptr() as *const BaseElement, values.len()) };
+    }
+
+    /// Re-interprets a slice of `BaseElement`s as a slice of raw field elements.
+    pub fn as_raw_slice(values: &[BaseElement]) -> &[u128] {
+        return unsafe { sp_std::slice::from_raw_parts(values.as_ptr() as *const u128, values.len()) };
+    }
+
+    /// Re-interprets a mutable slice of `BaseElement`s as a mutable slice of raw field elements.
+    pub fn as_raw_slice_mut(values: &mut [BaseElement]) -> &mut [u128] {
+        return unsafe { sp_std::slice::from_raw_parts_mut(values.as_mut_ptr() as *mut u128, values.len()) };
+    }
+}
+
+impl FieldElement for BaseElement {
+    type BaseField = BaseElement;
+
+    const ZERO: BaseElement = BaseElement(field::ZERO);
+    const ONE: BaseElement = BaseElement(field::ONE);
+    const ELEMENT_BYTES: usize = 16;
+    const EXTENSION_DEGREE: usize = 1;
+
+    #[inline(always)]
+    fn inv(self) -> BaseElement {
+        return BaseElement(field::inv(self.0));
+    }
+
+    fn exp(self, power: u128) -> BaseElement {
+        return BaseElement(field::exp(self.0, power));
+    }
+
+    fn rand() -> BaseElement {
+        return BaseElement(field::rand());
+    }
+
+    fn rand_vector(length: usize) -> Vec<BaseElement> {
+        return BaseElement::from_raw_vec(field::rand_vector(length));
+    }
+
+    fn prng_vector(seed: [u8; 32], length: usize) -> Vec<BaseElement> {
+        return BaseElement::from_raw_vec(field::prng_vector(seed, length));
+    }
+
+    fn from_bytes(bytes: &[u8]) -> BaseElement {
+        return BaseElement(field::from_bytes(bytes));
+    }
+
+    fn inv_many(values: &[BaseElement]) -> Vec<BaseElement> {
+        return BaseElement::from_raw_vec(field::inv_many(BaseElement::as_raw_slice(values)));
+    }
+
+    fn as_base_elements(elements: &[BaseElement]) -> &[BaseElement] {
+        return elements;
+    }
+
+    fn slice_from_base_elements(elements: &[BaseElement]) -> &[BaseElement] {
+        return elements;
+    }
+}
+
+/// Field extensions are not defined for this field: the field is large enough to provide the
+/// target level of security on its own, and so proofs are always generated over the field.
+impl StarkField for BaseElement {
+    type QuadField = BaseElement;
+    type CubeField = BaseElement;
+
+    const MODULUS: u128 = field::MODULUS;
+    const TWO_ADICITY: u32 = 40;
+    const TWO_ADIC_ROOT_OF_UNITY: BaseElement = BaseElement(field::G);
+
+    #[inline(always)]
+    fn as_int(&self) -> u128 {
+        return self.0;
+    }
+}
+
+// OPERATOR OVERLOADS
+// ================================================================================================
+
+impl Add for BaseElement {
+    type Output = BaseElement;
+
+    #[inline(always)]
+    fn add(self, rhs: BaseElement) -> BaseElement {
+        return BaseElement(field::add(self.0, rhs.0));
+    }
+}
+
+impl AddAssign for BaseElement {
+    #[inline(always)]
+    fn add_assign(&mut self, rhs: BaseElement) {
+        *self = *self + rhs;
+    }
+}
+
+impl Sub for BaseElement {
+    type Output = BaseElement;
+
+    #[inline(always)]
+    fn sub(self, rhs: BaseElement) -> BaseElement {
+        return BaseElement(field::sub(self.0, rhs.0));
+    }
+}
+
+impl SubAssign for BaseElement {
+    #[inline(always)]
+    fn sub_assign(&mut self, rhs: BaseElement) {
+        *self = *self - rhs;
+    }
+}
+
+impl Mul for BaseElement {
+    type Output = BaseElement;
+
+    #[inline(always)]
+    fn mul(self, rhs: BaseElement) -> BaseElement {
+        return BaseElement(field::mul(self.0, rhs.0));
+    }
+}
+
+impl MulAssign for BaseElement {
+    #[inline(always)]
+    fn mul_assign(&mut self, rhs: BaseElement) {
+        *self = *self * rhs;
+    }
+}
+
+impl Div for BaseElement {
+    type Output = BaseElement;
+
+    fn div(self, rhs: BaseElement) -> BaseElement {
+        return BaseElement(field::div(self.0, rhs.0));
+    }
+}
+
+impl DivAssign for BaseElement {
+    fn div_assign(&mut self, rhs: BaseElement) {
+        *self = *self / rhs;
+    }
+}
+
+impl Neg for BaseElement {
+    type Output = BaseElement;
+
+    #[inline(always)]
+    fn neg(self) -> BaseElement {
+        return BaseElement(field::neg(self.0));
+    }
+}
+
+// TYPE CONVERSIONS
+// ================================================================================================
+
+impl From<u128> for BaseElement {
+    /// Converts an integer into a field element by reducing it modulo the field modulus.
+    fn from(value: u128) -> BaseElement {
+        return BaseElement(if value >= field::M { value - field::M } else { value });
+    }
+}
+
+impl From<BaseElement> for u128 {
+    fn from(value: BaseElement) -> u128 {
+        return value.0;
+    }
+}
+
+impl fmt::Display for BaseElement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return write!(f, "{}", self.0);
+    }
+}
+
+// TESTS
+// ================================================================================================
+#[cfg(test)]
+mod tests {
+
+    use crate::math::{ field, FieldElement, StarkField };
+    use super::BaseElement;
+
+    #[test]
+    fn arithmetic() {
+        let (a, b) = (field::rand(), field::rand());
+        let (ea, eb) = (BaseElement::new(a), BaseElement::new(b));
+        assert_eq!(field::add(a, b), (ea + eb).as_int());
+        assert_eq!(field::sub(a, b), (ea - eb).as_int());
+        assert_eq!(field::mul(a, b), (ea * eb).as_int());
+        assert_eq!(field::div(a, b), (ea / eb).as_int());
+        assert_eq!(field::neg(a), (-ea).as_int());
+        assert_eq!(field::exp(a, 12345), ea.exp(12345).as_int());
+        assert_eq!(BaseElement::ONE, ea * ea.inv());
+    }
+
+    #[test]
+    fn get_root_of_unity() {
+        for &order in [1, 2, 64, 1 << 20, 1 << 40].iter() {
+            let expected = field::get_root_of_unity(order);
+            assert_eq!(expected, BaseElement::get_root_of_unity(order).as_int());
+        }
+    }
+
+    #[test]
+    fn raw_conversions() {
+        let values = field::rand_vector(16);
+        let elements = BaseElement::from_raw_vec(values.clone());
+        assert_eq!(values, BaseElement::as_raw_slice(&elements));
+        assert_eq!(values, BaseElement::into_raw_vec(elements));
+
+        // pseudo-random elements must be the same as raw pseudo-random values
+        let expected = field::prng_vector([1; 32], 16);
+        assert_eq!(expected, BaseElement::into_raw_vec(BaseElement::prng_vector([1; 32], 16)));
+    }
+}
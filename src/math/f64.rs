@@ -0,0 +1,350 @@
+use sp_std::{ fmt, convert::TryInto, ops::{ Add, Sub, Mul, Div, Neg, AddAssign, SubAssign, MulAssign, DivAssign } };
+use sp_std::vec::Vec;
+use rand::prelude::*;
+use rand::distributions::Uniform;
+use serde::{ Serialize, Deserialize };
+use crate::math::{ FieldElement, StarkField, ExtensibleField, QuadExtension, CubeExtension };
+
+// CONSTANTS
+// ================================================================================================
+
+/// Field modulus = 2^64 - 2^32 + 1
+pub const M: u64 = 0xFFFFFFFF00000001;
+
+/// 2^64 mod M; this is also 2^32 - 1
+const EPSILON: u64 = 0xFFFFFFFF;
+
+/// 2^32 root of unity
+const G: u64 = 1753635133440165772;
+
+// TYPES AND INTERFACES
+// ================================================================================================
+
+/// Element of the 64-bit prime field with modulus 2^64 - 2^32 + 1. Multiplication in this
+/// field is several times faster than in the 128-bit field, but the field is too small to
+/// provide sufficient security on its own; so, random values used by the protocol are drawn
+/// from an extension of the field (see `ProofOptions::with_field_extension()`).
+///
+/// Internal value is always kept in canonical form (smaller than the modulus).
+#[repr(transparent)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BaseElement(u64);
+
+// BASE ELEMENT IMPLEMENTATION
+// ================================================================================================
+impl BaseElement {
+
+    /// Returns a field element for the specified `value`; the value is reduced by the modulus.
+    pub const fn new(value: u64) -> BaseElement {
+        return BaseElement(if value >= M { value - M } else { value });
+    }
+}
+
+impl FieldElement for BaseElement {
+    type BaseField = BaseElement;
+
+    const ZERO: BaseElement = BaseElement(0);
+    const ONE: BaseElement = BaseElement(1);
+    const ELEMENT_BYTES: usize = 8;
+    const EXTENSION_DEGREE: usize = 1;
+
+    fn inv(self) -> BaseElement {
+        // by Fermat's little theorem, x^(M - 2) = x^-1; this also maps 0 to 0
+        return self.exp((M - 2) as u128);
+    }
+
+    fn rand() -> BaseElement {
+        let range = Uniform::from(0..M);
+        let mut g = rand::thread_rng();
+        return BaseElement(g.sample(range));
+    }
+
+    fn prng_vector(seed: [u8; 32], length: usize) -> Vec<BaseElement> {
+        let range = Uniform::from(0..M);
+        let g = StdRng::from_seed(seed);
+        return g.sample_iter(range).take(length).map(BaseElement).collect();
+    }
+
+    fn from_bytes(bytes: &[u8]) -> BaseElement {
+        return BaseElement::new(u64::from_le_bytes(bytes.try_into().unwrap()));
+    }
+
+    fn as_base_elements(elements: &[BaseElement]) -> &[BaseElement] {
+        return elements;
+    }
+
+    fn slice_from_base_elements(elements: &[BaseElement]) -> &[BaseElement] {
+        return elements;
+    }
+}
+
+impl StarkField for BaseElement {
+    type QuadField = QuadExtension<BaseElement>;
+    type CubeField = CubeExtension<BaseElement>;
+
+    const MODULUS: u128 = M as u128;
+    const TWO_ADICITY: u32 = 32;
+    const TWO_ADIC_ROOT_OF_UNITY: BaseElement = BaseElement(G);
+
+    #[inline(always)]
+    fn as_int(&self) -> u128 {
+        return self.0 as u128;
+    }
+}
+
+// FIELD EXTENSIONS
+// ================================================================================================
+
+/// Quadratic extension is defined by the irreducible polynomial x^2 - x + 2; thus,
+/// x^2 = x - 2.
+impl ExtensibleField<2> for BaseElement {
+
+    #[inline(always)]
+    fn mul(a: [BaseElement; 2], b: [BaseElement; 2]) -> [BaseElement; 2] {
+        let z = a[0] * b[0];
+        let t = a[1] * b[1];
+        return [z - t - t, (a[0] + a[1]) * (b[0] + b[1]) - z];
+    }
+
+    fn inv(x: [BaseElement; 2]) -> [BaseElement; 2] {
+        // conjugate of x is x0 + x1 * (1 - x), and the product of x and its conjugate is
+        // x0^2 + x0 * x1 + 2 * x1^2, which is an element of the base field
+        let conjugate = [x[0] + x[1], -x[1]];
+        let norm = x[0] * x[0] + x[0] * x[1] + (x[1] * x[1]).double();
+        let inv_norm = norm.inv();
+        return [conjugate[0] * inv_norm, conjugate[1] * inv_norm];
+    }
+}
+
+/// Cubic extension is defined by the irreducible polynomial x^3 - x - 1; thus, x^3 = x + 1.
+impl ExtensibleField<3> for BaseElement {
+
+    #[inline(always)]
+    fn mul(a: [BaseElement; 3], b: [BaseElement; 3]) -> [BaseElement; 3] {
+        // compute coefficients of the product before reduction
+        let c0 = a[0] * b[0];
+        let c1 = a[0] * b[1] + a[1] * b[0];
+        let c2 = a[0] * b[2] + a[1] * b[1] + a[2] * b[0];
+        let c3 = a[1] * b[2] + a[2] * b[1];
+        let c4 = a[2] * b[2];
+
+        // reduce using x^3 = x + 1 and x^4 = x^2 + x
+        return [c0 + c3, c1 + c3 + c4, c2 + c4];
+    }
+
+    fn inv(x: [BaseElement; 3]) -> [BaseElement; 3] {
+        // multiplication by x is a linear map with the matrix below; the inverse of x is the
+        // first column of the inverse of this matrix, which is computed using cofactors:
+        // | x0  x2       x1      |
+        // | x1  x0 + x2  x1 + x2 |
+        // | x2  x1       x0 + x2 |
+        let x02 = x[0] + x[2];
+        let c0 = x02 * x02 - (x[1] + x[2]) * x[1];
+        let c1 = (x[1] + x[2]) * x[2] - x[1] * x02;
+        let c2 = x[1] * x[1] - x02 * x[2];
+
+        let det = x[0] * c0 + x[2] * c1 + x[1] * c2;
+        let inv_det = det.inv();
+        return [c0 * inv_det, c1 * inv_det, c2 * inv_det];
+    }
+}
+
+// HELPER METHODS
+// ================================================================================================
+impl BaseElement {
+
+    #[inline(always)]
+    fn double(self) -> BaseElement {
+        return self + self;
+    }
+}
+
+/// Reduces a 128-bit value by the field modulus using the fact that 2^64 = 2^32 - 1 and
+/// 2^96 = -1 in this field.
+#[inline(always)]
+fn reduce128(x: u128) -> u64 {
+    let lo = x as u64;
+    let hi = (x >> 64) as u64;
+    let hi_hi = hi >> 32;
+    let hi_lo = hi & EPSILON;
+
+    // lo - hi_hi; on underflow, 2^64 was added, which must be compensated by subtracting EPSILON
+    let (mut t0, borrow) = lo.overflowing_sub(hi_hi);
+    if borrow {
+        t0 = t0.wrapping_sub(EPSILON);
+    }
+
+    // + hi_lo * (2^32 - 1); on overflow, 2^64 was subtracted, which is compensated by adding EPSILON
+    let t1 = hi_lo * EPSILON;
+    let (mut result, carry) = t0.overflowing_add(t1);
+    if carry {
+        result = result.wrapping_add(EPSILON);
+    }
+
+    return if result >= M { result - M } else { result };
+}
+
+// OPERATOR OVERLOADS
+// ================================================================================================
+
+impl Add for BaseElement {
+    type Output = BaseElement;
+
+    #[inline(always)]
+    fn add(self, rhs: BaseElement) -> BaseElement {
+        let (result, overflow) = self.0.overflowing_add(rhs.0);
+        // on overflow, 2^64 = EPSILON (mod M); the result cannot overflow again
+        let result = if overflow { result + EPSILON } else { result };
+        return BaseElement(if result >= M { result - M } else { result });
+    }
+}
+
+impl AddAssign for BaseElement {
+    #[inline(always)]
+    fn add_assign(&mut self, rhs: BaseElement) {
+        *self = *self + rhs;
+    }
+}
+
+impl Sub for BaseElement {
+    type Output = BaseElement;
+
+    #[inline(always)]
+    fn sub(self, rhs: BaseElement) -> BaseElement {
+        return BaseElement(if self.0 < rhs.0 { M - rhs.0 + self.0 } else { self.0 - rhs.0 });
+    }
+}
+
+impl SubAssign for BaseElement {
+    #[inline(always)]
+    fn sub_assign(&mut self, rhs: BaseElement) {
+        *self = *self - rhs;
+    }
+}
+
+impl Mul for BaseElement {
+    type Output = BaseElement;
+
+    #[inline(always)]
+    fn mul(self, rhs: BaseElement) -> BaseElement {
+        return BaseElement(reduce128((self.0 as u128) * (rhs.0 as u128)));
+    }
+}
+
+impl MulAssign for BaseElement {
+    #[inline(always)]
+    fn mul_assign(&mut self, rhs: BaseElement) {
+        *self = *self * rhs;
+    }
+}
+
+impl Div for BaseElement {
+    type Output = BaseElement;
+
+    fn div(self, rhs: BaseElement) -> BaseElement {
+        return self * rhs.inv();
+    }
+}
+
+impl DivAssign for BaseElement {
+    fn div_assign(&mut self, rhs: BaseElement) {
+        *self = *self / rhs;
+    }
+}
+
+impl Neg for BaseElement {
+    type Output = BaseElement;
+
+    #[inline(always)]
+    fn neg(self) -> BaseElement {
+        return BaseElement(if self.0 == 0 { 0 } else { M - self.0 });
+    }
+}
+
+// TYPE CONVERSIONS
+// ================================================================================================
+
+impl From<u128> for BaseElement {
+    /// Converts an integer into a field element by reducing it modulo the field modulus.
+    fn from(value: u128) -> BaseElement {
+        return BaseElement((value % (M as u128)) as u64);
+    }
+}
+
+impl From<u64> for BaseElement {
+    /// Converts an integer into a field element by reducing it modulo the field modulus.
+    fn from(value: u64) -> BaseElement {
+        return BaseElement::new(value);
+    }
+}
+
+impl From<BaseElement> for u128 {
+    fn from(value: BaseElement) -> u128 {
+        return value.0 as u128;
+    }
+}
+
+impl fmt::Display for BaseElement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return write!(f, "{}", self.0);
+    }
+}
+
+// TESTS
+// ================================================================================================
+#[cfg(test)]
+mod tests {
+
+    use crate::math::{ FieldElement, StarkField, ExtensibleField };
+    use super::{ BaseElement, M };
+
+    #[test]
+    fn arithmetic() {
+        let m = M as u128;
+        for _ in 0..100 {
+            let (a, b) = (BaseElement::rand(), BaseElement::rand());
+            let (x, y) = (a.as_int(), b.as_int());
+            assert_eq!((x + y) % m, (a + b).as_int());
+            assert_eq!((x + m - y) % m, (a - b).as_int());
+            assert_eq!((x * y) % m, (a * b).as_int());
+            assert_eq!((m - x) % m, (-a).as_int());
+            assert_eq!(BaseElement::ONE, a * a.inv());
+            assert_eq!(a, (a / b) * b);
+        }
+
+        // values close to the modulus
+        let a = BaseElement::new(M - 1);
+        assert_eq!(BaseElement::new(M - 2), a + a);
+        assert_eq!(BaseElement::ONE, a * a);
+        assert_eq!(BaseElement::ZERO, a + BaseElement::ONE);
+        assert_eq!(BaseElement::ZERO, BaseElement::ZERO.inv());
+    }
+
+    #[test]
+    fn get_root_of_unity() {
+        let root = BaseElement::get_root_of_unity(1 << 32);
+        assert_eq!(BaseElement::ONE, root.exp(1 << 32));
+        assert_ne!(BaseElement::ONE, root.exp(1 << 31));
+
+        let root = BaseElement::get_root_of_unity(8);
+        assert_eq!(BaseElement::ONE, root.exp(8));
+        assert_ne!(BaseElement::ONE, root.exp(4));
+    }
+
+    #[test]
+    fn extensions() {
+        let a = [BaseElement::rand(), BaseElement::rand()];
+        let b = <BaseElement as ExtensibleField<2>>::inv(a);
+        assert_eq!([BaseElement::ONE, BaseElement::ZERO], <BaseElement as ExtensibleField<2>>::mul(a, b));
+
+        let a = [BaseElement::rand(), BaseElement::rand(), BaseElement::rand()];
+        let b = <BaseElement as ExtensibleField<3>>::inv(a);
+        let one = [BaseElement::ONE, BaseElement::ZERO, BaseElement::ZERO];
+        assert_eq!(one, <BaseElement as ExtensibleField<3>>::mul(a, b));
+
+        // x * x * x = x + 1
+        let x = [BaseElement::ZERO, BaseElement::ONE, BaseElement::ZERO];
+        let x3 = <BaseElement as ExtensibleField<3>>::mul(<BaseElement as ExtensibleField<3>>::mul(x, x), x);
+        assert_eq!([BaseElement::ONE, BaseElement::ONE, BaseElement::ZERO], x3);
+    }
+}
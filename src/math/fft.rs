@@ -1,4 +1,4 @@
-use crate::math::field;
+use crate::math::FieldElement;
 use sp_std::vec::Vec;
 
 // CONSTANTS
@@ -13,7 +13,7 @@ const MAX_LOOP: usize = 256;
 /// performed in multiple threads. Number of threads must be a power of 2.
 /// 
 /// Adapted from: https://github.com/0xProject/OpenZKP/tree/master/algebra/primefield/src/fft
-pub fn fft_in_place(values: &mut [u128], twiddles: &[u128], count: usize, stride: usize, offset: usize, num_threads: usize) {
+pub fn fft_in_place<E: FieldElement>(values: &mut [E], twiddles: &[E], count: usize, stride: usize, offset: usize, num_threads: usize) {
     
     let size = values.len() / stride;
     debug_assert!(size.is_power_of_two());
@@ -59,20 +59,20 @@ pub fn fft_in_place(values: &mut [u128], twiddles: &[u128], count: usize, stride
     }
 }
 
-pub fn get_twiddles(root: u128, size: usize) -> Vec<u128> {
+pub fn get_twiddles<E: FieldElement>(root: E, size: usize) -> Vec<E> {
     assert!(size.is_power_of_two());
-    assert!(field::exp(root, size as u128) == field::ONE);
-    let mut twiddles = field::get_power_series(root, size / 2);
+    assert!(root.exp(size as u128) == E::ONE);
+    let mut twiddles = E::get_power_series(root, size / 2);
     permute(&mut twiddles);
     return twiddles;
 }
 
-pub fn get_inv_twiddles(root: u128, size: usize) -> Vec<u128> {
-    let inv_root = field::exp(root, (size - 1) as u128);
+pub fn get_inv_twiddles<E: FieldElement>(root: E, size: usize) -> Vec<E> {
+    let inv_root = root.exp((size - 1) as u128);
     return get_twiddles(inv_root, size);
 }
 
-pub fn permute(v: &mut [u128]) {
+pub fn permute<E: Copy>(v: &mut [E]) {
     let n = v.len();
     for i in 0..n {
         let j = permute_index(n, i);
@@ -93,67 +93,67 @@ fn permute_index(size: usize, index: usize) -> usize {
 }
 
 #[inline(always)]
-fn butterfly(values: &mut [u128], offset: usize, stride: usize) {
+fn butterfly<E: FieldElement>(values: &mut [E], offset: usize, stride: usize) {
     let i = offset;
     let j = offset + stride;
     let temp = values[i];
-    values[i] = field::add(temp, values[j]);
-    values[j] = field::sub(temp, values[j]);
+    values[i] = temp + values[j];
+    values[j] = temp - values[j];
 }
 
 #[inline(always)]
-fn butterfly_twiddle(values: &mut [u128], twiddle: u128, offset: usize, stride: usize) {
+fn butterfly_twiddle<E: FieldElement>(values: &mut [E], twiddle: E, offset: usize, stride: usize) {
     let i = offset;
     let j = offset + stride;
     let temp = values[i];
-    values[j] = field::mul(values[j], twiddle);
-    values[i] = field::add(temp, values[j]);
-    values[j] = field::sub(temp, values[j]);
+    values[j] = values[j] * twiddle;
+    values[i] = temp + values[j];
+    values[j] = temp - values[j];
 }
 
 // TESTS
 // ================================================================================================
 #[cfg(test)]
 mod tests {
-    use crate::math::{ field, polynom };
+    use crate::math::{ f128::BaseElement, polynom, FieldElement, StarkField };
 
     #[test]
     fn fft_in_place() {
         // degree 3
-        let mut p: [u128; 4] = [1, 2, 3, 4];
-        let g = field::get_root_of_unity(4);
-        let xs = field::get_power_series(g, 4);
-        let expected: Vec<u128> = xs.into_iter().map(|x| polynom::eval(&p, x)).collect();
+        let mut p: [BaseElement; 4] = [1, 2, 3, 4].map(BaseElement::new);
+        let g = BaseElement::get_root_of_unity(4);
+        let xs = BaseElement::get_power_series(g, 4);
+        let expected: Vec<BaseElement> = xs.into_iter().map(|x| polynom::eval(&p, x)).collect();
         let twiddles = super::get_twiddles(g, 4);
         super::fft_in_place(&mut p, &twiddles, 1, 1, 0, 1);
         super::permute(&mut p);
         assert_eq!(expected, p);
 
         // degree 7
-        let mut p: [u128; 8] = [1, 2, 3, 4, 5, 6, 7, 8];
-        let g = field::get_root_of_unity(8);
+        let mut p: [BaseElement; 8] = [1, 2, 3, 4, 5, 6, 7, 8].map(BaseElement::new);
+        let g = BaseElement::get_root_of_unity(8);
         let twiddles = super::get_twiddles(g, 8);
-        let xs = field::get_power_series(g, 8);
-        let expected: Vec<u128> = xs.into_iter().map(|x| polynom::eval(&p, x)).collect();
+        let xs = BaseElement::get_power_series(g, 8);
+        let expected: Vec<BaseElement> = xs.into_iter().map(|x| polynom::eval(&p, x)).collect();
         super::fft_in_place(&mut p, &twiddles, 1, 1, 0, 1);
         super::permute(&mut p);
         assert_eq!(expected, p);
 
         // degree 15
-        let mut p: [u128; 16] = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16];
-        let g = field::get_root_of_unity(16);
+        let mut p: [BaseElement; 16] = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16].map(BaseElement::new);
+        let g = BaseElement::get_root_of_unity(16);
         let twiddles = super::get_twiddles(g, 16);
-        let xs = field::get_power_series(g, 16);
-        let expected: Vec<u128> = xs.into_iter().map(|x| polynom::eval(&p, x)).collect();
+        let xs = BaseElement::get_power_series(g, 16);
+        let expected: Vec<BaseElement> = xs.into_iter().map(|x| polynom::eval(&p, x)).collect();
         super::fft_in_place(&mut p, &twiddles, 1, 1, 0, 1);
         super::permute(&mut p);
         assert_eq!(expected, p);
 
         // degree 1023
-        let mut p = field::rand_vector(1024);
-        let g = field::get_root_of_unity(1024);
-        let roots = field::get_power_series(g, 1024);
-        let expected = roots.iter().map(|x| polynom::eval(&p, *x)).collect::<Vec<u128>>();
+        let mut p = BaseElement::rand_vector(1024);
+        let g = BaseElement::get_root_of_unity(1024);
+        let roots = BaseElement::get_power_series(g, 1024);
+        let expected = roots.iter().map(|x| polynom::eval(&p, *x)).collect::<Vec<BaseElement>>();
         let twiddles = super::get_twiddles(g, 1024);
         super::fft_in_place(&mut p, &twiddles, 1, 1, 0, 1);
         super::permute(&mut p);
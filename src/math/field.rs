@@ -14,6 +14,9 @@ pub const M: u128 = 340282366920938463463374557953744961537;
 // 2^40 root of unity
 pub const G: u128 = 23953097886125630542083529559205016746;
 
+// 2^128 mod m = 45 * 2^40 - 1
+const C: u128 = 45 * (1 << 40) - 1;
+
 // public constants
 pub const MODULUS: u128 = M;
 pub const RANGE: Range<u128> = Range { start: 0, end: M };
@@ -36,8 +39,36 @@ pub fn sub(a: u128, b: u128) -> u128 {
 }
 
 /// Computes (a * b) % m; a and b are assumed to be valid field elements.
+///
+/// The reduction relies on the special form of the modulus: since 2^128 = 45 * 2^40 - 1 (mod m),
+/// the high 128 bits of the product can be folded into the low 128 bits by multiplying them
+/// by a 46-bit constant, and after two such folds the result is smaller than 2m.
+#[inline(always)]
 pub fn mul(a: u128, b: u128) -> u128 {
+    let (z0, z1, z2, z3) = mul_128x128(a, b);                 // z = a * b
+
+    // fold the high 128 bits of z into the low 128 bits: x = z_lo + z_hi * c
+    let (p0, p1) = ((z2 as u128) * C, (z3 as u128) * C);
+    let t0 = (z0 as u128) + (p0 as u64 as u128);
+    let t1 = (z1 as u128) + (p0 >> 64) + (p1 as u64 as u128) + (t0 >> 64);
+    let x2 = ((p1 >> 64) + (t1 >> 64)) as u64;                // x2 < 2^47
+    let (x0, x1) = (t0 as u64, t1 as u64);
+
+    // fold the remaining high bits: y = x_lo + x2 * c; x2 * c < 2^93, and so if the addition
+    // overflows, the wrapped-around value is small and adding c to it cannot overflow again
+    let x = ((x1 as u128) << 64) | (x0 as u128);
+    let (mut y, overflow) = x.overflowing_add((x2 as u128) * C);
+    if overflow {
+        y = y + C;
+    }
 
+    // make sure y is smaller than m
+    return if y >= M { y - M } else { y };
+}
+
+/// Computes (a * b) % m using a generic 128-bit reduction which does not rely on the form of
+/// the modulus; this is slower than `mul()`, and is kept to cross-check and benchmark `mul()`.
+pub fn mul_generic(a: u128, b: u128) -> u128 {
     let (x0, x1, x2) = mul_128x64(a, (b >> 64) as u64);         // x = a * b_hi
     let (mut x0, mut x1, x2) = mul_reduce(x0, x1, x2);          // x = x - (x >> 128) * m
     if x2 == 1 {
@@ -61,7 +92,7 @@ pub fn mul(a: u128, b: u128) -> u128 {
         let (t0, t1) = sub_modulus(y1, y2);                     // y = y - (m << 64)
         y1 = t0; y2 = t1;
     }
-    
+
     let (mut z0, mut z1, z2) = mul_reduce(y0, y1, y2);          // z = y - (y >> 128) * m
 
     // make sure z is smaller than m
@@ -292,6 +323,54 @@ pub fn from_bytes(bytes: &[u8]) -> u128 {
 // HELPER FUNCTIONS
 // ================================================================================================
 
+/// Computes the full 256-bit product of a and b, and returns it as four 64-bit limbs
+/// (least significant limb first).
+#[cfg(not(all(target_arch = "x86_64", target_feature = "bmi2", target_feature = "adx")))]
+#[inline(always)]
+fn mul_128x128(a: u128, b: u128) -> (u64, u64, u64, u64) {
+    let (a0, a1) = (a as u64 as u128, (a >> 64) as u64 as u128);
+    let (b0, b1) = (b as u64 as u128, (b >> 64) as u64 as u128);
+
+    let lo = a0 * b0;
+    let mid1 = a0 * b1;
+    let mid2 = a1 * b0;
+    let hi = a1 * b1;
+
+    let t1 = (lo >> 64) + (mid1 as u64 as u128) + (mid2 as u64 as u128);
+    let t2 = (hi as u64 as u128) + (mid1 >> 64) + (mid2 >> 64) + (t1 >> 64);
+    let z3 = (hi >> 64) + (t2 >> 64);
+    return (lo as u64, t1 as u64, t2 as u64, z3 as u64);
+}
+
+/// Same as above, but uses MULX and ADCX/ADOX instructions; this version is compiled only
+/// when BMI2 and ADX target features are enabled (e.g. with `-C target-cpu=native`).
+#[cfg(all(target_arch = "x86_64", target_feature = "bmi2", target_feature = "adx"))]
+#[inline(always)]
+fn mul_128x128(a: u128, b: u128) -> (u64, u64, u64, u64) {
+    use core::arch::x86_64::{ _mulx_u64, _addcarryx_u64 };
+
+    let (a0, a1) = (a as u64, (a >> 64) as u64);
+    let (b0, b1) = (b as u64, (b >> 64) as u64);
+    unsafe {
+        let (mut lo_hi, mut mid1_hi, mut mid2_hi, mut hi_hi) = (0u64, 0u64, 0u64, 0u64);
+        let z0 = _mulx_u64(a0, b0, &mut lo_hi);
+        let mid1 = _mulx_u64(a0, b1, &mut mid1_hi);
+        let mid2 = _mulx_u64(a1, b0, &mut mid2_hi);
+        let hi = _mulx_u64(a1, b1, &mut hi_hi);
+
+        let (mut z1, mut z2, mut z3) = (0u64, 0u64, 0u64);
+        let carry = _addcarryx_u64(0, lo_hi, mid1, &mut z1);
+        let carry = _addcarryx_u64(carry, hi, mid1_hi, &mut z2);
+        _addcarryx_u64(carry, hi_hi, 0, &mut z3);
+
+        let carry = _addcarryx_u64(0, z1, mid2, &mut z1);
+        let carry = _addcarryx_u64(carry, z2, mid2_hi, &mut z2);
+        _addcarryx_u64(carry, z3, 0, &mut z3);
+
+        return (z0, z1, z2, z3);
+    }
+}
+
 #[inline(always)]
 fn mul_128x64(a: u128, b: u64) -> (u64, u64, u64) {
     let z_lo = ((a as u64) as u128) * (b as u128);
@@ -430,6 +509,35 @@ mod tests {
         }
     }
 
+    #[test]
+    fn mul_matches_reference() {
+        // values close to the edges of 64-bit limbs and close to the modulus
+        let m: u128 = super::MODULUS;
+        let mut edge_values = vec![0, 1, 2, m - 1, m - 2, (m + 1) / 2, super::C, super::C + 1];
+        for &limb in [u64::MAX as u128, (u64::MAX as u128) << 64, 1u128 << 64, 1u128 << 127].iter() {
+            edge_values.push(limb % m);
+            edge_values.push((limb - 1) % m);
+        }
+
+        for &r1 in edge_values.iter() {
+            for &r2 in edge_values.iter() {
+                assert_eq!(super::mul_generic(r1, r2), super::mul(r1, r2), "{} * {}", r1, r2);
+                assert_eq!(mul_biguint(r1, r2), super::mul(r1, r2), "{} * {}", r1, r2);
+            }
+        }
+
+        // random values, including values in which the high limb is close to the modulus
+        let v1: Vec<u128> = super::rand_vector(10000);
+        let v2: Vec<u128> = super::rand_vector(10000);
+        for i in 0..v1.len() {
+            let r1 = v1[i];
+            let r2 = if i % 2 == 0 { v2[i] } else { m - 1 - (v2[i] >> 64) };
+            let expected = mul_biguint(r1, r2);
+            assert_eq!(expected, super::mul_generic(r1, r2));
+            assert_eq!(expected, super::mul(r1, r2));
+        }
+    }
+
     #[test]
     fn inv() {
         // identity
@@ -455,4 +563,13 @@ mod tests {
         assert_eq!(expected, root_39);
         assert_eq!(1, super::exp(root_39, u128::pow(2, 39)));
     }
-}
\ No newline at end of file
+
+    // HELPER FUNCTIONS
+    // --------------------------------------------------------------------------------------------
+    fn mul_biguint(a: u128, b: u128) -> u128 {
+        let result = (BigUint::from(a) * BigUint::from(b)) % BigUint::from(super::M);
+        let mut bytes = result.to_bytes_le();
+        bytes.resize(16, 0);
+        return u128::from_le_bytes(bytes[..].try_into().unwrap());
+    }
+}
@@ -2,4 +2,13 @@ pub mod field;
 pub mod fft;
 pub mod polynom;
 pub mod quartic;
-pub mod parallel;
\ No newline at end of file
+pub mod batch;
+pub mod parallel;
+pub mod f128;
+pub mod f64;
+
+mod traits;
+pub use traits::{ FieldElement, StarkField, ExtensibleField };
+
+mod extensions;
+pub use extensions::{ QuadExtension, CubeExtension };
@@ -1,5 +1,5 @@
 use crossbeam_utils::thread;
-use crate::math::{ field };
+use crate::math::FieldElement;
 use crate::utils::{ uninit_vector };
 use sp_std::vec::Vec;
 // use wasm_bindgen_test::*;
@@ -9,7 +9,7 @@ use sp_std::vec::Vec;
 
 /// Computes a[i] + b[i] for all i and returns the results. The addition is split into batches
 /// which are distributed across multiple threads.
-pub fn add(a: &[u128], b: &[u128], num_threads: usize) -> Vec<u128> {
+pub fn add<E: FieldElement>(a: &[E], b: &[E], num_threads: usize) -> Vec<E> {
     let n = a.len();
     assert!(n == b.len(), "number of values must be the same for both operands");
     // assert!(n % num_threads == 0, "number of values must be divisible by number of threads");
@@ -18,16 +18,16 @@ pub fn add(a: &[u128], b: &[u128], num_threads: usize) -> Vec<u128> {
     // allocate space for the results
     let mut result = uninit_vector(n);
     for i in(0..n){
-        result[i] = field::add(a[i], b[i]);
+        result[i] = a[i] + b[i];
 
     }
     // add batches of values in separate threads
     // thread::scope(|s| {
     //     for i in (0..n).step_by(batch_size) {
-    //         let result = unsafe { &mut *(&mut result[..] as *mut [u128]) };
+    //         let result = unsafe { &mut *(&mut result[..] as *mut [E]) };
     //         s.spawn(move |_| {
     //             for j in i..(i + batch_size) {
-    //                 result[j] = field::add(a[j], b[j]);
+    //                 result[j] = a[j] + b[j];
     //             }
     //         });
     //     }
@@ -39,22 +39,22 @@ pub fn add(a: &[u128], b: &[u128], num_threads: usize) -> Vec<u128> {
 
 /// Computes a[i] + b[i] for all i and stores the results in b[i]. The addition is split into
 /// batches which are distributed across multiple threads.
-pub fn add_in_place(a: &mut [u128], b: &[u128], num_threads: usize) {
+pub fn add_in_place<E: FieldElement>(a: &mut [E], b: &[E], num_threads: usize) {
     let n = a.len();
     assert!(n == b.len(), "number of values must be the same for both operands");
     //assert!(n % num_threads == 0, "number of values must be divisible by number of threads");
     for i in(0..n){
-        a[i] = field::add(a[i],b[i]);
+        a[i] = a[i] + b[i];
     }
     
     // let batch_size = n / num_threads;
     // // add batches of values in separate threads
     // thread::scope(|s| {
     //     for i in (0..n).step_by(batch_size) {
-    //         let a = unsafe { &mut *(a as *mut [u128]) };
+    //         let a = unsafe { &mut *(a as *mut [E]) };
     //         s.spawn(move |_| {
     //             for j in i..(i + batch_size) {
-    //                 a[j] = field::add(a[j], b[j]);
+    //                 a[j] = a[j] + b[j];
     //             }
     //         });
     //     }
@@ -68,22 +68,22 @@ pub fn add_in_place(a: &mut [u128], b: &[u128], num_threads: usize) {
 
 /// Computes a[i] - b for all i and stores the results in a[i]. The subtraction is split into
 /// batches which are distributed across multiple threads.
-pub fn sub_const_in_place(a: &mut [u128], b: u128, num_threads: usize) {
+pub fn sub_const_in_place<E: FieldElement>(a: &mut [E], b: E, num_threads: usize) {
     let n = a.len();
     assert!(n % num_threads == 0, "number of values must be divisible by number of threads");
     // let batch_size = n / num_threads;
 
     for i in(0..n){
-        a[i] = field::sub(a[i], b);
+        a[i] = a[i] - b;
 
     };
     // subtract batches of values in separate threads
     // thread::scope(|s| {
     //     for i in (0..n).step_by(batch_size) {
-    //         let a = unsafe { &mut *(a as *mut [u128]) };
+    //         let a = unsafe { &mut *(a as *mut [E]) };
     //         s.spawn(move |_| {
     //             for j in i..(i + batch_size) {
-    //                 a[j] = field::sub(a[j], b);
+    //                 a[j] = a[j] - b;
     //             }
     //         });
     //     }
@@ -95,7 +95,7 @@ pub fn sub_const_in_place(a: &mut [u128], b: u128, num_threads: usize) {
 
 /// Computes a[i] * b[i] for all i and returns the results. The multiplication is split into
 /// batches which are distributed across multiple threads.
-pub fn mul(a: &[u128], b: &[u128], num_threads: usize) -> Vec<u128> {
+pub fn mul<E: FieldElement>(a: &[E], b: &[E], num_threads: usize) -> Vec<E> {
     let n = a.len();
     assert!(n == b.len(), "number of values must be the same for both operands");
     // assert!(n % num_threads == 0, "number of values must be divisible by number of threads");
@@ -103,20 +103,20 @@ pub fn mul(a: &[u128], b: &[u128], num_threads: usize) -> Vec<u128> {
 
     // allocate space for the results
     let mut result = uninit_vector(n);
-    // let result = unsafe { &mut *(&mut result[..] as *mut [u128]) };
+    // let result = unsafe { &mut *(&mut result[..] as *mut [E]) };
 
     for i in (0..n) {
-        result[i] = field::mul(a[i], b[i]);
+        result[i] = a[i] * b[i];
 
     }
 
     // // multiply batches of values in separate threads
     // thread::scope(|s| {
     //     for i in (0..n).step_by(batch_size) {
-    //         let result = unsafe { &mut *(&mut result[..] as *mut [u128]) };
+    //         let result = unsafe { &mut *(&mut result[..] as *mut [E]) };
     //         s.spawn(move |_| {
     //             for j in i..(i + batch_size) {
-    //                 result[j] = field::mul(a[j], b[j]);
+    //                 result[j] = a[j] * b[j];
     //             }
     //         });
     //     }
@@ -128,22 +128,22 @@ pub fn mul(a: &[u128], b: &[u128], num_threads: usize) -> Vec<u128> {
 
 /// Computes a[i] * b[i] for all i and stores the results in b[i]. The multiplication is 
 /// split into batches which are distributed across multiple threads.
-pub fn mul_in_place(a: &mut [u128], b: &[u128], num_threads: usize) {
+pub fn mul_in_place<E: FieldElement>(a: &mut [E], b: &[E], num_threads: usize) {
     let n = a.len();
     assert!(n == b.len(), "number of values must be the same for both operands");
     // assert!(n % num_threads == 0, "number of values must be divisible by number of threads");
     // let batch_size = n / num_threads;
     for i in (0..n){
-        a[i] = field::mul(a[i], b[i]);
+        a[i] = a[i] * b[i];
 
     }
     // multiply batches of values in separate threads
     // thread::scope(|s| {
     //     for i in (0..n).step_by(batch_size) {
-    //         let a = unsafe { &mut *(a as *mut [u128]) };
+    //         let a = unsafe { &mut *(a as *mut [E]) };
     //         s.spawn(move |_| {
     //             for j in i..(i + batch_size) {
-    //                 a[j] = field::mul(a[j], b[j]);
+    //                 a[j] = a[j] * b[j];
     //             }
     //         });
     //     }
@@ -152,22 +152,22 @@ pub fn mul_in_place(a: &mut [u128], b: &[u128], num_threads: usize) {
 
 /// Computes a[i] + b[i] * c for all i and saves result into a. The operation is 
 /// split into batches which are distributed across multiple threads.
-pub fn mul_acc(a: &mut[u128], b: &[u128], c: u128, num_threads: usize) {
+pub fn mul_acc<E: FieldElement>(a: &mut[E], b: &[E], c: E, num_threads: usize) {
     let n = a.len();
     assert!(n == b.len(), "number of values must be the same for both arrays");
     // assert!(n % num_threads == 0, "number of values must be divisible by number of threads");
     // let batch_size = n / num_threads;
     for i in (0..n){
-        a[i] = field::add(a[i], field::mul(b[i], c));
+        a[i] = a[i] + b[i] * c;
 
     }
     // // accumulate batches of values in separate threads
     // thread::scope(|s| {
     //     for i in (0..n).step_by(batch_size) {
-    //         let a = unsafe { &mut *(a as *mut [u128]) };
+    //         let a = unsafe { &mut *(a as *mut [E]) };
     //         s.spawn(move |_| {
     //             for j in i..(i + batch_size) {
-    //                 a[j] = field::add(a[j], field::mul(b[j], c));
+    //                 a[j] = a[j] + b[j] * c;
     //             }
     //         });
     //     }
@@ -179,7 +179,7 @@ pub fn mul_acc(a: &mut[u128], b: &[u128], c: u128, num_threads: usize) {
 
 /// Computes multiplicative inverse of provided values. The inversion is split into batches which
 /// are distributed across multiple threads.
-pub fn inv(values: &[u128], num_threads: usize) -> Vec<u128> {
+pub fn inv<E: FieldElement>(values: &[E], num_threads: usize) -> Vec<E> {
     let n = values.len();
     // assert!(n % num_threads == 0, "number of values must be divisible by number of threads");
     // let batch_size = n / num_threads;
@@ -187,17 +187,17 @@ pub fn inv(values: &[u128], num_threads: usize) -> Vec<u128> {
     // allocate space for the results
     let result = uninit_vector(n);
         let values_slice = &values[..];
-        let values_slice = unsafe { &*(values_slice as *const _ as *const [u128]) };
+        let values_slice = unsafe { &*(values_slice as *const _ as *const [E]) };
         let result_slice = &result[..];
-        let result_slice = unsafe { &mut *(result_slice as *const _ as *mut [u128]) };
-        field::inv_many_fill(values_slice, result_slice);
+        let result_slice = unsafe { &mut *(result_slice as *const _ as *mut [E]) };
+        E::inv_many_fill(values_slice, result_slice);
     // break up the values into batches and invert each batch in a separate thread
     // thread::scope(|s| {
     //     for i in (0..n).step_by(batch_size) {
     //         let values_slice = &values[i..(i + batch_size)];
-    //         let values_slice = unsafe { &*(values_slice as *const _ as *const [u128]) };
+    //         let values_slice = unsafe { &*(values_slice as *const _ as *const [E]) };
     //         let result_slice = &result[i..(i + batch_size)];
-    //         let result_slice = unsafe { &mut *(result_slice as *const _ as *mut [u128]) };
+    //         let result_slice = unsafe { &mut *(result_slice as *const _ as *mut [E]) };
     //         s.spawn(move |_| {
     //             field::inv_many_fill(values_slice, result_slice);
     //         });
@@ -212,20 +212,20 @@ pub fn inv(values: &[u128], num_threads: usize) -> Vec<u128> {
 // ================================================================================================
 #[cfg(test)]
 mod tests {
-    use crate::math::{ field };
+    use crate::math::{ f128::BaseElement, FieldElement };
 
     #[test]
     fn add() {
 
         let n: usize = 1024;
         let num_threads: usize = 4;
-        let x = field::rand_vector(n);
-        let y = field::rand_vector(n);
+        let x = BaseElement::rand_vector(n);
+        let y = BaseElement::rand_vector(n);
 
         // compute expected results
-        let mut expected = vec![field::ZERO; n];
+        let mut expected = vec![BaseElement::ZERO; n];
         for i in 0..n {
-            expected[i] = field::add(x[i], y[i]);
+            expected[i] = x[i] + y[i];
         }
 
         assert_eq!(expected, super::add(&x, &y, num_threads));
@@ -236,13 +236,13 @@ mod tests {
 
         let n: usize = 1024;
         let num_threads: usize = 4;
-        let x = field::rand_vector(n);
-        let y = field::rand_vector(n);
+        let x = BaseElement::rand_vector(n);
+        let y = BaseElement::rand_vector(n);
 
         // compute expected results
-        let mut expected = vec![field::ZERO; n];
+        let mut expected = vec![BaseElement::ZERO; n];
         for i in 0..n {
-            expected[i] = field::add(x[i], y[i]);
+            expected[i] = x[i] + y[i];
         }
 
         let mut z = y.clone();
@@ -255,13 +255,13 @@ mod tests {
 
         let n: usize = 1024;
         let num_threads: usize = 4;
-        let mut x = field::rand_vector(n);
-        let y = field::rand();
+        let mut x = BaseElement::rand_vector(n);
+        let y = BaseElement::rand();
 
         // compute expected results
-        let mut expected = vec![field::ZERO; n];
+        let mut expected = vec![BaseElement::ZERO; n];
         for i in 0..n {
-            expected[i] = field::sub(x[i], y);
+            expected[i] = x[i] - y;
         }
 
         super::sub_const_in_place(&mut x, y, num_threads);
@@ -273,13 +273,13 @@ mod tests {
 
         let n: usize = 1024;
         let num_threads: usize = 4;
-        let x = field::rand_vector(n);
-        let y = field::rand_vector(n);
+        let x = BaseElement::rand_vector(n);
+        let y = BaseElement::rand_vector(n);
 
         // compute expected results
-        let mut expected = vec![field::ZERO; n];
+        let mut expected = vec![BaseElement::ZERO; n];
         for i in 0..n {
-            expected[i] = field::mul(x[i], y[i]);
+            expected[i] = x[i] * y[i];
         }
 
         assert_eq!(expected, super::mul(&x, &y, num_threads));
@@ -290,13 +290,13 @@ mod tests {
 
         let n: usize = 1024;
         let num_threads: usize = 4;
-        let x = field::rand_vector(n);
-        let y = field::rand_vector(n);
+        let x = BaseElement::rand_vector(n);
+        let y = BaseElement::rand_vector(n);
 
         // compute expected results
-        let mut expected = vec![field::ZERO; n];
+        let mut expected = vec![BaseElement::ZERO; n];
         for i in 0..n {
-            expected[i] = field::mul(x[i], y[i]);
+            expected[i] = x[i] * y[i];
         }
 
         let mut z = y.clone();
@@ -308,13 +308,12 @@ mod tests {
     fn mul_acc() {
         let n: usize = 1024;
         let num_threads: usize = 4;
-        let mut x = field::rand_vector(n);
-        let y = field::rand_vector(n);
-        let z = field::rand();
+        let mut x = BaseElement::rand_vector(n);
+        let y = BaseElement::rand_vector(n);
+        let z = BaseElement::rand();
 
         // compute expected result
-        let mut expected = x.clone();
-        field::mul_acc(&mut expected, &y, z);
+        let expected = x.iter().zip(y.iter()).map(|(&x, &y)| x + y * z).collect::<Vec<_>>();
 
         super::mul_acc(&mut x, &y, z, num_threads);
         assert_eq!(expected, x);
@@ -325,10 +324,10 @@ mod tests {
 
         let n: usize = 1024;
         let num_threads: usize = 4;
-        let v = field::rand_vector(n);
+        let v = BaseElement::rand_vector(n);
 
         // compute expected results
-        let expected = field::inv_many(&v);
+        let expected = BaseElement::inv_many(&v);
 
         assert_eq!(expected, super::inv(&v, num_threads));
     }
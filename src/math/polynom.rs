@@ -1,5 +1,5 @@
 use sp_std::mem;
-use crate::math::{ field, fft };
+use crate::math::{ fft, FieldElement, StarkField };
 use crate::utils::{ uninit_vector, filled_vector };
 use sp_std::vec;
 use sp_std::vec::Vec;
@@ -9,12 +9,22 @@ use sp_std::vec::Vec;
 // ================================================================================================
 
 /// Evaluates polynomial `p` at coordinate `x`
-pub fn eval(p: &[u128], x: u128) -> u128 {
-    let mut y = field::ZERO;
-    let mut power_of_x = field::ONE;
+pub fn eval<E: FieldElement>(p: &[E], x: E) -> E {
+    let mut y = E::ZERO;
+    let mut power_of_x = E::ONE;
     for i in 0..p.len() {
-        y = field::add(y, field::mul(p[i], power_of_x));
-        power_of_x = field::mul(power_of_x, x);
+        y = y + p[i] * power_of_x;
+        power_of_x = power_of_x * x;
+    }
+    return y;
+}
+
+/// Evaluates polynomial `p` with coefficients from the base field at coordinate `x` from an
+/// extension of that field.
+pub fn eval_ext<E: FieldElement>(p: &[E::BaseField], x: E) -> E {
+    let mut y = E::ZERO;
+    for &coefficient in p.iter().rev() {
+        y = y * x + E::from(coefficient);
     }
     return y;
 }
@@ -23,8 +33,8 @@ pub fn eval(p: &[u128], x: u128) -> u128 {
 /// `p` is updated with results of the evaluation.
 /// 
 /// If `unpermute` parameter is set to false, the evaluations will be left in permuted state.
-pub fn eval_fft(p: &mut [u128], unpermute: bool) {
-    let g = field::get_root_of_unity(p.len());
+pub fn eval_fft<E: StarkField>(p: &mut [E], unpermute: bool) {
+    let g = E::get_root_of_unity(p.len());
     let twiddles = fft::get_twiddles(g, p.len());
     eval_fft_twiddles(p, &twiddles, unpermute);
 }
@@ -34,7 +44,7 @@ pub fn eval_fft(p: &mut [u128], unpermute: bool) {
 /// does not generate twiddles internally. Thus, the twiddles must be supplied as a parameter.
 /// 
 /// If `unpermute` parameter is set to false, the evaluations will be left in permuted state.
-pub fn eval_fft_twiddles(p: &mut [u128], twiddles: &[u128], unpermute: bool) {
+pub fn eval_fft_twiddles<E: FieldElement>(p: &mut [E], twiddles: &[E], unpermute: bool) {
     debug_assert!(p.len() == twiddles.len() * 2, "Invalid number of twiddles");
     // TODO: don't hard-code num_threads
     fft::fft_in_place(p, &twiddles, 1, 1, 0, 1);
@@ -43,33 +53,44 @@ pub fn eval_fft_twiddles(p: &mut [u128], twiddles: &[u128], unpermute: bool) {
     }
 }
 
+/// Multiplies every coefficient p_i of polynomial `p` by offset^i; evaluating the resulting
+/// polynomial over a domain is the same as evaluating `p` over the domain shifted by `offset`.
+pub fn shift<E: FieldElement>(p: &mut [E], offset: E) {
+    if offset == E::ONE { return; }
+    let mut power_of_offset = E::ONE;
+    for i in 0..p.len() {
+        p[i] = p[i] * power_of_offset;
+        power_of_offset = power_of_offset * offset;
+    }
+}
+
 // POLYNOMIAL INTERPOLATION
 // ================================================================================================
 
 /// Uses Lagrange interpolation to build a polynomial from X and Y coordinates.
-pub fn interpolate(xs: &[u128], ys: &[u128]) -> Vec<u128> {
+pub fn interpolate<E: FieldElement>(xs: &[E], ys: &[E]) -> Vec<E> {
     debug_assert!(xs.len() == ys.len(), "Number of X and Y coordinates must be the same");
 
     let roots = get_zero_roots(xs);
-    let mut divisor = [field::ZERO, field::ONE];
-    let mut numerators: Vec<Vec<u128>> = Vec::with_capacity(xs.len());
+    let mut divisor = [E::ZERO, E::ONE];
+    let mut numerators: Vec<Vec<E>> = Vec::with_capacity(xs.len());
     for i in 0..xs.len() {
-        divisor[0] = field::neg(xs[i]);
+        divisor[0] = -xs[i];
         numerators.push(div(&roots, &divisor));
     }
 
-    let mut denominators: Vec<u128> = Vec::with_capacity(xs.len());
+    let mut denominators: Vec<E> = Vec::with_capacity(xs.len());
     for i in 0..xs.len() {
         denominators.push(eval(&numerators[i], xs[i]));
     }
-    let denominators = field::inv_many(&denominators);
+    let denominators = E::inv_many(&denominators);
 
-    let mut result = vec![field::ZERO; xs.len()];
+    let mut result = vec![E::ZERO; xs.len()];
     for i in 0..xs.len() {
-        let y_slice = field::mul(ys[i], denominators[i]);
+        let y_slice = ys[i] * denominators[i];
         for j in 0..xs.len() {
-            if numerators[i][j] != field::ZERO && ys[i] != field::ZERO {
-                result[j] = field::add(result[j], field::mul(numerators[i][j], y_slice));
+            if numerators[i][j] != E::ZERO && ys[i] != E::ZERO {
+                result[j] = result[j] + numerators[i][j] * y_slice;
             }
         }
     }
@@ -81,8 +102,8 @@ pub fn interpolate(xs: &[u128], ys: &[u128]) -> Vec<u128> {
 /// is done in-place, meaning `v` is updated with polynomial coefficients.
 /// 
 /// If `unpermute` parameter is set to false, the coefficients will be left in permuted state.
-pub fn interpolate_fft(v: &mut [u128], unpermute: bool) {
-    let g = field::get_root_of_unity(v.len());
+pub fn interpolate_fft<E: FieldElement>(v: &mut [E], unpermute: bool) {
+    let g = E::from(E::BaseField::get_root_of_unity(v.len()));
     let twiddles = fft::get_inv_twiddles(g, v.len());
     interpolate_fft_twiddles(v, &twiddles, unpermute);
 }
@@ -93,12 +114,12 @@ pub fn interpolate_fft(v: &mut [u128], unpermute: bool) {
 /// must be supplied as a parameter.
 /// 
 /// If `unpermute` parameter is set to false, the evaluations will be left in permuted state.
-pub fn interpolate_fft_twiddles(v: &mut [u128], inv_twiddles: &[u128], unpermute: bool) {
+pub fn interpolate_fft_twiddles<E: FieldElement>(v: &mut [E], inv_twiddles: &[E], unpermute: bool) {
     // TODO: don't hard-code num_threads
     fft::fft_in_place(v, &inv_twiddles, 1, 1, 0, 1);
-    let inv_length = field::inv(v.len() as u128);
+    let inv_length = E::from(v.len() as u128).inv();
     for e in v.iter_mut() {
-        *e = field::mul(*e, inv_length);
+        *e = *e * inv_length;
     }
     if unpermute {
         fft::permute(v);
@@ -109,54 +130,54 @@ pub fn interpolate_fft_twiddles(v: &mut [u128], inv_twiddles: &[u128], unpermute
 // ================================================================================================
 
 /// Adds polynomial `a` to polynomial `b`
-pub fn add(a: &[u128], b: &[u128]) -> Vec<u128> {
+pub fn add<E: FieldElement>(a: &[E], b: &[E]) -> Vec<E> {
     let result_len = sp_std::cmp::max(a.len(), b.len());
     let mut result = Vec::with_capacity(result_len);
     for i in 0..result_len {
-        let c1 = if i < a.len() { a[i] } else { field::ZERO };
-        let c2 = if i < b.len() { b[i] } else { field::ZERO };
-        result.push(field::add(c1, c2));
+        let c1 = if i < a.len() { a[i] } else { E::ZERO };
+        let c2 = if i < b.len() { b[i] } else { E::ZERO };
+        result.push(c1 + c2);
     }
     return result;
 }
 
 /// Subtracts polynomial `b` from polynomial `a`
-pub fn sub(a: &[u128], b: &[u128]) -> Vec<u128> {
+pub fn sub<E: FieldElement>(a: &[E], b: &[E]) -> Vec<E> {
     let result_len = sp_std::cmp::max(a.len(), b.len());
     let mut result = Vec::with_capacity(result_len);
     for i in 0..result_len {
-        let c1 = if i < a.len() { a[i] } else { field::ZERO };
-        let c2 = if i < b.len() { b[i] } else { field::ZERO };
-        result.push(field::sub(c1, c2));
+        let c1 = if i < a.len() { a[i] } else { E::ZERO };
+        let c2 = if i < b.len() { b[i] } else { E::ZERO };
+        result.push(c1 - c2);
     }
     return result;
 }
 
 /// Multiplies polynomial `a` by polynomial `b`
-pub fn mul(a: &[u128], b: &[u128]) -> Vec<u128> {
+pub fn mul<E: FieldElement>(a: &[E], b: &[E]) -> Vec<E> {
     let result_len = a.len() + b.len() - 1;
-    let mut result = vec![field::ZERO; result_len];
+    let mut result = vec![E::ZERO; result_len];
     for i in 0..a.len() {
         for j in 0..b.len() {
-            let s = field::mul(a[i], b[j]);
-            result[i + j] = field::add(result[i + j], s);
+            let s = a[i] * b[j];
+            result[i + j] = result[i + j] + s;
         }
     }
     return result;
 }
 
 /// Multiplies every coefficient of polynomial `p` by constant `k`
-pub fn mul_by_const(p: &[u128], k: u128) -> Vec<u128> {
+pub fn mul_by_const<E: FieldElement>(p: &[E], k: E) -> Vec<E> {
     let mut result = Vec::with_capacity(p.len());
     for i in 0..p.len() {
-        result.push(field::mul(p[i], k));
+        result.push(p[i] * k);
     }
     return result;
 }
 
 /// Divides polynomial `a` by polynomial `b`; if the polynomials don't divide evenly,
 /// the remainder is ignored.
-pub fn div(a: &[u128], b: &[u128]) -> Vec<u128> {
+pub fn div<E: FieldElement>(a: &[E], b: &[E]) -> Vec<E> {
     
     let mut apos = degree_of(a);
     let mut a = a.to_vec();
@@ -164,15 +185,15 @@ pub fn div(a: &[u128], b: &[u128]) -> Vec<u128> {
     let bpos = degree_of(b);
     assert!(apos >= bpos, "cannot divide by polynomial of higher degree");
     if bpos == 0 {
-        assert!(b[0] != field::ZERO, "cannot divide polynomial by zero");
+        assert!(b[0] != E::ZERO, "cannot divide polynomial by zero");
     }
 
-    let mut result = vec![field::ZERO; apos - bpos + 1];
+    let mut result = vec![E::ZERO; apos - bpos + 1];
     for i in (0..result.len()).rev() {
-        let quot = field::div(a[apos], b[bpos]);
+        let quot = a[apos] / b[bpos];
         result[i] = quot;
         for j in (0..bpos).rev() {
-            a[i + j] = field::sub(a[i + j], field::mul(b[j], quot));
+            a[i + j] = a[i + j] - b[j] * quot;
         }
         apos = apos.wrapping_sub(1);
     }
@@ -182,7 +203,7 @@ pub fn div(a: &[u128], b: &[u128]) -> Vec<u128> {
 
 /// Divides polynomial `a` by binomial (x - `b`) using Synthetic division method;
 /// if the polynomials don't divide evenly, the remainder is ignored.
-pub fn syn_div(a: &[u128], b: u128) -> Vec<u128> {
+pub fn syn_div<E: FieldElement>(a: &[E], b: E) -> Vec<E> {
     let mut result = a.to_vec();
     syn_div_in_place(&mut result, b);
     return result;
@@ -190,10 +211,10 @@ pub fn syn_div(a: &[u128], b: u128) -> Vec<u128> {
 
 /// Divides polynomial `a` by binomial (x - `b`) using Synthetic division method and stores the
 /// result in `a`; if the polynomials don't divide evenly, the remainder is ignored.
-pub fn syn_div_in_place(a: &mut [u128], b: u128) {
-    let mut c = field::ZERO;
+pub fn syn_div_in_place<E: FieldElement>(a: &mut [E], b: E) {
+    let mut c = E::ZERO;
     for i in (0..a.len()).rev() {
-        let temp = field::add(a[i], field::mul(b, c));
+        let temp = a[i] + b * c;
         a[i] = c;
         c = temp;
     }
@@ -202,31 +223,31 @@ pub fn syn_div_in_place(a: &mut [u128], b: u128) {
 /// Divides polynomial `a` by polynomial (x^degree - 1) / (x - exceptions[i]) for all i using
 /// Synthetic division method and stores the result in `a`; if the polynomials don't divide evenly,
 /// the remainder is ignored.
-pub fn syn_div_expanded_in_place(a: &mut [u128], degree: usize, exceptions: &[u128]) {
+pub fn syn_div_expanded_in_place<E: FieldElement>(a: &mut [E], degree: usize, exceptions: &[E]) {
 
     // allocate space for the result
-    let mut result = filled_vector(a.len(), a.len() + exceptions.len(), field::ZERO);
+    let mut result = filled_vector(a.len(), a.len() + exceptions.len(), E::ZERO);
 
     // compute a / (x^degree - 1)
     result.copy_from_slice(&a);
     let degree_offset = a.len() - degree;
     for i in (0..degree_offset).rev() {
-        result[i] = field::add(result[i], result[i + degree]);
+        result[i] = result[i] + result[i + degree];
     }
 
     // multiply result by (x - exceptions[i]) in place
     for &exception in exceptions {
 
         // exception term is negative
-        let exception = field::neg(exception);
+        let exception = -exception;
 
         // extend length of result since we are raising degree
         unsafe { result.set_len(result.len() + 1); }
 
         let mut next_term = result[0];
-        result[0] = field::ZERO;
+        result[0] = E::ZERO;
         for i in 0..(result.len() - 1) {
-            result[i] = field::add(result[i], field::mul(next_term, exception));
+            result[i] = result[i] + next_term * exception;
             mem::swap(&mut next_term, &mut result[i + 1]);
         }
     }
@@ -235,23 +256,23 @@ pub fn syn_div_expanded_in_place(a: &mut [u128], degree: usize, exceptions: &[u1
     a[..(degree_offset + exceptions.len())].copy_from_slice(&result[degree..]);
 
     // fill the rest of the result with 0
-    for i in (degree_offset + exceptions.len())..a.len() { a[i] = field::ZERO; }
+    for i in (degree_offset + exceptions.len())..a.len() { a[i] = E::ZERO; }
 }
 
 // DEGREE INFERENCE
 // ================================================================================================
 
 /// Returns degree of the polynomial `poly`
-pub fn degree_of(poly: &[u128]) -> usize {
+pub fn degree_of<E: FieldElement>(poly: &[E]) -> usize {
     for i in (0..poly.len()).rev() {
-        if poly[i] != field::ZERO { return i; }
+        if poly[i] != E::ZERO { return i; }
     }
     return 0;
 }
 
 /// Returns degree of a polynomial with which evaluates to `evaluations` over the domain of
 /// corresponding roots of unity.
-pub fn infer_degree(evaluations: &[u128]) -> usize {
+pub fn infer_degree<E: FieldElement>(evaluations: &[E]) -> usize {
     assert!(evaluations.len().is_power_of_two(), "number of evaluations must be a power of 2");
     let mut poly = evaluations.to_vec();
     interpolate_fft(&mut poly, true);
@@ -260,18 +281,18 @@ pub fn infer_degree(evaluations: &[u128]) -> usize {
 
 // HELPER FUNCTIONS
 // ================================================================================================
-fn get_zero_roots(xs: &[u128]) -> Vec<u128> {
+fn get_zero_roots<E: FieldElement>(xs: &[E]) -> Vec<E> {
     let mut n = xs.len() + 1;
     let mut result = uninit_vector(n);
     
     n -= 1;
-    result[n] = field::ONE;
+    result[n] = E::ONE;
 
     for i in 0..xs.len() {
         n -= 1;
-        result[n] = field::ZERO;
+        result[n] = E::ZERO;
         for j in n..xs.len() {
-            result[j] = field::sub(result[j], field::mul(result[j + 1], xs[i]));
+            result[j] = result[j] - result[j + 1] * xs[i];
         }
     }
 
@@ -283,36 +304,44 @@ fn get_zero_roots(xs: &[u128]) -> Vec<u128> {
 #[cfg(test)]
 mod tests {
 
-    use crate::math::{ field };
+    use crate::math::{ f128::BaseElement, f64, QuadExtension, FieldElement, StarkField };
     use crate::utils::remove_leading_zeros;
 
     #[test]
     fn eval() {
-        let x: u128 = 11269864713250585702;
-        let poly: [u128; 4] = [384863712573444386, 7682273369345308472, 13294661765012277990, 16234810094004944758];
+        let x = BaseElement::new(11269864713250585702);
+        let poly = to_elements(&[384863712573444386, 7682273369345308472, 13294661765012277990, 16234810094004944758]);
 
-        assert_eq!(0, super::eval(&[], x));
+        assert_eq!(BaseElement::ZERO, super::eval(&[], x));
 
         // constant
         assert_eq!(poly[0], super::eval(&poly[..1], x));
 
         // degree 1
-        assert_eq!(field::add(poly[0], field::mul(poly[1], x)), super::eval(&poly[..2], x));
+        assert_eq!(poly[0] + poly[1] * x, super::eval(&poly[..2], x));
 
         // degree 2
-        let x2 = field::exp(x, 2);
-        assert_eq!(field::add(
-            poly[0], field::add(
-            field::mul(poly[1], x), 
-            field::mul(poly[2], x2))), super::eval(&poly[..3], x));
+        let x2 = x.exp(2);
+        assert_eq!(poly[0] + poly[1] * x + poly[2] * x2, super::eval(&poly[..3], x));
 
         // degree 3
-        let x3 = field::exp(x, 3);
-        assert_eq!(field::add(
-            poly[0], field::add(
-            field::mul(poly[1], x), field::add(
-            field::mul(poly[2], x2),
-            field::mul(poly[3], x3)))), super::eval(&poly, x));
+        let x3 = x.exp(3);
+        assert_eq!(poly[0] + poly[1] * x + poly[2] * x2 + poly[3] * x3, super::eval(&poly, x));
+    }
+
+    #[test]
+    fn eval_ext() {
+        let poly = f64::BaseElement::rand_vector(16);
+
+        // evaluation at a base field point is the same as regular evaluation
+        let x = f64::BaseElement::rand();
+        assert_eq!(super::eval(&poly, x), super::eval_ext(&poly, x));
+
+        // evaluation at an extension field point is the same as evaluation of the polynomial
+        // with coefficients mapped into the extension field
+        let x = QuadExtension::<f64::BaseElement>::rand();
+        let ext_poly = poly.iter().map(|&c| QuadExtension::from(c)).collect::<Vec<_>>();
+        assert_eq!(super::eval(&ext_poly, x), super::eval_ext(&poly, x));
     }
 
     #[test]
@@ -320,44 +349,62 @@ mod tests {
         let n: usize = 1024;
 
         // create a random polynomial
-        let poly = field::rand_vector(n);
+        let poly = BaseElement::rand_vector(n);
 
         // evaluate polynomial using FFT
         let mut y1 = poly.clone();
         super::eval_fft(&mut y1, true);
 
         // evaluate polynomial using simple evaluation
-        let roots = field::get_power_series(field::get_root_of_unity(n), n);
-        let y2 = roots.iter().map(|&x| super::eval(&poly, x)).collect::<Vec<u128>>();
+        let roots = BaseElement::get_power_series(BaseElement::get_root_of_unity(n), n);
+        let y2 = roots.iter().map(|&x| super::eval(&poly, x)).collect::<Vec<BaseElement>>();
         
         assert_eq!(y1, y2);
     }
 
+    #[test]
+    fn shift() {
+        let n: usize = 64;
+        let offset = BaseElement::new(3);
+
+        // evaluate shifted polynomial over the domain using FFT
+        let poly = BaseElement::rand_vector(n);
+        let mut y1 = poly.clone();
+        super::shift(&mut y1, offset);
+        super::eval_fft(&mut y1, true);
+
+        // evaluate the original polynomial over the shifted domain
+        let roots = BaseElement::get_power_series(BaseElement::get_root_of_unity(n), n);
+        let y2 = roots.iter().map(|&x| super::eval(&poly, x * offset)).collect::<Vec<BaseElement>>();
+
+        assert_eq!(y1, y2);
+    }
+
     #[test]
     fn add() {
-        let poly1: [u128; 3] = [384863712573444386, 7682273369345308472, 13294661765012277990];
-        let poly2: [u128; 3] = [9918505539874556741, 16401861429499852246, 12181445947541805654];
+        let poly1 = to_elements(&[384863712573444386, 7682273369345308472, 13294661765012277990]);
+        let poly2 = to_elements(&[9918505539874556741, 16401861429499852246, 12181445947541805654]);
 
         // same degree
         let pr = vec![
-            field::add(poly1[0], poly2[0]),
-            field::add(poly1[1], poly2[1]),
-            field::add(poly1[2], poly2[2]),
+            poly1[0] + poly2[0],
+            poly1[1] + poly2[1],
+            poly1[2] + poly2[2],
         ];
         assert_eq!(pr, super::add(&poly1, &poly2));
 
         // poly1 is lower degree
         let pr = vec![
-            field::add(poly1[0], poly2[0]),
-            field::add(poly1[1], poly2[1]),
+            poly1[0] + poly2[0],
+            poly1[1] + poly2[1],
             poly2[2]
         ];
         assert_eq!(pr, super::add(&poly1[..2], &poly2));
 
         // poly2 is lower degree
         let pr = vec![
-            field::add(poly1[0], poly2[0]),
-            field::add(poly1[1], poly2[1]),
+            poly1[0] + poly2[0],
+            poly1[1] + poly2[1],
             poly1[2]
         ];
         assert_eq!(pr, super::add(&poly1, &poly2[..2]));
@@ -365,83 +412,80 @@ mod tests {
 
     #[test]
     fn sub() {
-        let poly1: [u128; 3] = [384863712573444386, 7682273369345308472, 13294661765012277990];
-        let poly2: [u128; 3] = [9918505539874556741, 16401861429499852246, 12181445947541805654];
+        let poly1 = to_elements(&[384863712573444386, 7682273369345308472, 13294661765012277990]);
+        let poly2 = to_elements(&[9918505539874556741, 16401861429499852246, 12181445947541805654]);
 
         // same degree
         let pr = vec![
-            field::sub(poly1[0], poly2[0]),
-            field::sub(poly1[1], poly2[1]),
-            field::sub(poly1[2], poly2[2]),
+            poly1[0] - poly2[0],
+            poly1[1] - poly2[1],
+            poly1[2] - poly2[2],
         ];
         assert_eq!(pr, super::sub(&poly1, &poly2));
 
         // poly1 is lower degree
         let pr = vec![
-            field::sub(poly1[0], poly2[0]),
-            field::sub(poly1[1], poly2[1]),
-            field::sub(0,        poly2[2]),
+            poly1[0] - poly2[0],
+            poly1[1] - poly2[1],
+            -poly2[2],
         ];
         assert_eq!(pr, super::sub(&poly1[..2], &poly2));
 
         // poly2 is lower degree
         let pr = vec![
-            field::sub(poly1[0], poly2[0]),
-            field::sub(poly1[1], poly2[1]),
-            field::sub(poly1[2], 0),
+            poly1[0] - poly2[0],
+            poly1[1] - poly2[1],
+            poly1[2],
         ];
         assert_eq!(pr, super::sub(&poly1, &poly2[..2]));
     }
 
     #[test]
     fn mul() {
-        let poly1: [u128; 3] = [384863712573444386, 7682273369345308472, 13294661765012277990];
-        let poly2: [u128; 3] = [9918505539874556741, 16401861429499852246, 12181445947541805654];
+        let poly1 = to_elements(&[384863712573444386, 7682273369345308472, 13294661765012277990]);
+        let poly2 = to_elements(&[9918505539874556741, 16401861429499852246, 12181445947541805654]);
 
         // same degree
         let pr = vec![
-            field::mul(poly1[0], poly2[0]),
-            field::add(field::mul(poly1[0], poly2[1]), field::mul(poly2[0], poly1[1])),
-            field::add(
-                field::mul(poly1[1], poly2[1]),
-                field::add(field::mul(poly1[2], poly2[0]), field::mul(poly2[2], poly1[0]))
-            ),
-            field::add(field::mul(poly1[2], poly2[1]), field::mul(poly2[2], poly1[1])),
-            field::mul(poly1[2], poly2[2])
+            poly1[0] * poly2[0],
+            poly1[0] * poly2[1] + poly2[0] * poly1[1],
+            poly1[1] * poly2[1] + poly1[2] * poly2[0] + poly2[2] * poly1[0],
+            poly1[2] * poly2[1] + poly2[2] * poly1[1],
+            poly1[2] * poly2[2]
             ];
         assert_eq!(pr, super::mul(&poly1, &poly2));
 
         // poly1 is lower degree
         let pr = vec![
-            field::mul(poly1[0], poly2[0]),
-            field::add(field::mul(poly1[0], poly2[1]), field::mul(poly2[0], poly1[1])),
-            field::add(field::mul(poly1[0], poly2[2]), field::mul(poly2[1], poly1[1])),
-            field::mul(poly1[1], poly2[2]),
+            poly1[0] * poly2[0],
+            poly1[0] * poly2[1] + poly2[0] * poly1[1],
+            poly1[0] * poly2[2] + poly2[1] * poly1[1],
+            poly1[1] * poly2[2],
             ];
         assert_eq!(pr, super::mul(&poly1[..2], &poly2));
 
         // poly2 is lower degree
         let pr = vec![
-            field::mul(poly1[0], poly2[0]),
-            field::add(field::mul(poly1[0], poly2[1]), field::mul(poly2[0], poly1[1])),
-            field::add(field::mul(poly1[2], poly2[0]), field::mul(poly2[1], poly1[1])),
-            field::mul(poly1[2], poly2[1]),
+            poly1[0] * poly2[0],
+            poly1[0] * poly2[1] + poly2[0] * poly1[1],
+            poly1[2] * poly2[0] + poly2[1] * poly1[1],
+            poly1[2] * poly2[1],
             ];
         assert_eq!(pr, super::mul(&poly1, &poly2[..2]));
     }
 
     #[test]
     fn mul_by_const() {
-        let poly = [384863712573444386, 7682273369345308472, 13294661765012277990];
-        let c: u128 = 11269864713250585702;
-        let pr = vec![ field::mul(poly[0], c), field::mul(poly[1], c), field::mul(poly[2], c) ];
+        let poly = to_elements(&[384863712573444386, 7682273369345308472, 13294661765012277990]);
+        let c = BaseElement::new(11269864713250585702);
+        let pr = vec![ poly[0] * c, poly[1] * c, poly[2] * c ];
         assert_eq!(pr, super::mul_by_const(&poly, c));
     }
 
     #[test]
     fn div() {
-        let poly1: Vec<u128> = vec![384863712573444386, 7682273369345308472, 13294661765012277990];
-        let poly2: Vec<u128> = vec![9918505539874556741, 16401861429499852246, 12181445947541805654];
+        let poly1 = to_elements(&[384863712573444386, 7682273369345308472, 13294661765012277990]);
+        let poly2 = to_elements(&[9918505539874556741, 16401861429499852246, 12181445947541805654]);
 
         // divide degree 4 by degree 2
         let poly3 = super::mul(&poly1, &poly2);
@@ -452,16 +496,16 @@ mod tests {
         assert_eq!(poly1[..2].to_vec(), super::div(&poly3, &poly2));
 
         // divide degree 3 by degree 3
-        let poly3 = super::mul_by_const(&poly1, 11269864713250585702);
-        assert_eq!(vec![11269864713250585702], super::div(&poly3, &poly1));
+        let poly3 = super::mul_by_const(&poly1, BaseElement::new(11269864713250585702));
+        assert_eq!(to_elements(&[11269864713250585702]), super::div(&poly3, &poly1));
     }
 
     #[test]
     fn syn_div() {
-        let poly = super::mul(&[2, 1], &[3, 1]);
+        let poly = super::mul(&to_elements(&[2, 1]), &to_elements(&[3, 1]));
 
-        let result = super::syn_div(&poly, field::neg(3));
-        let expected = super::div(&poly, &[3, 1]);
+        let result = super::syn_div(&poly, -BaseElement::new(3));
+        let expected = super::div(&poly, &to_elements(&[3, 1]));
 
         assert_eq!(expected, remove_leading_zeros(&result));
     }
@@ -470,17 +514,17 @@ mod tests {
     fn syn_div_expanded_in_place() {
 
         // build the polynomial
-        let ys = vec![0, 1, 2, 3, 0, 5, 6, 7, 0, 9, 10, 11, 12, 13, 14, 15];
+        let ys = to_elements(&[0, 1, 2, 3, 0, 5, 6, 7, 0, 9, 10, 11, 12, 13, 14, 15]);
         let mut poly = ys.clone();
         super::interpolate_fft(&mut poly, true);
 
         // build the divisor polynomial
-        let root = field::get_root_of_unity(poly.len());
-        let domain = field::get_power_series(root, poly.len());
+        let root = BaseElement::get_root_of_unity(poly.len());
+        let domain = BaseElement::get_power_series(root, poly.len());
 
-        let z_poly = vec![field::neg(field::ONE), 0, 0, 0, 1];
+        let z_poly = vec![-BaseElement::ONE, BaseElement::ZERO, BaseElement::ZERO, BaseElement::ZERO, BaseElement::ONE];
         let z_degree = z_poly.len() - 1;
-        let z_poly = super::div(&z_poly, &[field::neg(domain[12]), 1]);
+        let z_poly = super::div(&z_poly, &[-domain[12], BaseElement::ONE]);
         
         // compute the result
         let mut result = poly.clone();
@@ -494,26 +538,30 @@ mod tests {
 
     #[test]
     fn degree_of() {
-        assert_eq!(0, super::degree_of(&[]));
-        assert_eq!(0, super::degree_of(&[1]));
-        assert_eq!(1, super::degree_of(&[1, 2]));
-        assert_eq!(1, super::degree_of(&[1, 2, 0]));
-        assert_eq!(2, super::degree_of(&[1, 2, 3]));
-        assert_eq!(2, super::degree_of(&[1, 2, 3, 0]));
+        assert_eq!(0, super::degree_of(&to_elements(&[])));
+        assert_eq!(0, super::degree_of(&to_elements(&[1])));
+        assert_eq!(1, super::degree_of(&to_elements(&[1, 2])));
+        assert_eq!(1, super::degree_of(&to_elements(&[1, 2, 0])));
+        assert_eq!(2, super::degree_of(&to_elements(&[1, 2, 3])));
+        assert_eq!(2, super::degree_of(&to_elements(&[1, 2, 3, 0])));
     }
 
     #[test]
     fn infer_degree() {
-        let poly: Vec<u128> = vec![1, 2, 3, 4];
+        let poly = to_elements(&[1, 2, 3, 4]);
 
         let mut evaluations = poly.clone();
-        evaluations.resize(16, 0);
+        evaluations.resize(16, BaseElement::ZERO);
         super::eval_fft(&mut evaluations, true);
         assert_eq!(super::degree_of(&poly), super::infer_degree(&evaluations));
 
         let mut evaluations = poly.clone();
-        evaluations.resize(32, 0);
+        evaluations.resize(32, BaseElement::ZERO);
         super::eval_fft(&mut evaluations, true);
         assert_eq!(super::degree_of(&poly), super::infer_degree(&evaluations));
     }
-}
\ No newline at end of file
+
+    fn to_elements(values: &[u128]) -> Vec<BaseElement> {
+        return values.iter().map(|&v| BaseElement::new(v)).collect();
+    }
+}
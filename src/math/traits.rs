@@ -0,0 +1,182 @@
+use sp_std::{ fmt::{ Debug, Display }, ops::{ Add, Sub, Mul, Div, Neg, AddAssign, SubAssign, MulAssign, DivAssign } };
+use sp_std::vec::Vec;
+use serde::{ Serialize, de::DeserializeOwned };
+use crate::utils::uninit_vector;
+
+// FIELD ELEMENT
+// ================================================================================================
+
+/// Defines an element of a finite field. All arithmetic operators are implemented for field
+/// elements and perform modular arithmetic; thus, the internal representation of an element is
+/// never exposed to generic code.
+pub trait FieldElement:
+    Copy + Clone + Debug + Display + Default + PartialEq + Eq + Send + Sync
+    + From<u128> + From<<Self as FieldElement>::BaseField> + Serialize + DeserializeOwned
+    + Add<Self, Output = Self> + Sub<Self, Output = Self>
+    + Mul<Self, Output = Self> + Div<Self, Output = Self> + Neg<Output = Self>
+    + AddAssign<Self> + SubAssign<Self> + MulAssign<Self> + DivAssign<Self>
+{
+    /// The prime field over which this field is defined; for prime fields, this is the
+    /// field itself.
+    type BaseField: StarkField;
+
+    /// The additive identity.
+    const ZERO: Self;
+
+    /// The multiplicative identity.
+    const ONE: Self;
+
+    /// Number of bytes needed to encode an element.
+    const ELEMENT_BYTES: usize;
+
+    /// Degree of this field over its base field; for prime fields, this is 1.
+    const EXTENSION_DEGREE: usize;
+
+    /// Returns a multiplicative inverse of this element; the inverse of ZERO is ZERO.
+    fn inv(self) -> Self;
+
+    /// Returns this element raised to the specified `power`.
+    fn exp(self, power: u128) -> Self {
+        let mut r = Self::ONE;
+        let mut b = self;
+        let mut p = power;
+        while p > 0 {
+            if p & 1 == 1 {
+                r *= b;
+            }
+            p = p >> 1;
+            b *= b;
+        }
+        return r;
+    }
+
+    /// Generates a random field element.
+    fn rand() -> Self;
+
+    /// Generates a vector of pseudo-random field elements from a given `seed`.
+    fn prng_vector(seed: [u8; 32], length: usize) -> Vec<Self>;
+
+    /// Reads an element from its little-endian encoding; the encoding must consist of exactly
+    /// `ELEMENT_BYTES` bytes.
+    fn from_bytes(bytes: &[u8]) -> Self;
+
+    /// Re-interprets a slice of field elements as a slice of base field elements; every
+    /// element is represented by EXTENSION_DEGREE base field elements.
+    fn as_base_elements(elements: &[Self]) -> &[Self::BaseField];
+
+    /// Re-interprets a slice of base field elements as a slice of field elements; the length
+    /// of the slice must be divisible by EXTENSION_DEGREE.
+    fn slice_from_base_elements(elements: &[Self::BaseField]) -> &[Self];
+
+    /// Returns the product of this element and an element of the base field.
+    fn mul_base(self, other: Self::BaseField) -> Self {
+        return self * Self::from(other);
+    }
+
+    /// Generates a vector of random field elements.
+    fn rand_vector(length: usize) -> Vec<Self> {
+        return (0..length).map(|_| Self::rand()).collect();
+    }
+
+    /// Generates a pseudo-random field element from a given `seed`.
+    fn prng(seed: [u8; 32]) -> Self {
+        return Self::prng_vector(seed, 1)[0];
+    }
+
+    /// Computes multiplicative inverses of all slice elements using batch inversion method.
+    fn inv_many(values: &[Self]) -> Vec<Self> {
+        let mut result = uninit_vector(values.len());
+        Self::inv_many_fill(values, &mut result);
+        return result;
+    }
+
+    /// Computes multiplicative inverses of all slice elements using batch inversion method
+    /// and stores the result into the provided slice.
+    fn inv_many_fill(values: &[Self], result: &mut [Self]) {
+        let mut last = Self::ONE;
+        for i in 0..values.len() {
+            result[i] = last;
+            if values[i] != Self::ZERO {
+                last *= values[i];
+            }
+        }
+
+        last = last.inv();
+        for i in (0..values.len()).rev() {
+            if values[i] == Self::ZERO {
+                result[i] = Self::ZERO;
+            }
+            else {
+                result[i] *= last;
+                last *= values[i];
+            }
+        }
+    }
+
+    /// Generates a vector with values [1, b, b^2, b^3, b^4, ..., b^(length - 1)].
+    fn get_power_series(b: Self, length: usize) -> Vec<Self> {
+        let mut result = uninit_vector(length);
+        if length == 0 { return result; }
+        result[0] = Self::ONE;
+        for i in 1..result.len() {
+            result[i] = result[i - 1] * b;
+        }
+        return result;
+    }
+}
+
+// STARK FIELD
+// ================================================================================================
+
+/// Defines a prime field which can be used as the base field of a STARK: the multiplicative
+/// group of the field must have a large subgroup of order 2^TWO_ADICITY so that FFT-based
+/// interpolation and evaluation can be performed over domains of roots of unity.
+pub trait StarkField: FieldElement<BaseField = Self> {
+
+    /// Quadratic extension of the field used when a proof is generated over the extension;
+    /// fields for which the extension is not defined set this to the field itself.
+    type QuadField: FieldElement<BaseField = Self>;
+
+    /// Cubic extension of the field used when a proof is generated over the extension; fields
+    /// for which the extension is not defined set this to the field itself.
+    type CubeField: FieldElement<BaseField = Self>;
+
+    /// Prime modulus of the field.
+    const MODULUS: u128;
+
+    /// Largest k such that 2^k divides MODULUS - 1.
+    const TWO_ADICITY: u32;
+
+    /// A root of unity of order 2^TWO_ADICITY.
+    const TWO_ADIC_ROOT_OF_UNITY: Self;
+
+    /// Returns the canonical integer representation of this element.
+    fn as_int(&self) -> u128;
+
+    /// Returns a root of unity of the specified `order`; the order must be a power of 2 which
+    /// is not greater than 2^TWO_ADICITY.
+    fn get_root_of_unity(order: usize) -> Self {
+        assert!(order != 0, "cannot get root of unity for order 0");
+        assert!(order.is_power_of_two(), "order must be a power of 2");
+        assert!(order.trailing_zeros() <= Self::TWO_ADICITY,
+            "order cannot exceed 2^{}", Self::TWO_ADICITY);
+        let p = 1u128 << (Self::TWO_ADICITY - order.trailing_zeros());
+        return Self::TWO_ADIC_ROOT_OF_UNITY.exp(p);
+    }
+}
+
+// EXTENSIBLE FIELD
+// ================================================================================================
+
+/// Defines arithmetic in an extension of degree N of a prime field; an element of the extension
+/// is represented by N coefficients of a polynomial over the prime field, and the extension is
+/// defined by an irreducible polynomial of degree N.
+pub trait ExtensibleField<const N: usize>: Sized {
+
+    /// Returns the product of `a` and `b` in the extension field.
+    fn mul(a: [Self; N], b: [Self; N]) -> [Self; N];
+
+    /// Returns the multiplicative inverse of `x` in the extension field; the inverse of zero
+    /// is zero.
+    fn inv(x: [Self; N]) -> [Self; N];
+}
@@ -0,0 +1,258 @@
+use crate::{ math::field, programs::MerkleStore };
+use serde::{ Serialize, Deserialize };
+use sp_std::{ boxed::Box, collections::btree_map::BTreeMap, convert::TryFrom, vec, vec::Vec };
+use alloc::string::String;
+
+// TYPES AND INTERFACES
+// ================================================================================================
+
+/// Describes nondeterministic inputs which a program requests from the advice provider. Advice
+/// values are pushed onto the stack by READ operations in the order in which they are returned
+/// by the provider, and so the last value ends up at the top of the stack. Advice is not checked
+/// by the VM: programs must make sure that the values they receive are valid.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum AdviceRequest {
+    /// Inverse of the value at the top of the stack.
+    Inv,
+    /// Remainder and quotient of integer division of the second value on the stack by the value
+    /// at the top of the stack; the quotient ends up at the top of the stack.
+    Div,
+    /// Integer square root (rounded down) of the value at the top of the stack.
+    Sqrt,
+    /// The specified number of values computed by a custom handler with the specified id.
+    Custom(u32, u32),
+    /// Authentication path of a leaf in a Merkle tree of the specified depth; the root of the
+    /// tree is expected at the top of the stack (2 values) followed by the index of the leaf.
+    /// The sibling of the leaf ends up at the top of the stack.
+    MerklePath(u32),
+}
+
+/// Computes advice values for the requests of an executing program.
+pub trait AdviceProvider {
+
+    /// Returns advice values for the `request` based on the current state of the `stack`; the
+    /// stack contains all values currently on the stack, starting with the value at the top.
+    fn get_advice(&mut self, request: AdviceRequest, stack: &[u128]) -> Result<Vec<u128>, String>;
+}
+
+/// A handler which computes custom advice values based on the current state of the stack.
+pub type AdviceHandler = Box<dyn FnMut(&[u128]) -> Result<Vec<u128>, String>>;
+
+/// Advice provider which handles built-in requests and dispatches custom requests to the
+/// handlers registered with it. Merkle paths are served from the Merkle stores added to it.
+#[derive(Default)]
+pub struct AdviceHandlers {
+    handlers    : BTreeMap<u32, AdviceHandler>,
+    stores      : BTreeMap<[u128; 2], MerkleStore>,
+}
+
+// ADVICE REQUEST IMPLEMENTATION
+// ================================================================================================
+impl AdviceRequest {
+
+    /// Returns the number of values which must be provided for this request.
+    pub fn num_values(&self) -> usize {
+        return match self {
+            AdviceRequest::Inv              => 1,
+            AdviceRequest::Div              => 2,
+            AdviceRequest::Sqrt             => 1,
+            AdviceRequest::Custom(_, count) => *count as usize,
+            AdviceRequest::MerklePath(depth)=> 2 * (*depth as usize - 1),
+        };
+    }
+}
+
+impl sp_std::fmt::Display for AdviceRequest {
+    fn fmt(&self, f: &mut sp_std::fmt::Formatter) -> sp_std::fmt::Result {
+        return match self {
+            AdviceRequest::Inv                  => write!(f, "adv.inv"),
+            AdviceRequest::Div                  => write!(f, "adv.div"),
+            AdviceRequest::Sqrt                 => write!(f, "adv.sqrt"),
+            AdviceRequest::Custom(id, count)    => write!(f, "adv.{}.{}", id, count),
+            AdviceRequest::MerklePath(depth)    => write!(f, "adv.mpath.{}", depth),
+        };
+    }
+}
+
+// ADVICE HANDLERS IMPLEMENTATION
+// ================================================================================================
+impl AdviceHandlers {
+
+    /// Returns an advice provider which handles only built-in requests.
+    pub fn new() -> AdviceHandlers {
+        return AdviceHandlers { handlers: BTreeMap::new(), stores: BTreeMap::new() };
+    }
+
+    /// Registers the `handler` for custom requests with the specified `id`; a previously
+    /// registered handler with the same id is replaced.
+    pub fn register(&mut self, id: u32, handler: AdviceHandler) {
+        self.handlers.insert(id, handler);
+    }
+
+    /// Adds the `store` to the stores from which Merkle paths are served; paths are looked up
+    /// by the root of the store at the time it was added, and so the store cannot be changed
+    /// afterwards. A previously added store with the same root is replaced.
+    pub fn add_merkle_store(&mut self, store: MerkleStore) {
+        self.stores.insert(store.root(), store);
+    }
+
+    /// Returns the authentication path of the leaf of a tree of the specified `depth`; the
+    /// root of the tree and the index of the leaf are read from the `stack`.
+    fn get_merkle_path(&self, depth: u32, stack: &[u128]) -> Result<Vec<u128>, String> {
+        if stack.len() < 3 {
+            return Err(String::from("stack must contain at least 3 values"));
+        }
+
+        let root = [stack[1], stack[0]];
+        let store = match self.stores.get(&root) {
+            Some(store) => store,
+            None => return Err(format!("no Merkle store with root [{}, {}] was added", root[0], root[1])),
+        };
+        if store.depth() != depth as usize {
+            return Err(format!("Merkle store with root [{}, {}] has depth {}, but depth {} was requested",
+                root[0], root[1], store.depth(), depth));
+        }
+
+        // the index may not fit into usize on 32-bit targets even if it is within the tree
+        let index = match usize::try_from(stack[2]) {
+            Ok(index) if stack[2] >> (depth - 1) == 0 => index,
+            _ => return Err(format!("leaf index {} is out of bounds for a tree of depth {}", stack[2], depth)),
+        };
+
+        // values are pushed onto the stack one after another, and so nodes closer to the root
+        // must be provided first
+        let mut values = Vec::with_capacity(2 * (store.depth() - 1));
+        for node in store.path(index).iter().rev() {
+            values.push(node[0]);
+            values.push(node[1]);
+        }
+        return Ok(values);
+    }
+}
+
+impl AdviceProvider for AdviceHandlers {
+
+    fn get_advice(&mut self, request: AdviceRequest, stack: &[u128]) -> Result<Vec<u128>, String> {
+        return match request {
+            AdviceRequest::Custom(id, _) => match self.handlers.get_mut(&id) {
+                Some(handler) => handler(stack),
+                None => Err(format!("no handler is registered for advice {}", id)),
+            },
+            AdviceRequest::MerklePath(depth) => self.get_merkle_path(depth, stack),
+            _ => get_builtin_advice(request, stack),
+        };
+    }
+}
+
+impl <T: AdviceProvider + ?Sized> AdviceProvider for &mut T {
+
+    fn get_advice(&mut self, request: AdviceRequest, stack: &[u128]) -> Result<Vec<u128>, String> {
+        return (**self).get_advice(request, stack);
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Computes advice values for built-in requests.
+fn get_builtin_advice(request: AdviceRequest, stack: &[u128]) -> Result<Vec<u128>, String> {
+    let min_depth = if request == AdviceRequest::Div { 2 } else { 1 };
+    if stack.len() < min_depth {
+        return Err(format!("stack must contain at least {} values", min_depth));
+    }
+
+    return match request {
+        AdviceRequest::Inv => {
+            let x = stack[0];
+            if x == field::ZERO {
+                return Err(format!("cannot provide inverse of {}", x));
+            }
+            Ok(vec![field::inv(x)])
+        },
+        AdviceRequest::Div => {
+            let b = stack[0];
+            let a = stack[1];
+            if b == 0 {
+                return Err(format!("cannot provide quotient of {} divided by {}", a, b));
+            }
+            Ok(vec![a % b, a / b])
+        },
+        AdviceRequest::Sqrt => Ok(vec![isqrt(stack[0])]),
+        AdviceRequest::Custom(id, _) => Err(format!("advice {} is not a built-in advice", id)),
+        AdviceRequest::MerklePath(_) => Err(format!("{} is not a built-in advice", request)),
+    };
+}
+
+/// Returns the largest integer whose square is not greater than `x`.
+fn isqrt(x: u128) -> u128 {
+    if x < 2 { return x; }
+
+    // start from an estimate which is not smaller than the result, and refine it
+    // using Newton's method until it stops decreasing
+    let mut result = 1u128 << ((128 - x.leading_zeros() + 1) / 2);
+    loop {
+        let next = (result + x / result) / 2;
+        if next >= result { break; }
+        result = next;
+    }
+    return result;
+}
+
+// TESTS
+// ================================================================================================
+#[cfg(test)]
+mod tests {
+
+    use crate::{ math::field, programs::MerkleStore };
+    use super::{ AdviceHandlers, AdviceProvider, AdviceRequest };
+
+    #[test]
+    fn builtin_advice() {
+        let mut advice = AdviceHandlers::new();
+
+        let x = field::rand();
+        let result = advice.get_advice(AdviceRequest::Inv, &[x, 3]).unwrap();
+        assert_eq!(vec![field::inv(x)], result);
+        assert!(advice.get_advice(AdviceRequest::Inv, &[0, 3]).is_err());
+
+        let result = advice.get_advice(AdviceRequest::Div, &[7, 100]).unwrap();
+        assert_eq!(vec![2, 14], result);
+        assert!(advice.get_advice(AdviceRequest::Div, &[0, 100]).is_err());
+
+        for &(x, expected) in [(0, 0), (1, 1), (15, 3), (16, 4), (17, 4), (u128::MAX, u64::MAX as u128)].iter() {
+            let result = advice.get_advice(AdviceRequest::Sqrt, &[x]).unwrap();
+            assert_eq!(vec![expected], result);
+        }
+    }
+
+    #[test]
+    fn custom_advice() {
+        let mut advice = AdviceHandlers::new();
+        assert!(advice.get_advice(AdviceRequest::Custom(3, 2), &[1, 2]).is_err());
+
+        advice.register(3, Box::new(|stack| Ok(vec![stack[1], stack[0]])));
+        let result = advice.get_advice(AdviceRequest::Custom(3, 2), &[1, 2]).unwrap();
+        assert_eq!(vec![2, 1], result);
+    }
+
+    #[test]
+    fn merkle_path_advice() {
+        let store = MerkleStore::from_leaves(3, &[[1, 2], [3, 4], [5, 6]]);
+        let root = store.root();
+        let path = store.path(2);
+
+        let mut advice = AdviceHandlers::new();
+        let request = AdviceRequest::MerklePath(3);
+        assert!(advice.get_advice(request, &[root[1], root[0], 2]).is_err());
+
+        advice.add_merkle_store(store);
+        let result = advice.get_advice(request, &[root[1], root[0], 2]).unwrap();
+        assert_eq!(vec![path[1][0], path[1][1], path[0][0], path[0][1]], result);
+        assert_eq!(request.num_values(), result.len());
+
+        // index out of bounds, wrong depth, and unknown root
+        assert!(advice.get_advice(request, &[root[1], root[0], 4]).is_err());
+        assert!(advice.get_advice(AdviceRequest::MerklePath(4), &[root[1], root[0], 2]).is_err());
+        assert!(advice.get_advice(request, &[root[0], root[1], 2]).is_err());
+    }
+}
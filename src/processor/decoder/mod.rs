@@ -3,6 +3,7 @@ use crate::{
     utils::sponge,
     MAX_CONTEXT_DEPTH, MAX_LOOP_DEPTH,
     NUM_CF_OP_BITS, NUM_LD_OP_BITS, NUM_HD_OP_BITS,
+    NUM_LD_LO_HELPERS, NUM_LD_HI_HELPERS, NUM_OP_FLAG_HELPERS,
     SPONGE_WIDTH, BASE_CYCLE_LENGTH, PUSH_OP_ALIGNMENT,
 };
 use super::opcodes::{ FlowOps, UserOps };
@@ -16,6 +17,7 @@ mod tests;
 pub struct Decoder {
 
     step        : usize,
+    first_step  : usize,
 
     op_counter  : Vec<u128>,
     sponge_trace: [Vec<u128>; SPONGE_WIDTH],
@@ -24,6 +26,7 @@ pub struct Decoder {
     cf_op_bits  : [Vec<u128>; NUM_CF_OP_BITS],
     ld_op_bits  : [Vec<u128>; NUM_LD_OP_BITS],
     hd_op_bits  : [Vec<u128>; NUM_HD_OP_BITS],
+    op_helpers  : Vec<Vec<u128>>,
 
     ctx_stack   : Vec<Vec<u128>>,
     ctx_depth   : usize,
@@ -63,6 +66,9 @@ impl Decoder {
             vec![field::ZERO; init_trace_length], vec![field::ZERO; init_trace_length]
         ];
 
+        // initialize op flag helper registers
+        let op_helpers = vec![vec![field::ZERO; init_trace_length]; NUM_OP_FLAG_HELPERS];
+
         // initialize the stacks
         let ctx_stack = vec![vec![field::ZERO; init_trace_length]];
         let ctx_depth = ctx_stack.len();
@@ -72,9 +78,9 @@ impl Decoder {
 
         // create and return decoder
         return Decoder {
-            step: 0, 
+            step: 0, first_step: 0,
             op_counter, sponge, sponge_trace,
-            cf_op_bits, ld_op_bits, hd_op_bits,
+            cf_op_bits, ld_op_bits, hd_op_bits, op_helpers,
             ctx_stack, ctx_depth, loop_stack, loop_depth,
         };
     }
@@ -84,12 +90,24 @@ impl Decoder {
         return self.op_counter.len();
     }
 
+    /// Extends register traces so that they can hold at least `trace_length` steps.
+    pub fn reserve_trace(&mut self, trace_length: usize) {
+        if trace_length > self.trace_length() {
+            self.resize_registers(trace_length);
+        }
+    }
+
     /// Returns value of the current step pointer.
-    #[cfg(test)]
     pub fn current_step(&self) -> usize {
         return self.step;
     }
 
+    /// Returns the number of steps executed so far; unlike `current_step()`, this includes steps
+    /// which were moved into segments by `split_segment()`.
+    pub fn cycles(&self) -> usize {
+        return self.first_step + self.step;
+    }
+
     /// Returns the max value of the context stack reached during program execution.
     pub fn max_ctx_stack_depth(&self) -> usize {
         // outer-most context doesn't count because it is always just 0
@@ -111,6 +129,7 @@ impl Decoder {
         for register in self.cf_op_bits.iter()   { state.push(register[step]); }
         for register in self.ld_op_bits.iter()   { state.push(register[step]); }
         for register in self.hd_op_bits.iter()   { state.push(register[step]); }
+        for register in self.op_helpers.iter()   { state.push(register[step]); }
         for register in self.ctx_stack.iter()    { state.push(register[step]); }
         for register in self.loop_stack.iter()   { state.push(register[step]); }
 
@@ -145,6 +164,8 @@ impl Decoder {
         registers.push(r0);
         registers.push(r1);
 
+        registers.append(&mut self.op_helpers);
+
         // for context stack, first get rid of the outer-most context because it is always 0
         self.ctx_stack.pop();
         registers.append(&mut self.ctx_stack);
@@ -191,6 +212,36 @@ impl Decoder {
         }
     }
 
+    /// Initiates a dynamic call to the block with the specified `callee_hash`; the hash is saved
+    /// on the context stack under the hash of the current program block so that `end_dyncall()`
+    /// can check it against the hash of the executed block.
+    pub fn start_dyncall(&mut self, callee_hash: u128) {
+        assert!(self.step % BASE_CYCLE_LENGTH == BASE_CYCLE_LENGTH - 1,
+            "cannot start dynamic call at step {}: operation alignment is not valid", self.step);
+
+        self.advance_step(false);
+        self.save_call_context(callee_hash);
+        self.copy_loop_stack();
+        self.set_op_bits(FlowOps::Begin, UserOps::DynCall);
+        self.set_sponge([0, 0, 0, 0]);
+    }
+
+    /// Terminates a dynamic call; hash of the executed block is merged into the hash of the
+    /// parent block in the same way as by `end_block()` for a Group block.
+    pub fn end_dyncall(&mut self) {
+        assert!(self.step % BASE_CYCLE_LENGTH == 0,
+            "cannot exit dynamic call at step {}: operation alignment is not valid", self.step);
+
+        self.advance_step(false);
+        let (context_hash, callee_hash) = self.pop_call_context();
+        self.copy_loop_stack();
+        assert!(self.sponge[0] == callee_hash,
+            "cannot exit dynamic call at step {}: hash of the executed block doesn't match callee", self.step);
+
+        self.set_op_bits(FlowOps::Tend, UserOps::DynCall);
+        self.set_sponge([context_hash, callee_hash, 0, 0]);
+    }
+
     /// Initiates a new Loop block
     pub fn start_loop(&mut self, loop_image: u128) {
         assert!(self.step % BASE_CYCLE_LENGTH == BASE_CYCLE_LENGTH - 1,
@@ -261,6 +312,12 @@ impl Decoder {
         for register in self.ld_op_bits.iter_mut() { fill_register(register, self.step, field::ONE); }
         for register in self.hd_op_bits.iter_mut() { fill_register(register, self.step, field::ONE); }
 
+        // set op flag helpers to the values implied by VOID and NOOP operations
+        for (i, register) in self.op_helpers.iter_mut().enumerate() {
+            let value = get_op_flag_helper(FlowOps::Void as u8, UserOps::Noop as u8, i);
+            fill_register(register, self.step, value);
+        }
+
         // for sponge and stack registers, just copy the value of the last state of the register
         for register in self.sponge_trace.iter_mut() { fill_register(register, self.step + 1, register[self.step]); }
         for register in self.ctx_stack.iter_mut()    { fill_register(register, self.step + 1, register[self.step]); }
@@ -270,25 +327,76 @@ impl Decoder {
         self.step = self.trace_length() - 1;
     }
 
+    /// Moves register traces for all steps up to and including the current step into a segment
+    /// of `trace_length` steps, and makes the current step the first step of the remaining
+    /// trace. The segment is padded in the same way as by `finalize_trace()`, and its registers
+    /// are merged in the same way as by `into_register_traces()`.
+    pub fn split_segment(&mut self, trace_length: usize) -> Vec<Vec<u128>> {
+        let step = self.step;
+        assert!(trace_length > step, "segment of {} steps cannot hold {} steps", trace_length, step + 1);
+
+        let [s0, s1, s2, s3] = &mut self.sponge_trace;
+        let [c0, c1, c2] = &mut self.cf_op_bits;
+        let [l0, l1, l2, l3, l4] = &mut self.ld_op_bits;
+        let [h0, h1] = &mut self.hd_op_bits;
+
+        let mut segment = Decoder {
+            step        : step,
+            first_step  : self.first_step,
+            op_counter  : split_register(&mut self.op_counter, step, trace_length),
+            sponge_trace: [
+                split_register(s0, step, trace_length), split_register(s1, step, trace_length),
+                split_register(s2, step, trace_length), split_register(s3, step, trace_length),
+            ],
+            sponge      : self.sponge,
+            cf_op_bits  : [
+                split_register(c0, step, trace_length), split_register(c1, step, trace_length),
+                split_register(c2, step, trace_length),
+            ],
+            ld_op_bits  : [
+                split_register(l0, step, trace_length), split_register(l1, step, trace_length),
+                split_register(l2, step, trace_length), split_register(l3, step, trace_length),
+                split_register(l4, step, trace_length),
+            ],
+            hd_op_bits  : [
+                split_register(h0, step, trace_length), split_register(h1, step, trace_length),
+            ],
+            op_helpers  : self.op_helpers.iter_mut().map(|r| split_register(r, step, trace_length)).collect(),
+            ctx_stack   : self.ctx_stack.iter_mut().map(|r| split_register(r, step, trace_length)).collect(),
+            ctx_depth   : self.ctx_depth,
+            loop_stack  : self.loop_stack.iter_mut().map(|r| split_register(r, step, trace_length)).collect(),
+            loop_depth  : self.loop_depth,
+        };
+
+        self.first_step += step;
+        self.step = 0;
+
+        segment.finalize_trace();
+        return segment.into_register_traces();
+    }
+
     // HELPER METHODS
     // --------------------------------------------------------------------------------------------
 
     /// Moves step pointer to the next step and ensures that register traces have sufficient size.
+    fn resize_registers(&mut self, new_length: usize) {
+        self.op_counter.resize(new_length, field::ZERO);
+        for register in self.sponge_trace.iter_mut() { register.resize(new_length, field::ZERO); }
+        for register in self.cf_op_bits.iter_mut()   { register.resize(new_length, field::ZERO); }
+        for register in self.ld_op_bits.iter_mut()   { register.resize(new_length, field::ZERO); }
+        for register in self.hd_op_bits.iter_mut()   { register.resize(new_length, field::ZERO); }
+        for register in self.op_helpers.iter_mut()   { register.resize(new_length, field::ZERO); }
+        for register in self.ctx_stack.iter_mut()    { register.resize(new_length, field::ZERO); }
+        for register in self.loop_stack.iter_mut()   { register.resize(new_length, field::ZERO); }
+    }
+
     fn advance_step(&mut self, is_user_op: bool) {
         // increment step by 1
         self.step += 1;
 
         // make sure there is enough memory allocated for register traces
         if self.step >= self.trace_length() {
-            let new_length = self.trace_length() * 2;
-
-            self.op_counter.resize(new_length, field::ZERO);
-            for register in self.sponge_trace.iter_mut() { register.resize(new_length, field::ZERO); }
-            for register in self.cf_op_bits.iter_mut()   { register.resize(new_length, field::ZERO); }
-            for register in self.ld_op_bits.iter_mut()   { register.resize(new_length, field::ZERO); }
-            for register in self.hd_op_bits.iter_mut()   { register.resize(new_length, field::ZERO); }
-            for register in self.ctx_stack.iter_mut()    { register.resize(new_length, field::ZERO); }
-            for register in self.loop_stack.iter_mut()   { register.resize(new_length, field::ZERO); }
+            self.resize_registers(self.trace_length() * 2);
         }
 
         // for user ops, increment counter by 1; otherwise, copy counter from thee previous step
@@ -319,6 +427,10 @@ impl Decoder {
         for i in 0..NUM_HD_OP_BITS {
             self.hd_op_bits[i][step] = ((user_op >> (i + NUM_LD_OP_BITS)) & 1) as u128;
         }
+
+        for i in 0..NUM_OP_FLAG_HELPERS {
+            self.op_helpers[i][step] = get_op_flag_helper(flow_op, user_op, i);
+        }
     }
 
     // CONTEXT STACK HELPERS
@@ -362,6 +474,44 @@ impl Decoder {
         return self.ctx_stack[0][self.step - 1];
     }
 
+    /// Pushes hash of the current program block and then `callee_hash` onto the context stack;
+    /// the hash of the current block ends up at the top of the stack.
+    fn save_call_context(&mut self, callee_hash: u128) {
+        // increment context depth and make sure it doesn't overflow the stack
+        self.ctx_depth += 2;
+        assert!(self.ctx_depth <= MAX_CONTEXT_DEPTH, "context stack overflow at step {}", self.step);
+
+        // add new register traces to the stack if the depth exceeds the number of registers
+        while self.ctx_depth > self.ctx_stack.len() {
+            self.ctx_stack.push(vec![field::ZERO; self.trace_length()]);
+        }
+
+        // shift all stack values by two items to the right
+        for i in 2..self.ctx_stack.len() {
+            self.ctx_stack[i][self.step] = self.ctx_stack[i - 2][self.step - 1];
+        }
+
+        self.ctx_stack[0][self.step] = self.sponge[0];
+        self.ctx_stack[1][self.step] = callee_hash;
+    }
+
+    /// Removes two top values saved by `save_call_context()` from the context stack and returns
+    /// them as (context hash, callee hash).
+    fn pop_call_context(&mut self) -> (u128, u128) {
+        // make sure the stack holds both values
+        assert!(self.ctx_depth > 1, "context stack underflow at step {}", self.step);
+
+        // shift all stack values by two items to the left
+        for i in 2..self.ctx_stack.len() {
+            self.ctx_stack[i - 2][self.step] = self.ctx_stack[i][self.step - 1];
+        }
+
+        // update the stack depth and return the values that were at the top of the stack
+        // before it was shifted to the left
+        self.ctx_depth -= 2;
+        return (self.ctx_stack[0][self.step - 1], self.ctx_stack[1][self.step - 1]);
+    }
+
     /// Copies contents of the context stack from the previous to the current step.
     fn copy_context_stack(&mut self) {
         for i in 0..self.ctx_stack.len() {
@@ -464,4 +614,47 @@ fn fill_register(register: &mut Vec<u128>, from: usize, value: u128) {
     let to = register.len();
     register.resize(from, field::ZERO);
     register.resize(to, value);
-}
\ No newline at end of file
+}
+
+/// Copies values of the `register` for all steps up to and including `step` into a new register
+/// of `trace_length` steps, and removes all values before `step` from the `register`.
+fn split_register(register: &mut Vec<u128>, step: usize, trace_length: usize) -> Vec<u128> {
+    let mut segment = register[..=step].to_vec();
+    segment.resize(trace_length, field::ZERO);
+    register.drain(..step);
+    return segment;
+}
+
+/// Returns the value of op flag helper register at the specified `index` for the provided
+/// combination of control flow and user operation codes. Helpers are laid out as follows:
+/// HACC, VOID, BEGIN, and TEND flags, flags for combinations of ld_op bits 0 and 1, flags for
+/// combinations of ld_op bits 2, 3, and 4, and flags for all high-degree operations.
+fn get_op_flag_helper(flow_op: u8, user_op: u8, index: usize) -> u128 {
+    let ld_lo_start = 4;
+    let ld_hi_start = ld_lo_start + NUM_LD_LO_HELPERS;
+    let hd_start = ld_hi_start + NUM_LD_HI_HELPERS;
+
+    let is_set = if index == 0 {
+        flow_op == FlowOps::Hacc as u8
+    }
+    else if index == 1 {
+        flow_op == FlowOps::Void as u8
+    }
+    else if index == 2 {
+        flow_op == FlowOps::Begin as u8
+    }
+    else if index == 3 {
+        flow_op == FlowOps::Tend as u8
+    }
+    else if index < ld_hi_start {
+        (user_op & 0b11) as usize == index - ld_lo_start
+    }
+    else if index < hd_start {
+        ((user_op >> 2) & 0b111) as usize == index - ld_hi_start
+    }
+    else {
+        (user_op >> NUM_LD_OP_BITS) as usize == index - hd_start
+    };
+
+    return if is_set { field::ONE } else { field::ZERO };
+}
@@ -0,0 +1,94 @@
+use sp_std::{ fmt, cmp };
+use alloc::string::String;
+use crate::MIN_TRACE_LENGTH;
+
+// TYPES AND INTERFACES
+// ================================================================================================
+
+/// Resource limits enforced while a program is being executed.
+///
+/// * `max_cycles` is the maximum number of VM cycles (steps of the execution trace) the program
+///    is allowed to execute;
+/// * `max_trace_length` is the maximum length of the execution trace; since trace length is
+///    always a power of 2, this limit is reached before the cycle limit for most values. When
+///    a program is executed in segments, this limits the trace length of every segment.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ExecutionLimits {
+    pub max_cycles      : usize,
+    pub max_trace_length: usize,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ExecutionError {
+    /// Execution exceeded the cycle limit; contains the number of cycles executed so far.
+    CycleLimitExceeded(usize),
+    /// Execution trace exceeded the length limit; contains the trace length required so far.
+    TraceLengthLimitExceeded(usize),
+    /// A dynamic call was made to a block which is not in the program library; contains the
+    /// body hash of the block.
+    UnknownCallee(u128),
+    /// A dynamic call bound to one callee was made to a different block; contains the body hash
+    /// of the block.
+    UnboundCallee(u128),
+    /// Execution reached a part of the program which was hidden behind a Proxy block; contains
+    /// the first hash value of the proxy.
+    HiddenBlock(u128),
+    /// The advice provider failed to supply values requested by the program; contains the
+    /// description of the failure.
+    Advice(String),
+}
+
+// EXECUTION LIMITS IMPLEMENTATION
+// ================================================================================================
+impl ExecutionLimits {
+
+    pub fn new(max_cycles: usize, max_trace_length: usize) -> ExecutionLimits {
+        assert!(max_trace_length >= MIN_TRACE_LENGTH,
+            "max trace length cannot be smaller than {}", MIN_TRACE_LENGTH);
+        return ExecutionLimits { max_cycles, max_trace_length };
+    }
+
+    /// Returns an error if execution which ran for the specified number of `cycles` and reached
+    /// the specified `step` of the current execution trace is outside of the limits.
+    pub fn check(&self, cycles: usize, step: usize) -> Result<(), ExecutionError> {
+        if cycles > self.max_cycles {
+            return Err(ExecutionError::CycleLimitExceeded(cycles));
+        }
+
+        let trace_length = cmp::max((step + 1).next_power_of_two(), MIN_TRACE_LENGTH);
+        if trace_length > self.max_trace_length {
+            return Err(ExecutionError::TraceLengthLimitExceeded(trace_length));
+        }
+
+        return Ok(());
+    }
+}
+
+impl Default for ExecutionLimits {
+    /// Returns limits which never stop execution.
+    fn default() -> ExecutionLimits {
+        return ExecutionLimits {
+            max_cycles      : usize::MAX,
+            max_trace_length: usize::MAX,
+        };
+    }
+}
+
+impl fmt::Display for ExecutionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return match self {
+            ExecutionError::CycleLimitExceeded(cycles) =>
+                write!(f, "execution exceeded cycle limit after {} cycles", cycles),
+            ExecutionError::TraceLengthLimitExceeded(length) =>
+                write!(f, "execution trace of length {} exceeds trace length limit", length),
+            ExecutionError::UnknownCallee(hash) =>
+                write!(f, "block {} was not found in the program library", hash),
+            ExecutionError::UnboundCallee(hash) =>
+                write!(f, "block {} is not the callee bound to the dynamic call site", hash),
+            ExecutionError::HiddenBlock(hash) =>
+                write!(f, "cannot execute hidden block {}", hash),
+            ExecutionError::Advice(message) =>
+                write!(f, "{}", message),
+        };
+    }
+}
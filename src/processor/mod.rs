@@ -1,9 +1,9 @@
 use crate::{
     math::field,
-    programs::{ Program, ProgramInputs, blocks::{ ProgramBlock, Span, Loop } },
+    programs::{ Program, ProgramInputs, ProgramLibrary, blocks::{ ProgramBlock, Span, Loop, DynCall } },
     MIN_TRACE_LENGTH, HACC_NUM_ROUNDS,
 };
-use sp_std::vec::Vec;
+use sp_std::{ boxed::Box, vec::Vec };
 
 // RE-EXPORTS
 // ================================================================================================
@@ -14,6 +14,16 @@ pub use decoder::{ Decoder };
 mod stack;
 pub use stack::{ Stack };
 
+mod advice;
+pub use advice::{ AdviceProvider, AdviceRequest, AdviceHandler, AdviceHandlers };
+
+mod limits;
+pub use limits::{ ExecutionLimits, ExecutionError };
+
+mod segments;
+pub use segments::{ VmState, ExecutionSegment };
+use segments::Segmenter;
+
 pub mod opcodes;
 pub use opcodes::{ UserOps as OpCode, OpHint };
 
@@ -21,17 +31,33 @@ pub use opcodes::{ UserOps as OpCode, OpHint };
 // ================================================================================================
 
 /// Returns register traces resulting from executing the `program` against the specified inputs.
-pub fn execute(program: &Program, inputs: &ProgramInputs) -> (Vec<Vec<u128>>, usize, usize)
+/// Execution is aborted with an error as soon as it exceeds any of the specified `limits`.
+pub fn execute(program: &Program, inputs: &ProgramInputs, limits: &ExecutionLimits)
+    -> Result<(Vec<Vec<u128>>, usize, usize), ExecutionError>
+{
+    return execute_with_advice(program, inputs, limits, &mut AdviceHandlers::new());
+}
+
+/// Executes the `program` in the same way as `execute()`, but requests of the program for
+/// nondeterministic inputs are handled by the specified `advice` provider.
+pub fn execute_with_advice(program: &Program, inputs: &ProgramInputs, limits: &ExecutionLimits, advice: &mut dyn AdviceProvider)
+    -> Result<(Vec<Vec<u128>>, usize, usize), ExecutionError>
 {
     // initialize decoder and stack components
     let mut decoder = Decoder::new(MIN_TRACE_LENGTH);
-    let mut stack = Stack::new(inputs, MIN_TRACE_LENGTH);
+    let mut stack = Stack::with_advice(inputs, MIN_TRACE_LENGTH, Box::new(advice));
+    let mut segmenter = Segmenter::disabled();
 
     // execute body of the program
-    execute_blocks(program.root().body(), &mut decoder, &mut stack);
-    close_block(&mut decoder, &mut stack, field::ZERO, true);
-
-    // fill in remaining steps to make sure the length of the trace is a power of 2
+    execute_blocks(program.root().body(), &mut decoder, &mut stack, inputs.get_library(), &mut segmenter, limits)?;
+    close_block(&mut decoder, &mut stack, field::ZERO, true, limits)?;
+
+    // fill in remaining steps to make sure the length of the trace is a power of 2; if range
+    // checks were performed, the trace must also be long enough to hold the range check table
+    let trace_length = stack.min_trace_length();
+    limits.check(decoder.cycles(), trace_length - 1)?;
+    decoder.reserve_trace(trace_length);
+    stack.reserve_trace(trace_length);
     decoder.finalize_trace();
     stack.finalize_trace();
 
@@ -43,45 +69,67 @@ pub fn execute(program: &Program, inputs: &ProgramInputs) -> (Vec<Vec<u128>>, us
     let mut register_traces = decoder.into_register_traces();
     register_traces.append(&mut stack.into_register_traces());
 
-    return (register_traces, context_depth, loop_depth);
+    return Ok((register_traces, context_depth, loop_depth));
+}
+
+/// Executes the `program` in the same way as `execute()`, but splits the execution trace into
+/// segments and passes every segment to the `handler` as soon as the segment is complete; thus,
+/// at most one segment of the trace is kept in memory at a time. The trace is cut at the first
+/// Span block which starts after the current segment reached `segment_length` steps, and so
+/// segments may be longer than `segment_length`. The `limits` apply to the total number of
+/// executed cycles and to the trace length of every segment.
+pub fn execute_segments(
+    program         : &Program,
+    inputs          : &ProgramInputs,
+    limits          : &ExecutionLimits,
+    segment_length  : usize,
+    handler         : &mut dyn FnMut(ExecutionSegment)) -> Result<(), ExecutionError>
+{
+    let mut decoder = Decoder::new(MIN_TRACE_LENGTH);
+    let mut stack = Stack::new(inputs, MIN_TRACE_LENGTH);
+    let mut segmenter = Segmenter::new(segment_length, handler);
+
+    execute_blocks(program.root().body(), &mut decoder, &mut stack, inputs.get_library(), &mut segmenter, limits)?;
+    close_block(&mut decoder, &mut stack, field::ZERO, true, limits)?;
+
+    // the remainder of the trace becomes the last segment
+    segmenter.finish(&mut decoder, &mut stack);
+    return Ok(());
 }
 
 // HELPER FUNCTIONS
 // ================================================================================================
-fn execute_blocks(blocks: &[ProgramBlock], decoder: &mut Decoder, stack: &mut Stack)
+fn execute_blocks(blocks: &[ProgramBlock], decoder: &mut Decoder, stack: &mut Stack, library: &ProgramLibrary,
+    segmenter: &mut Segmenter, limits: &ExecutionLimits)
+    -> Result<(), ExecutionError>
 {
     // execute first block in the sequence, which mast be a Span block
-
     match &blocks[0] {
-        ProgramBlock::Span(block) => {
-
-            execute_span(block, decoder, stack, true)
-        
-        },
-
+        ProgramBlock::Span(block) => execute_span(block, decoder, stack, true, segmenter, limits)?,
+        ProgramBlock::Proxy(block) => return Err(ExecutionError::HiddenBlock(block.get_hash().0)),
         _ => panic!("first block in a sequence must be a Span block"),
     }
 
     // execute all other blocks in the sequence one after another
     for block in blocks.iter().skip(1) {
         match block {
-            ProgramBlock::Span(block) => execute_span(block, decoder, stack, false),
+            ProgramBlock::Span(block) => execute_span(block, decoder, stack, false, segmenter, limits)?,
             ProgramBlock::Group(block) => {
-                start_block(decoder, stack);
-                execute_blocks(block.body(), decoder, stack);
-                close_block(decoder, stack, field::ZERO, true);
+                start_block(decoder, stack)?;
+                execute_blocks(block.body(), decoder, stack, library, segmenter, limits)?;
+                close_block(decoder, stack, field::ZERO, true, limits)?;
             },
             ProgramBlock::Switch(block) => {
-                start_block(decoder, stack);
+                start_block(decoder, stack)?;
                 let condition = stack.get_stack_top();
                 match condition {
                     0 => {
-                        execute_blocks(block.false_branch(), decoder, stack);
-                        close_block(decoder, stack, block.true_branch_hash(), false);
+                        execute_blocks(block.false_branch(), decoder, stack, library, segmenter, limits)?;
+                        close_block(decoder, stack, block.true_branch_hash(), false, limits)?;
                     },
                     1 => {
-                        execute_blocks(block.true_branch(), decoder, stack);
-                        close_block(decoder, stack, block.false_branch_hash(), true);
+                        execute_blocks(block.true_branch(), decoder, stack, library, segmenter, limits)?;
+                        close_block(decoder, stack, block.false_branch_hash(), true, limits)?;
                     },
                     _ => panic!("cannot select a branch based on a non-binary condition {}", condition)
                 };
@@ -90,88 +138,138 @@ fn execute_blocks(blocks: &[ProgramBlock], decoder: &mut Decoder, stack: &mut St
                 let condition = stack.get_stack_top();
                 match condition {
                     0 => {
-                        start_block(decoder, stack);
-                        execute_blocks(block.skip(), decoder, stack);
-                        close_block(decoder, stack, block.body_hash(), false);
+                        start_block(decoder, stack)?;
+                        execute_blocks(block.skip(), decoder, stack, library, segmenter, limits)?;
+                        close_block(decoder, stack, block.body_hash(), false, limits)?;
                     },
-                    1 => execute_loop(block, decoder, stack),
+                    1 => execute_loop(block, decoder, stack, library, segmenter, limits)?,
                     _ => panic!("cannot enter loop based on a non-binary condition {}", condition)
                 }
             },
+            ProgramBlock::DynCall(block) => execute_dyncall(block, decoder, stack, library, segmenter, limits)?,
+            ProgramBlock::Proxy(block) => return Err(ExecutionError::HiddenBlock(block.get_hash().0)),
         }
     }
+
+    return Ok(());
 }
 
 /// Executes all instructions in a Span block.
-fn execute_span(block: &Span, decoder: &mut Decoder, stack: &mut Stack, is_first: bool)
+fn execute_span(block: &Span, decoder: &mut Decoder, stack: &mut Stack, is_first: bool, segmenter: &mut Segmenter, limits: &ExecutionLimits)
+    -> Result<(), ExecutionError>
 {
     // if this is the first Span block in a sequence of blocks, it needs to be
     // pre-padded with a NOOP to make sure the first instruction in the block
     // starts executing on a step which is a multiple of 16
     if !is_first {
         decoder.decode_op(OpCode::Noop, field::ZERO);
-        stack.execute(OpCode::Noop, OpHint::None);
+        stack.execute(OpCode::Noop, OpHint::None)?;
     }
 
+    // the start of a Span block is the only place where the trace can be cut into segments
+    segmenter.checkpoint(decoder, stack);
+
     // execute all other instructions in the block
     for i in 0..block.length() {
         let (op_code, op_hint) = block.get_op(i);
         decoder.decode_op(op_code, op_hint.value());
-
-        stack.execute(op_code, op_hint);
-
+        stack.execute(op_code, op_hint)?;
+        limits.check(decoder.cycles(), decoder.current_step())?;
     }
+
+    return Ok(());
 }
 
 /// Starts executing a new program block.
-fn start_block(decoder: &mut Decoder, stack: &mut Stack)
+fn start_block(decoder: &mut Decoder, stack: &mut Stack) -> Result<(), ExecutionError>
 {
     decoder.start_block();
-    stack.execute(OpCode::Noop, OpHint::None);
+    return stack.execute(OpCode::Noop, OpHint::None);
 }
 
 /// Closes the currently executing program block.
-fn close_block(decoder: &mut Decoder, stack: &mut Stack, sibling_hash: u128, is_true_branch: bool)
+fn close_block(decoder: &mut Decoder, stack: &mut Stack, sibling_hash: u128, is_true_branch: bool, limits: &ExecutionLimits)
+    -> Result<(), ExecutionError>
 {
     // a sequence of blocks always ends on a step which is one less than a multiple of 16;
     // all sequences end one operation short of multiple of 16 - so, we need to pad them
     // with a single NOOP ensure proper alignment
     decoder.decode_op(OpCode::Noop, field::ZERO);
-    stack.execute(OpCode::Noop, OpHint::None);
+    stack.execute(OpCode::Noop, OpHint::None)?;
 
     // end the block, this prepares decoder registers for merging block hash into
     // program hash
     decoder.end_block(sibling_hash, is_true_branch);
-    stack.execute(OpCode::Noop, OpHint::None);
+    stack.execute(OpCode::Noop, OpHint::None)?;
 
     // execute NOOPs to merge block hash into the program hash
     for _ in 0..HACC_NUM_ROUNDS {
         decoder.decode_op(OpCode::Noop, field::ZERO);
-        stack.execute(OpCode::Noop, OpHint::None);
+        stack.execute(OpCode::Noop, OpHint::None)?;
     }
+
+    return limits.check(decoder.cycles(), decoder.current_step());
+}
+
+/// Executes the block from the `library` whose body hash is at the top of the stack; the hash
+/// remains on the stack when the block starts executing. Hash of the executed block is checked
+/// against the hash at the top of the stack, and is then merged into the hash of the program.
+fn execute_dyncall(block: &DynCall, decoder: &mut Decoder, stack: &mut Stack, library: &ProgramLibrary,
+    segmenter: &mut Segmenter, limits: &ExecutionLimits) -> Result<(), ExecutionError>
+{
+    let callee_hash = stack.get_stack_top();
+    if block.callee() != callee_hash {
+        return Err(ExecutionError::UnboundCallee(callee_hash));
+    }
+
+    let callee = match library.get(callee_hash) {
+        Some(callee) => callee,
+        None => return Err(ExecutionError::UnknownCallee(callee_hash)),
+    };
+
+    decoder.start_dyncall(callee_hash);
+    stack.execute(OpCode::DynCall, OpHint::None)?;
+
+    execute_blocks(callee.body(), decoder, stack, library, segmenter, limits)?;
+
+    // pad the callee in the same way as in close_block(), but end the call with a DYNCALL
+    decoder.decode_op(OpCode::Noop, field::ZERO);
+    stack.execute(OpCode::Noop, OpHint::None)?;
+
+    decoder.end_dyncall();
+    stack.execute(OpCode::DynCall, OpHint::None)?;
+
+    for _ in 0..HACC_NUM_ROUNDS {
+        decoder.decode_op(OpCode::Noop, field::ZERO);
+        stack.execute(OpCode::Noop, OpHint::None)?;
+    }
+
+    return limits.check(decoder.cycles(), decoder.current_step());
 }
 
 /// Executes the specified loop.
-fn execute_loop(block: &Loop, decoder: &mut Decoder, stack: &mut Stack)
+fn execute_loop(block: &Loop, decoder: &mut Decoder, stack: &mut Stack, library: &ProgramLibrary,
+    segmenter: &mut Segmenter, limits: &ExecutionLimits)
+    -> Result<(), ExecutionError>
 {
     // mark the beginning of the loop block
     decoder.start_loop(block.image());
-    stack.execute(OpCode::Noop, OpHint::None);
+    stack.execute(OpCode::Noop, OpHint::None)?;
 
     // execute blocks in loop body until top of the stack becomes 0
     loop {
-        execute_blocks(block.body(), decoder, stack);
+        execute_blocks(block.body(), decoder, stack, library, segmenter, limits)?;
 
         let condition = stack.get_stack_top();
         match condition {
             0 => {
                 decoder.break_loop();
-                stack.execute(OpCode::Noop, OpHint::None);
+                stack.execute(OpCode::Noop, OpHint::None)?;
                 break;
             },
             1 => {
                 decoder.wrap_loop();
-                stack.execute(OpCode::Noop, OpHint::None);
+                stack.execute(OpCode::Noop, OpHint::None)?;
             },
             _ => panic!("cannot exit loop based on a non-binary condition {}", condition)
         };
@@ -179,12 +277,12 @@ fn execute_loop(block: &Loop, decoder: &mut Decoder, stack: &mut Stack)
 
     // execute the contents of the skip block to make sure the loop was exited correctly
     match &block.skip()[0] {
-        ProgramBlock::Span(block) => execute_span(block, decoder, stack, true),
+        ProgramBlock::Span(block) => execute_span(block, decoder, stack, true, segmenter, limits)?,
         _ => panic!("invalid skip block content: content must be a Span block"),
     }
 
     // close block
-    close_block(decoder, stack, block.skip_hash(), true);
+    return close_block(decoder, stack, block.skip_hash(), true, limits);
 }
 
 // TESTS
@@ -193,19 +291,19 @@ fn execute_loop(block: &Loop, decoder: &mut Decoder, stack: &mut Stack)
 #[cfg(test)]
 mod tests {
 
-    use crate::{ programs::assembly, stark::TraceState, utils::as_bytes };
-    use super::{ ProgramInputs };
+    use crate::{ math::field, programs::{ assembly, MerkleStore }, stark::TraceState, utils::as_bytes, BASE_CYCLE_LENGTH };
+    use super::{ ProgramInputs, ExecutionLimits, ExecutionError, VmState, AdviceHandlers };
 
     #[test]
     fn execute_span() {
         let program = assembly::compile("begin add push.5 mul push.7 end").unwrap();
         let inputs = ProgramInputs::from_public(&[1, 2]);
 
-        let (trace, ctx_depth, loop_depth) = super::execute(&program, &inputs);
+        let (trace, ctx_depth, loop_depth) = super::execute(&program, &inputs, &ExecutionLimits::default()).unwrap();
         let trace_length = trace[0].len();
 
         assert_eq!(64, trace_length);
-        assert_eq!(17, trace.len());
+        assert_eq!(38, trace.len());
         let mut state = build_trace_state(trace.len(), ctx_depth, loop_depth) ;
         state.update_from_trace(&trace, trace_length - 1);
 
@@ -223,11 +321,11 @@ mod tests {
         let program = assembly::compile("begin add block push.5 mul push.7 end end").unwrap();
         let inputs = ProgramInputs::from_public(&[1, 2]);
 
-        let (trace, ctx_depth, loop_depth) = super::execute(&program, &inputs);
+        let (trace, ctx_depth, loop_depth) = super::execute(&program, &inputs, &ExecutionLimits::default()).unwrap();
         let trace_length = trace[0].len();
 
         assert_eq!(64, trace_length);
-        assert_eq!(18, trace.len());
+        assert_eq!(39, trace.len());
 
         let mut state = build_trace_state(trace.len(), ctx_depth, loop_depth) ;
         state.update_from_trace(&trace, trace_length - 1);
@@ -249,11 +347,11 @@ mod tests {
         
         // execute true branch
         let inputs = ProgramInputs::new(&[5, 3], &[1], &[]);
-        let (trace, ctx_depth, loop_depth) = super::execute(&program, &inputs);
+        let (trace, ctx_depth, loop_depth) = super::execute(&program, &inputs, &ExecutionLimits::default()).unwrap();
         let trace_length = trace[0].len();
 
         assert_eq!(128, trace_length);
-        assert_eq!(19, trace.len());
+        assert_eq!(40, trace.len());
 
         let mut state = build_trace_state(trace.len(), ctx_depth, loop_depth) ;
         state.update_from_trace(&trace, trace_length - 1);
@@ -269,11 +367,11 @@ mod tests {
 
         // execute false branch
         let inputs = ProgramInputs::new(&[5, 3], &[0], &[]);
-        let (trace, ctx_depth, loop_depth) = super::execute(&program, &inputs);
+        let (trace, ctx_depth, loop_depth) = super::execute(&program, &inputs, &ExecutionLimits::default()).unwrap();
         let trace_length = trace[0].len();
 
         assert_eq!(128, trace_length);
-        assert_eq!(19, trace.len());
+        assert_eq!(40, trace.len());
 
         let mut state = build_trace_state(trace.len(), ctx_depth, loop_depth) ;
         state.update_from_trace(&trace, trace_length - 1);
@@ -295,11 +393,11 @@ mod tests {
 
         // don't enter the loop
         let inputs = ProgramInputs::new(&[5, 3], &[0], &[]);
-        let (trace, ctx_depth, loop_depth) = super::execute(&program, &inputs);
+        let (trace, ctx_depth, loop_depth) = super::execute(&program, &inputs, &ExecutionLimits::default()).unwrap();
         let trace_length = trace[0].len();
 
         assert_eq!(64, trace_length);
-        assert_eq!(18, trace.len());
+        assert_eq!(39, trace.len());
 
         let mut state = build_trace_state(trace.len(), ctx_depth, loop_depth) ;
         state.update_from_trace(&trace, trace_length - 1);
@@ -315,11 +413,11 @@ mod tests {
 
         // execute one iteration
         let inputs = ProgramInputs::new(&[5, 3], &[1, 0], &[]);
-        let (trace, ctx_depth, loop_depth) = super::execute(&program, &inputs);
+        let (trace, ctx_depth, loop_depth) = super::execute(&program, &inputs, &ExecutionLimits::default()).unwrap();
         let trace_length = trace[0].len();
 
         assert_eq!(128, trace_length);
-        assert_eq!(19, trace.len());
+        assert_eq!(40, trace.len());
 
         let mut state = build_trace_state(trace.len(), ctx_depth, loop_depth) ;
         state.update_from_trace(&trace, trace_length - 1);
@@ -335,11 +433,11 @@ mod tests {
 
         // execute five iteration
         let inputs = ProgramInputs::new(&[5, 3], &[1, 1, 1, 1, 1, 0], &[]);
-        let (trace, ctx_depth, loop_depth) = super::execute(&program, &inputs);
+        let (trace, ctx_depth, loop_depth) = super::execute(&program, &inputs, &ExecutionLimits::default()).unwrap();
         let trace_length = trace[0].len();
 
         assert_eq!(256, trace_length);
-        assert_eq!(19, trace.len());
+        assert_eq!(40, trace.len());
 
         let mut state = build_trace_state(trace.len(), ctx_depth, loop_depth) ;
         state.update_from_trace(&trace, trace_length - 1);
@@ -354,9 +452,117 @@ mod tests {
         assert_eq!([43143988327398919500410556793212890625, 0, 0, 0, 0, 0, 0, 0], state.user_stack());
     }
 
+    #[test]
+    fn execute_with_limits() {
+        let program = assembly::compile(
+            "begin mul read while.true dup mul read end end").unwrap();
+        let inputs = ProgramInputs::new(&[5, 3], &[1; 100], &[]);
+
+        // the loop never terminates within the limits
+        let limits = ExecutionLimits::new(1000, 4096);
+        assert_eq!(Err(ExecutionError::CycleLimitExceeded(1001)), super::execute(&program, &inputs, &limits));
+
+        let limits = ExecutionLimits::new(10000, 512);
+        assert_eq!(Err(ExecutionError::TraceLengthLimitExceeded(1024)), super::execute(&program, &inputs, &limits));
+
+        // the limits are not reached
+        let inputs = ProgramInputs::new(&[5, 3], &[1, 1, 1, 1, 1, 0], &[]);
+        let limits = ExecutionLimits::new(255, 256);
+        assert!(super::execute(&program, &inputs, &limits).is_ok());
+    }
+
+    #[test]
+    fn execute_segments() {
+        let program = assembly::compile(
+            "begin mul read while.true dup mul read end end").unwrap();
+        let inputs = ProgramInputs::new(&[5, 3], &[1, 1, 1, 1, 1, 0], &[]);
+
+        let (trace, ctx_depth, loop_depth) = super::execute(&program, &inputs, &ExecutionLimits::default()).unwrap();
+        let last_step = trace[0].len() - 1;
+
+        let mut segments = Vec::new();
+        super::execute_segments(&program, &inputs, &ExecutionLimits::default(), 32, &mut |segment| segments.push(segment)).unwrap();
+        assert_eq!(4, segments.len());
+
+        // every segment starts in the state in which the previous segment ended, and the
+        // segment traces contain the same values as the trace of the entire execution
+        assert_eq!(VmState::initial(&[5, 3]), segments[0].entry);
+        for pair in segments.windows(2) {
+            assert_eq!(pair[0].exit, pair[1].entry);
+            assert_eq!(0, pair[1].first_step % BASE_CYCLE_LENGTH);
+            for step in 0..(pair[1].first_step - pair[0].first_step) {
+                let expected = VmState::from_trace(&trace, ctx_depth, loop_depth, pair[0].first_step + step);
+                let actual = VmState::from_trace(&pair[0].trace, pair[0].ctx_depth, pair[0].loop_depth, step);
+                assert_eq!(expected, actual);
+            }
+        }
+
+        // the last segment ends in the same state as the entire execution
+        let expected = VmState::from_trace(&trace, ctx_depth, loop_depth, last_step);
+        assert_eq!(expected, segments[3].exit);
+        assert_eq!(program.hash(), as_bytes(&segments[3].exit.sponge[..2]));
+    }
+
+    #[test]
+    fn execute_segments_with_limits() {
+        let program = assembly::compile(
+            "begin mul read while.true dup mul read end end").unwrap();
+        let inputs = ProgramInputs::new(&[5, 3], &[1; 100], &[]);
+
+        // cycle limit applies to the entire execution, and trace length limit to every segment
+        let limits = ExecutionLimits::new(1000, 64);
+        let result = super::execute_segments(&program, &inputs, &limits, 32, &mut |_| ());
+        assert_eq!(Err(ExecutionError::CycleLimitExceeded(1001)), result);
+    }
+
+    #[test]
+    fn execute_with_advice() {
+        let program = assembly::compile("begin adv.div adv.1 end").unwrap();
+        let inputs = ProgramInputs::from_public(&[7, 100]);
+
+        let mut advice = AdviceHandlers::new();
+        advice.register(1, Box::new(|stack| Ok(vec![field::mul(stack[0], stack[1])])));
+
+        let (trace, ctx_depth, loop_depth) = super::execute_with_advice(&program, &inputs, &ExecutionLimits::default(), &mut advice).unwrap();
+        let trace_length = trace[0].len();
+
+        let mut state = build_trace_state(trace.len(), ctx_depth, loop_depth);
+        state.update_from_trace(&trace, trace_length - 1);
+        assert_eq!([28, 14, 2, 7, 100, 0, 0, 0], state.user_stack());
+    }
+
+    #[test]
+    fn execute_with_merkle_path_advice() {
+        let store = MerkleStore::from_leaves(3, &[[1, 2], [3, 4], [5, 6]]);
+        let root = store.root();
+        let path = store.path(2);
+
+        let program = assembly::compile("begin adv.mpath.3 end").unwrap();
+        let inputs = ProgramInputs::from_public(&[root[1], root[0], 2]);
+
+        let mut advice = AdviceHandlers::new();
+        advice.add_merkle_store(store);
+
+        let (trace, ctx_depth, loop_depth) = super::execute_with_advice(&program, &inputs, &ExecutionLimits::default(), &mut advice).unwrap();
+        let trace_length = trace[0].len();
+
+        let mut state = build_trace_state(trace.len(), ctx_depth, loop_depth);
+        state.update_from_trace(&trace, trace_length - 1);
+        assert_eq!([path[0][1], path[0][0], path[1][1], path[1][0], root[1], root[0], 2, 0], state.user_stack());
+    }
+
+    #[test]
+    fn execute_without_advice_handler() {
+        let program = assembly::compile("begin adv.div adv.1 end").unwrap();
+        let inputs = ProgramInputs::from_public(&[7, 100]);
+        let result = super::execute(&program, &inputs, &ExecutionLimits::default());
+        let expected = "failed to get adv.1.1 at step 4: no handler is registered for advice 1";
+        assert_eq!(Err(ExecutionError::Advice(String::from(expected))), result.map(|_| ()));
+    }
+
     fn build_trace_state(num_registers: usize, ctx_depth: usize, loop_depth: usize) -> TraceState {
         let decoder_width = TraceState::compute_decoder_width(ctx_depth, loop_depth);
-        let stack_depth = num_registers - decoder_width;
+        let stack_depth = num_registers - decoder_width - crate::NUM_RANGE_CHECK_REGISTERS;
         return TraceState::new(ctx_depth, loop_depth, stack_depth);
     }
 }
\ No newline at end of file
@@ -1,4 +1,7 @@
+use sp_std::convert::TryFrom;
+use alloc::string::String;
 use serde::{ Serialize, Deserialize };
+use super::AdviceRequest;
 
 // FLOW CONTROL OPERATIONS
 // ================================================================================================
@@ -21,6 +24,24 @@ impl FlowOps {
     }
 }
 
+impl TryFrom<u8> for FlowOps {
+    type Error = String;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        return match value {
+            0b000 => Ok(FlowOps::Hacc),
+            0b001 => Ok(FlowOps::Begin),
+            0b010 => Ok(FlowOps::Tend),
+            0b011 => Ok(FlowOps::Fend),
+            0b100 => Ok(FlowOps::Loop),
+            0b101 => Ok(FlowOps::Wrap),
+            0b110 => Ok(FlowOps::Break),
+            0b111 => Ok(FlowOps::Void),
+            _ => Err(format!("value {} is not a valid flow control operation", value)),
+        };
+    }
+}
+
 impl sp_std::fmt::Display for FlowOps {
 
     fn fmt(&self, f: &mut sp_std::fmt::Formatter) -> sp_std::fmt::Result {
@@ -64,7 +85,7 @@ pub enum UserOps {
     Inv         = 0b0_11_01100,         // no shift
     Neg         = 0b0_11_01101,         // no shift
     Not         = 0b0_11_01110,         // no shift
-    //???       = 0b0_11_01111,
+    DynCall     = 0b0_11_01111,         // no shift
 
     Read        = 0b0_11_10000,         // right shift: 1
     Read2       = 0b0_11_10001,         // right shift: 2
@@ -81,7 +102,7 @@ pub enum UserOps {
     Roll4       = 0b0_11_11011,         // no shift
     Roll8       = 0b0_11_11100,         // no shift
     BinAcc      = 0b0_11_11101,         // no shift
-    //???       = 0b0_11_11110,
+    RcAcc       = 0b0_11_11110,         // no shift
 
     // high-degree operations
     Push        = 0b0_00_11111,         // right shift: 1
@@ -118,6 +139,55 @@ impl UserOps {
     }
 }
 
+impl TryFrom<u8> for UserOps {
+    type Error = String;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        return match value {
+            0b0_11_00000 => Ok(UserOps::Assert),
+            0b0_11_00001 => Ok(UserOps::AssertEq),
+            0b0_11_00010 => Ok(UserOps::Eq),
+            0b0_11_00011 => Ok(UserOps::Drop),
+            0b0_11_00100 => Ok(UserOps::Drop4),
+            0b0_11_00101 => Ok(UserOps::Choose),
+            0b0_11_00110 => Ok(UserOps::Choose2),
+            0b0_11_00111 => Ok(UserOps::CSwap2),
+
+            0b0_11_01000 => Ok(UserOps::Add),
+            0b0_11_01001 => Ok(UserOps::Mul),
+            0b0_11_01010 => Ok(UserOps::And),
+            0b0_11_01011 => Ok(UserOps::Or),
+            0b0_11_01100 => Ok(UserOps::Inv),
+            0b0_11_01101 => Ok(UserOps::Neg),
+            0b0_11_01110 => Ok(UserOps::Not),
+            0b0_11_01111 => Ok(UserOps::DynCall),
+
+            0b0_11_10000 => Ok(UserOps::Read),
+            0b0_11_10001 => Ok(UserOps::Read2),
+            0b0_11_10010 => Ok(UserOps::Dup),
+            0b0_11_10011 => Ok(UserOps::Dup2),
+            0b0_11_10100 => Ok(UserOps::Dup4),
+            0b0_11_10101 => Ok(UserOps::Pad2),
+
+            0b0_11_11000 => Ok(UserOps::Swap),
+            0b0_11_11001 => Ok(UserOps::Swap2),
+            0b0_11_11010 => Ok(UserOps::Swap4),
+            0b0_11_11011 => Ok(UserOps::Roll4),
+            0b0_11_11100 => Ok(UserOps::Roll8),
+            0b0_11_11101 => Ok(UserOps::BinAcc),
+            0b0_11_11110 => Ok(UserOps::RcAcc),
+
+            0b0_00_11111 => Ok(UserOps::Push),
+            0b0_01_11111 => Ok(UserOps::Cmp),
+            0b0_10_11111 => Ok(UserOps::RescR),
+
+            0b0_00_00000 => Ok(UserOps::Begin),
+            0b0_11_11111 => Ok(UserOps::Noop),
+            _ => Err(format!("value {} is not a valid user operation", value)),
+        };
+    }
+}
+
 impl sp_std::fmt::Display for UserOps {
 
     fn fmt(&self, f: &mut sp_std::fmt::Formatter) -> sp_std::fmt::Result {
@@ -163,8 +233,11 @@ impl sp_std::fmt::Display for UserOps {
             UserOps::Eq         => write!(f, "eq"),
             UserOps::Cmp        => write!(f, "cmp"),
             UserOps::BinAcc     => write!(f, "binacc"),
+            UserOps::RcAcc      => write!(f, "rcacc"),
     
-            UserOps::RescR      => write!(f, "rescr")
+            UserOps::RescR      => write!(f, "rescr"),
+
+            UserOps::DynCall    => write!(f, "dyncall"),
         };
     }
 }
@@ -174,10 +247,13 @@ impl sp_std::fmt::Display for UserOps {
 #[derive(Copy, Clone, Debug, Serialize, Deserialize)]
 pub enum OpHint {
     EqStart,
+    LtStart,
     RcStart(u32),
     CmpStart(u32),
     PmpathStart(u32),
+    MupdateStart(u32),
     PushValue(u128),
+    Advice(AdviceRequest),
     None,
 }
 
@@ -194,10 +270,13 @@ impl sp_std::fmt::Display for OpHint {
     fn fmt(&self, f: &mut sp_std::fmt::Formatter) -> sp_std::fmt::Result {
         return match self {
             OpHint::EqStart          => write!(f, "::eq"),
+            OpHint::LtStart          => write!(f, "::lt"),
             OpHint::RcStart(value)   => write!(f, ".{}", value),
             OpHint::CmpStart(value)     => write!(f, ".{}", value),
             OpHint::PmpathStart(value)  => write!(f, ".{}", value),
+            OpHint::MupdateStart(value) => write!(f, ".{}", value),
             OpHint::PushValue(value)    => write!(f, "({})", value),
+            OpHint::Advice(request)     => write!(f, "::{}", request),
             OpHint::None             => Ok(()),
         };
     }
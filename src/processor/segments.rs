@@ -0,0 +1,185 @@
+use serde::{ Serialize, Deserialize };
+use sp_std::{ cmp, ops::Range, vec::Vec };
+use crate::{
+    crypto::hash::blake3,
+    utils::as_bytes,
+    MIN_TRACE_LENGTH, BASE_CYCLE_LENGTH, MAX_CONTEXT_DEPTH, MAX_LOOP_DEPTH, MAX_STACK_DEPTH,
+    OP_COUNTER_IDX, SPONGE_WIDTH, SPONGE_RANGE, OP_FLAG_HELPERS_RANGE, NUM_RANGE_CHECK_REGISTERS,
+};
+use super::{ Decoder, Stack };
+
+// TYPES AND INTERFACES
+// ================================================================================================
+
+/// State of the VM at a specific step of program execution: the operation counter, the state
+/// of the operation sponge, and the context, loop, and user stacks. All stacks are padded with
+/// 0s to their max depths so that states taken from execution traces of different layouts can
+/// be compared.
+///
+/// Positions of input tapes A and B are not part of the state: values read from the tapes are
+/// secret inputs which are not constrained by the segment AIR, and thus, the number of values
+/// consumed by a segment is not something a verifier could check.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct VmState {
+    pub op_counter      : u128,
+    pub sponge          : [u128; SPONGE_WIDTH],
+    pub ctx_stack       : Vec<u128>,
+    pub loop_stack      : Vec<u128>,
+    pub user_stack      : Vec<u128>,
+}
+
+/// A contiguous part of the execution trace of a program. The first step of the segment is
+/// executed in the `entry` state, and the last executed step of the segment leaves the VM in
+/// the `exit` state; the next segment starts in the `exit` state of the previous segment.
+pub struct ExecutionSegment {
+    pub trace       : Vec<Vec<u128>>,
+    pub ctx_depth   : usize,
+    pub loop_depth  : usize,
+    pub first_step  : usize,
+    pub entry       : VmState,
+    pub exit        : VmState,
+}
+
+/// Cuts execution trace into segments at the starts of Span blocks, and passes complete
+/// segments to the handler; when there is no handler, the trace is never cut.
+pub struct Segmenter<'a> {
+    segment_length  : usize,
+    handler         : Option<&'a mut dyn FnMut(ExecutionSegment)>,
+}
+
+// VM STATE IMPLEMENTATION
+// ================================================================================================
+impl VmState {
+
+    /// Returns the state of the VM before the first step of a program executed with the
+    /// specified public inputs.
+    pub fn initial(public_inputs: &[u128]) -> VmState {
+        let mut user_stack = public_inputs.to_vec();
+        user_stack.resize(cmp::max(public_inputs.len(), MAX_STACK_DEPTH), 0);
+
+        return VmState {
+            op_counter      : 0,
+            sponge          : [0; SPONGE_WIDTH],
+            ctx_stack       : vec![0; MAX_CONTEXT_DEPTH],
+            loop_stack      : vec![0; MAX_LOOP_DEPTH],
+            user_stack      : user_stack,
+        };
+    }
+
+    /// Reads the state of the VM at the specified `step` of an execution trace with the
+    /// specified context and loop depths.
+    pub fn from_trace(trace: &[Vec<u128>], ctx_depth: usize, loop_depth: usize, step: usize) -> VmState {
+        let ctx_start = OP_FLAG_HELPERS_RANGE.end;
+        let loop_start = ctx_start + ctx_depth;
+        let stack_start = loop_start + loop_depth;
+        let stack_end = trace.len() - NUM_RANGE_CHECK_REGISTERS;
+
+        let mut sponge = [0; SPONGE_WIDTH];
+        sponge.copy_from_slice(&read_registers(trace, SPONGE_RANGE, step, SPONGE_WIDTH));
+
+        return VmState {
+            op_counter      : trace[OP_COUNTER_IDX][step],
+            sponge          : sponge,
+            ctx_stack       : read_registers(trace, ctx_start..loop_start, step, MAX_CONTEXT_DEPTH),
+            loop_stack      : read_registers(trace, loop_start..stack_start, step, MAX_LOOP_DEPTH),
+            user_stack      : read_registers(trace, stack_start..stack_end, step, MAX_STACK_DEPTH),
+        };
+    }
+
+    /// Returns a blake3 hash which commits to all values of the state.
+    pub fn hash(&self) -> [u8; 32] {
+        let mut values = Vec::with_capacity(1 + SPONGE_WIDTH + MAX_CONTEXT_DEPTH + MAX_LOOP_DEPTH + MAX_STACK_DEPTH + 3);
+        values.push(self.op_counter);
+        values.extend_from_slice(&self.sponge);
+        values.push(self.ctx_stack.len() as u128);
+        values.extend_from_slice(&self.ctx_stack);
+        values.push(self.loop_stack.len() as u128);
+        values.extend_from_slice(&self.loop_stack);
+        values.push(self.user_stack.len() as u128);
+        values.extend_from_slice(&self.user_stack);
+
+        let mut result = [0u8; 32];
+        blake3(as_bytes(&values), &mut result);
+        return result;
+    }
+}
+
+// SEGMENTER IMPLEMENTATION
+// ================================================================================================
+impl <'a> Segmenter<'a> {
+
+    /// Returns a segmenter which cuts the trace at the first Span block which starts after the
+    /// current segment reached `segment_length` steps.
+    pub fn new(segment_length: usize, handler: &'a mut dyn FnMut(ExecutionSegment)) -> Segmenter<'a> {
+        assert!(segment_length >= MIN_TRACE_LENGTH,
+            "segment length cannot be smaller than {}", MIN_TRACE_LENGTH);
+
+        return Segmenter {
+            segment_length  : segment_length,
+            handler         : Some(handler),
+        };
+    }
+
+    /// Returns a segmenter which never cuts the trace.
+    pub fn disabled() -> Segmenter<'static> {
+        return Segmenter {
+            segment_length  : usize::MAX,
+            handler         : None,
+        };
+    }
+
+    /// Cuts the trace at the current step if the current segment is long enough; must be
+    /// invoked only at the start of a Span block.
+    pub fn checkpoint(&mut self, decoder: &mut Decoder, stack: &mut Stack) {
+        if self.handler.is_none() || decoder.current_step() < self.segment_length {
+            return;
+        }
+
+        // segments start on steps which are multiples of the cycle length so that periodic
+        // columns of all segments are aligned with the cycles of the entire execution
+        assert!(decoder.cycles() % BASE_CYCLE_LENGTH == 0,
+            "cannot cut execution trace at step {}: alignment is not valid", decoder.cycles());
+        self.emit_segment(decoder, stack);
+    }
+
+    /// Passes the remainder of the trace to the handler as the last segment.
+    pub fn finish(&mut self, decoder: &mut Decoder, stack: &mut Stack) {
+        if self.handler.is_some() {
+            self.emit_segment(decoder, stack);
+        }
+    }
+
+    // HELPER METHODS
+    // --------------------------------------------------------------------------------------------
+
+    fn emit_segment(&mut self, decoder: &mut Decoder, stack: &mut Stack) {
+        let first_step = decoder.cycles() - decoder.current_step();
+        let last_step = decoder.current_step();
+        let trace_length = cmp::max((last_step + 1).next_power_of_two(), MIN_TRACE_LENGTH);
+        let trace_length = cmp::max(trace_length, stack.min_trace_length());
+
+        // context depth and loop depth must be read before the decoder is split
+        let ctx_depth = decoder.max_ctx_stack_depth();
+        let loop_depth = decoder.max_loop_stack_depth();
+
+        let mut trace = decoder.split_segment(trace_length);
+        trace.append(&mut stack.split_segment(trace_length));
+
+        let entry = VmState::from_trace(&trace, ctx_depth, loop_depth, 0);
+        let exit = VmState::from_trace(&trace, ctx_depth, loop_depth, last_step);
+
+        let handler = self.handler.as_mut().unwrap();
+        handler(ExecutionSegment { trace, ctx_depth, loop_depth, first_step, entry, exit });
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Returns values of the registers in the specified `range` at the specified `step` padded
+/// with 0s to `size` values.
+fn read_registers(trace: &[Vec<u128>], range: Range<usize>, step: usize, size: usize) -> Vec<u128> {
+    let mut values: Vec<u128> = trace[range].iter().map(|register| register[step]).collect();
+    values.resize(cmp::max(values.len(), size), 0);
+    return values;
+}
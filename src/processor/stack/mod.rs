@@ -3,18 +3,22 @@ use crate::{
     utils::hasher,
     ProgramInputs, OpCode, OpHint,
     HASH_STATE_WIDTH, MIN_STACK_DEPTH, MAX_STACK_DEPTH,
+    MIN_TRACE_LENGTH, RANGE_CHECK_TABLE_SIZE, MIN_RANGE_CHECK_TRACE_LENGTH,
 };
-use sp_std::{vec, vec::Vec};
+use sp_std::{ boxed::Box, vec, vec::Vec };
+use super::{ AdviceProvider, AdviceRequest, AdviceHandlers, ExecutionError };
 
 #[cfg(test)]
 mod tests;
 
 // TYPES AND INTERFACES
 // ================================================================================================
-pub struct Stack {
+pub struct Stack<'a> {
     registers   : Vec<Vec<u128>>,
     tape_a      : Vec<u128>,
     tape_b      : Vec<u128>,
+    advice      : Box<dyn AdviceProvider + 'a>,
+    lookups     : [u128; RANGE_CHECK_TABLE_SIZE],
     max_depth   : usize,
     depth       : usize,
     step        : usize,
@@ -22,12 +26,19 @@ pub struct Stack {
 
 // STACK IMPLEMENTATION
 // ================================================================================================
-impl Stack {
+impl <'a> Stack<'a> {
 
     /// Returns a new Stack with enough memory allocated for each register to hold trace lengths
     /// of `init_trace_length` steps. Register traces will be expanded dynamically if the number
-    /// of actual steps exceeds this initial setting.
-    pub fn new(inputs: &ProgramInputs, init_trace_length: usize) -> Stack {
+    /// of actual steps exceeds this initial setting. Only built-in advice requests can be
+    /// handled by the returned Stack.
+    pub fn new(inputs: &ProgramInputs, init_trace_length: usize) -> Stack<'a> {
+        return Stack::with_advice(inputs, init_trace_length, Box::new(AdviceHandlers::new()));
+    }
+
+    /// Returns a new Stack in the same way as `new()`, but with advice requests handled by the
+    /// specified `advice` provider.
+    pub fn with_advice(inputs: &ProgramInputs, init_trace_length: usize, advice: Box<dyn AdviceProvider + 'a>) -> Stack<'a> {
 
         // allocate space for register traces and initialize the first state with public inputs
         let public_inputs = inputs.get_public_inputs();
@@ -52,14 +63,17 @@ impl Stack {
             registers,
             tape_a,
             tape_b,
+            advice,
+            lookups: [0; RANGE_CHECK_TABLE_SIZE],
             max_depth: public_inputs.len(),
             depth: public_inputs.len(),
             step: 0,
         };
     }
 
-    /// Executes `opcode` against the current state of the stack.
-    pub fn execute(&mut self, op_code: OpCode, op_hint: OpHint) {
+    /// Executes `opcode` against the current state of the stack; returns an error if the
+    /// operation requested advice which the advice provider failed to supply.
+    pub fn execute(&mut self, op_code: OpCode, op_hint: OpHint) -> Result<(), ExecutionError> {
 
         // increment step pointer and make sure there is enough memory allocated to hold the trace
         self.advance_step();
@@ -69,12 +83,13 @@ impl Stack {
 
             OpCode::Begin       => self.op_noop(),
             OpCode::Noop        => self.op_noop(),
+            OpCode::DynCall     => self.op_noop(),
 
             OpCode::Assert      => self.op_assert(),
             OpCode::AssertEq    => self.op_asserteq(),
 
             OpCode::Push        => self.op_push(op_hint),
-            OpCode::Read        => self.op_read(op_hint),
+            OpCode::Read        => self.op_read(op_hint)?,
             OpCode::Read2       => self.op_read2(op_hint),
 
             OpCode::Dup         => self.op_dup(),
@@ -107,9 +122,12 @@ impl Stack {
             OpCode::Eq          => self.op_eq(),
             OpCode::Cmp         => self.op_cmp(op_hint),
             OpCode::BinAcc      => self.op_binacc(op_hint),
+            OpCode::RcAcc       => self.op_rcacc(op_hint),
 
             OpCode::RescR       => self.op_rescr(),
         }
+
+        return Ok(());
     }
 
     /// Returns trace length of register traces in the decoder.
@@ -128,6 +146,54 @@ impl Stack {
         return self.registers[0][self.step];
     }
 
+    /// Returns the minimum length of a trace which can hold range check lookups performed so
+    /// far; if there were any lookups, the range check table must fit into the trace before its
+    /// last step.
+    pub fn min_trace_length(&self) -> usize {
+        if self.lookups.iter().all(|&count| count == 0) {
+            return MIN_TRACE_LENGTH;
+        }
+        return MIN_RANGE_CHECK_TRACE_LENGTH;
+    }
+
+    /// Extends register traces so that they can hold at least `trace_length` steps.
+    pub fn reserve_trace(&mut self, trace_length: usize) {
+        if trace_length > self.trace_length() {
+            for register in self.registers.iter_mut() {
+                register.resize(trace_length, field::ZERO);
+            }
+        }
+    }
+
+    /// Returns the number of values which remain on input tapes A and B.
+    pub fn remaining_inputs(&self) -> [usize; 2] {
+        return [self.tape_a.len(), self.tape_b.len()];
+    }
+
+    /// Moves register traces for all steps up to and including the current step into a segment
+    /// of `trace_length` steps, and makes the current step the first step of the remaining
+    /// trace. The segment is padded in the same way as by `finalize_trace()`, and registers
+    /// beyond the max depth of the stack are omitted from it. The last register of the segment
+    /// holds counts of range check lookups performed within the segment.
+    pub fn split_segment(&mut self, trace_length: usize) -> Vec<Vec<u128>> {
+        let step = self.step;
+        assert!(trace_length > step, "segment of {} steps cannot hold {} steps", trace_length, step + 1);
+
+        let mut segment = Vec::with_capacity(self.max_depth);
+        for (i, register) in self.registers.iter_mut().enumerate() {
+            if i < self.max_depth {
+                let mut values = register[..=step].to_vec();
+                values.resize(trace_length, register[step]);
+                segment.push(values);
+            }
+            register.drain(..step);
+        }
+        segment.push(self.take_lookup_counts(trace_length));
+
+        self.step = 0;
+        return segment;
+    }
+
     /// Populate all register traces with values for steps between the current step
     /// and the end of the trace.
     pub fn finalize_trace(&mut self) {
@@ -141,9 +207,12 @@ impl Stack {
         self.step = self.trace_length() - 1;
     }
 
-    /// Merges all register traces into a single vector of traces.
+    /// Merges all register traces into a single vector of traces; the last register holds
+    /// counts of range check lookups.
     pub fn into_register_traces(mut self) -> Vec<Vec<u128>> {
+        let lookup_counts = self.take_lookup_counts(self.trace_length());
         self.registers.truncate(self.max_depth);
+        self.registers.push(lookup_counts);
         return self.registers;
     }
 
@@ -179,7 +248,7 @@ impl Stack {
         self.registers[0][self.step] = op_value;
     }
 
-    fn op_read(&mut self, hint: OpHint) {
+    fn op_read(&mut self, hint: OpHint) -> Result<(), ExecutionError> {
         // process execution hint
         match hint {
             OpHint::EqStart => {
@@ -196,6 +265,19 @@ impl Stack {
                     self.tape_a.push(field::inv(field::sub(x, y)));
                 }
             },
+            OpHint::LtStart => {
+                // if we are about to start less-than comparison sequence, push 1 onto tape A
+                // if the top stack value is smaller than the next value; otherwise push 0
+                assert!(self.depth >= 2, "stack underflow at step {}", self.step);
+                let x = self.registers[0][self.step - 1];
+                let y = self.registers[1][self.step - 1];
+                self.tape_a.push(if x < y { field::ONE } else { field::ZERO });
+            },
+            OpHint::Advice(request) => {
+                // if we are about to read advice values, get them from the advice provider and
+                // put them onto tape A so that they are read before all other values on the tape
+                self.inject_advice(request)?;
+            },
             OpHint::None => {
                 assert!(self.tape_a.len() > 0, "1attempt to read from empty tape A at step {}", self.step);
             },
@@ -205,6 +287,7 @@ impl Stack {
         self.shift_right(0, 1);
         let value = self.tape_a.pop().unwrap();
         self.registers[0][self.step] = value;
+        return Ok(());
     }
 
     fn op_read2(&mut self, hint: OpHint) {
@@ -539,13 +622,27 @@ impl Stack {
         // process execution hint
         match hint {
             OpHint::RcStart(n) => {
-                // if we are about to start range check sequence, push binary decompositions
-                // of the value onto tape A
-                assert!(self.depth >= 5, "stack underflow at step {}", self.step);
-                let val = self.registers[4][self.step - 1];
+                // if we are about to start range check sequence, push decomposition of the
+                // value onto tape A; the sequence starts with up to 16 BINACC operations
+                // followed by RCACC operations
+                self.push_range_check_inputs(n, (n - 1) % 16 + 1);
+            },
+            OpHint::MupdateStart(n) => {
+                assert!(self.depth >= 9, "stack underflow at step {}", self.step);
+
+                let n = (n - 1) as usize;
+                assert!(self.tape_a.len() >= n, "too few items on tape A for mupdate macro");
+                assert!(self.tape_b.len() >= n, "too few items on tape B for mupdate macro");
+
+                let idx = self.registers[8][self.step - 1];
+
+                // interlace binary decomposition of index with node values already present on
+                // tape A; unlike in pmpath macro, every bit is read before the node it belongs to
+                let v_a = self.tape_a.split_off(self.tape_a.len() - n);
                 for i in 0..n {
-                    // most significant bit is pushed first
-                    self.tape_a.push((val >> (n - i - 1)) & 1);
+                    // most significant bit is pushed first, right after the last node of the path
+                    self.tape_a.push(v_a[i]);
+                    self.tape_a.push((idx >> (n - i - 1)) & 1);
                 }
             },
             OpHint::None => {
@@ -578,6 +675,49 @@ impl Stack {
         self.copy_state(4);
     }
 
+    fn op_rcacc(&mut self, hint: OpHint) {
+        // process execution hint
+        match hint {
+            OpHint::RcStart(n) => {
+                // if we are about to start range check sequence which consists of RCACC
+                // operations only, push 16-bit limbs of the value onto tape A
+                assert!(n % 16 == 0, "range check of {} bits cannot start with RCACC operation", n);
+                self.push_range_check_inputs(n, 0);
+            },
+            OpHint::None => {
+                assert!(self.depth >= 4, "stack underflow at step {}", self.step);
+                assert!(self.tape_a.len() > 0, "attempt to read from empty tape A at step {}", self.step);
+            },
+            _ => panic!("execution hint {:?} is not valid for RCACC operation", hint)
+        }
+
+        // get the next 16-bit limb of the value from tape A and split it into bytes
+        let limb = self.tape_a.pop().unwrap();
+        assert!(limb < 65536, "expected 16-bit input at step {} but received: {}", self.step, limb);
+        let (hi, lo) = (limb >> 8, limb & 0xFF);
+
+        // compute current power of 2 for limb decomposition
+        let power_of_two = self.registers[2][self.step - 1];
+        assert!(power_of_two.is_power_of_two(),
+            "expected 3rd value from the top of the stack at step {} to be a power of 2, but received {}",
+            self.step, power_of_two);
+        let next_power_of_two = field::mul(power_of_two, 65536);
+
+        let acc = self.registers[3][self.step - 1];
+
+        // update the next state of the computation
+        self.registers[0][self.step] = hi;
+        self.registers[1][self.step] = lo;
+        self.registers[2][self.step] = next_power_of_two;
+        self.registers[3][self.step] = field::add(acc, field::mul(limb, power_of_two));
+
+        // both bytes of the limb are looked up in the range check table
+        self.lookups[hi as usize] += 1;
+        self.lookups[lo as usize] += 1;
+
+        self.copy_state(4);
+    }
+
     // CRYPTOGRAPHIC OPERATIONS
     // --------------------------------------------------------------------------------------------
     fn op_rescr(&mut self) {
@@ -606,6 +746,54 @@ impl Stack {
     // HELPER METHODS
     // --------------------------------------------------------------------------------------------
 
+    fn inject_advice(&mut self, request: AdviceRequest) -> Result<(), ExecutionError> {
+        let stack: Vec<u128> = self.registers[..self.depth].iter().map(|r| r[self.step - 1]).collect();
+        let values = match self.advice.get_advice(request, &stack) {
+            Ok(values) => values,
+            Err(message) => return Err(ExecutionError::Advice(
+                format!("failed to get {} at step {}: {}", request, self.step, message))),
+        };
+        if values.len() != request.num_values() {
+            return Err(ExecutionError::Advice(format!("expected {} values for {} at step {}, but received {}",
+                request.num_values(), request, self.step, values.len())));
+        }
+
+        // tapes are consumed from the end; so, the first value must be put last
+        self.tape_a.extend(values.iter().rev());
+        return Ok(());
+    }
+
+    /// Pushes decomposition of the 5th value from the top of the stack for a range check of
+    /// `n` bits onto tape A: the lowest `num_bits` bits are read first (least significant bit
+    /// first), followed by 16-bit limbs of the remaining bits (least significant limb first).
+    fn push_range_check_inputs(&mut self, n: u32, num_bits: u32) {
+        assert!(self.depth >= 5, "stack underflow at step {}", self.step);
+        let val = self.registers[4][self.step - 1];
+
+        // tapes are consumed from the end; so, the most significant limb is pushed first
+        let num_limbs = (n - num_bits) / 16;
+        for i in (0..num_limbs).rev() {
+            self.tape_a.push((val >> (num_bits + 16 * i)) & 0xFFFF);
+        }
+        for i in (0..num_bits).rev() {
+            self.tape_a.push((val >> i) & 1);
+        }
+    }
+
+    /// Returns counts of range check lookups performed so far as a register trace of
+    /// `trace_length` steps, and resets the counts; the count for byte value v is placed at
+    /// step v, where the range check table holds this value.
+    fn take_lookup_counts(&mut self, trace_length: usize) -> Vec<u128> {
+        assert!(trace_length >= self.min_trace_length(),
+            "trace of {} steps cannot hold range check lookups", trace_length);
+        let mut result = vec![field::ZERO; trace_length];
+        if trace_length >= RANGE_CHECK_TABLE_SIZE {
+            result[..RANGE_CHECK_TABLE_SIZE].copy_from_slice(&self.lookups);
+        }
+        self.lookups = [0; RANGE_CHECK_TABLE_SIZE];
+        return result;
+    }
+
     fn copy_state(&mut self, start: usize) {
         for i in start..self.depth {
             self.registers[i][self.step] = self.registers[i][self.step - 1];
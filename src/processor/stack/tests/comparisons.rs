@@ -10,15 +10,15 @@ fn eq() {
     let inv_diff = field::inv(field::sub(1, 4));
     let mut stack = init_stack(&[3, 3, 4, 5], &[0, inv_diff], &[], TRACE_LENGTH);
 
-    stack.execute(OpCode::Read, OpHint::None);
-    stack.execute(OpCode::Eq, OpHint::None);
+    stack.execute(OpCode::Read, OpHint::None).unwrap();
+    stack.execute(OpCode::Eq, OpHint::None).unwrap();
     assert_eq!(vec![1, 4, 5, 0, 0, 0, 0, 0], get_stack_state(&stack, 2));
 
     assert_eq!(3, stack.depth);
     assert_eq!(5, stack.max_depth);
 
-    stack.execute(OpCode::Read, OpHint::None);
-    stack.execute(OpCode::Eq, OpHint::None);
+    stack.execute(OpCode::Read, OpHint::None).unwrap();
+    stack.execute(OpCode::Eq, OpHint::None).unwrap();
     assert_eq!(vec![0, 5, 0, 0, 0, 0, 0, 0], get_stack_state(&stack, 4));
 
     assert_eq!(2, stack.depth);
@@ -29,15 +29,15 @@ fn eq() {
 fn eq_with_hint() {
     let mut stack = init_stack(&[3, 3, 4, 5], &[], &[], TRACE_LENGTH);
 
-    stack.execute(OpCode::Read, OpHint::EqStart);
-    stack.execute(OpCode::Eq, OpHint::None);
+    stack.execute(OpCode::Read, OpHint::EqStart).unwrap();
+    stack.execute(OpCode::Eq, OpHint::None).unwrap();
     assert_eq!(vec![1, 4, 5, 0, 0, 0, 0, 0], get_stack_state(&stack, 2));
 
     assert_eq!(3, stack.depth);
     assert_eq!(5, stack.max_depth);
 
-    stack.execute(OpCode::Read, OpHint::EqStart);
-    stack.execute(OpCode::Eq, OpHint::None);
+    stack.execute(OpCode::Read, OpHint::EqStart).unwrap();
+    stack.execute(OpCode::Eq, OpHint::None).unwrap();
     assert_eq!(vec![0, 5, 0, 0, 0, 0, 0, 0], get_stack_state(&stack, 4));
 
     assert_eq!(2, stack.depth);
@@ -57,12 +57,12 @@ fn cmp_128() {
     // initialize the stack
     let (inputs_a, inputs_b) = build_inputs_for_cmp(a, b, 128);
     let mut stack = init_stack(&[0, 0, 0, 0, 0, a, b], &inputs_a, &inputs_b, 256);
-    stack.execute(OpCode::Pad2, OpHint::None);
-    stack.execute(OpCode::Push, OpHint::PushValue(p127));
+    stack.execute(OpCode::Pad2, OpHint::None).unwrap();
+    stack.execute(OpCode::Push, OpHint::PushValue(p127)).unwrap();
 
     // execute CMP operations
     for i in 2..130 {
-        stack.execute(OpCode::Cmp, OpHint::None);
+        stack.execute(OpCode::Cmp, OpHint::None).unwrap();
 
         let state = get_stack_state(&stack, i);
         let next  = get_stack_state(&stack, i + 1);
@@ -91,12 +91,12 @@ fn cmp_64() {
     // initialize the stack
     let (inputs_a, inputs_b) = build_inputs_for_cmp(a, b, 64);
     let mut stack = init_stack(&[0, 0, 0, 0, 0, a, b], &inputs_a, &inputs_b, 256);
-    stack.execute(OpCode::Pad2, OpHint::None);
-    stack.execute(OpCode::Push, OpHint::PushValue(p63));
+    stack.execute(OpCode::Pad2, OpHint::None).unwrap();
+    stack.execute(OpCode::Push, OpHint::PushValue(p63)).unwrap();
 
     // execute CMP operations
     for i in 2..66 {
-        stack.execute(OpCode::Cmp, OpHint::None);
+        stack.execute(OpCode::Cmp, OpHint::None).unwrap();
 
         let state = get_stack_state(&stack, i);
         let next  = get_stack_state(&stack, i + 1);
@@ -128,12 +128,12 @@ fn lt() {
     // initialize the stack
     let (inputs_a, inputs_b) = build_inputs_for_cmp(a, b, 128);
     let mut stack = init_stack(&[0, 0, 0, a, b, 7, 11], &inputs_a, &inputs_b, 256);
-    stack.execute(OpCode::Pad2, OpHint::None);
-    stack.execute(OpCode::Pad2, OpHint::None);
-    stack.execute(OpCode::Push, OpHint::PushValue(p127));
+    stack.execute(OpCode::Pad2, OpHint::None).unwrap();
+    stack.execute(OpCode::Pad2, OpHint::None).unwrap();
+    stack.execute(OpCode::Push, OpHint::PushValue(p127)).unwrap();
 
     // execute CMP operations
-    for _ in 3..131 { stack.execute(OpCode::Cmp, OpHint::None); }
+    for _ in 3..131 { stack.execute(OpCode::Cmp, OpHint::None).unwrap(); }
 
     // execute program finale
     lt_finale(&mut stack);
@@ -154,12 +154,12 @@ fn gt() {
     // initialize the stack
     let (inputs_a, inputs_b) = build_inputs_for_cmp(a, b, 128);
     let mut stack = init_stack(&[0, 0, 0, a, b, 7, 11], &inputs_a, &inputs_b, 256);
-    stack.execute(OpCode::Pad2, OpHint::None);
-    stack.execute(OpCode::Pad2, OpHint::None);
-    stack.execute(OpCode::Push, OpHint::PushValue(p127));
+    stack.execute(OpCode::Pad2, OpHint::None).unwrap();
+    stack.execute(OpCode::Pad2, OpHint::None).unwrap();
+    stack.execute(OpCode::Push, OpHint::PushValue(p127)).unwrap();
 
     // execute CMP operations
-    for _ in 3..131 { stack.execute(OpCode::Cmp, OpHint::None); }
+    for _ in 3..131 { stack.execute(OpCode::Cmp, OpHint::None).unwrap(); }
 
     // execute program finale
     gt_finale(&mut stack);
@@ -190,12 +190,12 @@ fn binacc_128() {
         256);
 
     // execute binary aggregation operations
-    for _ in 0..128 { stack.execute(OpCode::BinAcc, OpHint::None); }
+    for _ in 0..128 { stack.execute(OpCode::BinAcc, OpHint::None).unwrap(); }
 
     // check the result
-    stack.execute(OpCode::Drop, OpHint::None);
-    stack.execute(OpCode::Drop, OpHint::None);
-    stack.execute(OpCode::Drop, OpHint::None);
+    stack.execute(OpCode::Drop, OpHint::None).unwrap();
+    stack.execute(OpCode::Drop, OpHint::None).unwrap();
+    stack.execute(OpCode::Drop, OpHint::None).unwrap();
     let state = get_stack_state(&stack, 131);
     assert_eq!(vec![x, x, 7, 11, 0, 0, 0, 0], state);
 }
@@ -217,16 +217,48 @@ fn binacc_64() {
         256);
 
     // execute binary aggregation operations
-    for _ in 0..64 { stack.execute(OpCode::BinAcc, OpHint::None); }
+    for _ in 0..64 { stack.execute(OpCode::BinAcc, OpHint::None).unwrap(); }
 
     // check the result
-    stack.execute(OpCode::Drop, OpHint::None);
-    stack.execute(OpCode::Drop, OpHint::None);
-    stack.execute(OpCode::Drop, OpHint::None);
+    stack.execute(OpCode::Drop, OpHint::None).unwrap();
+    stack.execute(OpCode::Drop, OpHint::None).unwrap();
+    stack.execute(OpCode::Drop, OpHint::None).unwrap();
     let state = get_stack_state(&stack, 67);
     assert_eq!(vec![x, x, 7, 11, 0, 0, 0, 0], state);
 }
 
+#[test]
+fn binacc_mupdate() {
+
+    // initialize the stack with index 6 below the leaves and 3 nodes on the tapes
+    let mut stack = init_stack(
+        &[1, 0, 1, 2, 3, 4, 6],
+        &[10, 20, 30],
+        &[11, 21, 31],
+        16);
+    stack.execute(OpCode::Pad2, OpHint::None).unwrap();
+
+    // bits of the index are interlaced with the nodes, and every bit is read before its node
+    stack.execute(OpCode::BinAcc, OpHint::MupdateStart(4)).unwrap();
+    let state = get_stack_state(&stack, 2);
+    assert_eq!(vec![0, 0, 2, 0, 1, 2, 3, 4, 6], state[..9].to_vec());
+
+    // read the remaining bits and nodes, dropping every node after it has been read
+    for i in 1..3 {
+        stack.execute(OpCode::Read2, OpHint::None).unwrap();
+        let state = get_stack_state(&stack, 4 * i - 1);
+        assert_eq!(vec![i as u128 * 10 + 1, i as u128 * 10], state[..2].to_vec());
+        stack.execute(OpCode::Drop, OpHint::None).unwrap();
+        stack.execute(OpCode::Drop, OpHint::None).unwrap();
+        stack.execute(OpCode::BinAcc, OpHint::None).unwrap();
+    }
+
+    stack.execute(OpCode::Read2, OpHint::None).unwrap();
+    let state = get_stack_state(&stack, 11);
+    assert_eq!(vec![31, 30, 1, 0, 8, 6, 1, 2, 3, 4, 6], state[..11].to_vec());
+    assert_eq!([0, 0], stack.remaining_inputs());
+}
+
 #[test]
 fn isodd_128() {
 
@@ -245,25 +277,59 @@ fn isodd_128() {
         256);
 
     // read the first bit and make sure it is saved at the end of the stack
-    stack.execute(OpCode::BinAcc, OpHint::None);
-    stack.execute(OpCode::Swap2, OpHint::None);
-    stack.execute(OpCode::Roll4, OpHint::None);
-    stack.execute(OpCode::Dup, OpHint::None);
+    stack.execute(OpCode::BinAcc, OpHint::None).unwrap();
+    stack.execute(OpCode::Swap2, OpHint::None).unwrap();
+    stack.execute(OpCode::Roll4, OpHint::None).unwrap();
+    stack.execute(OpCode::Dup, OpHint::None).unwrap();
 
     // execute remaining binary aggregation operations
-    for _ in 0..127 { stack.execute(OpCode::BinAcc, OpHint::None); }
+    for _ in 0..127 { stack.execute(OpCode::BinAcc, OpHint::None).unwrap(); }
 
     // check the result
-    stack.execute(OpCode::Drop, OpHint::None);
-    stack.execute(OpCode::Drop, OpHint::None);
-    stack.execute(OpCode::Swap, OpHint::None);
-    stack.execute(OpCode::Roll4, OpHint::None);
-    stack.execute(OpCode::AssertEq, OpHint::None);
-    stack.execute(OpCode::Drop, OpHint::None);
+    stack.execute(OpCode::Drop, OpHint::None).unwrap();
+    stack.execute(OpCode::Drop, OpHint::None).unwrap();
+    stack.execute(OpCode::Swap, OpHint::None).unwrap();
+    stack.execute(OpCode::Roll4, OpHint::None).unwrap();
+    stack.execute(OpCode::AssertEq, OpHint::None).unwrap();
+    stack.execute(OpCode::Drop, OpHint::None).unwrap();
     let state = get_stack_state(&stack, 137);
     assert_eq!(vec![is_odd, 7, 11, 0, 0, 0, 0, 0], state);
 }
 
+#[test]
+fn rcacc_64() {
+
+    let x: u128 = (field::rand() as u64) as u128;
+
+    // initialize the stack; the value is decomposed into 16-bit limbs by the hint
+    let mut stack = init_stack(&[0, 0, 1, 0, x, 7, 11], &[], &[], 256);
+
+    // execute range check aggregation operations
+    stack.execute(OpCode::RcAcc, OpHint::RcStart(64)).unwrap();
+    for _ in 0..3 { stack.execute(OpCode::RcAcc, OpHint::None).unwrap(); }
+
+    // the last limb is split into bytes at the top of the stack
+    let state = get_stack_state(&stack, 4);
+    assert_eq!(vec![x >> 56, (x >> 48) & 0xFF, field::exp(2, 64), x, x, 7, 11, 0], state);
+
+    // every limb results in lookups of two bytes, and the lookups require a long trace
+    assert_eq!(8, stack.lookups.iter().sum::<u128>());
+    assert_eq!(crate::MIN_RANGE_CHECK_TRACE_LENGTH, stack.min_trace_length());
+
+    stack.execute(OpCode::Drop, OpHint::None).unwrap();
+    stack.execute(OpCode::Drop, OpHint::None).unwrap();
+    stack.execute(OpCode::Drop, OpHint::None).unwrap();
+    let state = get_stack_state(&stack, 7);
+    assert_eq!(vec![x, x, 7, 11, 0, 0, 0, 0], state);
+}
+
+#[test]
+#[should_panic(expected = "expected 16-bit input at step 1")]
+fn rcacc_invalid_limb() {
+    let mut stack = init_stack(&[0, 0, 1, 0, 1 << 16], &[1 << 16], &[], 256);
+    stack.execute(OpCode::RcAcc, OpHint::None).unwrap();
+}
+
 // HELPER FUNCTIONS
 // ================================================================================================
 fn build_inputs_for_cmp(a: u128, b: u128, size: usize) -> (Vec<u128>, Vec<u128>) {
@@ -281,24 +347,24 @@ fn build_inputs_for_cmp(a: u128, b: u128, size: usize) -> (Vec<u128>, Vec<u128>)
 }
 
 fn lt_finale(stack: &mut Stack) {
-    stack.execute(OpCode::Drop4, OpHint::None);
-    stack.execute(OpCode::Pad2, OpHint::None);
-    stack.execute(OpCode::Swap4, OpHint::None);
-    stack.execute(OpCode::Roll4, OpHint::None);
-    stack.execute(OpCode::AssertEq, OpHint::None);
-    stack.execute(OpCode::AssertEq, OpHint::None);
-    stack.execute(OpCode::Dup, OpHint::None);
-    stack.execute(OpCode::Drop4, OpHint::None);
+    stack.execute(OpCode::Drop4, OpHint::None).unwrap();
+    stack.execute(OpCode::Pad2, OpHint::None).unwrap();
+    stack.execute(OpCode::Swap4, OpHint::None).unwrap();
+    stack.execute(OpCode::Roll4, OpHint::None).unwrap();
+    stack.execute(OpCode::AssertEq, OpHint::None).unwrap();
+    stack.execute(OpCode::AssertEq, OpHint::None).unwrap();
+    stack.execute(OpCode::Dup, OpHint::None).unwrap();
+    stack.execute(OpCode::Drop4, OpHint::None).unwrap();
 }
 
 fn gt_finale(stack: &mut Stack) {
-    stack.execute(OpCode::Drop4, OpHint::None);
-    stack.execute(OpCode::Pad2, OpHint::None);
-    stack.execute(OpCode::Swap4, OpHint::None);
-    stack.execute(OpCode::Roll4, OpHint::None);
-    stack.execute(OpCode::AssertEq, OpHint::None);
-    stack.execute(OpCode::AssertEq, OpHint::None);
-    stack.execute(OpCode::Roll4, OpHint::None);
-    stack.execute(OpCode::Dup, OpHint::None);
-    stack.execute(OpCode::Drop4, OpHint::None);
+    stack.execute(OpCode::Drop4, OpHint::None).unwrap();
+    stack.execute(OpCode::Pad2, OpHint::None).unwrap();
+    stack.execute(OpCode::Swap4, OpHint::None).unwrap();
+    stack.execute(OpCode::Roll4, OpHint::None).unwrap();
+    stack.execute(OpCode::AssertEq, OpHint::None).unwrap();
+    stack.execute(OpCode::AssertEq, OpHint::None).unwrap();
+    stack.execute(OpCode::Roll4, OpHint::None).unwrap();
+    stack.execute(OpCode::Dup, OpHint::None).unwrap();
+    stack.execute(OpCode::Drop4, OpHint::None).unwrap();
 }
\ No newline at end of file
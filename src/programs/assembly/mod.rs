@@ -1,6 +1,7 @@
 use hashbrown::HashMap;
 use sp_std::{vec, vec::Vec};
 use super::{ Program, ProgramBlock, Span, Group, Switch, Loop, OpCode, OpHint, BASE_CYCLE_LENGTH };
+use super::blocks::DynCall;
 
 mod parsers;
 use parsers::*;
@@ -128,6 +129,14 @@ fn parse_block(parent: &mut Vec<ProgramBlock>, tokens: &[&str], mut i: usize) ->
             parent.push(Loop::new_block(body));
             return Ok(i + 1);
         },
+        "dyncall" => {
+            // make sure the instruction is valid; dynamic calls have no body
+            if head.len() > 1 {
+                return Err(AssemblyError::invalid_block_head(&head, i));
+            }
+            parent.push(DynCall::new_block());
+            return Ok(i + 1);
+        },
         _ => return Err(AssemblyError::invalid_block_head(&head, i)),
     }
 }
@@ -162,11 +171,22 @@ fn parse_branch(body: &mut Vec<ProgramBlock>, tokens: &[&str], mut i: usize) ->
     while i < tokens.len() {
         let op: Vec<&str> = tokens[i].split(".").collect();
         i = match op[0] {
-            "block" | "if" | "repeat" | "while" => {
+            "block" | "if" | "repeat" | "while" | "dyncall" => {
                 let force_span = body.len() == 0;
                 add_span(body, &mut op_codes, &mut op_hints, force_span);
                 parse_block(body, tokens, i)?
             },
+            "hashtape" if op.len() == 2 && op[1] == "many" => {
+                // a data-dependent number of values is hashed in a loop; so, the instruction
+                // expands into a Loop block rather than into operations of the current span
+                append_hashtape_start(&mut op_codes);
+                add_span(body, &mut op_codes, &mut op_hints, false);
+                let mut loop_body = Vec::new();
+                add_span(&mut loop_body, &mut build_hashtape_loop(), &mut HashMap::new(), false);
+                body.push(Loop::new_block(loop_body));
+                append_hashtape_end(&mut op_codes);
+                i + 1
+            },
             "else" => {
                 if head[0] != "if" {
                     return Err(AssemblyError::dangling_else(i));
@@ -209,6 +229,7 @@ fn parse_op_token(op: Vec<&str>, op_codes: &mut Vec<OpCode>, op_hints: &mut Hint
 
         "push"   => parse_push(op_codes, op_hints, &op, step),
         "read"   => parse_read(op_codes, &op, step),
+        "adv"    => parse_adv(op_codes, op_hints, &op, step),
 
         "dup"    => parse_dup(op_codes, &op, step),
         "pad"    => parse_pad(op_codes, &op, step),
@@ -239,6 +260,9 @@ fn parse_op_token(op: Vec<&str>, op_codes: &mut Vec<OpCode>, op_hints: &mut Hint
         "hash"   => parse_hash(op_codes, &op, step),
         "smpath" => parse_smpath(op_codes, &op, step),
         "pmpath" => parse_pmpath(op_codes, op_hints, &op, step),
+        "mupdate" => parse_mupdate(op_codes, op_hints, &op, step),
+        "hashtape" => parse_hashtape(op_codes, &op, step),
+        "lamport" => parse_lamport(op_codes, &op, step),
 
         _ => return Err(AssemblyError::invalid_op(&op, step))
     }?;
@@ -1,4 +1,5 @@
 use crate::{ math::field };
+use crate::processor::AdviceRequest;
 use super::{ AssemblyError, HintMap, OpCode, OpHint };
 use sp_std::{vec, vec::Vec};
 
@@ -6,6 +7,7 @@ use sp_std::{vec, vec::Vec};
 // ================================================================================================
 const PUSH_OP_ALIGNMENT: usize = 8;
 const HASH_OP_ALIGNMENT: usize = 16;
+const HASH_CHUNK_SIZE: usize = 4;
 
 // CONTROL FLOW OPERATIONS
 // ================================================================================================
@@ -81,6 +83,57 @@ pub fn parse_read(program: &mut Vec<OpCode>, op: &[&str], step: usize) -> Result
     return Ok(true);
 }
 
+/// Appends a sequence of READ operations to the program which push values computed by the
+/// advice provider onto the stack. Built-in advice is requested as `adv.inv`, `adv.div`, and
+/// `adv.sqrt`; `adv.mpath.n` requests a Merkle path for a tree of depth n, and `adv.n.k`
+/// requests k values from the custom handler with id n (k is 1 if omitted).
+pub fn parse_adv(program: &mut Vec<OpCode>, hints: &mut HintMap, op: &[&str], step: usize) -> Result<bool, AssemblyError> {
+    if op.len() == 1 {
+        return Err(AssemblyError::missing_param(op, step));
+    }
+
+    let request = match op[1] {
+        "inv" | "div" | "sqrt" if op.len() > 2 => return Err(AssemblyError::extra_param(op, step)),
+        "inv"  => AdviceRequest::Inv,
+        "div"  => AdviceRequest::Div,
+        "sqrt" => AdviceRequest::Sqrt,
+        "mpath" => {
+            if op.len() == 2 {
+                return Err(AssemblyError::missing_param(op, step));
+            } else if op.len() > 3 {
+                return Err(AssemblyError::extra_param(op, step));
+            }
+            match op[2].parse::<u32>() {
+                Ok(depth) if depth >= 2 && depth <= 256 => AdviceRequest::MerklePath(depth),
+                _ => return Err(AssemblyError::invalid_param_reason(op, step,
+                    format!("parameter {} is invalid; value must be between 2 and 256", op[2])))
+            }
+        },
+        _ => {
+            if op.len() > 3 {
+                return Err(AssemblyError::extra_param(op, step));
+            }
+            let id = match op[1].parse::<u32>() {
+                Ok(id) => id,
+                Err(_) => return Err(AssemblyError::invalid_param_reason(op, step,
+                    format!("parameter {} is invalid; allowed values are: [inv, div, sqrt, mpath] or a handler id", op[1])))
+            };
+            let count = match op.get(2).map(|count| count.parse::<u32>()) {
+                None => 1,
+                Some(Ok(count)) if count > 0 => count,
+                Some(_) => return Err(AssemblyError::invalid_param_reason(op, step,
+                    format!("parameter {} is invalid; value must be greater than 0", op[2])))
+            };
+            AdviceRequest::Custom(id, count)
+        }
+    };
+
+    // the first READ operation puts all advice values onto tape A
+    hints.insert(program.len(), OpHint::Advice(request));
+    program.resize(program.len() + request.num_values(), OpCode::Read);
+    return Ok(true);
+}
+
 // STACK MANIPULATION OPERATIONS
 // ================================================================================================
 
@@ -279,6 +332,14 @@ pub fn parse_gt(program: &mut Vec<OpCode>, hints: &mut HintMap, op: &[&str], ste
             format!("parameter {} is invalid; value must be between 4 and 128", n)))
     }
 
+    // a > b is the same as b < a; so, for values which can be range-checked with RCACC
+    // operations, swap the values and determine whether the top value is less than the next one
+    if is_lookup_comparison(n) {
+        program.push(OpCode::Swap);
+        append_lt_lookup(program, hints, n);
+        return Ok(true);
+    }
+
     // prepare the stack
     program.extend_from_slice(&[OpCode::Pad2, OpCode::Pad2, OpCode::Pad2, OpCode::Dup]);
     let power_of_two = u128::pow(2, n - 1);
@@ -311,6 +372,11 @@ pub fn parse_lt(program: &mut Vec<OpCode>, hints: &mut HintMap, op: &[&str], ste
             format!("parameter {} is invalid; value must be between 4 and 128", n)))
     }
 
+    if is_lookup_comparison(n) {
+        append_lt_lookup(program, hints, n);
+        return Ok(true);
+    }
+
     // prepare the stack
     program.extend_from_slice(&[OpCode::Pad2, OpCode::Pad2, OpCode::Pad2, OpCode::Dup]);
     let power_of_two = u128::pow(2, n - 1);
@@ -341,18 +407,11 @@ pub fn parse_rc(program: &mut Vec<OpCode>, hints: &mut HintMap, op: &[&str], ste
             format!("parameter {} is invalid; value must be between 4 and 128", n)))
     }
 
-    // prepare the stack
-    program.push(OpCode::Pad2);
-    append_push_op(program, hints, field::ONE);
-    program.extend_from_slice(&[OpCode::Swap, OpCode::Dup]);
-
-    // add a hint indicating that range-checking is about to start
-    hints.insert(program.len(), OpHint::RcStart(n));
-
-    // append BINACC operations
-    program.resize(program.len() + (n as usize), OpCode::BinAcc);
+    // prepare the stack and aggregate the value from bits and 16-bit limbs
+    program.extend_from_slice(&[OpCode::Pad2, OpCode::Not, OpCode::Pad2]);
+    append_rc_accumulators(program, hints, n);
 
-    // compare binary aggregation value with the original value
+    // compare aggregated value with the original value
     program.extend_from_slice(&[OpCode::Dup, OpCode::Drop4]);
     hints.insert(program.len(), OpHint::EqStart);
     program.extend_from_slice(&[OpCode::Read, OpCode::Eq]);
@@ -371,9 +430,7 @@ pub fn parse_isodd(program: &mut Vec<OpCode>, hints: &mut HintMap, op: &[&str],
     }
 
     // prepare the stack
-    program.push(OpCode::Pad2);
-    append_push_op(program, hints, field::ONE);
-    program.extend_from_slice(&[OpCode::Swap, OpCode::Dup]);
+    program.extend_from_slice(&[OpCode::Pad2, OpCode::Not, OpCode::Pad2]);
 
     // add a hint indicating that range-checking is about to start
     hints.insert(program.len(), OpHint::RcStart(n));
@@ -381,11 +438,12 @@ pub fn parse_isodd(program: &mut Vec<OpCode>, hints: &mut HintMap, op: &[&str],
     // read the first bit and make sure it is saved at the end of the stack
     program.extend_from_slice(&[OpCode::BinAcc, OpCode::Swap2, OpCode::Roll4, OpCode::Dup]);
 
-    // append remaining BINACC operations
-    let n = n - 1;
-    program.resize(program.len() + (n as usize), OpCode::BinAcc);
+    // append remaining BINACC operations followed by RCACC operations
+    let num_bits = (n - 1) % 16 + 1;
+    program.resize(program.len() + (num_bits - 1) as usize, OpCode::BinAcc);
+    program.resize(program.len() + ((n - num_bits) / 16) as usize, OpCode::RcAcc);
 
-    // compare binary aggregation value with the original value and drop all values used in
+    // compare aggregated value with the original value and drop all values used in
     // computations except for the least significant bit of the value we saved previously
     program.extend_from_slice(&[
         OpCode::Drop, OpCode::Drop, OpCode::Swap, OpCode::Roll4, OpCode::AssertEq, OpCode::Drop
@@ -393,6 +451,61 @@ pub fn parse_isodd(program: &mut Vec<OpCode>, hints: &mut HintMap, op: &[&str],
     return Ok(true);
 }
 
+/// Returns true if values of n bits should be compared by range-checking them and their
+/// difference with BINACC and RCACC operations; otherwise, values are compared bit by bit with
+/// CMP operations. A difference which wrapped around the field modulus p fits into n bits only
+/// if p < 2^(n + 1) - 1, and so lookups are sound for n of up to 126 bits. Below that, lookups
+/// are used whenever range checks of three values take fewer cycles than n CMP operations;
+/// this is the case for all n from 64 to 126, and for some n between 32 and 63.
+fn is_lookup_comparison(n: u32) -> bool {
+    if n > 126 {
+        return false;
+    }
+    let lookup_cycles = 3 * (n % 16 + n / 16) + 33;
+    return lookup_cycles < n + 13;
+}
+
+/// Appends a sequence of operations which determines whether the top value a on the stack is
+/// less than the next value b. Both values are range-checked to n bits; then, depending on the
+/// result r provided by the prover, either b - a - 1 (r = 1) or a - b (r = 0) is range-checked
+/// to n bits as well, which proves that r is correct.
+fn append_lt_lookup(program: &mut Vec<OpCode>, hints: &mut HintMap, n: u32) {
+    // read the result and save two copies of it at the end of the stack
+    hints.insert(program.len(), OpHint::LtStart);
+    program.extend_from_slice(&[OpCode::Read, OpCode::Dup, OpCode::Swap2, OpCode::Dup2]);
+
+    // make sure both values fit into n bits
+    append_rc_assertion(program, hints, n);
+    append_rc_assertion(program, hints, n);
+
+    // compute a - b and b - a - 1, and keep the one selected by the result
+    program.extend_from_slice(&[
+        OpCode::Swap, OpCode::Neg,  OpCode::Add,  OpCode::Dup, OpCode::Pad2, OpCode::Not,
+        OpCode::Swap, OpCode::Drop, OpCode::Add,  OpCode::Neg, OpCode::Choose
+    ]);
+
+    // make sure the selected difference fits into n bits
+    append_rc_assertion(program, hints, n);
+}
+
+/// Appends a sequence of operations which asserts that the top value on the stack can be
+/// represented with n bits, and removes the value from the stack.
+fn append_rc_assertion(program: &mut Vec<OpCode>, hints: &mut HintMap, n: u32) {
+    program.extend_from_slice(&[OpCode::Pad2, OpCode::Not, OpCode::Pad2]);
+    append_rc_accumulators(program, hints, n);
+    program.extend_from_slice(&[OpCode::Dup, OpCode::Drop4, OpCode::AssertEq]);
+}
+
+/// Appends operations which aggregate the 5th value from the top of the stack from its lowest
+/// n % 16 bits (BINACC operations) and the remaining 16-bit limbs (RCACC operations); the stack
+/// must be prepared as [0, 0, 1, 0, x].
+fn append_rc_accumulators(program: &mut Vec<OpCode>, hints: &mut HintMap, n: u32) {
+    // add a hint indicating that range-checking is about to start
+    hints.insert(program.len(), OpHint::RcStart(n));
+    program.resize(program.len() + (n % 16) as usize, OpCode::BinAcc);
+    program.resize(program.len() + (n / 16) as usize, OpCode::RcAcc);
+}
+
 // SELECTOR OPERATIONS
 // ================================================================================================
 
@@ -423,23 +536,66 @@ pub fn parse_hash(program: &mut Vec<OpCode>, op: &[&str], step: usize) -> Result
             format!("parameter {} is invalid; allowed values are: [1, 2, 3, 4]", n)))
     }
 
-    // pad with NOOPs to make sure hashing starts on a step which is a multiple of 16
-    let alignment = program.len() % HASH_OP_ALIGNMENT;
-    let pad_length = (HASH_OP_ALIGNMENT - alignment) % HASH_OP_ALIGNMENT;
-    program.resize(program.len() + pad_length, OpCode::Noop);
+    append_hash_rounds(program);
+    return Ok(true);
+}
 
-    // append operations to execute 10 rounds of Rescue
-    program.extend_from_slice(&[
-        OpCode::RescR, OpCode::RescR, OpCode::RescR, OpCode::RescR, OpCode::RescR,
-        OpCode::RescR, OpCode::RescR, OpCode::RescR, OpCode::RescR, OpCode::RescR
-    ]);
+/// Appends a sequence of operations to the program to hash n values read from input tape A.
+/// The values are absorbed in chunks of 4, and the result is the same as the digest computed
+/// by `hasher::digest_many()` function.
+pub fn parse_hashtape(program: &mut Vec<OpCode>, op: &[&str], step: usize) -> Result<bool, AssemblyError> {
+    let n = read_param(op, step)? as usize;
 
-    // truncate the state
-    program.push(OpCode::Drop4);
+    // initialize the digest of absorbed chunks to [0, 0], and then hash every full chunk of
+    // values together with this digest
+    program.push(OpCode::Pad2);
+    for _ in 0..(n / HASH_CHUNK_SIZE) {
+        program.extend_from_slice(&[OpCode::Read, OpCode::Read, OpCode::Read, OpCode::Read]);
+        append_hash_rounds(program);
+    }
+
+    // read the remaining values, and pad them with 1 followed by as many 0's as needed
+    // to fill the last chunk
+    let num_remaining = n % HASH_CHUNK_SIZE;
+    program.resize(program.len() + num_remaining, OpCode::Read);
+    program.extend_from_slice(&[OpCode::Pad2, OpCode::Drop, OpCode::Not]);
+    match num_remaining {
+        0 => program.extend_from_slice(&[OpCode::Pad2, OpCode::Pad2, OpCode::Drop]),
+        1 => program.push(OpCode::Pad2),
+        2 => program.extend_from_slice(&[OpCode::Pad2, OpCode::Drop]),
+        _ => ()
+    }
+    append_hash_rounds(program);
 
+    // hash the digest of all chunks in the same way as hashtape.many instruction does
+    append_hashtape_end(program);
     return Ok(true);
 }
 
+/// Appends operations which must precede the loop of `hashtape.many` instruction to the program:
+/// the digest of absorbed chunks is initialized to [0, 0], and the loop flag is read from tape A.
+pub fn append_hashtape_start(program: &mut Vec<OpCode>) {
+    program.extend_from_slice(&[OpCode::Pad2, OpCode::Read]);
+}
+
+/// Returns the body of the loop of `hashtape.many` instruction: every iteration reads a chunk of
+/// 4 values from tape A, hashes it together with the digest of all preceding chunks, and reads
+/// the flag which indicates whether there are more chunks to hash.
+pub fn build_hashtape_loop() -> Vec<OpCode> {
+    let mut program = vec![OpCode::Assert, OpCode::Read, OpCode::Read, OpCode::Read, OpCode::Read];
+    append_hash_rounds(&mut program);
+    program.push(OpCode::Read);
+    return program;
+}
+
+/// Appends operations which must follow the loop of `hashtape.many` instruction to the program:
+/// the digest of all absorbed chunks is hashed once more, with 1 at the top of the state, to
+/// separate the result from the results of `hash.n` instruction.
+pub fn append_hashtape_end(program: &mut Vec<OpCode>) {
+    program.extend_from_slice(&[OpCode::Pad2, OpCode::Pad2, OpCode::Not]);
+    append_hash_rounds(program);
+}
+
 /// Appends a sequence of operations to the program to compute the root of Merkle authentication
 /// path for a tree of depth n. Leaf index is expected to be provided via input tapes A and B.
 pub fn parse_smpath(program: &mut Vec<OpCode>, op: &[&str], step: usize) -> Result<bool, AssemblyError> {
@@ -538,9 +694,156 @@ pub fn parse_pmpath(program: &mut Vec<OpCode>, hints: &mut HintMap, op: &[&str],
     return Ok(true);
 }
 
+/// Appends a sequence of operations to the program to compute roots of a Merkle authentication
+/// path for a tree of depth n before and after a leaf is updated. The old leaf is expected to be
+/// at the top of the stack, followed by the new leaf and by the leaf index.
+pub fn parse_mupdate(program: &mut Vec<OpCode>, hints: &mut HintMap, op: &[&str], step: usize) -> Result<bool, AssemblyError> {
+    let n = read_param(op, step)?;
+    if n < 2 || n > 256 {
+        return Err(AssemblyError::invalid_param_reason(op, step,
+            format!("parameter {} is invalid; value must be between 2 and 256", n)))
+    }
+
+    // insert binary multiplier 1 and index accumulator 0 below the leaves
+    program.extend_from_slice(&[OpCode::Pad2, OpCode::Drop]);
+    append_push_op(program, hints, field::ONE);
+    program.push(OpCode::Pad2);
+
+    // add a hint indicating that mupdate macro is about to begin
+    hints.insert(program.len(), OpHint::MupdateStart(n));
+
+    // read the first bit of the index and the first node, and arrange the stack so that the
+    // node is paired with both leaves, and the bit is available for both pairs
+    program.extend_from_slice(&[
+        OpCode::BinAcc, OpCode::Swap4, OpCode::Read2,  OpCode::Roll8, OpCode::Roll8,
+        OpCode::Swap2,  OpCode::Dup2,  OpCode::Swap4,  OpCode::Dup2,  OpCode::Swap4,
+        OpCode::Swap2,  OpCode::CSwap2, OpCode::Pad2,
+    ]);
+
+    // pad with NOOPs to make sure hashing starts on a step which is a multiple of 16
+    let alignment = program.len() % HASH_OP_ALIGNMENT;
+    let pad_length = (HASH_OP_ALIGNMENT - alignment) % HASH_OP_ALIGNMENT;
+    program.resize(program.len() + pad_length, OpCode::Noop);
+
+    // repeat the following cycle of operations once for each remaining node:
+    // 1. compute hash of the first leaf (or node) paired with the path node
+    // 2. move the second pair of nodes to the top of the stack, swap them based on the index
+    //    bit, and compute their hash
+    // 3. read the next bit of the index (using binacc instruction) and the next node in the
+    //    authentication path, and pair the node with both of the computed hashes
+    // 4. based on position index bit = 1, swap the nodes of the first pair
+    // as a result, the roles of the old and new nodes alternate on every cycle
+    const SUB_CYCLE: [OpCode; 48] = [
+        OpCode::RescR,  OpCode::RescR, OpCode::RescR,  OpCode::RescR,
+        OpCode::RescR,  OpCode::RescR, OpCode::RescR,  OpCode::RescR,
+        OpCode::RescR,  OpCode::RescR, OpCode::Drop4,  OpCode::Swap2,
+        OpCode::Swap4,  OpCode::CSwap2, OpCode::Pad2,  OpCode::Noop,
+        OpCode::RescR,  OpCode::RescR, OpCode::RescR,  OpCode::RescR,
+        OpCode::RescR,  OpCode::RescR, OpCode::RescR,  OpCode::RescR,
+        OpCode::RescR,  OpCode::RescR, OpCode::Drop4,  OpCode::Pad2,
+        OpCode::Roll8,  OpCode::Roll8, OpCode::Swap2,  OpCode::BinAcc,
+        OpCode::Swap4,  OpCode::Read2, OpCode::Roll8,  OpCode::Roll8,
+        OpCode::Swap2,  OpCode::Dup2,  OpCode::Swap4,  OpCode::Dup2,
+        OpCode::Swap4,  OpCode::Swap2, OpCode::CSwap2, OpCode::Pad2,
+        OpCode::Noop,   OpCode::Noop,  OpCode::Noop,   OpCode::Noop,
+    ];
+
+    for _ in 0..(n - 2) {
+        program.extend_from_slice(&SUB_CYCLE);
+    }
+
+    // at the end, use the first 27 operations from the cycle since there is nothing else to
+    // read; then make sure the accumulated value of index is indeed equal to the leaf index
+    program.extend_from_slice(&SUB_CYCLE[..27]);
+    program.extend_from_slice(&[
+        OpCode::Pad2, OpCode::Roll8, OpCode::Roll8, OpCode::Drop, OpCode::Roll8, OpCode::AssertEq,
+        OpCode::Drop, OpCode::Drop
+    ]);
+
+    // the roots are swapped after every level of the tree; so, if the number of levels is odd,
+    // swap them once more to put the old root at the top of the stack
+    if n % 2 == 0 {
+        program.push(OpCode::Swap2);
+    }
+
+    return Ok(true);
+}
+
+/// Appends a sequence of operations to the program to verify a Lamport signature of a message
+/// of n bits. The message is expected to be at the top of the stack followed by the hash of the
+/// public key; the signature is expected to be provided via input tapes A and B.
+pub fn parse_lamport(program: &mut Vec<OpCode>, op: &[&str], step: usize) -> Result<bool, AssemblyError> {
+    let n = read_param(op, step)?;
+    if n < 1 || n > 127 {
+        return Err(AssemblyError::invalid_param_reason(op, step,
+            format!("parameter {} is invalid; value must be between 1 and 127", n)))
+    }
+
+    // set the initial binary multiplier to 1, and initialize the accumulator of the message bits
+    // and the digest of the public key to 0's; the stack becomes: [p, acc, h1, h0, m, k1, k0]
+    program.extend_from_slice(&[OpCode::Pad2, OpCode::Pad2, OpCode::Not]);
+
+    // for every bit of the message:
+    // 1. read the next bit and add it to the accumulator of the message bits
+    // 2. read the revealed secret and hash it to get the public value for the bit
+    // 3. read the public value for the opposite bit, and based on the bit, swap the public
+    //    values so that the one for bit value 0 is deeper in the stack
+    // 4. move the binary multiplier and the accumulator below the digest of the public key,
+    //    and hash both public values together with the digest
+    for i in 0..n {
+        if i > 0 {
+            program.push(OpCode::Swap2);
+        }
+        program.extend_from_slice(&[
+            OpCode::Pad2, OpCode::BinAcc, OpCode::Read2, OpCode::Pad2, OpCode::Pad2
+        ]);
+        append_hash_rounds(program);
+        program.extend_from_slice(&[
+            OpCode::Read2, OpCode::CSwap2, OpCode::Swap4, OpCode::Swap2, OpCode::Swap4
+        ]);
+        append_hash_rounds(program);
+    }
+
+    // make sure the accumulated bits are equal to the message
+    program.extend_from_slice(&[
+        OpCode::Swap2, OpCode::Drop, OpCode::Roll4, OpCode::AssertEq
+    ]);
+
+    // pad the public values with 1 followed by 0's in the same way as hashtape.n instruction
+    // does, finalize the digest, and make sure it is equal to the hash of the public key
+    program.extend_from_slice(&[
+        OpCode::Pad2, OpCode::Drop, OpCode::Not, OpCode::Pad2, OpCode::Pad2, OpCode::Drop
+    ]);
+    append_hash_rounds(program);
+    append_hashtape_end(program);
+    program.extend_from_slice(&[
+        OpCode::Swap, OpCode::Roll4, OpCode::AssertEq, OpCode::AssertEq
+    ]);
+
+    return Ok(true);
+}
+
 // HELPER FUNCTIONS
 // ================================================================================================
 
+/// Appends operations to execute 10 rounds of Rescue against the top 6 items of the stack and
+/// to truncate the state to 2 items; hashing starts on a step which is a multiple of 16.
+fn append_hash_rounds(program: &mut Vec<OpCode>) {
+    // pad with NOOPs to make sure hashing starts on a step which is a multiple of 16
+    let alignment = program.len() % HASH_OP_ALIGNMENT;
+    let pad_length = (HASH_OP_ALIGNMENT - alignment) % HASH_OP_ALIGNMENT;
+    program.resize(program.len() + pad_length, OpCode::Noop);
+
+    // append operations to execute 10 rounds of Rescue
+    program.extend_from_slice(&[
+        OpCode::RescR, OpCode::RescR, OpCode::RescR, OpCode::RescR, OpCode::RescR,
+        OpCode::RescR, OpCode::RescR, OpCode::RescR, OpCode::RescR, OpCode::RescR
+    ]);
+
+    // truncate the state
+    program.push(OpCode::Drop4);
+}
+
 fn read_param(op: &[&str], step: usize) -> Result<u32, AssemblyError> {
     if op.len() == 1 {
         // if no parameters were provided, assume parameter value 1
@@ -130,15 +130,27 @@ fn single_if_else_with_suffix() {
                 not assert mul dup add noop noop noop \
                 noop noop noop noop noop noop noop \
             end \
-            pad2 noop noop noop noop noop noop noop \
-            push(1) swap dup binacc.16 binacc binacc binacc binacc \
-            binacc binacc binacc binacc binacc binacc binacc binacc \
-            binacc binacc binacc dup drop4 read::eq eq \
+            pad2 not pad2 rcacc.16 dup drop4 read::eq eq \
+            noop noop noop noop noop noop noop \
         end";
 
     assert_eq!(expected, format!("{:?}", program));
 }
 
+#[test]
+fn lookup_comparisons() {
+    // lookups are used only when they are cheaper than CMP operations and sound
+    let cases = [(16, false), (32, true), (40, false), (50, true), (64, true), (126, true), (127, false), (128, false)];
+    for &(n, is_lookup) in cases.iter() {
+        for op in ["lt", "gt"].iter() {
+            let program = super::compile(&format!("begin {}.{} end", op, n)).unwrap();
+            let program = format!("{:?}", program);
+            assert_eq!(is_lookup, program.contains("rcacc"), "{}.{}", op, n);
+            assert_eq!(!is_lookup, program.contains("cmp"), "{}.{}", op, n);
+        }
+    }
+}
+
 #[test]
 fn nested_if_else() {
     let source = "
@@ -400,4 +412,127 @@ fn repeat_2_blocks_with_suffix() {
     end";
 
     assert_eq!(expected, format!("{:?}", program));
-}
\ No newline at end of file
+}
+
+// DYNAMIC CALLS
+// ================================================================================================
+
+#[test]
+fn dyncall() {
+    let source = "begin read dyncall push.1 add end";
+    let program = super::compile(source).unwrap();
+
+    let expected = "\
+        begin read noop noop noop noop noop noop noop noop noop noop noop noop noop \
+        dyncall \
+        push(1) add noop noop noop noop noop noop noop noop noop noop noop noop noop \
+        end";
+
+    assert_eq!(expected, format!("{:?}", program));
+
+    // binding the call site to a callee changes the program hash
+    let bound = program.bind_calls(&[1]);
+    assert_eq!(expected, format!("{:?}", bound));
+    assert_ne!(program.hash(), bound.hash());
+
+    // dynamic calls take no parameters
+    let source = "begin read dyncall.1 end";
+    assert!(super::compile(source).is_err());
+}
+
+// ADVICE INSTRUCTIONS
+// ================================================================================================
+
+#[test]
+fn advice() {
+    let source = "begin adv.div adv.sqrt adv.3 adv.4.2 end";
+    let program = super::compile(source).unwrap();
+
+    let expected = "\
+        begin read::adv.div read read::adv.sqrt read::adv.3.1 read::adv.4.2 read \
+        noop noop noop noop noop noop noop noop \
+        end";
+
+    assert_eq!(expected, format!("{:?}", program));
+
+    // malformed advice instructions
+    assert!(super::compile("begin adv end").is_err());
+    assert!(super::compile("begin adv.inv.2 end").is_err());
+    assert!(super::compile("begin adv.abc end").is_err());
+    assert!(super::compile("begin adv.3.0 end").is_err());
+    assert!(super::compile("begin adv.3.2.1 end").is_err());
+}
+
+#[test]
+fn advice_merkle_path() {
+    let source = "begin adv.mpath.3 end";
+    let program = super::compile(source).unwrap();
+
+    let expected = "\
+        begin read::adv.mpath.3 read read read noop noop noop noop \
+        noop noop noop noop noop noop \
+        end";
+
+    assert_eq!(expected, format!("{:?}", program));
+
+    // malformed Merkle path instructions
+    assert!(super::compile("begin adv.mpath end").is_err());
+    assert!(super::compile("begin adv.mpath.1 end").is_err());
+    assert!(super::compile("begin adv.mpath.257 end").is_err());
+    assert!(super::compile("begin adv.mpath.3.1 end").is_err());
+}
+
+// HASHTAPE INSTRUCTIONS
+// ================================================================================================
+
+#[test]
+fn hashtape() {
+    let source = "begin hashtape.5 end";
+    let program = super::compile(source).unwrap();
+
+    let expected = "\
+        begin pad2 read read read read noop noop noop noop noop noop noop noop noop noop \
+        rescr rescr rescr rescr rescr rescr rescr rescr rescr rescr drop4 read pad2 drop not pad2 \
+        rescr rescr rescr rescr rescr rescr rescr rescr rescr rescr drop4 pad2 pad2 not noop noop \
+        rescr rescr rescr rescr rescr rescr rescr rescr rescr rescr drop4 noop noop noop noop \
+        end";
+
+    assert_eq!(expected, format!("{:?}", program));
+}
+
+#[test]
+fn hashtape_many() {
+    let source = "begin push.3 hashtape.many add end";
+    let program = super::compile(source).unwrap();
+
+    let expected = "\
+    begin noop noop noop noop noop noop noop \
+        push(3) pad2 read noop noop noop noop \
+        while \
+            assert read read read read noop noop noop noop noop noop noop noop noop noop noop \
+            rescr rescr rescr rescr rescr rescr rescr rescr rescr rescr drop4 read noop noop noop \
+        end \
+        pad2 pad2 not noop noop noop noop noop noop noop noop noop noop noop noop noop \
+        rescr rescr rescr rescr rescr rescr rescr rescr rescr rescr drop4 add noop noop noop \
+    end";
+
+    assert_eq!(expected, format!("{:?}", program));
+
+    // hashtape instructions take exactly one parameter
+    assert!(super::compile("begin hashtape.0 end").is_err());
+    assert!(super::compile("begin hashtape.many.2 end").is_err());
+}
+
+// LAMPORT INSTRUCTIONS
+// ================================================================================================
+
+#[test]
+fn lamport_params() {
+    assert!(super::compile("begin lamport.1 end").is_ok());
+    assert!(super::compile("begin lamport.127 end").is_ok());
+
+    // the number of message bits must be between 1 and 127
+    assert!(super::compile("begin lamport.0 end").is_err());
+    assert!(super::compile("begin lamport.128 end").is_err());
+    assert!(super::compile("begin lamport.1.2 end").is_err());
+}
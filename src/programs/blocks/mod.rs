@@ -34,6 +34,8 @@ pub enum ProgramBlock {
     Group(Group),
     Switch(Switch),
     Loop(Loop),
+    Proxy(Proxy),
+    DynCall(DynCall),
 }
 
 #[derive(Clone, Serialize, Deserialize)]
@@ -59,6 +61,28 @@ pub struct Loop {
     skip        : Vec<ProgramBlock>,
 }
 
+/// Stands in for a hidden part of a program: either a single control block, or an entire
+/// sequence of blocks such as a branch of a Switch block or the body of a Loop block. A proxy
+/// contains only the values which the hidden part contributes to the hash of its parent; so,
+/// hiding parts of a program does not change the program hash, but hidden parts cannot be
+/// executed.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Proxy {
+    v0          : u128,
+    v1          : u128,
+}
+
+/// Executes a block selected at runtime: the body hash of the block is read from the top of
+/// the stack, and the block is taken from the program library supplied with program inputs.
+/// The call contributes to the hash of its parent in the same way as a Group block with the
+/// callee as its body; so, before a program hash is computed, every call site must be bound to
+/// the callee hash it resolves to, and all executions of a call site must resolve to the same
+/// callee.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct DynCall {
+    callee      : u128,
+}
+
 // PROGRAM BLOCK IMPLEMENTATION
 // ================================================================================================
 
@@ -71,6 +95,49 @@ impl ProgramBlock {
         };
     }
 
+    pub fn is_proxy(&self) -> bool {
+        return match self {
+            ProgramBlock::Proxy(_) => true,
+            _ => false,
+        };
+    }
+
+    /// Returns a Proxy block which can replace this block without changing the hash of the
+    /// program; Span blocks cannot be hidden.
+    pub fn to_proxy(&self) -> ProgramBlock {
+        let (v0, v1) = match self {
+            ProgramBlock::Group(block)  => block.get_hash(),
+            ProgramBlock::Switch(block) => block.get_hash(),
+            ProgramBlock::Loop(block)   => block.get_hash(),
+            ProgramBlock::Proxy(block)  => block.get_hash(),
+            ProgramBlock::DynCall(block)=> block.get_hash(),
+            ProgramBlock::Span(_)       => panic!("Span blocks cannot be hidden"),
+        };
+        return ProgramBlock::Proxy(Proxy { v0, v1 });
+    }
+
+    /// Returns a copy of this block in which all dynamic call sites are bound to the next
+    /// callee hashes from `callees`, in the order in which the call sites appear in the block.
+    pub fn bind_calls(&self, callees: &mut sp_std::slice::Iter<u128>) -> ProgramBlock {
+        return match self {
+            ProgramBlock::Span(_) | ProgramBlock::Proxy(_) => self.clone(),
+            ProgramBlock::Group(block) => ProgramBlock::Group(Group {
+                body        : bind_calls(&block.body, callees),
+            }),
+            ProgramBlock::Switch(block) => ProgramBlock::Switch(Switch {
+                t_branch    : bind_calls(&block.t_branch, callees),
+                f_branch    : bind_calls(&block.f_branch, callees),
+            }),
+            ProgramBlock::Loop(block) => ProgramBlock::Loop(Loop {
+                body        : bind_calls(&block.body, callees),
+                skip        : block.skip.clone(),
+            }),
+            ProgramBlock::DynCall(_) => {
+                let &callee = callees.next().expect("not enough callees to bind all dynamic call sites");
+                ProgramBlock::DynCall(DynCall { callee })
+            },
+        };
+    }
 }
 
 impl sp_std::fmt::Debug for ProgramBlock {
@@ -80,6 +147,8 @@ impl sp_std::fmt::Debug for ProgramBlock {
             ProgramBlock::Group(block)  => write!(f, "{:?}", block)?,
             ProgramBlock::Switch(block) => write!(f, "{:?}", block)?,
             ProgramBlock::Loop(block)   => write!(f, "{:?}", block)?,
+            ProgramBlock::Proxy(block)  => write!(f, "{:?}", block)?,
+            ProgramBlock::DynCall(block)=> write!(f, "{:?}", block)?,
         }
         return Ok(());
     }
@@ -252,7 +321,10 @@ impl Switch {
     }
 
     pub fn true_branch_hash(&self) -> u128 {
-        return hash_seq(&self.t_branch, &BLOCK_SUFFIX, BLOCK_SUFFIX_OFFSET);
+        return match get_proxy(&self.t_branch) {
+            Some(proxy) => proxy.v0,
+            None => hash_seq(&self.t_branch, &BLOCK_SUFFIX, BLOCK_SUFFIX_OFFSET),
+        };
     }
 
     pub fn false_branch(&self) -> &[ProgramBlock] {
@@ -260,7 +332,28 @@ impl Switch {
     }
 
     pub fn false_branch_hash(&self) -> u128 {
-        return hash_seq(&self.f_branch, &BLOCK_SUFFIX, BLOCK_SUFFIX_OFFSET);
+        return match get_proxy(&self.f_branch) {
+            Some(proxy) => proxy.v0,
+            None => hash_seq(&self.f_branch, &BLOCK_SUFFIX, BLOCK_SUFFIX_OFFSET),
+        };
+    }
+
+    /// Returns a copy of this block in which the true branch is replaced with a proxy.
+    pub fn hide_true_branch(&self) -> Switch {
+        let proxy = Proxy { v0: self.true_branch_hash(), v1: 0 };
+        return Switch {
+            t_branch    : vec![ProgramBlock::Proxy(proxy)],
+            f_branch    : self.f_branch.clone(),
+        };
+    }
+
+    /// Returns a copy of this block in which the false branch is replaced with a proxy.
+    pub fn hide_false_branch(&self) -> Switch {
+        let proxy = Proxy { v0: self.false_branch_hash(), v1: 0 };
+        return Switch {
+            t_branch    : self.t_branch.clone(),
+            f_branch    : vec![ProgramBlock::Proxy(proxy)],
+        };
     }
 
     pub fn get_hash(&self) -> (u128, u128) {
@@ -306,11 +399,27 @@ impl Loop {
     }
 
     pub fn image(&self) -> u128 {
-        return hash_seq(&self.body, &[], 0);
+        return match get_proxy(&self.body) {
+            Some(proxy) => proxy.v1,
+            None => hash_seq(&self.body, &[], 0),
+        };
     }
 
     pub fn body_hash(&self) -> u128 {
-        return hash_seq(&self.body, &LOOP_BLOCK_SUFFIX, 0);
+        return match get_proxy(&self.body) {
+            Some(proxy) => proxy.v0,
+            None => hash_seq(&self.body, &LOOP_BLOCK_SUFFIX, 0),
+        };
+    }
+
+    /// Returns a copy of this block in which the loop body is replaced with a proxy; such a
+    /// loop can be executed only if the body is never entered.
+    pub fn hide_body(&self) -> Loop {
+        let proxy = Proxy { v0: self.body_hash(), v1: self.image() };
+        return Loop {
+            body        : vec![ProgramBlock::Proxy(proxy)],
+            skip        : self.skip.clone(),
+        };
     }
 
     pub fn skip(&self) -> &[ProgramBlock] {
@@ -338,8 +447,69 @@ impl sp_std::fmt::Debug for Loop {
     }
 }
 
+// PROXY IMPLEMENTATION
+// ================================================================================================
+impl Proxy {
+
+    pub fn new(v0: u128, v1: u128) -> Proxy {
+        return Proxy { v0, v1 };
+    }
+
+    pub fn get_hash(&self) -> (u128, u128) {
+        return (self.v0, self.v1);
+    }
+}
+
+impl sp_std::fmt::Debug for Proxy {
+    fn fmt(&self, f: &mut sp_std::fmt::Formatter<'_>) -> sp_std::fmt::Result {
+        write!(f, "proxy.{}.{}", self.v0, self.v1)
+    }
+}
+
+// DYNAMIC CALL IMPLEMENTATION
+// ================================================================================================
+impl DynCall {
+
+    pub fn new() -> DynCall {
+        return DynCall { callee: 0 };
+    }
+
+    pub fn new_block() -> ProgramBlock {
+        return ProgramBlock::DynCall(DynCall::new());
+    }
+
+    /// Returns the body hash of the callee to which the call site is bound, or 0 if the call
+    /// site has not been bound yet.
+    pub fn callee(&self) -> u128 {
+        return self.callee;
+    }
+
+    pub fn get_hash(&self) -> (u128, u128) {
+        return (self.callee, 0);
+    }
+}
+
+impl sp_std::fmt::Debug for DynCall {
+    fn fmt(&self, f: &mut sp_std::fmt::Formatter<'_>) -> sp_std::fmt::Result {
+        write!(f, "dyncall")
+    }
+}
+
 // HELPER FUNCTIONS
 // ================================================================================================
+
+fn bind_calls(blocks: &[ProgramBlock], callees: &mut sp_std::slice::Iter<u128>) -> Vec<ProgramBlock> {
+    return blocks.iter().map(|block| block.bind_calls(callees)).collect();
+}
+
+/// Returns the proxy if the sequence of blocks is hidden behind a single proxy.
+fn get_proxy(blocks: &[ProgramBlock]) -> Option<&Proxy> {
+    return match blocks {
+        [ProgramBlock::Proxy(proxy)] => Some(proxy),
+        _ => None,
+    };
+}
+
 fn validate_block_list(blocks: &Vec<ProgramBlock>, starts_with: &[OpCode]) {
 
     assert!(blocks.len() > 0, "a sequence of blocks must contain at least one block");
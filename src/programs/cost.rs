@@ -0,0 +1,375 @@
+use core::time::Duration;
+use sp_std::{ cmp, vec::Vec };
+use crate::{
+    stark::{ ProofOptions, get_composition_degree },
+    OpCode, HACC_NUM_ROUNDS, MIN_TRACE_LENGTH, MAX_STACK_DEPTH,
+    NUM_RANGE_CHECK_REGISTERS, MIN_RANGE_CHECK_TRACE_LENGTH, OP_FLAG_HELPERS_RANGE,
+};
+use super::blocks::{ ProgramBlock, Span };
+
+// CONSTANTS
+// ================================================================================================
+
+/// Number of cycles needed to start a Group, Switch, or Loop block.
+const BLOCK_START_CYCLES: usize = 1;
+
+/// Number of cycles needed to close a block: a NOOP for alignment, TEND/FEND, and the rounds
+/// needed to merge the block hash into the parent hash.
+const BLOCK_END_CYCLES: usize = 2 + HACC_NUM_ROUNDS;
+
+/// Number of cycles needed to wrap or break a loop iteration.
+const LOOP_ITERATION_END_CYCLES: usize = 1;
+
+/// Number of static decoder registers: op_counter, sponge, op bits, and op flag helpers.
+const NUM_STATIC_DECODER_REGISTERS: usize = OP_FLAG_HELPERS_RANGE.end;
+
+/// Approximate time needed to generate a proof for a single cell of the extended execution
+/// trace (one register at one step of the evaluation domain); measured on a single core of a
+/// modern x86_64 machine with default proof options.
+const PROOF_NANOS_PER_CELL: u64 = 600;
+
+// TYPES AND INTERFACES
+// ================================================================================================
+
+/// Statically estimated cost of executing and proving a program.
+///
+/// * `min_cycles` is the number of cycles executed when no loop is entered and the cheapest
+///    branch is taken in every conditional block;
+/// * `max_cycles` is the number of cycles executed when the most expensive branch is taken in
+///    every conditional block; this is None if the program contains loops;
+/// * `loop_iteration_cycles` contains the number of cycles added by each iteration of every loop
+///    in the program (in order of appearance), assuming no nested loops are entered;
+/// * `range_checks` is true if the program contains RCACC operations; execution traces of such
+///    programs are at least 512 steps long and have an auxiliary register.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProgramCost {
+    pub min_cycles              : usize,
+    pub max_cycles              : Option<usize>,
+    pub loop_iteration_cycles   : Vec<usize>,
+    pub ctx_depth               : usize,
+    pub loop_depth              : usize,
+    pub stack_depth             : usize,
+    pub range_checks            : bool,
+
+    min_stack_shift             : isize,
+    max_stack_shift             : isize,
+}
+
+// PROGRAM COST IMPLEMENTATION
+// ================================================================================================
+impl ProgramCost {
+
+    /// Walks the block tree of a program and computes its cost.
+    pub fn from_blocks(blocks: &[ProgramBlock]) -> ProgramCost {
+        let mut cost = ProgramCost {
+            min_cycles              : 0,
+            max_cycles              : Some(0),
+            loop_iteration_cycles   : Vec::new(),
+            ctx_depth               : 0,
+            loop_depth              : 0,
+            stack_depth             : 0,
+            range_checks            : false,
+            min_stack_shift         : 0,
+            max_stack_shift         : 0,
+        };
+
+        let mut stack_shift = 0;
+        let (min_cycles, max_cycles) = cost.add_blocks(blocks, 0, 0, &mut stack_shift);
+        cost.min_cycles = min_cycles + BLOCK_END_CYCLES;
+        cost.max_cycles = max_cycles.map(|cycles| cycles + BLOCK_END_CYCLES);
+
+        // the program must start with enough inputs to cover the deepest drop below the initial
+        // stack, and the stack grows from there by the largest observed shift
+        let stack_depth = (cost.max_stack_shift - cost.min_stack_shift) as usize;
+        cost.stack_depth = cmp::min(cmp::max(stack_depth, 1), MAX_STACK_DEPTH);
+
+        return cost;
+    }
+
+    /// Returns true if the number of executed cycles is known exactly.
+    pub fn is_exact(&self) -> bool {
+        return self.max_cycles == Some(self.min_cycles);
+    }
+
+    /// Returns the length of execution trace for a program which executed `cycles` cycles.
+    pub fn trace_length(cycles: usize) -> usize {
+        return cmp::max((cycles + 1).next_power_of_two(), MIN_TRACE_LENGTH);
+    }
+
+    /// Returns the number of registers in the execution trace of the program; stack depth is
+    /// estimated statically and may be smaller than the actual depth if the program receives
+    /// more public inputs than it consumes.
+    pub fn register_count(&self) -> usize {
+        let aux_width = if self.range_checks { 1 } else { 0 };
+        return NUM_STATIC_DECODER_REGISTERS + self.ctx_depth + self.loop_depth + self.stack_depth
+            + NUM_RANGE_CHECK_REGISTERS + aux_width;
+    }
+
+    /// Returns predicted size (in bytes) of a proof for `cycles` cycles of the program.
+    pub fn proof_size(&self, cycles: usize, options: &ProofOptions) -> usize {
+        let trace_length = self.proven_trace_length(cycles, options);
+        let domain_size = trace_length * options.extension_factor();
+        let num_queries = options.num_queries();
+
+        // trace and constraint commitments and their query proofs; constraint evaluations
+        // are stored 2 per leaf, or 1 per leaf together with a mask in zero-knowledge mode
+        let trace_values = num_queries * self.register_count() * 16;
+        let trace_nodes = merkle_proof_size(domain_size, num_queries);
+
+        // the auxiliary register is committed to separately from the main registers
+        let aux_commitment = if self.range_checks { 32 + trace_nodes } else { 0 };
+        let constraint_values = num_queries * 32;
+        let constraint_leaves = if options.zk() { domain_size } else { domain_size / 2 };
+        let constraint_nodes = merkle_proof_size(constraint_leaves, num_queries);
+        let deep_values = 2 * self.register_count() * 16;
+
+        // FRI layers reduce the domain and the degree by the folding factor until the domain
+        // fits into the remainder; the remainder is sent as polynomial coefficients
+        let folding_factor = options.fri_folding_factor();
+        let mut fri_size = 0;
+        let mut fri_domain_size = domain_size;
+        let mut fri_degree_plus_1 = get_composition_degree(trace_length, options.blinding_rows()) + 1;
+        while fri_domain_size > options.fri_max_remainder_size() {
+            fri_size += 32 + num_queries * folding_factor * 16
+                + merkle_proof_size(fri_domain_size / folding_factor, num_queries);
+            fri_domain_size /= folding_factor;
+            fri_degree_plus_1 = (fri_degree_plus_1 + folding_factor - 1) / folding_factor;
+        }
+        fri_size += fri_degree_plus_1 * 16;
+
+        return 64 + trace_values + trace_nodes + aux_commitment + constraint_values + constraint_nodes
+            + deep_values + fri_size;
+    }
+
+    /// Returns predicted time needed to generate a proof for `cycles` cycles of the program on
+    /// a single core; this is a rough estimate intended for capacity planning.
+    pub fn proof_time(&self, cycles: usize, options: &ProofOptions) -> Duration {
+        let domain_size = self.proven_trace_length(cycles, options) * options.extension_factor();
+        let num_cells = (domain_size * self.register_count()) as u64;
+        return Duration::from_nanos(num_cells * PROOF_NANOS_PER_CELL);
+    }
+
+    // HELPER METHODS
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns the length of execution trace for `cycles` cycles after blinding rows required
+    /// by `options` have been appended to it.
+    fn proven_trace_length(&self, cycles: usize, options: &ProofOptions) -> usize {
+        let trace_length = Self::trace_length(cycles);
+        let trace_length = if self.range_checks { cmp::max(trace_length, MIN_RANGE_CHECK_TRACE_LENGTH) } else { trace_length };
+        if options.blinding_rows() == 0 { return trace_length; }
+        return (trace_length + options.blinding_rows()).next_power_of_two();
+    }
+
+    /// Adds costs of a sequence of blocks and returns (min, max) cycles for the sequence.
+    fn add_blocks(&mut self, blocks: &[ProgramBlock], ctx_depth: usize, loop_depth: usize,
+        stack_shift: &mut isize) -> (usize, Option<usize>)
+    {
+        self.ctx_depth = cmp::max(self.ctx_depth, ctx_depth);
+        self.loop_depth = cmp::max(self.loop_depth, loop_depth);
+
+        let mut min_cycles = 0;
+        let mut max_cycles = Some(0);
+
+        for (i, block) in blocks.iter().enumerate() {
+            let (block_min, block_max) = match block {
+                ProgramBlock::Span(block) => {
+                    // all spans but the first one in a sequence are prefixed with a NOOP
+                    let cycles = block.length() + if i == 0 { 0 } else { 1 };
+                    self.add_stack_ops(block, stack_shift);
+                    (cycles, Some(cycles))
+                },
+                ProgramBlock::Group(block) => {
+                    let (body_min, body_max) = self.add_blocks(block.body(), ctx_depth + 1, loop_depth, stack_shift);
+                    let overhead = BLOCK_START_CYCLES + BLOCK_END_CYCLES;
+                    (body_min + overhead, body_max.map(|c| c + overhead))
+                },
+                ProgramBlock::Switch(block) => {
+                    let mut f_shift = *stack_shift;
+                    let (t_min, t_max) = self.add_blocks(block.true_branch(), ctx_depth + 1, loop_depth, stack_shift);
+                    let (f_min, f_max) = self.add_blocks(block.false_branch(), ctx_depth + 1, loop_depth, &mut f_shift);
+                    *stack_shift = cmp::max(*stack_shift, f_shift);
+
+                    let overhead = BLOCK_START_CYCLES + BLOCK_END_CYCLES;
+                    let max_cycles = match (t_max, f_max) {
+                        (Some(t_max), Some(f_max)) => Some(cmp::max(t_max, f_max) + overhead),
+                        _ => None,
+                    };
+                    (cmp::min(t_min, f_min) + overhead, max_cycles)
+                },
+                ProgramBlock::Loop(block) => {
+                    // the loop body is accounted for once; its cost is recorded per iteration
+                    let iteration_idx = self.loop_iteration_cycles.len();
+                    self.loop_iteration_cycles.push(0);
+                    let (body_min, _) = self.add_blocks(block.body(), ctx_depth + 1, loop_depth + 1, stack_shift);
+                    self.loop_iteration_cycles[iteration_idx] = body_min + LOOP_ITERATION_END_CYCLES;
+
+                    // whether the loop is entered or not, the skip block is always executed
+                    let (skip_cycles, _) = self.add_blocks(block.skip(), ctx_depth + 1, loop_depth, stack_shift);
+                    (skip_cycles + BLOCK_START_CYCLES + BLOCK_END_CYCLES, None)
+                },
+                ProgramBlock::Proxy(_) => {
+                    // the cost of hidden blocks is unknown
+                    (0, None)
+                },
+                ProgramBlock::DynCall(_) => {
+                    // the callee is not known until the program is executed; the call itself
+                    // saves two values on the context stack
+                    self.ctx_depth = cmp::max(self.ctx_depth, ctx_depth + 2);
+                    let overhead = BLOCK_START_CYCLES + BLOCK_END_CYCLES;
+                    (overhead, None)
+                },
+            };
+
+            min_cycles += block_min;
+            max_cycles = match (max_cycles, block_max) {
+                (Some(max_cycles), Some(block_max)) => Some(max_cycles + block_max),
+                _ => None,
+            };
+        }
+
+        return (min_cycles, max_cycles);
+    }
+
+    /// Tracks the number of items on the stack relative to the initial stack depth.
+    fn add_stack_ops(&mut self, block: &Span, stack_shift: &mut isize) {
+        for i in 0..block.length() {
+            let (op_code, _) = block.get_op(i);
+            *stack_shift += get_stack_shift(op_code);
+            self.range_checks |= op_code == OpCode::RcAcc;
+            self.min_stack_shift = cmp::min(self.min_stack_shift, *stack_shift);
+            self.max_stack_shift = cmp::max(self.max_stack_shift, *stack_shift);
+        }
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Returns the change in stack depth caused by the operation.
+fn get_stack_shift(op_code: OpCode) -> isize {
+    return match op_code {
+        OpCode::Assert | OpCode::Drop       => -1,
+        OpCode::Add | OpCode::Mul           => -1,
+        OpCode::And | OpCode::Or            => -1,
+        OpCode::AssertEq | OpCode::Eq       => -2,
+        OpCode::Choose | OpCode::CSwap2     => -2,
+        OpCode::Drop4 | OpCode::Choose2     => -4,
+
+        OpCode::Push | OpCode::Read         =>  1,
+        OpCode::Dup                         =>  1,
+        OpCode::Read2 | OpCode::Dup2        =>  2,
+        OpCode::Pad2                        =>  2,
+        OpCode::Dup4                        =>  4,
+
+        _ => 0,
+    };
+}
+
+/// Returns an estimate of the size of a batch Merkle proof for `num_queries` leaves in a tree
+/// with `num_leaves` leaves; paths of the queried leaves are assumed to merge only after the
+/// first log2(num_queries) levels.
+fn merkle_proof_size(num_leaves: usize, num_queries: usize) -> usize {
+    let tree_depth = num_leaves.trailing_zeros() as usize;
+    let shared_depth = (num_queries.next_power_of_two().trailing_zeros() as usize)
+        .min(tree_depth);
+    return num_queries * (tree_depth - shared_depth) * 32;
+}
+
+// TESTS
+// ================================================================================================
+#[cfg(test)]
+mod tests {
+
+    use crate::{ processor, stark, programs::{ assembly, Program, ProgramInputs }, ExecutionLimits, ProofOptions };
+    use super::ProgramCost;
+
+    #[test]
+    fn estimate_loop_free_program() {
+        let program = assembly::compile("begin add push.5 mul push.7 end").unwrap();
+        let cost = program.estimate_cost();
+        assert!(cost.is_exact());
+        assert_eq!(0, cost.ctx_depth);
+        assert_eq!(get_cycles(&program, &ProgramInputs::from_public(&[1, 2])), cost.min_cycles);
+
+        let program = assembly::compile(
+            "begin read if.true add push.3 else push.7 add push.8 end mul end").unwrap();
+        let cost = program.estimate_cost();
+        assert!(!cost.is_exact());
+        assert_eq!(1, cost.ctx_depth);
+        assert_eq!(get_cycles(&program, &ProgramInputs::new(&[5, 3], &[1], &[])), cost.min_cycles);
+        assert_eq!(get_cycles(&program, &ProgramInputs::new(&[5, 3], &[0], &[])), cost.max_cycles.unwrap());
+    }
+
+    #[test]
+    fn estimate_loop_program() {
+        let program = assembly::compile(
+            "begin mul read while.true dup mul read end end").unwrap();
+        let cost = program.estimate_cost();
+        assert_eq!(None, cost.max_cycles);
+        assert_eq!(1, cost.loop_iteration_cycles.len());
+        assert_eq!(1, cost.loop_depth);
+
+        // cost of a loop which is never entered
+        let inputs = ProgramInputs::new(&[5, 3], &[0], &[]);
+        assert_eq!(get_cycles(&program, &inputs), cost.min_cycles);
+
+        // each iteration adds the same number of cycles
+        for i in 1..4 {
+            let mut tape = vec![1; i];
+            tape.push(0);
+            let inputs = ProgramInputs::new(&[5, 3], &tape, &[]);
+            let expected = cost.min_cycles + i * cost.loop_iteration_cycles[0];
+            assert_eq!(get_cycles(&program, &inputs), expected);
+        }
+    }
+
+    #[test]
+    fn estimate_proof_size() {
+        let program = assembly::compile("begin add push.5 mul push.7 end").unwrap();
+        let inputs = ProgramInputs::from_public(&[1, 2]);
+        check_proof_size(&program, &inputs);
+
+        // range checks require a longer trace and an auxiliary register
+        let program = assembly::compile("begin rc.64 end").unwrap();
+        let inputs = ProgramInputs::from_public(&[1 << 40]);
+        assert!(program.estimate_cost().range_checks);
+        check_proof_size(&program, &inputs);
+    }
+
+    fn check_proof_size(program: &Program, inputs: &ProgramInputs) {
+        let cost = program.estimate_cost();
+        let options = ProofOptions::default();
+        let (trace, ctx_depth, loop_depth) = processor::execute(program, inputs, &ExecutionLimits::default()).unwrap();
+        let mut trace = stark::TraceTable::new(trace, ctx_depth, loop_depth, options.extension_factor());
+        let outputs = trace.get_last_state().user_stack()[..1].to_vec();
+        let pub_inputs = stark::ProcessorPublicInputs {
+            program_hash: *program.hash(),
+            inputs      : inputs.get_public_inputs().to_vec(),
+            outputs     : outputs,
+        };
+        let proof = stark::prove::<stark::ProcessorAir>(&mut trace, &pub_inputs, &options);
+        let actual = bincode::serialize(&proof).unwrap().len();
+        let estimate = cost.proof_size(cost.min_cycles, &options);
+        assert!(estimate > actual * 9 / 10 && estimate < actual * 11 / 10,
+            "estimated proof size {} is too far from actual proof size {}", estimate, actual);
+    }
+
+    /// Returns the number of cycles executed by the program by finding the smallest cycle
+    /// limit which does not abort the execution.
+    fn get_cycles(program: &Program, inputs: &ProgramInputs) -> usize {
+        let mut max_cycles = 1;
+        while processor::execute(program, inputs, &ExecutionLimits { max_cycles, max_trace_length: usize::MAX }).is_err() {
+            max_cycles += 1;
+        }
+        return max_cycles;
+    }
+
+    #[test]
+    fn trace_length() {
+        assert_eq!(16, ProgramCost::trace_length(10));
+        assert_eq!(64, ProgramCost::trace_length(46));
+        assert_eq!(64, ProgramCost::trace_length(63));
+        assert_eq!(128, ProgramCost::trace_length(64));
+    }
+}
@@ -41,6 +41,8 @@ pub fn hash_seq(blocks: &Vec<ProgramBlock>, suffix: &[u8], suffix_offset: usize)
                     ProgramBlock::Group(block)  => block.get_hash(),
                     ProgramBlock::Switch(block) => block.get_hash(),
                     ProgramBlock::Loop(block)   => block.get_hash(),
+                    ProgramBlock::Proxy(block)  => block.get_hash(),
+                    ProgramBlock::DynCall(block)=> block.get_hash(),
                     ProgramBlock::Span(_)       => (0, 0),  // can't happen
                 };
 
@@ -1,4 +1,5 @@
 use crate::{ MAX_PUBLIC_INPUTS };
+use super::{ ProgramLibrary, MerkleStore, LamportSignature };
 use sp_std::{vec, vec::Vec};
 use serde::{Serialize, Deserialize};
 
@@ -6,6 +7,7 @@ use serde::{Serialize, Deserialize};
 pub struct ProgramInputs {
     public: Vec<u128>,
     secret: [Vec<u128>; 2],
+    library: ProgramLibrary,
 }
 
 impl ProgramInputs {
@@ -22,7 +24,8 @@ impl ProgramInputs {
 
         return ProgramInputs {
             public  : public.to_vec(),
-            secret  : [secret_a.to_vec(), secret_b.to_vec()]
+            secret  : [secret_a.to_vec(), secret_b.to_vec()],
+            library : ProgramLibrary::new(),
         };
     }
 
@@ -31,6 +34,7 @@ impl ProgramInputs {
         return ProgramInputs {
             public  : Vec::new(),
             secret  : [Vec::new(), Vec::new()],
+            library : ProgramLibrary::new(),
         };
     }
 
@@ -39,10 +43,67 @@ impl ProgramInputs {
     pub fn from_public(public: &[u128]) -> ProgramInputs {
         return ProgramInputs {
             public: public.to_vec(),
-            secret: [vec![], vec![]]
+            secret: [vec![], vec![]],
+            library: ProgramLibrary::new(),
         };
     }
 
+    /// Returns these inputs with the `library` of blocks which can be invoked by dynamic calls.
+    pub fn with_library(mut self, library: ProgramLibrary) -> ProgramInputs {
+        self.library = library;
+        return self;
+    }
+
+    /// Returns these inputs with the specified values appended to secret input tapes A and B.
+    pub fn with_secret(mut self, secret_a: &[u128], secret_b: &[u128]) -> ProgramInputs {
+        assert!(secret_a.len() == secret_b.len(),
+            "the same number of values must be appended to both secret input tapes");
+        self.secret[0].extend_from_slice(secret_a);
+        self.secret[1].extend_from_slice(secret_b);
+        return self;
+    }
+
+    /// Returns these inputs with the values consumed by `smpath.n` instruction for the leaf at
+    /// the specified `index` of the `store` appended to secret input tapes A and B.
+    pub fn with_smpath(self, store: &MerkleStore, index: usize) -> ProgramInputs {
+        let (a, b) = store.get_smpath_tapes(index);
+        return self.with_secret(&a, &b);
+    }
+
+    /// Returns these inputs with the values consumed by `pmpath.n` (or `mupdate.n`) instruction
+    /// for the leaf at the specified `index` of the `store` appended to secret input tapes A and B.
+    pub fn with_pmpath(self, store: &MerkleStore, index: usize) -> ProgramInputs {
+        let (a, b) = store.get_pmpath_tapes(index);
+        return self.with_secret(&a, &b);
+    }
+
+    /// Returns these inputs with the values consumed by `lamport.n` instruction for the specified
+    /// `signature` appended to secret input tapes A and B; tape A gets n more values than tape B.
+    pub fn with_lamport(mut self, signature: &LamportSignature) -> ProgramInputs {
+        let (a, b) = signature.get_tapes();
+        self.secret[0].extend_from_slice(&a);
+        self.secret[1].extend_from_slice(&b);
+        return self;
+    }
+
+    /// Returns these inputs with the values consumed by `hashtape.many` instruction for the
+    /// specified `values` appended to secret input tape A. The values are padded with 1 and as
+    /// many 0s as needed to fill the last chunk of 4 values, and every chunk is preceded by
+    /// flag 1; the last chunk is followed by flag 0. `hashtape.n` instruction consumes the
+    /// values as is, and these can be appended to tape A directly.
+    pub fn with_hashtape(mut self, values: &[u128]) -> ProgramInputs {
+        let mut padded = values.to_vec();
+        padded.push(1);
+        padded.resize((padded.len() + 3) / 4 * 4, 0);
+
+        for chunk in padded.chunks(4) {
+            self.secret[0].push(1);
+            self.secret[0].extend_from_slice(chunk);
+        }
+        self.secret[0].push(0);
+        return self;
+    }
+
     pub fn get_public_inputs(&self) -> &[u128] {
         return &self.public;
     }
@@ -50,4 +111,8 @@ impl ProgramInputs {
     pub fn get_secret_inputs(&self) -> &[Vec<u128>; 2] {
         return &self.secret;
     }
+
+    pub fn get_library(&self) -> &ProgramLibrary {
+        return &self.library;
+    }
 }
\ No newline at end of file
@@ -0,0 +1,216 @@
+use crate::utils::hasher;
+use serde::{ Serialize, Deserialize };
+use sp_std::vec::Vec;
+
+// CONSTANTS
+// ================================================================================================
+const MAX_MESSAGE_BITS: usize = 127;
+
+// TYPES AND INTERFACES
+// ================================================================================================
+
+/// A Lamport one-time signature key built with the Rescue hash function of Distaff VM, and thus,
+/// signatures made with the key can be verified by `lamport.n` instruction. The key signs messages
+/// of up to n bits: for every bit of a message, the secret key contains 2 secrets (one for bit
+/// value 0 and one for bit value 1), and the public key contains hashes of these secrets. Every
+/// secret and every public value is represented by 2 field elements.
+///
+/// A signature reveals one secret for every bit of the message, and thus, a key must never be
+/// used to sign more than one message.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LamportKey {
+    secret  : Vec<[[u128; 2]; 2]>,
+    public  : Vec<[[u128; 2]; 2]>,
+}
+
+/// A Lamport signature of a message: for every bit of the message, the secret for the value of
+/// the bit, and the public value for the opposite value of the bit.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LamportSignature {
+    message : u128,
+    secrets : Vec<[u128; 2]>,
+    publics : Vec<[u128; 2]>,
+}
+
+// LAMPORT KEY IMPLEMENTATION
+// ================================================================================================
+impl LamportKey {
+
+    /// Returns a key for signing messages of up to `num_bits` bits; all secrets of the key are
+    /// derived from the `seed`, and thus, the seed must be kept secret.
+    pub fn new(seed: [u128; 2], num_bits: usize) -> LamportKey {
+        assert!(num_bits >= 1 && num_bits <= MAX_MESSAGE_BITS,
+            "number of message bits must be between 1 and {}, but was {}", MAX_MESSAGE_BITS, num_bits);
+
+        let mut secret = Vec::with_capacity(num_bits);
+        let mut public = Vec::with_capacity(num_bits);
+        for i in 0..num_bits {
+            let s0 = hash_values(&[seed[0], seed[1], i as u128, 0]);
+            let s1 = hash_values(&[seed[0], seed[1], i as u128, 1]);
+            public.push([hash_values(&s0), hash_values(&s1)]);
+            secret.push([s0, s1]);
+        }
+
+        return LamportKey { secret, public };
+    }
+
+    /// Returns the number of bits in messages which can be signed with this key.
+    pub fn num_bits(&self) -> usize {
+        return self.public.len();
+    }
+
+    /// Returns the hash of the public key; this is the same as the result of hashing all public
+    /// values, starting with the public values for the least significant bit, with `hashtape.n`
+    /// instruction.
+    pub fn public_key_hash(&self) -> [u128; 2] {
+        let mut values = Vec::with_capacity(4 * self.public.len());
+        for public in self.public.iter() {
+            values.extend_from_slice(&[public[0][0], public[0][1], public[1][0], public[1][1]]);
+        }
+        let digest = hasher::digest_many(&values);
+        return [digest[0], digest[1]];
+    }
+
+    /// Signs the specified `message`; the message must fit into the number of bits of this key.
+    pub fn sign(&self, message: u128) -> LamportSignature {
+        assert!(message >> self.num_bits() == 0,
+            "message {} does not fit into {} bits", message, self.num_bits());
+
+        let mut secrets = Vec::with_capacity(self.num_bits());
+        let mut publics = Vec::with_capacity(self.num_bits());
+        for i in 0..self.num_bits() {
+            let bit = ((message >> i) & 1) as usize;
+            secrets.push(self.secret[i][bit]);
+            publics.push(self.public[i][bit ^ 1]);
+        }
+
+        return LamportSignature { message, secrets, publics };
+    }
+}
+
+// LAMPORT SIGNATURE IMPLEMENTATION
+// ================================================================================================
+impl LamportSignature {
+
+    /// Returns the signed message.
+    pub fn message(&self) -> u128 {
+        return self.message;
+    }
+
+    /// Returns true if this signature is valid for a key with the specified `public_key_hash`.
+    pub fn verify(&self, public_key_hash: [u128; 2]) -> bool {
+        let mut values = Vec::with_capacity(4 * self.secrets.len());
+        for i in 0..self.secrets.len() {
+            let public = hash_values(&self.secrets[i]);
+            if (self.message >> i) & 1 == 0 {
+                values.extend_from_slice(&[public[0], public[1], self.publics[i][0], self.publics[i][1]]);
+            }
+            else {
+                values.extend_from_slice(&[self.publics[i][0], self.publics[i][1], public[0], public[1]]);
+            }
+        }
+        let digest = hasher::digest_many(&values);
+        return digest[0] == public_key_hash[0] && digest[1] == public_key_hash[1];
+    }
+
+    /// Returns values of input tapes A and B consumed by `lamport.n` instruction which verifies
+    /// this signature: for every bit of the message, starting with the least significant bit,
+    /// the bit followed by the revealed secret and the public value for the opposite bit. Bits
+    /// are put onto tape A only, and thus, tape A gets n more values than tape B.
+    pub fn get_tapes(&self) -> (Vec<u128>, Vec<u128>) {
+        let mut a = Vec::with_capacity(3 * self.secrets.len());
+        let mut b = Vec::with_capacity(2 * self.secrets.len());
+        for i in 0..self.secrets.len() {
+            a.push((self.message >> i) & 1);
+            a.push(self.secrets[i][0]);
+            b.push(self.secrets[i][1]);
+            a.push(self.publics[i][0]);
+            b.push(self.publics[i][1]);
+        }
+        return (a, b);
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Hashes the specified values in the same way as `hash.n` instruction.
+fn hash_values(values: &[u128]) -> [u128; 2] {
+    let digest = hasher::digest(values);
+    return [digest[0], digest[1]];
+}
+
+// TESTS
+// ================================================================================================
+#[cfg(test)]
+mod tests {
+
+    use crate::{ programs::{ assembly, ProgramInputs }, processor, stark::TraceTable };
+    use super::LamportKey;
+
+    #[test]
+    fn sign_verify() {
+        let key = LamportKey::new([3, 5], 16);
+        let public_key_hash = key.public_key_hash();
+
+        let signature = key.sign(0xBEEF);
+        assert_eq!(0xBEEF, signature.message());
+        assert!(signature.verify(public_key_hash));
+
+        // a signature does not verify against a different key
+        let other_key = LamportKey::new([3, 6], 16);
+        assert!(!signature.verify(other_key.public_key_hash()));
+    }
+
+    #[test]
+    #[should_panic(expected = "message 65536 does not fit into 16 bits")]
+    fn sign_fail() {
+        let key = LamportKey::new([3, 5], 16);
+        key.sign(1 << 16);
+    }
+
+    #[test]
+    fn lamport() {
+        for &num_bits in [1, 8].iter() {
+            let key = LamportKey::new([3, 5], num_bits);
+            let pk = key.public_key_hash();
+
+            for &message in [0, 1, (1 << num_bits) - 1].iter() {
+                let signature = key.sign(message);
+                let inputs = ProgramInputs::from_public(&[message, pk[1], pk[0], 7]).with_lamport(&signature);
+                let stack = execute_lamport(num_bits, &inputs);
+                assert_eq!(vec![7, 0], stack[..2].to_vec());
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "ASSERTEQ failed")]
+    fn lamport_wrong_message() {
+        let key = LamportKey::new([3, 5], 8);
+        let pk = key.public_key_hash();
+
+        // the signature is valid, but for a different message
+        let signature = key.sign(25);
+        let inputs = ProgramInputs::from_public(&[26, pk[1], pk[0]]).with_lamport(&signature);
+        execute_lamport(8, &inputs);
+    }
+
+    #[test]
+    #[should_panic(expected = "ASSERTEQ failed")]
+    fn lamport_wrong_key() {
+        let key = LamportKey::new([3, 5], 8);
+        let pk = LamportKey::new([3, 6], 8).public_key_hash();
+
+        let signature = key.sign(25);
+        let inputs = ProgramInputs::from_public(&[25, pk[1], pk[0]]).with_lamport(&signature);
+        execute_lamport(8, &inputs);
+    }
+
+    fn execute_lamport(num_bits: usize, inputs: &ProgramInputs) -> Vec<u128> {
+        let program = assembly::compile(&format!("begin lamport.{} end", num_bits)).unwrap();
+        let (trace, ctx_depth, loop_depth) = processor::execute(&program, inputs, &Default::default()).unwrap();
+        let trace = TraceTable::new(trace, ctx_depth, loop_depth, 8);
+        return trace.get_last_state().user_stack().to_vec();
+    }
+}
@@ -0,0 +1,50 @@
+use serde::{ Serialize, Deserialize };
+use sp_std::vec::Vec;
+use crate::processor::OpCode;
+use super::{ Group, ProgramBlock };
+
+// TYPES AND INTERFACES
+// ================================================================================================
+
+/// A collection of blocks which can be invoked by dynamic calls; every block is identified by
+/// the hash of its body, and a program calls a block by putting this hash at the top of the
+/// stack before executing a `dyncall` instruction.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ProgramLibrary {
+    blocks      : Vec<(u128, Group)>,
+}
+
+// PROGRAM LIBRARY IMPLEMENTATION
+// ================================================================================================
+impl ProgramLibrary {
+
+    /// Returns an empty library.
+    pub fn new() -> ProgramLibrary {
+        return ProgramLibrary { blocks: Vec::new() };
+    }
+
+    /// Adds the `block` to the library and returns the hash by which the block can be called.
+    /// The block must be a nested block: the root block of a program starts with a BEGIN
+    /// operation and thus cannot be called.
+    pub fn add(&mut self, block: Group) -> u128 {
+        if let ProgramBlock::Span(span) = &block.body()[0] {
+            assert!(!span.starts_with(&[OpCode::Begin]), "the root block of a program cannot be added to a library");
+        }
+
+        let hash = block.body_hash();
+        if self.get(hash).is_none() {
+            self.blocks.push((hash, block));
+        }
+        return hash;
+    }
+
+    /// Returns the block with the specified body hash, if the library contains such a block.
+    pub fn get(&self, hash: u128) -> Option<&Group> {
+        return self.blocks.iter().find(|(block_hash, _)| *block_hash == hash).map(|(_, block)| block);
+    }
+
+    /// Returns the number of blocks in the library.
+    pub fn len(&self) -> usize {
+        return self.blocks.len();
+    }
+}
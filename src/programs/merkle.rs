@@ -0,0 +1,289 @@
+use crate::utils::hasher;
+use serde::{ Serialize, Deserialize };
+use sp_std::{ collections::btree_map::BTreeMap, vec::Vec };
+
+// TYPES AND INTERFACES
+// ================================================================================================
+
+/// A sparse Merkle tree built with the Rescue hash function of Distaff VM, and thus, its roots
+/// can be computed by `smpath.n` and `pmpath.n` instructions. Every node is represented by 2
+/// field elements, and a node is the hash of its left child followed by its right child. Leaves
+/// which have not been set are equal to [0, 0], and only non-empty nodes are kept in memory.
+///
+/// A tree of depth n has 2^(n - 1) leaves; leaves are added one after another by `insert()`
+/// and can be replaced by `update()`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MerkleStore {
+    depth       : usize,
+    num_leaves  : usize,
+    nodes       : BTreeMap<(usize, usize), [u128; 2]>,
+    empty_nodes : Vec<[u128; 2]>,
+}
+
+// MERKLE STORE IMPLEMENTATION
+// ================================================================================================
+impl MerkleStore {
+
+    /// Returns a Merkle store of the specified `depth` with all leaves set to [0, 0].
+    pub fn new(depth: usize) -> MerkleStore {
+        assert!(depth >= 2 && depth <= 64, "tree depth must be between 2 and 64, but was {}", depth);
+
+        // roots of empty subtrees for every level of the tree, starting with the leaves
+        let mut empty_nodes = Vec::with_capacity(depth);
+        empty_nodes.push([0, 0]);
+        for i in 1..depth {
+            let node = hash_nodes(&empty_nodes[i - 1], &empty_nodes[i - 1]);
+            empty_nodes.push(node);
+        }
+
+        return MerkleStore { depth, num_leaves: 0, nodes: BTreeMap::new(), empty_nodes };
+    }
+
+    /// Returns a Merkle store of the specified `depth` with the specified `leaves` inserted
+    /// into it.
+    pub fn from_leaves(depth: usize, leaves: &[[u128; 2]]) -> MerkleStore {
+        let mut store = MerkleStore::new(depth);
+        for &leaf in leaves.iter() {
+            store.insert(leaf);
+        }
+        return store;
+    }
+
+    /// Returns depth of the tree.
+    pub fn depth(&self) -> usize {
+        return self.depth;
+    }
+
+    /// Returns the number of leaves inserted into the tree.
+    pub fn num_leaves(&self) -> usize {
+        return self.num_leaves;
+    }
+
+    /// Returns the root of the tree.
+    pub fn root(&self) -> [u128; 2] {
+        return self.get_node(self.depth - 1, 0);
+    }
+
+    /// Returns the leaf at the specified `index`.
+    pub fn get(&self, index: usize) -> [u128; 2] {
+        assert!((index as u64) < self.capacity(), "leaf index {} is out of bounds", index);
+        return self.get_node(0, index);
+    }
+
+    /// Sets the leaf which follows the last inserted leaf to the specified value, and returns
+    /// the index of the leaf.
+    pub fn insert(&mut self, leaf: [u128; 2]) -> usize {
+        let index = self.num_leaves;
+        assert!((index as u64) < self.capacity(), "cannot insert more than {} leaves", self.capacity());
+        self.set_leaf(index, leaf);
+        self.num_leaves += 1;
+        return index;
+    }
+
+    /// Replaces the leaf at the specified `index` with the specified value, and returns the
+    /// replaced leaf; the leaf must have been inserted before.
+    pub fn update(&mut self, index: usize, leaf: [u128; 2]) -> [u128; 2] {
+        assert!(index < self.num_leaves, "leaf {} has not been inserted", index);
+        let old_leaf = self.get_node(0, index);
+        self.set_leaf(index, leaf);
+        return old_leaf;
+    }
+
+    /// Returns the authentication path of the leaf at the specified `index`: the siblings of
+    /// the leaf and of all its ancestors below the root, starting with the sibling of the leaf.
+    pub fn path(&self, index: usize) -> Vec<[u128; 2]> {
+        assert!((index as u64) < self.capacity(), "leaf index {} is out of bounds", index);
+
+        let mut path = Vec::with_capacity(self.depth - 1);
+        let mut index = index;
+        for level in 0..(self.depth - 1) {
+            path.push(self.get_node(level, index ^ 1));
+            index = index >> 1;
+        }
+        return path;
+    }
+
+    /// Returns values of input tapes A and B consumed by `smpath.n` instruction which computes
+    /// the root of the tree from the leaf at the specified `index`: for every node of the path,
+    /// the next bit of the index followed by the node.
+    pub fn get_smpath_tapes(&self, index: usize) -> (Vec<u128>, Vec<u128>) {
+        let mut a = Vec::with_capacity(2 * (self.depth - 1));
+        let mut b = Vec::with_capacity(2 * (self.depth - 1));
+
+        // bits are put onto tape B and complemented with 0's on tape A so that both can be
+        // read by a single READ2 operation
+        for (i, node) in self.path(index).iter().enumerate() {
+            a.push(0);
+            b.push(((index as u64 >> i) & 1) as u128);
+            a.push(node[0]);
+            b.push(node[1]);
+        }
+        return (a, b);
+    }
+
+    /// Returns values of input tapes A and B consumed by `pmpath.n` instruction which computes
+    /// the root of the tree from the leaf at the specified `index`; the same values are consumed
+    /// by `mupdate.n` instruction which updates the leaf.
+    pub fn get_pmpath_tapes(&self, index: usize) -> (Vec<u128>, Vec<u128>) {
+        let path = self.path(index);
+        let a = path.iter().map(|node| node[0]).collect();
+        let b = path.iter().map(|node| node[1]).collect();
+        return (a, b);
+    }
+
+    // HELPER METHODS
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns the number of leaves in the tree; this is computed with u64 values because trees
+    /// can have more leaves than can be counted with usize on 32-bit targets.
+    fn capacity(&self) -> u64 {
+        return 1 << (self.depth - 1);
+    }
+
+    fn get_node(&self, level: usize, index: usize) -> [u128; 2] {
+        return match self.nodes.get(&(level, index)) {
+            Some(&node) => node,
+            None => self.empty_nodes[level],
+        };
+    }
+
+    fn set_leaf(&mut self, index: usize, leaf: [u128; 2]) {
+        self.nodes.insert((0, index), leaf);
+
+        // update all ancestors of the leaf up to the root
+        let mut node = leaf;
+        let mut index = index;
+        for level in 0..(self.depth - 1) {
+            let sibling = self.get_node(level, index ^ 1);
+            node = if index & 1 == 0 { hash_nodes(&node, &sibling) } else { hash_nodes(&sibling, &node) };
+            index = index >> 1;
+            self.nodes.insert((level + 1, index), node);
+        }
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Hashes two nodes in the same way as `smpath.n` and `pmpath.n` instructions.
+fn hash_nodes(left: &[u128; 2], right: &[u128; 2]) -> [u128; 2] {
+    let digest = hasher::digest(&[left[0], left[1], right[0], right[1]]);
+    return [digest[0], digest[1]];
+}
+
+// TESTS
+// ================================================================================================
+#[cfg(test)]
+mod tests {
+
+    use crate::{ programs::{ assembly, ProgramInputs }, processor, stark::TraceTable };
+    use super::MerkleStore;
+
+    #[test]
+    fn insert_update() {
+        let mut store = MerkleStore::new(4);
+        let empty_root = store.root();
+
+        assert_eq!(0, store.insert([1, 2]));
+        assert_eq!(1, store.insert([3, 4]));
+        assert_eq!(2, store.num_leaves());
+        assert_eq!([3, 4], store.get(1));
+        assert_eq!([0, 0], store.get(7));
+        assert_ne!(empty_root, store.root());
+
+        // the root depends only on the values of the leaves
+        let root = store.root();
+        assert_eq!(root, MerkleStore::from_leaves(4, &[[1, 2], [3, 4]]).root());
+        assert_eq!([3, 4], store.update(1, [5, 6]));
+        assert_ne!(root, store.root());
+        assert_eq!([5, 6], store.update(1, [3, 4]));
+        assert_eq!(root, store.root());
+        assert_eq!([1, 2], store.update(0, [0, 0]));
+        store.update(1, [0, 0]);
+        assert_eq!(empty_root, store.root());
+    }
+
+    #[test]
+    fn deep_tree() {
+        let store = MerkleStore::from_leaves(64, &[[1, 2], [3, 4]]);
+        assert_eq!(1 << 63, store.capacity());
+        assert_eq!([3, 4], store.get(1));
+        assert_eq!(63, store.path(1).len());
+
+        // index bits above the 32nd are extracted without overflowing
+        let (_, b) = store.get_smpath_tapes(1);
+        assert_eq!(126, b.len());
+        assert_eq!(1, b[0]);
+        assert!(b.iter().step_by(2).skip(1).all(|&bit| bit == 0));
+    }
+
+    #[test]
+    #[should_panic(expected = "leaf 2 has not been inserted")]
+    fn update_fail() {
+        let mut store = MerkleStore::from_leaves(4, &[[1, 2], [3, 4]]);
+        store.update(2, [5, 6]);
+    }
+
+    #[test]
+    fn smpath_pmpath() {
+        let leaves: Vec<[u128; 2]> = (0..11).map(|i| [i, i * i]).collect();
+        let store = MerkleStore::from_leaves(5, &leaves);
+        let root = store.root();
+
+        for &index in [0, 5, 10, 15].iter() {
+            let leaf = store.get(index);
+
+            // the leaf is read from the tapes, and the index is pushed onto the stack
+            let source = format!("begin read.ab dup.2 smpath.5 swap.2 push.{} roll.4 swap swap.2 pmpath.5 end", index);
+            let program = assembly::compile(&source).unwrap();
+            let inputs = ProgramInputs::new(&[], &[leaf[0]], &[leaf[1]])
+                .with_smpath(&store, index)
+                .with_pmpath(&store, index);
+
+            let (trace, ctx_depth, loop_depth) = processor::execute(&program, &inputs, &Default::default()).unwrap();
+            let trace = TraceTable::new(trace, ctx_depth, loop_depth, 8);
+            let stack = trace.get_last_state().user_stack().to_vec();
+            assert_eq!(vec![root[1], root[0], root[1], root[0]], stack[..4].to_vec());
+        }
+    }
+
+    #[test]
+    fn mupdate() {
+        for &depth in [4, 5].iter() {
+            let leaves: Vec<[u128; 2]> = (0..7).map(|i| [i, i * i]).collect();
+            let mut store = MerkleStore::from_leaves(depth, &leaves);
+
+            for &index in [0, 3, 6, 5].iter() {
+                let old_root = store.root();
+                let old_leaf = store.get(index);
+                let new_leaf = [index as u128 + 100, 7];
+
+                // the authentication path is the same for the old and the new leaf
+                let inputs = ProgramInputs::new(&[old_leaf[1], old_leaf[0], new_leaf[1], new_leaf[0], index as u128, 5], &[], &[])
+                    .with_pmpath(&store, index);
+                let stack = execute_mupdate(depth, &inputs);
+
+                store.update(index, new_leaf);
+                let new_root = store.root();
+                assert_eq!(vec![old_root[1], old_root[0], new_root[1], new_root[0], 5], stack[..5].to_vec());
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "ASSERTEQ failed")]
+    fn mupdate_fail() {
+        let store = MerkleStore::from_leaves(4, &[[1, 2], [3, 4]]);
+
+        // index 9 does not fit into the 3 bits read for a tree of depth 4
+        let inputs = ProgramInputs::new(&[4, 3, 6, 5, 9], &[], &[]).with_pmpath(&store, 1);
+        execute_mupdate(4, &inputs);
+    }
+
+    fn execute_mupdate(depth: usize, inputs: &ProgramInputs) -> Vec<u128> {
+        let program = assembly::compile(&format!("begin mupdate.{} end", depth)).unwrap();
+        let (trace, ctx_depth, loop_depth) = processor::execute(&program, inputs, &Default::default()).unwrap();
+        let trace = TraceTable::new(trace, ctx_depth, loop_depth, 8);
+        return trace.get_last_state().user_stack().to_vec();
+    }
+}
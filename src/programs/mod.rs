@@ -12,6 +12,18 @@ use blocks::{ ProgramBlock, Span, Group, Switch, Loop };
 mod inputs;
 pub use inputs::{ ProgramInputs };
 
+mod library;
+pub use library::{ ProgramLibrary };
+
+mod merkle;
+pub use merkle::{ MerkleStore };
+
+mod lamport;
+pub use lamport::{ LamportKey, LamportSignature };
+
+mod cost;
+pub use cost::{ ProgramCost };
+
 mod hashing;
 use hashing::{ hash_op, hash_acc, hash_seq };
 use serde::{Serialize, Deserialize};
@@ -60,6 +72,23 @@ impl Program {
     pub fn hash(&self) -> &[u8; 32] {
         return &self.hash;
     }
+
+    /// Returns a copy of the program in which dynamic call sites are bound to the callees with
+    /// the specified body hashes, in the order in which the call sites appear in the program
+    /// source. The hash of the returned program is the hash against which executions of the
+    /// program resolving its call sites to these callees are verified.
+    pub fn bind_calls(&self, callees: &[u128]) -> Program {
+        let mut callees_iter = callees.iter();
+        let body = self.root.body().iter().map(|block| block.bind_calls(&mut callees_iter)).collect();
+        assert!(callees_iter.next().is_none(), "there are more callees than dynamic call sites");
+        return Program::new(Group::new(body));
+    }
+
+    /// Returns statically estimated cost of executing the program; the estimate is exact for
+    /// programs without loops and conditional blocks.
+    pub fn estimate_cost(&self) -> ProgramCost {
+        return ProgramCost::from_blocks(self.root.body());
+    }
 }
 
 impl sp_std::fmt::Debug for Program {
@@ -145,6 +145,49 @@ fn simple_loop() {
     assert_eq!(111, step);
 }
 
+#[test]
+fn hidden_blocks() {
+    let block1 = build_first_block(OpCode::Noop, 15);
+
+    let t_branch = vec![Span::new_block(vec![OpCode::Assert; 15])];
+    let f_branch = vec![
+        Span::new_block(vec![
+            OpCode::Not, OpCode::Assert, OpCode::Mul, OpCode::Mul,
+            OpCode::Mul, OpCode::Mul,    OpCode::Mul, OpCode::Mul,
+            OpCode::Mul, OpCode::Mul,    OpCode::Mul, OpCode::Mul,
+            OpCode::Mul, OpCode::Mul,    OpCode::Mul,
+        ]),
+        Group::new_block(vec![Span::new_block(vec![OpCode::Add; 15])]),
+    ];
+    let block2 = Switch::new(t_branch, f_branch);
+    let block3 = Loop::new(vec![Span::new_block(vec![OpCode::Assert; 15])]);
+    let block4 = Group::new_block(vec![Span::new_block(vec![OpCode::Inv; 15])]);
+
+    let program = Program::new(Group::new(vec![
+        block1.clone(),
+        ProgramBlock::Switch(block2.clone()),
+        ProgramBlock::Loop(block3.clone()),
+        block4.clone(),
+    ]));
+
+    // hiding branches, loop bodies, and entire blocks does not change program hash
+    let hidden = Program::new(Group::new(vec![
+        block1.clone(),
+        ProgramBlock::Switch(block2.hide_false_branch()),
+        ProgramBlock::Loop(block3.hide_body()),
+        block4.to_proxy(),
+    ]));
+    assert_eq!(program.hash(), hidden.hash());
+
+    let hidden = Program::new(Group::new(vec![
+        block1,
+        ProgramBlock::Switch(block2.hide_true_branch()).to_proxy(),
+        ProgramBlock::Loop(block3).to_proxy(),
+        block4.to_proxy().to_proxy(),
+    ]));
+    assert_eq!(program.hash(), hidden.hash());
+}
+
 // HELPER FUNCTIONS
 // ================================================================================================
 fn build_first_block(op_code: OpCode, length: usize) -> ProgramBlock {
@@ -61,6 +61,8 @@ pub fn traverse(blocks: &[ProgramBlock], stack: &mut Vec<u128>, hash: &mut [u128
                     _ => panic!("cannot enter loop based on a non-binary condition {}", condition)
                 }
             },
+            ProgramBlock::Proxy(_) => panic!("cannot execute a hidden block"),
+            ProgramBlock::DynCall(_) => panic!("dynamic calls are not supported"),
         };
     }
 
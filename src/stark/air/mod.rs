@@ -0,0 +1,355 @@
+use serde::{ Serialize, Deserialize };
+use alloc::string::String;
+use sp_std::vec::Vec;
+use crate::math::{ f128, FieldElement, StarkField };
+
+pub use super::{
+    TraceTable, ProofOptions, ProverConfig, StarkProof, FieldExtension,
+    prove, prove_with_metrics, verify, MAX_CONSTRAINT_DEGREE };
+
+// TYPES AND INTERFACES
+// ================================================================================================
+
+/// Algebraic intermediate representation of a computation. An AIR defines the layout of the
+/// execution trace, transition constraints which must hold between every pair of consecutive
+/// steps of the trace, and boundary assertions which must hold at specific steps of the trace.
+///
+/// The prover and the verifier instantiate the AIR from the same `TraceInfo` and public inputs;
+/// thus, everything the AIR depends on must be derivable from these two values.
+///
+/// When a proof is generated in zero-knowledge mode, the trace may be padded by repeating its
+/// last row before random rows are appended to it; transition constraints must hold for such
+/// padded rows as well.
+pub trait Air: Send + Sync + Sized {
+
+    /// Field in which the execution trace of the computation is defined.
+    type BaseElement: StarkField;
+
+    /// Inputs of the computation which are known to both the prover and the verifier.
+    type PublicInputs;
+
+    /// Returns an AIR for an execution trace of the specified shape; returns an error if the
+    /// trace cannot be an execution trace of this computation.
+    fn new(trace_info: &TraceInfo, pub_inputs: &Self::PublicInputs) -> Result<Self, String>;
+
+    /// Returns true if constraints of the computation can be evaluated over the specified field
+    /// extension; the prover and the verifier reject unsupported extensions before any
+    /// constraints are evaluated. All extensions defined for the base field are supported by
+    /// default.
+    fn supports_extension(_extension: FieldExtension) -> bool {
+        return true;
+    }
+
+    /// Returns the shape of the execution trace this AIR was instantiated for.
+    fn trace_info(&self) -> &TraceInfo;
+
+    /// Returns degrees of transition constraints; the degree of a constraint is the degree of
+    /// its expression in terms of trace registers, and cannot exceed `MAX_CONSTRAINT_DEGREE`.
+    fn transition_degrees(&self) -> &[usize];
+
+    /// Evaluates transition constraints against `current` and `next` states of the trace and
+    /// saves the evaluations into `result`; all evaluations must be zero for a valid trace.
+    /// `periodic_values` contains values of periodic columns at the current step.
+    ///
+    /// The prover evaluates constraints over the base field, but the verifier evaluates them
+    /// at an out-of-domain point which may be drawn from an extension of the base field; so,
+    /// the constraints must be defined for any extension of the base field.
+    fn evaluate_transition<E: FieldElement<BaseField = Self::BaseElement>>(&self,
+        current         : &[E],
+        next            : &[E],
+        periodic_values : &[E],
+        result          : &mut [E]);
+
+    /// Returns assertions against the execution trace; all assertions must be made against
+    /// steps which are not greater than `trace_info.last_step()`.
+    fn get_assertions(&self) -> Vec<Assertion<Self::BaseElement>>;
+
+    /// Returns values of periodic columns over a single cycle; the length of every column must
+    /// be a power of 2 which is not greater than the length of the trace.
+    fn get_periodic_columns(&self) -> Vec<Vec<Self::BaseElement>> {
+        return Vec::new();
+    }
+
+    // AUXILIARY TRACE SEGMENT
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns the number of registers in the auxiliary segment of the execution trace. The
+    /// auxiliary segment is built after the main trace has been committed to, and so it can
+    /// depend on random elements drawn from this commitment (e.g. running sums of lookup
+    /// arguments). Auxiliary registers follow main registers in all trace states passed to the
+    /// AIR, and assertions against them are made with register indexes starting at trace width.
+    fn aux_width(&self) -> usize {
+        return 0;
+    }
+
+    /// Returns the number of random elements on which the auxiliary segment depends; the
+    /// elements are drawn from the base field.
+    fn aux_rand_count(&self) -> usize {
+        return 0;
+    }
+
+    /// Builds `aux_width()` auxiliary registers from the main registers of the `trace` and the
+    /// random elements `rand`; the registers must be as long as the (blinded) trace.
+    fn build_aux_registers(&self, _trace: &TraceTable<Self::BaseElement>, _rand: &[Self::BaseElement])
+        -> Vec<Vec<Self::BaseElement>>
+    {
+        return Vec::new();
+    }
+
+    /// Returns degrees of transition constraints which involve auxiliary registers.
+    fn aux_transition_degrees(&self) -> &[usize] {
+        return &[];
+    }
+
+    /// Evaluates transition constraints which involve auxiliary registers in the same way as
+    /// `evaluate_transition()`; `current` and `next` states contain main registers followed by
+    /// auxiliary registers, and `rand` contains random elements of the auxiliary segment.
+    fn evaluate_aux_transition<E: FieldElement<BaseField = Self::BaseElement>>(&self,
+        _current        : &[E],
+        _next           : &[E],
+        _periodic_values: &[E],
+        _rand           : &[E],
+        _result         : &mut [E])
+    {
+    }
+}
+
+/// Describes the shape of an execution trace; `width` is the number of main registers, and does
+/// not include registers of the auxiliary segment. `meta` is opaque to the prover and the verifier;
+/// it is sent with the proof as is, and can be used to describe the layout of the trace to the
+/// AIR (e.g. the number of registers in a specific part of the trace).
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TraceInfo {
+    pub width           : usize,
+    pub length          : usize,
+    pub blinding_rows   : usize,
+    pub meta            : Vec<u8>,
+}
+
+/// Asserts that the value of the specified `register` at the specified `step` of the execution
+/// trace is equal to `value`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Assertion<E: StarkField = f128::BaseElement> {
+    pub register        : usize,
+    pub step            : usize,
+    pub value           : E,
+}
+
+// TRACE INFO IMPLEMENTATION
+// ================================================================================================
+impl TraceInfo {
+
+    /// Returns the last step of the computation; random rows appended to the trace in
+    /// zero-knowledge mode are not considered part of the computation.
+    pub fn last_step(&self) -> usize {
+        return self.length - self.blinding_rows - 1;
+    }
+}
+
+// ASSERTION IMPLEMENTATION
+// ================================================================================================
+impl<E: StarkField> Assertion<E> {
+    pub fn new(register: usize, step: usize, value: E) -> Assertion<E> {
+        return Assertion { register, step, value };
+    }
+}
+
+// TESTS
+// ================================================================================================
+#[cfg(test)]
+mod tests {
+
+    use alloc::string::String;
+    use crate::math::{ f128, f64, FieldElement, StarkField };
+    use crate::stark::{ FieldExtension, ProverConfig, ProverError, prove_with_metrics, verify_batch, verify_all };
+    use super::{ Air, Assertion, TraceInfo, TraceTable, ProofOptions, prove, verify };
+
+    const TRACE_LENGTH: usize = 32;
+    const NUM_ACTIVE_STEPS: usize = 13;
+    const INCREMENTS: [u128; 4] = [1, 2, 3, 4];
+
+    /// Register 0 is a binary flag which can only go from 1 to 0; while the flag is set, register
+    /// 1 is incremented by a periodic value at every step, and once the flag is cleared, register
+    /// 1 stays constant. Thus, repeating the last state of the trace keeps the trace valid.
+    struct CounterAir<B: StarkField> {
+        trace_info  : TraceInfo,
+        start       : B,
+        result      : B,
+    }
+
+    impl<B: StarkField> Air for CounterAir<B> {
+        type BaseElement = B;
+        type PublicInputs = (B, B);
+
+        fn new(trace_info: &TraceInfo, pub_inputs: &(B, B)) -> Result<CounterAir<B>, String> {
+            if trace_info.width != 2 {
+                return Err(String::from("execution trace must consist of 2 registers"));
+            }
+            return Ok(CounterAir { trace_info: trace_info.clone(), start: pub_inputs.0, result: pub_inputs.1 });
+        }
+
+        fn trace_info(&self) -> &TraceInfo {
+            return &self.trace_info;
+        }
+
+        fn transition_degrees(&self) -> &[usize] {
+            return &[2, 2, 2];
+        }
+
+        fn evaluate_transition<E: FieldElement<BaseField = B>>(&self, current: &[E], next: &[E], periodic_values: &[E], result: &mut [E]) {
+            let flag = current[0];
+            result[0] = flag * flag - flag;
+            result[1] = next[0] * (E::ONE - flag);
+            result[2] = next[1] - (current[1] + flag * periodic_values[0]);
+        }
+
+        fn get_assertions(&self) -> Vec<Assertion<B>> {
+            return vec![
+                Assertion::new(0, 0, B::ONE),
+                Assertion::new(1, 0, self.start),
+                Assertion::new(0, self.trace_info.last_step(), B::ZERO),
+                Assertion::new(1, self.trace_info.last_step(), self.result),
+            ];
+        }
+
+        fn get_periodic_columns(&self) -> Vec<Vec<B>> {
+            return vec![INCREMENTS.iter().map(|&v| B::from(v)).collect()];
+        }
+    }
+
+    /// Same as `CounterAir`, but the increments are scaled by a factor which is a part of
+    /// public inputs; thus, periodic columns are different for different public inputs.
+    struct ScaledCounterAir<B: StarkField> {
+        counter     : CounterAir<B>,
+        scale       : B,
+    }
+
+    impl<B: StarkField> Air for ScaledCounterAir<B> {
+        type BaseElement = B;
+        type PublicInputs = (B, B, B);
+
+        fn new(trace_info: &TraceInfo, pub_inputs: &(B, B, B)) -> Result<ScaledCounterAir<B>, String> {
+            let counter = CounterAir::new(trace_info, &(pub_inputs.0, pub_inputs.1))?;
+            return Ok(ScaledCounterAir { counter, scale: pub_inputs.2 });
+        }
+
+        fn trace_info(&self) -> &TraceInfo {
+            return self.counter.trace_info();
+        }
+
+        fn transition_degrees(&self) -> &[usize] {
+            return self.counter.transition_degrees();
+        }
+
+        fn evaluate_transition<E: FieldElement<BaseField = B>>(&self, current: &[E], next: &[E], periodic_values: &[E], result: &mut [E]) {
+            self.counter.evaluate_transition(current, next, periodic_values, result);
+        }
+
+        fn get_assertions(&self) -> Vec<Assertion<B>> {
+            return self.counter.get_assertions();
+        }
+
+        fn get_periodic_columns(&self) -> Vec<Vec<B>> {
+            return vec![INCREMENTS.iter().map(|&v| B::from(v) * self.scale).collect()];
+        }
+    }
+
+    #[test]
+    fn prove_verify() {
+        let options = ProofOptions::default();
+        let start = f128::BaseElement::new(5);
+        let (mut trace, result) = build_trace(start, &options);
+        let proof = prove::<CounterAir<_>>(&mut trace, &(start, result), &options);
+        assert_eq!(Ok(true), verify::<CounterAir<_>>(&proof, &(start, result)));
+
+        // proof for wrong public inputs should fail
+        let one = f128::BaseElement::ONE;
+        assert!(verify::<CounterAir<_>>(&proof, &(start, result + one)).is_err());
+        assert!(verify::<CounterAir<_>>(&proof, &(start + one, result)).is_err());
+    }
+
+    #[test]
+    fn prove_verify_zk() {
+        let options = ProofOptions::default().with_zk();
+        let start = f128::BaseElement::new(5);
+        let (mut trace, result) = build_trace(start, &options);
+        let proof = prove::<CounterAir<_>>(&mut trace, &(start, result), &options);
+        assert_eq!(Ok(true), verify::<CounterAir<_>>(&proof, &(start, result)));
+        assert!(verify::<CounterAir<_>>(&proof, &(start, result + f128::BaseElement::ONE)).is_err());
+    }
+
+    #[test]
+    fn prove_verify_f64() {
+        let start = f64::BaseElement::new(5);
+        let one = f64::BaseElement::ONE;
+        for &extension in [FieldExtension::None, FieldExtension::Quadratic, FieldExtension::Cubic].iter() {
+            for &zk in [false, true].iter() {
+                let options = ProofOptions::default().with_field_extension(extension);
+                let options = if zk { options.with_zk() } else { options };
+                let (mut trace, result) = build_trace(start, &options);
+                let proof = prove::<CounterAir<_>>(&mut trace, &(start, result), &options);
+                assert_eq!(Ok(true), verify::<CounterAir<_>>(&proof, &(start, result)), "{:?}, zk: {}", extension, zk);
+                assert!(verify::<CounterAir<_>>(&proof, &(start, result + one)).is_err());
+
+                // proofs survive serialization
+                let proof = bincode::deserialize(&bincode::serialize(&proof).unwrap()).unwrap();
+                assert_eq!(Ok(true), verify::<CounterAir<_>>(&proof, &(start, result)));
+            }
+        }
+    }
+
+    #[test]
+    fn prove_unsupported_extension() {
+        for &extension in [FieldExtension::Quadratic, FieldExtension::Cubic].iter() {
+            let options = ProofOptions::default().with_field_extension(extension);
+            let start = f128::BaseElement::new(5);
+            let (mut trace, result) = build_trace(start, &options);
+            let config = ProverConfig::default();
+            let result = prove_with_metrics::<CounterAir<_>>(&mut trace, &(start, result), &options, &config, None);
+            assert_eq!(Some(ProverError::UnsupportedExtension(extension)), result.err());
+        }
+    }
+
+    #[test]
+    fn verify_batch_periodic_columns() {
+        let options = ProofOptions::default();
+        let start = f128::BaseElement::new(5);
+
+        // proofs have the same trace shape and proof options, but different periodic columns
+        let mut proofs = Vec::new();
+        for &scale in [1, 2, 3].iter() {
+            let scale = f128::BaseElement::new(scale);
+            let (mut trace, result) = build_scaled_trace(start, scale, &options);
+            let pub_inputs = (start, result, scale);
+            let proof = prove::<ScaledCounterAir<_>>(&mut trace, &pub_inputs, &options);
+            proofs.push((proof, pub_inputs));
+        }
+
+        let batch: Vec<_> = proofs.iter().map(|(proof, pub_inputs)| (proof, *pub_inputs)).collect();
+        assert_eq!(vec![Ok(true); 3], verify_batch::<ScaledCounterAir<_>>(&batch, 1));
+        assert_eq!(Ok(true), verify_all::<ScaledCounterAir<_>>(&batch, 2));
+
+        // a proof does not verify against a different scale
+        let (proof, (start, result, _)) = &proofs[1];
+        let pub_inputs = (*start, *result, f128::BaseElement::new(3));
+        assert!(verify::<ScaledCounterAir<_>>(proof, &pub_inputs).is_err());
+    }
+
+    fn build_trace<B: StarkField>(start: B, options: &ProofOptions) -> (TraceTable<B>, B) {
+        return build_scaled_trace(start, B::ONE, options);
+    }
+
+    fn build_scaled_trace<B: StarkField>(start: B, scale: B, options: &ProofOptions) -> (TraceTable<B>, B) {
+        let mut flags = vec![B::ONE; NUM_ACTIVE_STEPS];
+        flags.resize(TRACE_LENGTH, B::ZERO);
+
+        let mut values = vec![start];
+        for i in 0..(TRACE_LENGTH - 1) {
+            values.push(values[i] + flags[i] * B::from(INCREMENTS[i % INCREMENTS.len()]) * scale);
+        }
+        let result = values[TRACE_LENGTH - 1];
+
+        let trace = TraceTable::with_meta(vec![flags, values], Vec::new(), options.extension_factor());
+        return (trace, result);
+    }
+}
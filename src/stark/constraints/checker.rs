@@ -0,0 +1,448 @@
+use sp_std::{ fmt, mem, convert::TryFrom, vec, vec::Vec };
+use crate::{
+    math::{ field, f128::BaseElement, FieldElement },
+    processor::{ ExecutionError, opcodes::{ FlowOps, UserOps } },
+    stark::{ TraceTable, TraceState },
+    RANGE_CHECK_TABLE_SIZE,
+};
+use super::{
+    ProcessorAir,
+    decoder::{ Decoder, NUM_OP_CONSTRAINTS, NUM_SPONGE_CONSTRAINTS, NUM_PERIODIC_COLUMNS },
+    stack::{ Stack, NUM_AUX_CONSTRAINTS },
+};
+
+// TYPES AND INTERFACES
+// ================================================================================================
+
+/// Operation decoded from op bits of a single trace state.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum TraceOp {
+    Flow(FlowOps),
+    User(UserOps),
+    Invalid,
+}
+
+/// Describes a single constraint which did not evaluate to zero over the execution trace.
+///
+/// * `group` identifies the module which defines the constraint (e.g. `stack::comparison`);
+/// * `index` is the index of the constraint within the decoder or the stack constraint list;
+///    for boundary constraints, this is the index of the constrained register;
+///    for range checks, this is the index of the looked up byte (0 or 1), or 2 if multiplicities
+///    do not match the lookups, in which case `step` is the table value;
+/// * `step` is the step of the un-extended trace at which the constraint failed;
+/// * `op` is the operation decoded from the trace at that step.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConstraintFailure {
+    pub group   : &'static str,
+    pub index   : usize,
+    pub step    : usize,
+    pub op      : TraceOp,
+}
+
+/// Reason why an execution did not pass the constraint check.
+#[derive(Clone, Debug, PartialEq)]
+pub enum CheckError {
+    /// Program execution failed, and so no execution trace was generated.
+    Execution(ExecutionError),
+    /// Execution trace was generated, but did not satisfy the listed constraints.
+    Constraints(Vec<ConstraintFailure>),
+}
+
+// PUBLIC FUNCTIONS
+// ================================================================================================
+
+/// Evaluates all decoder and stack transition constraints, as well as boundary constraints on
+/// decoder registers, against the un-extended execution trace and returns a list of constraints
+/// which were not satisfied. If the trace has already been extended, only the steps of the
+/// original trace are checked; blinding rows at the end of the trace are never checked.
+///
+/// Boundary constraints for inputs and outputs are checked in the same way as by the verifier:
+/// the top of the stack must be equal to `inputs` at the first step, and to `outputs` at the
+/// last step; values which do not fit into the stack must be 0s.
+///
+/// Range check lookups are checked by comparing the number of times every byte was looked up
+/// by RCACC operations against multiplicities in the range check register.
+pub fn check_trace(trace: &TraceTable, inputs: &[u128], outputs: &[u128]) -> Result<(), Vec<ConstraintFailure>>
+{
+    let ctx_depth = trace.ctx_depth();
+    let loop_depth = trace.loop_depth();
+    let stack_depth = trace.stack_depth();
+    let trace_length = trace.unextended_length();
+    let stride = if trace.is_extended() { trace.extension_factor() } else { 1 };
+    assert!(!trace.is_extended() || trace.domain_offset() == BaseElement::ONE,
+        "cannot check a trace extended over a coset of the evaluation domain");
+    let last_step = trace_length - trace.blinding_rows() - 1;
+
+    // constraints are evaluated only over the trace domain, so values of periodic columns
+    // can be read directly from the columns
+    let decoder = Decoder::new(ctx_depth, loop_depth);
+    let stack = Stack::new(stack_depth);
+    let periodic_columns = ProcessorAir::build_periodic_columns();
+    let mut periodic_values = vec![field::ZERO; periodic_columns.len()];
+    let decoder_constraint_num = decoder.constraint_count();
+    let stack_constraint_num = stack.constraint_degrees().len();
+
+    let mut failures = Vec::new();
+    let mut lookups = [0u128; RANGE_CHECK_TABLE_SIZE];
+
+    // check boundary constraints for the first step
+    let mut current = trace.get_state(0);
+    check_first_state(&current, &mut failures);
+    check_stack_boundary(&current, inputs, 0, &mut failures);
+
+    // check transition constraints for all steps
+    let mut next = TraceState::new(ctx_depth, loop_depth, stack_depth);
+    let mut evaluations = vec![field::ZERO; decoder_constraint_num + stack_constraint_num];
+    for step in 0..last_step {
+        trace.fill_state(&mut next, (step + 1) * stride);
+
+        for (value, column) in periodic_values.iter_mut().zip(periodic_columns.iter()) {
+            *value = column[step % column.len()];
+        }
+        let (d_periodic_values, s_periodic_values) = periodic_values.split_at(NUM_PERIODIC_COLUMNS);
+
+        for value in evaluations.iter_mut() { *value = field::ZERO; }
+        let (d_evaluations, s_evaluations) = evaluations.split_at_mut(decoder_constraint_num);
+        decoder.evaluate(&current, &next, d_periodic_values, d_evaluations);
+        stack.evaluate(&current, &next, s_periodic_values, s_evaluations);
+
+        let op = decode_op(&current);
+        for (i, &evaluation) in d_evaluations.iter().enumerate() {
+            if evaluation != field::ZERO {
+                let group = get_decoder_group(i, op);
+                failures.push(ConstraintFailure { group, index: i, step, op });
+            }
+        }
+
+        for (i, &evaluation) in s_evaluations.iter().enumerate() {
+            if evaluation != field::ZERO {
+                let group = get_stack_group(i, decode_user_op(&current));
+                failures.push(ConstraintFailure { group, index: i, step, op });
+            }
+        }
+
+        // bytes looked up by RCACC operation are placed at the top of the next state
+        if op == TraceOp::User(UserOps::RcAcc) {
+            for (i, &value) in next.user_stack()[..2].iter().enumerate() {
+                if value < RANGE_CHECK_TABLE_SIZE as u128 {
+                    lookups[value as usize] += 1;
+                }
+                else {
+                    failures.push(ConstraintFailure { group: "stack::range_check", index: i, step, op });
+                }
+            }
+        }
+
+        mem::swap(&mut current, &mut next);
+    }
+
+    // check boundary constraints for the last step
+    check_last_state(&current, last_step, &mut failures);
+    check_stack_boundary(&current, outputs, last_step, &mut failures);
+
+    // check that multiplicities of table values match the number of lookups
+    check_lookups(trace, &lookups, last_step, stride, &mut failures);
+
+    return if failures.is_empty() { Ok(()) } else { Err(failures) };
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// All decoder registers, except for op flag helpers, must be set to 0s at the first step.
+fn check_first_state(state: &TraceState, failures: &mut Vec<ConstraintFailure>) {
+    let op = decode_op(state);
+    let decoder_width = state.width() - state.stack_depth();
+    for (i, &value) in state.to_vec()[..decoder_width].iter().enumerate() {
+        if value != field::ZERO && !crate::OP_FLAG_HELPERS_RANGE.contains(&i) {
+            failures.push(ConstraintFailure { group: "decoder::boundary", index: i, step: 0, op });
+        }
+    }
+}
+
+/// At the last step, op bits must be set to VOID and NOOP, and context and loop stacks must
+/// be empty.
+fn check_last_state(state: &TraceState, step: usize, failures: &mut Vec<ConstraintFailure>) {
+    let op = decode_op(state);
+    let decoder_width = state.width() - state.stack_depth();
+    let op_bits_range = crate::CF_OP_BITS_RANGE.start..crate::HD_OP_BITS_RANGE.end;
+    let state = state.to_vec();
+    for i in op_bits_range.clone().chain(crate::OP_FLAG_HELPERS_RANGE.end..decoder_width) {
+        let expected = if op_bits_range.contains(&i) { field::ONE } else { field::ZERO };
+        if state[i] != expected {
+            failures.push(ConstraintFailure { group: "decoder::boundary", index: i, step, op });
+        }
+    }
+}
+
+/// The top of the stack must be set to the specified `values` at the specified `step`; values
+/// which do not fit into the stack are compared against 0s.
+fn check_stack_boundary(state: &TraceState, values: &[u128], step: usize, failures: &mut Vec<ConstraintFailure>) {
+    let op = decode_op(state);
+    let stack_start = state.width() - state.stack_depth();
+    let stack = state.user_stack();
+    for (i, &value) in values.iter().enumerate() {
+        let actual = if i < stack.len() { stack[i] } else { field::ZERO };
+        if actual != value {
+            failures.push(ConstraintFailure { group: "stack::boundary", index: stack_start + i, step, op });
+        }
+    }
+}
+
+/// The range check table repeats every byte value with a period of the table size; for every
+/// value, the sum of multiplicities at the steps holding this value must be equal to the
+/// number of times the value was looked up.
+fn check_lookups(trace: &TraceTable, lookups: &[u128], last_step: usize, stride: usize, failures: &mut Vec<ConstraintFailure>) {
+    let multiplicities = trace.get_unextended_register(trace.main_width() - 1);
+    let mut table_counts = [field::ZERO; RANGE_CHECK_TABLE_SIZE];
+    for (step, &multiplicity) in multiplicities[..last_step].iter().enumerate() {
+        let count = &mut table_counts[step % RANGE_CHECK_TABLE_SIZE];
+        *count = field::add(*count, multiplicity.into());
+    }
+
+    for (value, (&count, &expected)) in table_counts.iter().zip(lookups.iter()).enumerate() {
+        if count != expected {
+            let op = if value <= last_step { decode_op(&trace.get_state(value * stride)) } else { TraceOp::Invalid };
+            failures.push(ConstraintFailure { group: "stack::range_check", index: 2, step: value, op });
+        }
+    }
+}
+
+fn get_decoder_group(index: usize, op: TraceOp) -> &'static str {
+    if index < NUM_OP_CONSTRAINTS {
+        return "decoder::op_bits";
+    }
+
+    // sponge constraints are enforced by HACC operation when a user operation is executed, and
+    // by flow control operations otherwise
+    return match op {
+        TraceOp::User(_) if index < NUM_OP_CONSTRAINTS + NUM_SPONGE_CONSTRAINTS => "decoder::sponge",
+        _ => "decoder::flow_ops",
+    };
+}
+
+fn get_stack_group(index: usize, op: TraceOp) -> &'static str {
+    if index < NUM_AUX_CONSTRAINTS {
+        return "stack::aux";
+    }
+
+    return match op {
+        TraceOp::User(op) => match op {
+            UserOps::Assert | UserOps::AssertEq | UserOps::Eq
+            | UserOps::Cmp | UserOps::BinAcc | UserOps::RcAcc => "stack::comparison",
+
+            UserOps::Push | UserOps::Read | UserOps::Read2 => "stack::input",
+
+            UserOps::Dup | UserOps::Dup2 | UserOps::Dup4 | UserOps::Pad2
+            | UserOps::Drop | UserOps::Drop4
+            | UserOps::Swap | UserOps::Swap2 | UserOps::Swap4
+            | UserOps::Roll4 | UserOps::Roll8 => "stack::manipulation",
+
+            UserOps::Add | UserOps::Mul | UserOps::Inv | UserOps::Neg
+            | UserOps::Not | UserOps::And | UserOps::Or => "stack::arithmetic",
+
+            UserOps::Choose | UserOps::Choose2 | UserOps::CSwap2 => "stack::conditional",
+
+            UserOps::RescR => "stack::hash",
+
+            UserOps::Begin | UserOps::Noop | UserOps::DynCall => "stack::copy",
+        },
+        _ => "stack",
+    };
+}
+
+/// Returns a flow control operation decoded from the state, or a user operation if the flow
+/// control operation is HACC.
+fn decode_op(state: &TraceState) -> TraceOp {
+    return match bits_to_code(state.cf_op_bits()) {
+        Some(0) => decode_user_op(state),
+        Some(code) => match FlowOps::try_from(code) {
+            Ok(op) => TraceOp::Flow(op),
+            Err(_) => TraceOp::Invalid,
+        },
+        None => TraceOp::Invalid,
+    };
+}
+
+fn decode_user_op(state: &TraceState) -> TraceOp {
+    let ld_code = bits_to_code(state.ld_op_bits());
+    let hd_code = bits_to_code(state.hd_op_bits());
+    return match (ld_code, hd_code) {
+        (Some(ld_code), Some(hd_code)) => match UserOps::try_from(ld_code | (hd_code << 5)) {
+            Ok(op) => TraceOp::User(op),
+            Err(_) => TraceOp::Invalid,
+        },
+        _ => TraceOp::Invalid,
+    };
+}
+
+/// Converts a little-endian list of binary field elements into a number; returns None if
+/// any of the elements is not binary.
+fn bits_to_code(bits: &[u128]) -> Option<u8> {
+    let mut code = 0;
+    for (i, &bit) in bits.iter().enumerate() {
+        if bit == field::ONE {
+            code |= 1 << i;
+        }
+        else if bit != field::ZERO {
+            return None;
+        }
+    }
+    return Some(code);
+}
+
+// FORMATTING
+// ================================================================================================
+impl fmt::Display for TraceOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return match self {
+            TraceOp::Flow(op) => write!(f, "{}", op),
+            TraceOp::User(op) => write!(f, "{}", op),
+            TraceOp::Invalid  => write!(f, "invalid"),
+        };
+    }
+}
+
+impl fmt::Display for ConstraintFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return write!(f, "constraint {}[{}] failed at step {} ({})",
+            self.group, self.index, self.step, self.op);
+    }
+}
+
+impl From<ExecutionError> for CheckError {
+    fn from(err: ExecutionError) -> CheckError {
+        return CheckError::Execution(err);
+    }
+}
+
+impl From<Vec<ConstraintFailure>> for CheckError {
+    fn from(failures: Vec<ConstraintFailure>) -> CheckError {
+        return CheckError::Constraints(failures);
+    }
+}
+
+impl fmt::Display for CheckError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return match self {
+            CheckError::Execution(err) => write!(f, "{}", err),
+            CheckError::Constraints(failures) => {
+                write!(f, "{} constraint(s) failed", failures.len())?;
+                for failure in failures {
+                    write!(f, "\n{}", failure)?;
+                }
+                Ok(())
+            },
+        };
+    }
+}
+
+// TESTS
+// ================================================================================================
+#[cfg(test)]
+mod tests {
+
+    use crate::{ processor, programs::{ assembly, ProgramInputs }, stark::TraceTable, MIN_EXTENSION_FACTOR };
+    use super::{ check_trace, TraceOp, UserOps, decode_op };
+
+    #[test]
+    fn check_valid_trace() {
+        let (registers, ctx_depth, loop_depth) = execute("begin push.3 push.5 add push.7 mul end", &[2]);
+        let trace = TraceTable::new(registers, ctx_depth, loop_depth, MIN_EXTENSION_FACTOR);
+        assert_eq!(Ok(()), check_trace(&trace, &[2], &[56, 2]));
+    }
+
+    #[test]
+    fn check_invalid_transition() {
+        let (mut registers, ctx_depth, loop_depth) = execute("begin push.3 push.5 add end", &[]);
+        let trace = TraceTable::new(registers.clone(), ctx_depth, loop_depth, MIN_EXTENSION_FACTOR);
+
+        // find the step at which ADD operation is executed and corrupt its result
+        let add_step = (0..trace.unextended_length())
+            .find(|&i| decode_op(&trace.get_state(i)) == TraceOp::User(UserOps::Add))
+            .unwrap();
+        let stack_start = registers.len() - trace.stack_depth() - crate::NUM_RANGE_CHECK_REGISTERS;
+        registers[stack_start][add_step + 1] = 9;
+
+        let trace = TraceTable::new(registers, ctx_depth, loop_depth, MIN_EXTENSION_FACTOR);
+        let failures = check_trace(&trace, &[], &[]).unwrap_err();
+        assert_eq!(2, failures.len());
+
+        // the ADD result does not match its operands
+        assert_eq!("stack::arithmetic", failures[0].group);
+        assert_eq!(2, failures[0].index);
+        assert_eq!(add_step, failures[0].step);
+        assert_eq!(TraceOp::User(UserOps::Add), failures[0].op);
+
+        // the next operation does not copy the corrupted value correctly
+        assert_eq!(add_step + 1, failures[1].step);
+    }
+
+    #[test]
+    fn check_invalid_boundary() {
+        let (mut registers, ctx_depth, loop_depth) = execute("begin push.3 push.5 add end", &[]);
+        let last_step = registers[0].len() - 1;
+        registers[1][0] = 1;    // sponge is not 0 at the first step
+        registers[5][last_step] = 0;    // cf op bits are not VOID at the last step
+
+        let trace = TraceTable::new(registers, ctx_depth, loop_depth, MIN_EXTENSION_FACTOR);
+        let failures = check_trace(&trace, &[], &[]).unwrap_err();
+
+        let boundary_failures: Vec<(usize, usize)> = failures.iter()
+            .filter(|f| f.group == "decoder::boundary")
+            .map(|f| (f.step, f.index))
+            .collect();
+        assert_eq!(vec![(0, 1), (last_step, 5)], boundary_failures);
+    }
+
+    #[test]
+    fn check_invalid_stack_boundary() {
+        let (registers, ctx_depth, loop_depth) = execute("begin push.3 push.5 add end", &[2]);
+        let trace = TraceTable::new(registers, ctx_depth, loop_depth, MIN_EXTENSION_FACTOR);
+        let stack_start = trace.get_state(0).width() - trace.stack_depth();
+        let last_step = trace.unextended_length() - 1;
+        assert_eq!(Ok(()), check_trace(&trace, &[2], &[8, 2]));
+
+        // the input does not match the first state, and the second output does not match the
+        // last state; values which do not fit into the stack must be 0s
+        let mut outputs = vec![8, 3];
+        outputs.resize(trace.stack_depth() + 1, 0);
+        outputs[trace.stack_depth()] = 1;
+
+        let failures = check_trace(&trace, &[4], &outputs).unwrap_err();
+        let boundary_failures: Vec<(usize, usize)> = failures.iter()
+            .map(|f| (f.step, f.index))
+            .collect();
+        assert!(failures.iter().all(|f| f.group == "stack::boundary"));
+        assert_eq!(vec![
+            (0, stack_start),
+            (last_step, stack_start + 1),
+            (last_step, stack_start + trace.stack_depth()),
+        ], boundary_failures);
+    }
+
+    #[test]
+    fn check_invalid_lookups() {
+        let (mut registers, ctx_depth, loop_depth) = execute("begin rc.32 end", &[0x1234]);
+        let trace = TraceTable::new(registers.clone(), ctx_depth, loop_depth, MIN_EXTENSION_FACTOR);
+        assert_eq!(Ok(()), check_trace(&trace, &[0x1234], &[]));
+
+        // byte 0x12 is looked up once, but its multiplicity is changed to 2
+        let multiplicities = registers.last_mut().unwrap();
+        assert_eq!(1, multiplicities[0x12]);
+        multiplicities[0x12] = 2;
+
+        let trace = TraceTable::new(registers, ctx_depth, loop_depth, MIN_EXTENSION_FACTOR);
+        let failures = check_trace(&trace, &[], &[]).unwrap_err();
+        assert_eq!(1, failures.len());
+        assert_eq!("stack::range_check", failures[0].group);
+        assert_eq!(2, failures[0].index);
+        assert_eq!(0x12, failures[0].step);
+    }
+
+    fn execute(source: &str, inputs: &[u128]) -> (Vec<Vec<u128>>, usize, usize) {
+        let program = assembly::compile(source).unwrap();
+        let inputs = ProgramInputs::from_public(inputs);
+        return processor::execute(&program, &inputs, &Default::default()).unwrap();
+    }
+}
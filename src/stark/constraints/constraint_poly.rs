@@ -1,49 +1,53 @@
-use crate::math::{ field, polynom, parallel };
-use crate::stark::{ MAX_CONSTRAINT_DEGREE, utils::CompositionCoefficients };
+use crate::math::{ polynom, parallel, FieldElement };
+use crate::stark::utils::CompositionCoefficients;
 use sp_std::{vec, vec::Vec};
 
 // TYPES AND INTERFACES
 // ================================================================================================
-pub struct ConstraintPoly {
-    poly: Vec<u128>
+pub struct ConstraintPoly<E: FieldElement> {
+    poly    : Vec<E>,
+    degree  : usize,
 }
 
 // CONSTRAINT POLY IMPLEMENTATION
 // ================================================================================================
-impl ConstraintPoly {
-    pub fn new(poly: Vec<u128>) -> ConstraintPoly {
+impl<E: FieldElement> ConstraintPoly<E> {
+    pub fn new(poly: Vec<E>, degree: usize) -> ConstraintPoly<E> {
 
         assert!(poly.len().is_power_of_two(), "poly length must be a power of two");
-        debug_assert!(get_expected_degree(&poly) == polynom::degree_of(&poly),
+        assert!(degree < poly.len(), "degree must be smaller than poly length");
+        debug_assert!(degree == polynom::degree_of(&poly),
             "expected polynomial of degree {} but received degree {}",
-            get_expected_degree(&poly),
+            degree,
             polynom::degree_of(&poly));
 
-        return ConstraintPoly { poly };
+        return ConstraintPoly { poly, degree };
     }
 
     pub fn degree(&self) -> usize {
-        return get_expected_degree(&self.poly);
+        return self.degree;
     }
 
-    pub fn eval(&self, twiddles: &[u128]) -> Vec<u128> {
+    /// Evaluates the polynomial over the domain defined by `twiddles` shifted by `domain_offset`.
+    pub fn eval(&self, twiddles: &[E], domain_offset: E) -> Vec<E> {
         let domain_size = twiddles.len() * 2;
         assert!(domain_size > self.poly.len(), "domain size must be greater than poly length");
 
-        let mut evaluations = vec![field::ZERO; domain_size];
+        let mut evaluations = vec![E::ZERO; domain_size];
         evaluations[..self.poly.len()].copy_from_slice(&self.poly);
+        polynom::shift(&mut evaluations[..self.poly.len()], domain_offset);
         polynom::eval_fft_twiddles(&mut evaluations, twiddles, true);
 
         return evaluations;
     }
 
-    pub fn merge_into(mut self, result: &mut Vec<u128>, z: u128, cc: &CompositionCoefficients) -> u128 {
+    pub fn merge_into(mut self, result: &mut Vec<E>, z: E, cc: &CompositionCoefficients<E>) -> E {
 
         // evaluate the polynomial at point z
         let z_value = polynom::eval(&self.poly, z);
 
         // compute C(x) = (P(x) - P(z)) / (x - z)
-        self.poly[0] = field::sub(self.poly[0], z_value);
+        self.poly[0] = self.poly[0] - z_value;
         polynom::syn_div_in_place(&mut self.poly, z);
 
         // add C(x) * cc into the result
@@ -53,10 +57,3 @@ impl ConstraintPoly {
     }
 
 }
-
-// HELPER FUNCTIONS
-// ================================================================================================
-fn get_expected_degree(poly: &[u128]) -> usize {
-    let trace_length = poly.len() / MAX_CONSTRAINT_DEGREE;
-    return poly.len() - trace_length;
-}
\ No newline at end of file
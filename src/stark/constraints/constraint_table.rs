@@ -1,29 +1,40 @@
-use crate::math::{ field, parallel, fft, polynom };
-use crate::stark::{ TraceTable, TraceState };
+use crate::math::{ parallel, fft, polynom, FieldElement, StarkField };
+use crate::stark::{ Air, TraceTable, utils };
 use crate::utils::{ uninit_vector };
-use super::{ ConstraintEvaluator, ConstraintPoly };
+use super::{ ConstraintEvaluator, ConstraintPoly, super::MAX_CONSTRAINT_DEGREE };
 // use wasm_bindgen_test::*;
-use sp_std::{vec, vec::Vec};
+use sp_std::{ cmp, iter, vec, vec::Vec };
+use crossbeam_utils::thread;
 
 // TYPES AND INTERFACES
 // ================================================================================================
-pub struct ConstraintTable {
-    evaluator       : ConstraintEvaluator,
-    i_evaluations   : Vec<u128>,    // combined evaluations of boundary constraints at the first step
-    f_evaluations   : Vec<u128>,    // combined evaluations of boundary constraints at the last step
-    t_evaluations   : Vec<u128>,    // combined evaluations of transition constraints
+pub struct ConstraintTable<A: Air, E: FieldElement<BaseField = A::BaseElement>> {
+    evaluator       : ConstraintEvaluator<A, E>,
+    b_evaluations   : Vec<E>,   // combined evaluations of boundary constraints for every asserted step
+    t_evaluations   : Vec<E>,   // combined evaluations of transition constraints
 }
 
 // CONSTRAINT TABLE IMPLEMENTATION
 // ================================================================================================
-impl ConstraintTable {
-    pub fn new(trace: &TraceTable, trace_root: &[u8; 32], inputs: &[u128], outputs: &[u128]) -> ConstraintTable {
-        let evaluator = ConstraintEvaluator::from_trace(trace, trace_root, inputs, outputs);
+impl<A: Air, E: FieldElement<BaseField = A::BaseElement>> ConstraintTable<A, E> {
+    /// Returns a table for evaluating constraints defined by the `air` over the evaluation domain
+    /// of the `trace`; coefficients for combining the constraints are derived from `seed`, and
+    /// `aux_rand` are random elements from which the auxiliary segment of the trace was built.
+    pub fn new(air: A, trace: &TraceTable<A::BaseElement>, seed: &[u8; 32], aux_rand: Vec<A::BaseElement>) -> ConstraintTable<A, E> {
+        #[allow(unused_mut)]
+        let mut evaluator = ConstraintEvaluator::new(air, seed, aux_rand, trace.domain_offset());
+
+        // when in debug mode, keep individual evaluations of transition constraints
+        #[cfg(debug_assertions)]
+        evaluator.init_transition_evaluations();
+
+        // boundary constraint evaluations are stored row by row: a row holds evaluations
+        // for all asserted steps at a single step of the evaluation domain
         let evaluation_domain_size = evaluator.domain_size();
+        let b_evaluations_size = evaluation_domain_size * evaluator.assertion_step_count();
         return ConstraintTable {
             evaluator       : evaluator,
-            i_evaluations   : uninit_vector(evaluation_domain_size),
-            f_evaluations   : uninit_vector(evaluation_domain_size),
+            b_evaluations   : uninit_vector(b_evaluations_size),
             t_evaluations   : uninit_vector(evaluation_domain_size),
         };
     }
@@ -43,50 +54,139 @@ impl ConstraintTable {
         return self.evaluator.trace_length();
     }
 
-    /// Evaluates transition and boundary constraints at the specified step.
-    pub fn evaluate(&mut self, current: &TraceState, next: &TraceState, x: u128, step: usize) {
-        let (init_bound, last_bound) = self.evaluator.evaluate_boundaries(current, x);
-        self.i_evaluations[step] = init_bound;
-        self.f_evaluations[step] = last_bound;
-        self.t_evaluations[step] = self.evaluator.evaluate_transition(current, next, x, step);
+    /// Evaluates transition and boundary constraints over the entire evaluation domain. The
+    /// domain is split into `num_threads` contiguous chunks, and each chunk is evaluated in
+    /// a separate thread. If the trace has been extended lazily, constraints are evaluated
+    /// one trace segment at a time instead.
+    pub fn evaluate_trace(&mut self, trace: &TraceTable<A::BaseElement>, lde_domain: &[A::BaseElement], num_threads: usize) {
+        assert!(num_threads > 0, "number of threads must be greater than 0");
+        if trace.is_lazy() {
+            self.evaluate_segments(trace, lde_domain, num_threads);
+            return;
+        }
+
+        // we don't need to evaluate constraints over the entire extended execution trace; we
+        // need to evaluate them over the domain extended to match max constraint degree - thus,
+        // we can skip most trace states for the purposes of constraint evaluation.
+        let stride = trace.extension_factor() / MAX_CONSTRAINT_DEGREE;
+        let evaluator = &self.evaluator;
+
+        if num_threads == 1 {
+            evaluate_chunk(evaluator, trace, lde_domain, stride, 0,
+                &mut self.b_evaluations, &mut self.t_evaluations);
+            return;
+        }
+
+        // when there are no assertions, boundary evaluations are empty; chunk size is kept
+        // non-zero so that every batch still gets its (empty) slice of boundary evaluations
+        let batch_size = (self.evaluation_domain_size() + num_threads - 1) / num_threads;
+        let b_batch_size = cmp::max(batch_size * evaluator.assertion_step_count(), 1);
+        let b_batches = self.b_evaluations.chunks_mut(b_batch_size)
+            .chain(iter::repeat_with(|| &mut [][..]));
+        let batches = b_batches.zip(self.t_evaluations.chunks_mut(batch_size));
+        thread::scope(|s| {
+            for (i, (b_evaluations, t_evaluations)) in batches.enumerate() {
+                s.spawn(move |_| {
+                    evaluate_chunk(evaluator, trace, lde_domain, stride, i * batch_size,
+                        b_evaluations, t_evaluations);
+                });
+            }
+        }).unwrap();
+    }
+
+    /// Evaluates transition and boundary constraints over the evaluation domain of a lazily
+    /// extended trace. The evaluation domain is covered by MAX_CONSTRAINT_DEGREE segments of
+    /// the extended trace: step `i * MAX_CONSTRAINT_DEGREE + k` of the domain is row `i` of
+    /// segment `k * stride`. Thus, only these segments are evaluated, one at a time, and the
+    /// rows of each segment are split across `num_threads` threads.
+    fn evaluate_segments(&mut self, trace: &TraceTable<A::BaseElement>, lde_domain: &[A::BaseElement], num_threads: usize) {
+        let stride = trace.extension_factor() / MAX_CONSTRAINT_DEGREE;
+        let trace_length = trace.unextended_length();
+        let num_steps = self.evaluator.assertion_step_count();
+        let evaluator = &self.evaluator;
+
+        let mut segment = vec![Vec::new(); trace.register_count()];
+        let mut b_evaluations = uninit_vector::<E>(trace_length * num_steps);
+        let mut t_evaluations = uninit_vector::<E>(trace_length);
+
+        for k in 0..MAX_CONSTRAINT_DEGREE {
+            trace.eval_segment(k * stride, &mut segment, num_threads);
+
+            if num_threads == 1 {
+                evaluate_segment_chunk(evaluator, &segment, lde_domain, stride, k, 0,
+                    &mut b_evaluations, &mut t_evaluations);
+            }
+            else {
+                let segment = &segment;
+                let batch_size = (trace_length + num_threads - 1) / num_threads;
+                let b_batch_size = cmp::max(batch_size * num_steps, 1);
+                let b_batches = b_evaluations.chunks_mut(b_batch_size)
+                    .chain(iter::repeat_with(|| &mut [][..]));
+                let batches = b_batches.zip(t_evaluations.chunks_mut(batch_size));
+                thread::scope(|s| {
+                    for (i, (b_evaluations, t_evaluations)) in batches.enumerate() {
+                        s.spawn(move |_| {
+                            evaluate_segment_chunk(evaluator, segment, lde_domain, stride, k, i * batch_size,
+                                b_evaluations, t_evaluations);
+                        });
+                    }
+                }).unwrap();
+            }
+
+            // move evaluations of the segment to their steps in the evaluation domain
+            for i in 0..trace_length {
+                let step = i * MAX_CONSTRAINT_DEGREE + k;
+                self.t_evaluations[step] = t_evaluations[i];
+                self.b_evaluations[(step * num_steps)..((step + 1) * num_steps)]
+                    .copy_from_slice(&b_evaluations[(i * num_steps)..((i + 1) * num_steps)]);
+            }
+        }
     }
 
     /// Interpolates all constraint evaluations into polynomials and combines all these 
     /// polynomials into a single polynomial using pseudo-random linear combination.
-    pub fn combine_polys(mut self) -> ConstraintPoly
+    pub fn combine_polys(mut self) -> ConstraintPoly<E>
     {
-        let combination_root = field::get_root_of_unity(self.evaluation_domain_size());
+        let combination_root = A::BaseElement::get_root_of_unity(self.evaluation_domain_size());
         let inv_twiddles = fft::get_inv_twiddles(combination_root, self.evaluation_domain_size());
+        let inv_twiddles: Vec<E> = inv_twiddles.into_iter().map(E::from).collect();
+
+        // constraints may have been evaluated over a coset of the evaluation domain; in such a
+        // case, interpolated polynomials are shifted back by the inverse of the coset offset
+        let inv_offset = E::from(self.evaluator.domain_offset().inv());
      
         #[cfg(debug_assertions)]
         self.validate_transition_degrees();
         
-        let mut combined_poly = uninit_vector(self.evaluation_domain_size());
-        
-        // 1 ----- boundary constraints for the initial step --------------------------------------
-        // interpolate initial step boundary constraint combination into a polynomial, divide the 
-        // polynomial by Z(x) = (x - 1), and add it to the result
-        polynom::interpolate_fft_twiddles(&mut self.i_evaluations, &inv_twiddles, true);
-        polynom::syn_div_in_place(&mut self.i_evaluations, field::ONE);
-        combined_poly.copy_from_slice(&self.i_evaluations);
-
-        // 2 ----- boundary constraints for the final step ----------------------------------------
-        // interpolate final step boundary constraint combination into a polynomial, divide the 
-        // polynomial by Z(x) = (x - x_at_last_step), and add it to the result
-        polynom::interpolate_fft_twiddles(&mut self.f_evaluations, &inv_twiddles, true);
-        let x_at_last_step = self.evaluator.get_x_at_last_step();
-        polynom::syn_div_in_place(&mut self.f_evaluations, x_at_last_step);
-        parallel::add_in_place(&mut combined_poly, &self.f_evaluations, 1);
-
-        // 3 ----- transition constraints ---------------------------------------------------------
+        let mut combined_poly = vec![E::ZERO; self.evaluation_domain_size()];
+
+        // 1 ----- boundary constraints -----------------------------------------------------------
+        // for every asserted step, interpolate boundary constraint combination into a polynomial,
+        // divide the polynomial by Z(x) = (x - x_at_step), and add it to the result
+        let num_steps = self.evaluator.assertion_step_count();
+        for (i, x_at_step) in self.evaluator.get_assertion_points().into_iter().enumerate() {
+            let mut evaluations: Vec<E> = self.b_evaluations.iter()
+                .skip(i).step_by(num_steps).copied()
+                .collect();
+            polynom::interpolate_fft_twiddles(&mut evaluations, &inv_twiddles, true);
+            polynom::shift(&mut evaluations, inv_offset);
+            polynom::syn_div_in_place(&mut evaluations, E::from(x_at_step));
+            parallel::add_in_place(&mut combined_poly, &evaluations, 1);
+        }
+
+        // 2 ----- transition constraints ---------------------------------------------------------
         // interpolate transition constraint combination into a polynomial, divide the polynomial
-        // by Z(x) = (x^steps - 1) / (x - x_at_last_step), and add it to the result
+        // by Z(x) = (x^steps - 1) / ((x - x_at_last_step) * ... * (x - x_at_blinding_rows)),
+        // and add it to the result
         let trace_length = self.trace_length();
+        let exceptions: Vec<E> = self.evaluator.get_transition_exceptions().into_iter().map(E::from).collect();
         polynom::interpolate_fft_twiddles(&mut self.t_evaluations, &inv_twiddles, true);
-        polynom::syn_div_expanded_in_place(&mut self.t_evaluations, trace_length, &[x_at_last_step]);
+        polynom::shift(&mut self.t_evaluations, inv_offset);
+        polynom::syn_div_expanded_in_place(&mut self.t_evaluations, trace_length, &exceptions);
         parallel::add_in_place(&mut combined_poly, &self.t_evaluations, 1);
 
-        return ConstraintPoly::new(combined_poly);
+        let degree = utils::get_composition_degree(trace_length, self.evaluator.blinding_rows()) + 1;
+        return ConstraintPoly::new(combined_poly, degree);
     }
 
     #[cfg(debug_assertions)]
@@ -112,4 +212,78 @@ impl ConstraintTable {
             }
         }
     }
-}
\ No newline at end of file
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Evaluates constraints at steps of the evaluation domain starting with `first_step`, and
+/// saves the results into the provided slices.
+fn evaluate_chunk<A: Air, E: FieldElement<BaseField = A::BaseElement>>(
+    evaluator       : &ConstraintEvaluator<A, E>,
+    trace           : &TraceTable<A::BaseElement>,
+    lde_domain      : &[A::BaseElement],
+    stride          : usize,
+    first_step      : usize,
+    b_evaluations   : &mut [E],
+    t_evaluations   : &mut [E])
+{
+    // allocate space to hold current and next states for constraint evaluations
+    let mut current = vec![A::BaseElement::ZERO; trace.register_count()];
+    let mut next = vec![A::BaseElement::ZERO; trace.register_count()];
+    let num_steps = evaluator.assertion_step_count();
+
+    for i in 0..t_evaluations.len() {
+        let step = first_step + i;
+        let position = step * stride;
+
+        // copy current and next states from the trace table; next state may wrap around the
+        // execution trace (close to the end of the trace)
+        trace.fill_row(&mut current, position);
+        trace.fill_row(&mut next, (position + trace.extension_factor()) % trace.domain_size());
+
+        // evaluate the constraints
+        let x = lde_domain[position];
+        evaluator.evaluate_boundaries(&current, x, &mut b_evaluations[(i * num_steps)..((i + 1) * num_steps)]);
+        t_evaluations[i] = evaluator.evaluate_transition(&current, &next, x, step);
+    }
+}
+
+/// Evaluates constraints at rows of a trace segment starting with `first_row`, and saves the
+/// results into the provided slices; the segment covers steps of the evaluation domain with
+/// offset `k`, that is, row `i` of the segment is step `i * MAX_CONSTRAINT_DEGREE + k`.
+fn evaluate_segment_chunk<A: Air, E: FieldElement<BaseField = A::BaseElement>>(
+    evaluator       : &ConstraintEvaluator<A, E>,
+    segment         : &[Vec<A::BaseElement>],
+    lde_domain      : &[A::BaseElement],
+    stride          : usize,
+    k               : usize,
+    first_row       : usize,
+    b_evaluations   : &mut [E],
+    t_evaluations   : &mut [E])
+{
+    // allocate space to hold current and next states for constraint evaluations
+    let mut current = vec![A::BaseElement::ZERO; segment.len()];
+    let mut next = vec![A::BaseElement::ZERO; segment.len()];
+    let num_steps = evaluator.assertion_step_count();
+    let segment_length = segment[0].len();
+
+    for i in 0..t_evaluations.len() {
+        let row = first_row + i;
+        let step = row * MAX_CONSTRAINT_DEGREE + k;
+
+        // copy current and next states from the segment; next state is the next row of the
+        // same segment, and may wrap around the segment (close to the end of the trace)
+        for (register, value) in segment.iter().zip(current.iter_mut()) {
+            *value = register[row];
+        }
+        for (register, value) in segment.iter().zip(next.iter_mut()) {
+            *value = register[(row + 1) % segment_length];
+        }
+
+        // evaluate the constraints
+        let x = lde_domain[step * stride];
+        evaluator.evaluate_boundaries(&current, x, &mut b_evaluations[(i * num_steps)..((i + 1) * num_steps)]);
+        t_evaluations[i] = evaluator.evaluate_transition(&current, &next, x, step);
+    }
+}
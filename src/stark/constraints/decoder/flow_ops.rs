@@ -1,6 +1,6 @@
 use super::{
-    TraceState, SPONGE_WIDTH,
-    are_equal, is_zero, EvaluationResult,
+    field::{ self, mul }, TraceState, UserOps, SPONGE_WIDTH,
+    are_equal, is_zero, binary_not, EvaluationResult,
     enforce_left_shift, enforce_right_shift, enforce_stack_copy
 };
 
@@ -16,13 +16,30 @@ pub fn enforce_begin(result: &mut [u128], current: &TraceState, next: &TraceStat
     result.agg_constraint(2, op_flag, is_zero(new_sponge[2]));
     result.agg_constraint(3, op_flag, is_zero(new_sponge[3]));
 
+    // BEGIN executed together with DYNCALL starts a dynamic call
+    let dyncall_flag = current.ld_op_flags()[UserOps::DynCall.ld_index()];
+    let call_flag = mul(op_flag, dyncall_flag);
+    let block_flag = mul(op_flag, binary_not(dyncall_flag));
+
     // make sure hash of parent block was pushed onto the context stack
     let parent_hash = current.sponge()[0];
     let ctx_stack_start = SPONGE_WIDTH + 1; // 1 is for loop image constraint
     let ctx_stack_end = ctx_stack_start + current.ctx_stack().len();
     let ctx_result = &mut result[ctx_stack_start..ctx_stack_end];
     ctx_result.agg_constraint(0, op_flag, are_equal(parent_hash, next.ctx_stack()[0]));
-    enforce_right_shift(ctx_result, current.ctx_stack(), next.ctx_stack(), 1, op_flag);
+    enforce_right_shift(ctx_result, current.ctx_stack(), next.ctx_stack(), 1, block_flag);
+
+    // for dynamic calls, make sure hash of the callee was taken from the top of the user stack
+    // and was pushed onto the context stack under the hash of parent block
+    if current.ctx_stack().len() > 1 {
+        let callee_hash = current.user_stack()[0];
+        ctx_result.agg_constraint(1, call_flag, are_equal(callee_hash, next.ctx_stack()[1]));
+        enforce_right_shift(ctx_result, current.ctx_stack(), next.ctx_stack(), 2, call_flag);
+    }
+    else {
+        // context stack is too shallow for dynamic calls
+        ctx_result.agg_constraint(0, call_flag, field::ONE);
+    }
 
     // make sure loop stack didn't change
     let loop_result = &mut result[ctx_stack_end..ctx_stack_end + current.loop_stack().len()];
@@ -34,6 +51,11 @@ pub fn enforce_tend(result: &mut [u128], current: &TraceState, next: &TraceState
     let parent_hash = current.ctx_stack()[0];
     let block_hash = current.sponge()[0];
 
+    // TEND executed together with DYNCALL ends a dynamic call
+    let dyncall_flag = current.ld_op_flags()[UserOps::DynCall.ld_index()];
+    let call_flag = mul(op_flag, dyncall_flag);
+    let block_flag = mul(op_flag, binary_not(dyncall_flag));
+
     let new_sponge = next.sponge();
     result.agg_constraint(0, op_flag, are_equal(parent_hash, new_sponge[0]));
     result.agg_constraint(1, op_flag, are_equal(block_hash, new_sponge[1]));
@@ -44,7 +66,21 @@ pub fn enforce_tend(result: &mut [u128], current: &TraceState, next: &TraceState
     let ctx_stack_start = SPONGE_WIDTH + 1; // 1 is for loop image constraint
     let ctx_stack_end = ctx_stack_start + current.ctx_stack().len();
     let ctx_result = &mut result[ctx_stack_start..ctx_stack_end];
-    enforce_left_shift(ctx_result, current.ctx_stack(), next.ctx_stack(), 1, 1, op_flag);
+    enforce_left_shift(ctx_result, current.ctx_stack(), next.ctx_stack(), 1, 1, block_flag);
+
+    // for dynamic calls, make sure hash of the executed block is equal to the callee hash
+    // saved under the parent hash, and pop both hashes from the context stack; the callee hash
+    // check uses the slot of loop image constraint which is otherwise unused by TEND
+    if current.ctx_stack().len() > 1 {
+        let callee_hash = current.ctx_stack()[1];
+        result.agg_constraint(SPONGE_WIDTH, call_flag, are_equal(block_hash, callee_hash));
+        let ctx_result = &mut result[ctx_stack_start..ctx_stack_end];
+        enforce_left_shift(ctx_result, current.ctx_stack(), next.ctx_stack(), 2, 2, call_flag);
+    }
+    else {
+        // context stack is too shallow for dynamic calls
+        result.agg_constraint(SPONGE_WIDTH, call_flag, field::ONE);
+    }
 
     // make sure loop stack didn't change
     let loop_result = &mut result[ctx_stack_end..ctx_stack_end + current.loop_stack().len()];
@@ -244,6 +280,78 @@ mod tests {
         assert_eq!(vec![3, 1, 0, 4, 0, 1, 6, 0], evaluations);
     }
 
+    #[test]
+    fn op_begin_dyncall() {
+
+        // correct transition, context depth = 2
+        let state1 = new_dyncall_state(15, FlowOps::Begin, &[3, 5, 7, 9], &[0, 0], 11);
+        let state2 = new_state(16, FlowOps::Void, &[0, 0, 0, 0], &[3, 11], &[]);
+
+        let mut evaluations = vec![0; 8];
+        super::enforce_begin(&mut evaluations, &state1, &state2, 1);
+        assert_eq!(vec![0, 0, 0, 0, 0, 0, 0, 0], evaluations);
+
+        // correct transition, context depth = 3
+        let state1 = new_dyncall_state(15, FlowOps::Begin, &[3, 5, 7, 9], &[2, 0, 0], 11);
+        let state2 = new_state(16, FlowOps::Void, &[0, 0, 0, 0], &[3, 11, 2], &[]);
+
+        let mut evaluations = vec![0; 9];
+        super::enforce_begin(&mut evaluations, &state1, &state2, 1);
+        assert_eq!(vec![0, 0, 0, 0, 0, 0, 0, 0, 0], evaluations);
+
+        // incorrect transition (callee hash is not the top of the stack), context depth = 2
+        let state1 = new_dyncall_state(15, FlowOps::Begin, &[3, 5, 7, 9], &[0, 0], 11);
+        let state2 = new_state(16, FlowOps::Void, &[0, 0, 0, 0], &[3, 12], &[]);
+
+        let mut evaluations = vec![0; 8];
+        super::enforce_begin(&mut evaluations, &state1, &state2, 1);
+        assert_eq!(vec![0, 0, 0, 0, 0, 0, are_equal(11, 12), 0], evaluations);
+
+        // incorrect transition (context stack is too shallow), context depth = 1
+        let state1 = new_dyncall_state(15, FlowOps::Begin, &[3, 5, 7, 9], &[0], 11);
+        let state2 = new_state(16, FlowOps::Void, &[0, 0, 0, 0], &[3], &[]);
+
+        let mut evaluations = vec![0; 7];
+        super::enforce_begin(&mut evaluations, &state1, &state2, 1);
+        assert_eq!(vec![0, 0, 0, 0, 0, 1, 0], evaluations);
+    }
+
+    #[test]
+    fn op_tend_dyncall() {
+
+        // correct transition, context depth = 2
+        let state1 = new_dyncall_state(16, FlowOps::Tend, &[3, 5, 7, 9], &[8, 3], 11);
+        let state2 = new_state(17, FlowOps::Void, &[8, 3, 4, 0], &[0, 0], &[]);
+
+        let mut evaluations = vec![0; 8];
+        super::enforce_tend(&mut evaluations, &state1, &state2, 1);
+        assert_eq!(vec![0, 0, 0, 0, 0, 0, 0, 0], evaluations);
+
+        // correct transition, context depth = 3
+        let state1 = new_dyncall_state(16, FlowOps::Tend, &[3, 5, 7, 9], &[8, 3, 2], 11);
+        let state2 = new_state(17, FlowOps::Void, &[8, 3, 4, 0], &[2, 0, 0], &[]);
+
+        let mut evaluations = vec![0; 9];
+        super::enforce_tend(&mut evaluations, &state1, &state2, 1);
+        assert_eq!(vec![0, 0, 0, 0, 0, 0, 0, 0, 0], evaluations);
+
+        // incorrect transition (block hash doesn't match callee hash), context depth = 2
+        let state1 = new_dyncall_state(16, FlowOps::Tend, &[3, 5, 7, 9], &[8, 6], 11);
+        let state2 = new_state(17, FlowOps::Void, &[8, 3, 4, 0], &[0, 0], &[]);
+
+        let mut evaluations = vec![0; 8];
+        super::enforce_tend(&mut evaluations, &state1, &state2, 1);
+        assert_eq!(vec![0, 0, 0, 0, are_equal(3, 6), 0, 0, 0], evaluations);
+
+        // incorrect transition (block hash not merged into parent hash), context depth = 2
+        let state1 = new_dyncall_state(16, FlowOps::Tend, &[3, 5, 7, 9], &[8, 3], 11);
+        let state2 = new_state(17, FlowOps::Void, &[8, 0, 4, 0], &[0, 0], &[]);
+
+        let mut evaluations = vec![0; 8];
+        super::enforce_tend(&mut evaluations, &state1, &state2, 1);
+        assert_eq!(vec![0, are_equal(3, 0), 0, 0, 0, 0, 0, 0], evaluations);
+    }
+
     #[test]
     fn op_fend() {
 
@@ -458,6 +566,24 @@ mod tests {
         state.extend_from_slice(loop_stack);
         state.push(101); // single value for user stack
 
-        return TraceState::from_vec(ctx_depth, loop_depth, 1, &state);
+        return TraceState::from_vec_without_helpers(ctx_depth, loop_depth, 1, &state);
+    }
+
+    fn new_dyncall_state(step: usize, flow_op: FlowOps, sponge: &[u128; 4], ctx_stack: &[u128], stack_top: u128) -> TraceState 
+    {
+        let mut state = vec![step as u128, sponge[0], sponge[1], sponge[2], sponge[3]];
+
+        for i in 0..3 {
+            state.push(((flow_op as u128) >> i) & 1);
+        }
+
+        for i in 0..7 {
+            state.push(((UserOps::DynCall as u128) >> i) & 1);
+        }
+
+        state.extend_from_slice(ctx_stack);
+        state.push(stack_top);
+
+        return TraceState::from_vec_without_helpers(ctx_stack.len(), 0, 1, &state);
     }
 }
\ No newline at end of file
@@ -19,20 +19,21 @@ pub fn enforce_op_bits(result: &mut [u128], current: &TraceState, next: &TraceSt
         i += 1;
     }
 
-    let mut ld_bit_prod = 1;
     for &op_bit in current.ld_op_bits() {
         result[i] = is_binary(op_bit);
-        ld_bit_prod = mul(ld_bit_prod, op_bit);
         i += 1;
     }
 
-    let mut hd_bit_prod = 1;
     for &op_bit in current.hd_op_bits() {
         result[i] = is_binary(op_bit);
-        hd_bit_prod = mul(hd_bit_prod, op_bit);
         i += 1;
     }
 
+    // products of ld and hd op bits are equal to NOOP flags of the respective op banks; these
+    // flags are computed from op flag helpers, and thus, have degree 2 and 1 respectively
+    let ld_bit_prod = current.ld_op_flags()[UserOps::Noop.ld_index()];
+    let hd_bit_prod = current.hd_op_flags()[UserOps::Noop.hd_index()];
+
     // when cf_ops = hacc, operation counter should be incremented by 1;
     // otherwise, operation counter should remain the same
     let op_counter = current.op_counter();
@@ -47,12 +48,18 @@ pub fn enforce_op_bits(result: &mut [u128], current: &TraceState, next: &TraceSt
     result[i] = mul(op_counter, mul(binary_not(ld_bit_prod), binary_not(hd_bit_prod)));
     i += 1;
 
-    // when cf_ops are not all 0s, ld_ops and hd_ops must be all 1s
-    result[i] = mul(cf_bit_sum, binary_not(mul(ld_bit_prod, hd_bit_prod)));
+    // when cf_ops are not all 0s, user op must be NOOP or DYNCALL
+    let dyncall_flag = current.ld_op_flags()[UserOps::DynCall.ld_index()];
+    result[i] = mul(cf_bit_sum, binary_not(mul(add(ld_bit_prod, dyncall_flag), hd_bit_prod)));
     i += 1;
     
     let cf_op_flags = current.cf_op_flags();
 
+    // DYNCALL can be executed only together with BEGIN or TEND
+    let begin_or_tend = add(cf_op_flags[FlowOps::Begin.op_index()], cf_op_flags[FlowOps::Tend.op_index()]);
+    result[i] = mul(dyncall_flag, binary_not(begin_or_tend));
+    i += 1;
+
     // VOID can be followed only by VOID
     let current_void_flag = cf_op_flags[FlowOps::Void.op_index()];
     let next_void_flag = next.cf_op_flags()[FlowOps::Void.op_index()];
@@ -76,6 +83,14 @@ pub fn enforce_op_bits(result: &mut [u128], current: &TraceState, next: &TraceSt
     // PUSH is allowed only on multiples of 8
     let push_cycle_mask = masks[PUSH_MASK_IDX];
     result.agg_constraint(i, hd_op_flags[UserOps::Push.hd_index()], push_cycle_mask);
+    i += 1;
+
+    // op flag helpers must be consistent with op bits
+    let expected_helpers = current.get_expected_op_helpers();
+    for (&helper, &expected) in current.op_helpers().iter().zip(expected_helpers.iter()) {
+        result[i] = are_equal(helper, expected);
+        i += 1;
+    }
 }
 
 // TESTS
@@ -128,9 +143,10 @@ mod tests {
             assert_ne!(success_result, evaluate_state(&state, [0, 0, 0], false));
         }
 
-        // when cf_ops are not all 0s, user_ops must be all 1s
+        // when cf_ops are not all 0s, user_ops must be all 1s or DYNCALL
         for cf_op in 1..8 {
             for user_op in 0..127 {
+                if user_op == UserOps::DynCall as u8 { continue; }
                 let state = new_state(cf_op as u8, user_op as u8, 1);
                 assert_ne!(success_result, evaluate_state(&state, [0, 0, 0], false));
             }
@@ -138,6 +154,17 @@ mod tests {
             let state = new_state(cf_op as u8, UserOps::Noop as u8, 1);
             assert_eq!(success_result, evaluate_state(&state, [0, 0, 0], false));
         }
+
+        // DYNCALL is allowed only together with BEGIN and TEND
+        for cf_op in 0..8 {
+            let state = new_state(cf_op as u8, UserOps::DynCall as u8, 1);
+            if cf_op == FlowOps::Begin as u8 || cf_op == FlowOps::Tend as u8 {
+                assert_eq!(success_result, evaluate_state(&state, [0, 0, 0], false));
+            }
+            else {
+                assert_ne!(success_result, evaluate_state(&state, [0, 0, 0], false));
+            }
+        }
     }
 
     #[test]
@@ -1,18 +1,16 @@
 use crate::utils::sponge::{ apply_round as apply_hacc_round };
-use super::{ Decoder, TraceState, UserOps };
+use super::{ Decoder, TraceState, UserOps, BASE_CYCLE_LENGTH };
 
-// CONSTANTS
-// ================================================================================================
-const TRACE_LENGTH: usize = 16;
-const EXTENSION_FACTOR: usize = 8;
+const CTX_DEPTH : usize = 1;
+const LOOP_DEPTH: usize = 0;
 
 // BEGIN OPERATION
 // ================================================================================================
 #[test]
 fn enforce_begin() {
 
-    let decoder = new_decoder(1, 0);
-    let step = 15 * EXTENSION_FACTOR;
+    let decoder = new_decoder();
+    let step = 15;
     let success_result = vec![0; decoder.constraint_degrees().len()];
     
     // correct transition
@@ -50,7 +48,7 @@ fn enforce_begin() {
 // ================================================================================================
 #[test]
 fn enforce_hacc() {
-    let decoder = new_decoder(1, 0);
+    let decoder = new_decoder();
     let success_result = vec![0; decoder.constraint_degrees().len()];
 
     // correct transition, push.9, step = 0
@@ -66,7 +64,7 @@ fn enforce_hacc() {
     let state1     = vec![1,  3, 5, 7, 9,  0, 0, 0,  1, 1, 1, 1, 1,  0, 0,  0,  11];
     let mut state2 = vec![2,  3, 5, 7, 9,  1, 0, 0,  1, 1, 1, 1, 1,  1, 1,  0,  push_value];
     apply_hacc_round(&mut state2[1..5], UserOps::Push as u128, push_value, 8);
-    let evaluations = evaluate_transition(&decoder, 8 * EXTENSION_FACTOR, state1, state2);
+    let evaluations = evaluate_transition(&decoder, 8, state1, state2);
     assert_eq!(success_result, evaluations);
 
     // correct transition, add, step = 0
@@ -102,16 +100,20 @@ fn enforce_hacc() {
 
 // HELPER FUNCTIONS
 // ================================================================================================
-fn new_decoder(ctx_depth: usize, loop_depth: usize) -> Decoder {
-    return Decoder::new(TRACE_LENGTH, EXTENSION_FACTOR, ctx_depth, loop_depth);
+fn new_decoder() -> Decoder {
+    return Decoder::new(CTX_DEPTH, LOOP_DEPTH);
 }
 
 fn evaluate_transition(decoder: &Decoder, step: usize, state1: Vec<u128>, state2: Vec<u128>) -> Vec<u128>
 {
-    let state1 = TraceState::from_vec(decoder.ctx_depth(), decoder.loop_depth(), 1, &state1);
-    let state2 = TraceState::from_vec(decoder.ctx_depth(), decoder.loop_depth(), 1, &state2);
+    let state1 = TraceState::from_vec_without_helpers(CTX_DEPTH, LOOP_DEPTH, 1, &state1);
+    let state2 = TraceState::from_vec_without_helpers(CTX_DEPTH, LOOP_DEPTH, 1, &state2);
+
+    let periodic_values: Vec<u128> = Decoder::get_periodic_columns().iter()
+        .map(|column| column[step % BASE_CYCLE_LENGTH])
+        .collect();
 
     let mut evaluations = vec![0; decoder.constraint_degrees().len()];
-    decoder.evaluate(&state1, &state2, step, &mut evaluations);
+    decoder.evaluate(&state1, &state2, &periodic_values, &mut evaluations);
     return evaluations
 }
\ No newline at end of file
@@ -1,116 +1,109 @@
 use crate::{
-    math::field,
-    utils::uninit_vector,
-    stark::{ StarkProof, TraceTable, TraceState, ConstraintCoefficients },
-    PROGRAM_DIGEST_SIZE,
+    math::{ fft, polynom, FieldElement, StarkField },
+    stark::{ Air, Assertion, ConstraintCoefficients },
 };
-use super::{ decoder::Decoder, stack::Stack, super::MAX_CONSTRAINT_DEGREE };
+use super::super::MAX_CONSTRAINT_DEGREE;
 use sp_std::{vec, vec::Vec};
 
 // TYPES AND INTERFACES
 // ================================================================================================
-pub struct Evaluator {
-    decoder         : Decoder,
-    stack           : Stack,
+pub struct Evaluator<A: Air, E: FieldElement<BaseField = A::BaseElement>> {
+    air             : A,
 
-    coefficients    : ConstraintCoefficients,
+    coefficients    : ConstraintCoefficients<E>,
     domain_size     : usize,
     extension_factor: usize,
+    domain_offset   : A::BaseElement,
+    aux_rand        : Vec<A::BaseElement>,
 
     t_constraint_num: usize,
     t_degree_groups : Vec<(u128, Vec<usize>)>,
-    t_evaluations   : Vec<Vec<u128>>,
+    t_evaluations   : Vec<Vec<A::BaseElement>>,
 
     b_constraint_num: usize,
-    program_hash    : Vec<u128>,
-    op_count        : u128,
-    inputs          : Vec<u128>,
-    outputs         : Vec<u128>,
+    b_groups        : Vec<AssertionGroup<A::BaseElement>>,
     b_degree_adj    : u128,
+
+    p_polys         : Vec<Vec<A::BaseElement>>,
+    p_values        : Vec<Vec<A::BaseElement>>,
+}
+
+/// Assertions made against the same step of the execution trace; `x` is the point of the trace
+/// domain which corresponds to the step.
+struct AssertionGroup<E: StarkField> {
+    x               : E,
+    registers       : Vec<usize>,
+    values          : Vec<E>,
 }
 
 // EVALUATOR IMPLEMENTATION
 // ================================================================================================
-impl Evaluator {
+impl<A: Air, E: FieldElement<BaseField = A::BaseElement>> Evaluator<A, E> {
 
-    pub fn from_trace(trace: &TraceTable, trace_root: &[u8; 32], inputs: &[u128], outputs: &[u128]) -> Evaluator
+    /// Returns an evaluator for constraints defined by the `air` over the evaluation domain
+    /// shifted by `domain_offset`; coefficients for the pseudo-random linear combination of
+    /// constraints are derived from `seed`, and `aux_rand` are random elements on which the
+    /// auxiliary segment of the trace depends.
+    pub fn new(air: A, seed: &[u8; 32], aux_rand: Vec<A::BaseElement>, domain_offset: A::BaseElement) -> Evaluator<A, E>
     {
-        let last_state = trace.get_last_state();
-        let ctx_depth = trace.ctx_depth();
-        let loop_depth = trace.loop_depth();
-        let stack_depth = trace.stack_depth();
-        let trace_length = trace.unextended_length();
-        let extension_factor = MAX_CONSTRAINT_DEGREE;
-
-        // instantiate decoder and stack constraint evaluators 
-        let decoder = Decoder::new(trace_length, extension_factor, ctx_depth, loop_depth);
-        let stack = Stack::new(trace_length, extension_factor, stack_depth);
-
-        // build a list of transition constraint degrees
-        let t_constraint_degrees = [
-            decoder.constraint_degrees(), stack.constraint_degrees()
-        ].concat();
+        // interpolate periodic columns and evaluate them over the evaluation domain
+        let (p_polys, p_values) = extend_periodic_columns(
+            air.get_periodic_columns(), air.trace_info().length, MAX_CONSTRAINT_DEGREE, domain_offset);
 
-        // if we are in debug mode, initialize vectors to hold individual evaluations
-        // of transition constraints
-        let domain_size = trace_length * extension_factor;
-        let t_evaluations = if cfg!(debug_assertions) {
-            t_constraint_degrees.iter().map(|_| uninit_vector(domain_size)).collect()
-        }
-        else {
-            Vec::new()
-        };
+        return Self::build(air, seed, aux_rand, domain_offset, p_polys, p_values);
+    }
 
-        return Evaluator {
-            decoder         : decoder,
-            stack           : stack,
-            coefficients    : ConstraintCoefficients::new(*trace_root, ctx_depth, loop_depth, stack_depth),
-            domain_size     : domain_size,
-            extension_factor: extension_factor,
-            t_constraint_num: t_constraint_degrees.len(),
-            t_degree_groups : group_transition_constraints(t_constraint_degrees, trace_length),
-            t_evaluations   : t_evaluations,
-            b_constraint_num: get_boundary_constraint_num(&inputs, &outputs),
-            program_hash    : last_state.program_hash().to_vec(),
-            op_count        : last_state.op_counter(),
-            inputs          : inputs.to_vec(),
-            outputs         : outputs.to_vec(),
-            b_degree_adj    : get_boundary_constraint_adjustment_degree(trace_length),
-        };
+    /// Returns an evaluator which can evaluate transition constraints only at arbitrary points
+    /// via `evaluate_transition_at()`; `p_polys` must be polynomials of periodic columns of the
+    /// `air` (see `interpolate_periodic_columns()`). Unlike `new()`, this does not evaluate the
+    /// periodic columns over the evaluation domain, and so the verifier can reuse periodic
+    /// polynomials across proofs of the same computation.
+    pub fn with_periodic_polys(air: A, seed: &[u8; 32], aux_rand: Vec<A::BaseElement>, domain_offset: A::BaseElement,
+        p_polys: Vec<Vec<A::BaseElement>>) -> Evaluator<A, E>
+    {
+        return Self::build(air, seed, aux_rand, domain_offset, p_polys, Vec::new());
     }
 
-    pub fn from_proof(proof: &StarkProof, program_hash: &[u8; 32], inputs: &[u128], outputs: &[u128]) -> Evaluator
+    fn build(air: A, seed: &[u8; 32], aux_rand: Vec<A::BaseElement>, domain_offset: A::BaseElement,
+        p_polys: Vec<Vec<A::BaseElement>>, p_values: Vec<Vec<A::BaseElement>>) -> Evaluator<A, E>
     {
-        let ctx_depth = proof.ctx_depth();
-        let loop_depth = proof.loop_depth();
-        let stack_depth = proof.stack_depth();
-        let trace_length = proof.trace_length();
-        let extension_factor = proof.options().extension_factor();
-        
-        // instantiate decoder and stack constraint evaluators 
-        let decoder = Decoder::new(trace_length, extension_factor, ctx_depth, loop_depth);
-        let stack = Stack::new(trace_length, extension_factor, stack_depth);
-
-        // build a list of transition constraint degrees
-        let t_constraint_degrees = [
-            decoder.constraint_degrees(), stack.constraint_degrees()
-        ].concat();
+        assert!(aux_rand.len() == air.aux_rand_count(),
+            "expected {} random elements for the auxiliary segment, but received {}",
+            air.aux_rand_count(), aux_rand.len());
+
+        let trace_length = air.trace_info().length;
+        let extension_factor = MAX_CONSTRAINT_DEGREE;
+        let blinding_rows = air.trace_info().blinding_rows;
+
+        // group transition constraints by degree; constraints over the auxiliary segment
+        // follow constraints over the main segment
+        let t_constraint_degrees = [air.transition_degrees(), air.aux_transition_degrees()].concat();
+        for &degree in t_constraint_degrees.iter() {
+            assert!(degree > 0 && degree <= MAX_CONSTRAINT_DEGREE,
+                "transition constraint degree must be between 1 and {}", MAX_CONSTRAINT_DEGREE);
+        }
+        let t_constraint_num = t_constraint_degrees.len();
+
+        // group assertions by step
+        let assertions = air.get_assertions();
+        let b_constraint_num = assertions.len();
+        let b_groups = group_assertions(&assertions, trace_length, air.trace_info().last_step());
 
         return Evaluator {
-            decoder         : decoder,
-            stack           : stack,
-            coefficients    : ConstraintCoefficients::new(*proof.trace_root(), ctx_depth, loop_depth, stack_depth),
-            domain_size     : proof.domain_size(),
+            air             : air,
+            coefficients    : ConstraintCoefficients::new(*seed, t_constraint_num, b_constraint_num),
+            domain_size     : trace_length * extension_factor,
             extension_factor: extension_factor,
-            t_constraint_num: t_constraint_degrees.len(),
+            domain_offset   : domain_offset,
+            aux_rand        : aux_rand,
+            t_constraint_num: t_constraint_num,
             t_degree_groups : group_transition_constraints(t_constraint_degrees, trace_length),
             t_evaluations   : Vec::new(),
-            b_constraint_num: get_boundary_constraint_num(&inputs, &outputs),
-            program_hash    : parse_program_hash(program_hash),
-            op_count        : proof.op_count(),
-            inputs          : inputs.to_vec(),
-            outputs         : outputs.to_vec(),
-            b_degree_adj    : get_boundary_constraint_adjustment_degree(trace_length),
+            b_constraint_num: b_constraint_num,
+            b_groups        : b_groups,
+            b_degree_adj    : get_boundary_constraint_adjustment_degree(trace_length, blinding_rows),
+            p_polys         : p_polys,
+            p_values        : p_values,
         };
     }
 
@@ -126,9 +119,36 @@ impl Evaluator {
         return self.domain_size / self.extension_factor;
     }
 
-    pub fn get_x_at_last_step(&self) -> u128 {
-        let trace_root = field::get_root_of_unity(self.trace_length());
-        return field::exp(trace_root, (self.trace_length() - 1) as u128);
+    pub fn trace_width(&self) -> usize {
+        return self.air.trace_info().width;
+    }
+
+    pub fn blinding_rows(&self) -> usize {
+        return self.air.trace_info().blinding_rows;
+    }
+
+    pub fn domain_offset(&self) -> A::BaseElement {
+        return self.domain_offset;
+    }
+
+    /// Returns the number of distinct steps against which assertions are made; boundary
+    /// constraints are combined separately for each of these steps.
+    pub fn assertion_step_count(&self) -> usize {
+        return self.b_groups.len();
+    }
+
+    /// Returns points of the trace domain which correspond to the steps against which
+    /// assertions are made.
+    pub fn get_assertion_points(&self) -> Vec<A::BaseElement> {
+        return self.b_groups.iter().map(|group| group.x).collect();
+    }
+
+    /// Returns points of the trace domain at which transition constraints are not enforced:
+    /// the last step of the computation and all blinding rows which follow it.
+    pub fn get_transition_exceptions(&self) -> Vec<A::BaseElement> {
+        let trace_root = A::BaseElement::get_root_of_unity(self.trace_length());
+        let last_step = self.air.trace_info().last_step();
+        return (last_step..self.trace_length()).map(|i| trace_root.exp(i as u128)).collect();
     }
 
     // CONSTRAINT EVALUATORS
@@ -137,12 +157,16 @@ impl Evaluator {
     /// Computes pseudo-random linear combination of transition constraints D_i at point x as:
     /// cc_{i * 2} * D_i + cc_{i * 2 + 1} * D_i * x^p for all i, where cc_j are the coefficients
     /// used in the linear combination and x^p is a degree adjustment factor (different for each degree).
-    pub fn evaluate_transition(&self, current: &TraceState, next: &TraceState, x: u128, step: usize) -> u128 {
-        
+    pub fn evaluate_transition(&self, current: &[A::BaseElement], next: &[A::BaseElement], x: A::BaseElement, step: usize) -> E {
+
+        // determine values of periodic columns at the specified step
+        let periodic_values: Vec<A::BaseElement> = self.p_values.iter()
+            .map(|values| values[step % values.len()])
+            .collect();
+
         // evaluate transition constraints
-        let mut evaluations = vec![field::ZERO; self.t_constraint_num];
-        self.decoder.evaluate(&current, &next, step, &mut evaluations);
-        self.stack.evaluate(&current, &next, step, &mut evaluations[self.decoder.constraint_count()..]);
+        let mut evaluations = vec![A::BaseElement::ZERO; self.t_constraint_num];
+        self.evaluate_constraints(current, next, &periodic_values, &self.aux_rand, &mut evaluations);
 
         // when in debug mode, save transition evaluations before they are combined
         #[cfg(debug_assertions)]
@@ -153,9 +177,9 @@ impl Evaluator {
         if self.should_evaluate_to_zero_at(step) {
             let step = step / self.extension_factor;
             for i in 0..evaluations.len() {
-                assert!(evaluations[i] == field::ZERO, "transition constraint at step {} were not satisfied", step);
+                assert!(evaluations[i] == A::BaseElement::ZERO, "transition constraint at step {} were not satisfied", step);
             }
-            return field::ZERO;
+            return E::ZERO;
         }
 
         // compute a pseudo-random linear combination of all transition constraints
@@ -165,203 +189,119 @@ impl Evaluator {
     /// Computes pseudo-random liner combination of transition constraints at point x. This function
     /// is similar to the one above but it can also be used to evaluate constraints at any point
     /// in the filed (not just in the evaluation domain). However, it is also much slower.
-    pub fn evaluate_transition_at(&self, current: &TraceState, next: &TraceState, x: u128) -> u128 {
+    pub fn evaluate_transition_at(&self, current: &[E], next: &[E], x: E) -> E {
+
+        // determine values of periodic columns at the specified x coordinate; a column with
+        // cycle length c is a polynomial in x^(trace_length / c)
+        let periodic_values: Vec<E> = self.p_polys.iter()
+            .map(|poly| {
+                let poly: Vec<E> = poly.iter().map(|&c| E::from(c)).collect();
+                polynom::eval(&poly, x.exp((self.trace_length() / poly.len()) as u128))
+            })
+            .collect();
+
         // evaluate transition constraints
-        let mut evaluations = vec![field::ZERO; self.t_constraint_num];
-        self.decoder.evaluate_at(&current, &next, x, &mut evaluations);
-        self.stack.evaluate_at(&current, &next, x, &mut evaluations[self.decoder.constraint_count()..]);
+        let aux_rand: Vec<E> = self.aux_rand.iter().map(|&r| E::from(r)).collect();
+        let mut evaluations = vec![E::ZERO; self.t_constraint_num];
+        self.evaluate_constraints(current, next, &periodic_values, &aux_rand, &mut evaluations);
 
         // compute a pseudo-random linear combination of all transition constraints
         return self.combine_transition_constraints(&evaluations, x);
     }
 
-    /// Computes pseudo-random linear combination of boundary constraints B_i at point x  separately
-    /// for the first and for the last steps of the program; the constraints are computed as:
-    /// cc_{i * 2} * B_i + cc_{i * 2 + 1} * B_i * x^p for all i, where cc_j are the coefficients
-    /// used in the linear combination and x^p is a degree adjustment factor.
-    pub fn evaluate_boundaries(&self, current: &TraceState, x: u128) -> (u128, u128) {
-        
-        // compute degree adjustment factor
-        let xp = field::exp(x, self.b_degree_adj);
-
-        // 1 ----- compute combination of boundary constraints for the first step ------------------
-        let mut i_result = field::ZERO;
-        let mut result_adj = field::ZERO;
-
-        let cc = &self.coefficients.i_boundary;
-
-        // make sure op_counter is set to 0
-        let op_counter = current.op_counter();
-        i_result = field::add(i_result, field::mul(op_counter, cc.op_counter[0]));
-        result_adj = field::add(result_adj, field::mul(op_counter, cc.op_counter[1]));
+    /// Computes pseudo-random linear combination of boundary constraints B_i at point x separately
+    /// for every step against which assertions are made, and saves the results into `result`;
+    /// the constraints are computed as: cc_{i * 2} * B_i + cc_{i * 2 + 1} * B_i * x^p for all i,
+    /// where cc_j are the coefficients used in the linear combination and x^p is a degree
+    /// adjustment factor. The point x can be either in the base field or in the extension field
+    /// from which the coefficients are drawn.
+    pub fn evaluate_boundaries<F>(&self, current: &[F], x: F, result: &mut [E])
+        where F: FieldElement<BaseField = A::BaseElement>, E: From<F>
+    {
 
-        // make sure operation sponge registers are set to 0s
-        let sponge = current.sponge();
-        for i in 0..sponge.len() {
-            i_result = field::add(i_result, field::mul(sponge[i], cc.sponge[i * 2]));
-            result_adj = field::add(result_adj, field::mul(sponge[i], cc.sponge[i * 2 + 1]));
-        }
+        // compute degree adjustment factor
+        let xp = x.exp(self.b_degree_adj);
 
-        // make sure cf_bits are set to HACC (000)
+        let cc = &self.coefficients.boundary;
         let mut cc_idx = 0;
-        let op_bits = current.cf_op_bits();
-        for i in 0..op_bits.len() {
-            i_result = field::add(i_result, field::mul(op_bits[i], cc.op_bits[cc_idx]));
-            result_adj = field::add(result_adj, field::mul(op_bits[i], cc.op_bits[cc_idx + 1]));
-            cc_idx += 2;
-        }
-
-        // make sure low-degree op_bits are set to BEGIN (0000)
-        let op_bits = current.ld_op_bits();
-        for i in 0..op_bits.len() {
-            i_result = field::add(i_result, field::mul(op_bits[i], cc.op_bits[cc_idx]));
-            result_adj = field::add(result_adj, field::mul(op_bits[i], cc.op_bits[cc_idx + 1]));
-            cc_idx += 2;
-        }
-
-        // make sure high-degree op_bits are set to BEGIN (00)
-        let op_bits = current.hd_op_bits();
-        for i in 0..op_bits.len() {
-            i_result = field::add(i_result, field::mul(op_bits[i], cc.op_bits[cc_idx]));
-            result_adj = field::add(result_adj, field::mul(op_bits[i], cc.op_bits[cc_idx + 1]));
-            cc_idx += 2;
-        }
-
-        // make sure all context stack registers are 0s
-        let ctx_stack = current.ctx_stack();
-        for i in 0..ctx_stack.len() {
-            i_result = field::add(i_result, field::mul(ctx_stack[i], cc.ctx_stack[i * 2]));
-            result_adj = field::add(result_adj, field::mul(ctx_stack[i], cc.ctx_stack[i * 2 + 1]));
-        }
-
-        // make sure all loop stack registers are 0s
-        let loop_stack = current.loop_stack();
-        for i in 0..loop_stack.len() {
-            i_result = field::add(i_result, field::mul(loop_stack[i], cc.loop_stack[i * 2]));
-            result_adj = field::add(result_adj, field::mul(loop_stack[i], cc.loop_stack[i * 2 + 1]));
-        }
-
-        // make sure stack registers are set to inputs
-        let user_stack = current.user_stack();
-        for i in 0..self.inputs.len() {
-            let val = field::sub(user_stack[i], self.inputs[i]);
-            i_result = field::add(i_result, field::mul(val, cc.user_stack[i * 2]));
-            result_adj = field::add(result_adj, field::mul(val, cc.user_stack[i * 2 + 1]));
-        }
+        for (group, result) in self.b_groups.iter().zip(result.iter_mut()) {
+            let mut group_result = E::ZERO;
+            let mut result_adj = E::ZERO;
+
+            // make sure the register is set to the asserted value
+            for (&register, &value) in group.registers.iter().zip(group.values.iter()) {
+                let val = E::from(current[register] - F::from(value));
+                group_result = group_result + val * cc[cc_idx];
+                result_adj = result_adj + val * cc[cc_idx + 1];
+                cc_idx += 2;
+            }
 
-        // raise the degree of adjusted terms and sum all the terms together
-        i_result = field::add(i_result, field::mul(result_adj, xp));
-
-        // 2 ----- compute combination of boundary constraints for the last step -------------------
-        let mut f_result = field::ZERO;
-        let mut result_adj = field::ZERO;
-
-        let cc = &self.coefficients.f_boundary;
-        
-        // make sure op_counter register is set to the claimed value of operations
-        let val = field::sub(current.op_counter(), self.op_count);
-        f_result = field::add(f_result, field::mul(val, cc.op_counter[0]));
-        result_adj = field::add(result_adj, field::mul(val, cc.op_counter[1]));
-
-        // make sure operation sponge contains program hash
-        let program_hash = current.program_hash();
-        for i in 0..self.program_hash.len() {
-            let val = field::sub(program_hash[i], self.program_hash[i]);
-            f_result = field::add(f_result, field::mul(val, cc.sponge[i * 2]));
-            result_adj = field::add(result_adj, field::mul(val, cc.sponge[i * 2 + 1]));
+            // raise the degree of adjusted terms and sum all the terms together
+            *result = group_result + result_adj * E::from(xp);
         }
+    }
 
-        // make sure control flow op_bits are set VOID (111)
-        let mut cc_idx = 0;
-        let op_bits = current.cf_op_bits();
-        for i in 0..op_bits.len() {
-            let val = field::sub(op_bits[i], field::ONE);
-            f_result = field::add(f_result, field::mul(val, cc.op_bits[cc_idx]));
-            result_adj = field::add(result_adj, field::mul(val, cc.op_bits[cc_idx + 1]));
-            cc_idx += 2;
-        }
-        
-        // make sure low-degree op_bits are set to NOOP (11111)
-        let op_bits = current.ld_op_bits();
-        for i in 0..op_bits.len() {
-            let val = field::sub(op_bits[i], field::ONE);
-            f_result = field::add(f_result, field::mul(val, cc.op_bits[cc_idx]));
-            result_adj = field::add(result_adj, field::mul(val, cc.op_bits[cc_idx + 1]));
-            cc_idx += 2;
-        }
-        
-        // make sure high-degree op_bits are set to NOOP (11)
-        let op_bits = current.hd_op_bits();
-        for i in 0..op_bits.len() {
-            let val = field::sub(op_bits[i], field::ONE);
-            f_result = field::add(f_result, field::mul(val, cc.op_bits[cc_idx]));
-            result_adj = field::add(result_adj, field::mul(val, cc.op_bits[cc_idx + 1]));
-            cc_idx += 2;
-        }
-        
-        // make sure all context stack registers are 0s
-        let ctx_stack = current.ctx_stack();
-        for i in 0..ctx_stack.len() {
-            f_result = field::add(f_result, field::mul(ctx_stack[i], cc.ctx_stack[i * 2]));
-            result_adj = field::add(result_adj, field::mul(ctx_stack[i], cc.ctx_stack[i * 2 + 1]));
-        }
+    // HELPER METHODS
+    // -------------------------------------------------------------------------------------------
 
-        // make sure all loop stack registers are 0s
-        let loop_stack = current.loop_stack();
-        for i in 0..loop_stack.len() {
-            f_result = field::add(f_result, field::mul(loop_stack[i], cc.loop_stack[i * 2]));
-            result_adj = field::add(result_adj, field::mul(loop_stack[i], cc.loop_stack[i * 2 + 1]));
+    /// Evaluates transition constraints over the main segment of the trace followed by
+    /// transition constraints over the auxiliary segment.
+    fn evaluate_constraints<F>(&self, current: &[F], next: &[F], periodic_values: &[F], aux_rand: &[F], result: &mut [F])
+        where F: FieldElement<BaseField = A::BaseElement>
+    {
+        let (main_result, aux_result) = result.split_at_mut(self.air.transition_degrees().len());
+        self.air.evaluate_transition(current, next, periodic_values, main_result);
+        if !aux_result.is_empty() {
+            self.air.evaluate_aux_transition(current, next, periodic_values, aux_rand, aux_result);
         }
-
-        // make sure user stack registers are set to outputs
-        for i in 0..self.outputs.len() {
-            let val = field::sub(user_stack[i], self.outputs[i]);
-            f_result = field::add(f_result, field::mul(val, cc.user_stack[i * 2]));
-            result_adj = field::add(result_adj, field::mul(val, cc.user_stack[i * 2 + 1]));
-        }        
-
-        // raise the degree of adjusted terms and sum all the terms together
-        f_result = field::add(f_result, field::mul(result_adj, xp));
-
-        return (i_result, f_result);
     }
 
-    // HELPER METHODS
-    // -------------------------------------------------------------------------------------------
     fn should_evaluate_to_zero_at(&self, step: usize) -> bool {
-        return (step & (self.extension_factor - 1) == 0) // same as: step % extension_factor == 0
-            && (step != self.domain_size - self.extension_factor);
+        // when the evaluation domain is a coset, it does not contain any steps of the trace
+        return (self.domain_offset == A::BaseElement::ONE)
+            && (step & (self.extension_factor - 1) == 0) // same as: step % extension_factor == 0
+            && (step / self.extension_factor < self.air.trace_info().last_step());
     }
 
-    fn combine_transition_constraints(&self, evaluations: &Vec<u128>, x: u128) -> u128 {
+    fn combine_transition_constraints<F>(&self, evaluations: &[F], x: F) -> E
+        where F: FieldElement<BaseField = A::BaseElement>, E: From<F>
+    {
         let cc = &self.coefficients.transition;
-        let mut result = field::ZERO;
+        let mut result = E::ZERO;
 
         let mut i = 0;
         for (incremental_degree, constraints) in self.t_degree_groups.iter() {
 
             // for each group of constraints with the same degree, separately compute
             // combinations of D(x) and D(x) * x^p
-            let mut result_adj = field::ZERO;
+            let mut result_adj = E::ZERO;
             for &constraint_idx in constraints.iter() {
-                let evaluation = evaluations[constraint_idx];
-                result = field::add(result, field::mul(evaluation, cc[i * 2]));
-                result_adj = field::add(result_adj, field::mul(evaluation, cc[i * 2 + 1]));
+                let evaluation = E::from(evaluations[constraint_idx]);
+                result = result + evaluation * cc[i * 2];
+                result_adj = result_adj + evaluation * cc[i * 2 + 1];
                 i += 1;
             }
 
             // increase the degree of D(x) * x^p
-            let xp = field::exp(x, *incremental_degree);
-            result = field::add(result, field::mul(result_adj, xp));
+            let xp = x.exp(*incremental_degree);
+            result = result + result_adj * E::from(xp);
         }
 
         return result;
     }
 
+    /// Allocates space for individual evaluations of transition constraints; this is used
+    /// only in debug mode to validate degrees of transition constraints.
     #[cfg(debug_assertions)]
-    fn save_transition_evaluations(&self, evaluations: &[u128], step: usize) {
+    pub fn init_transition_evaluations(&mut self) {
+        self.t_evaluations = (0..self.t_constraint_num).map(|_| crate::utils::uninit_vector(self.domain_size)).collect();
+    }
+
+    #[cfg(debug_assertions)]
+    fn save_transition_evaluations(&self, evaluations: &[A::BaseElement], step: usize) {
+        if self.t_evaluations.is_empty() { return; }
         unsafe {
-            let mutable_self = &mut *(self as *const _ as *mut Evaluator);
+            let mutable_self = &mut *(self as *const _ as *mut Evaluator<A, E>);
             for i in 0..evaluations.len() {
                 mutable_self.t_evaluations[i][step] = evaluations[i];
             }
@@ -369,15 +309,13 @@ impl Evaluator {
     }
 
     #[cfg(debug_assertions)]
-    pub fn get_transition_evaluations(&self) -> &Vec<Vec<u128>> {
+    pub fn get_transition_evaluations(&self) -> &Vec<Vec<A::BaseElement>> {
         return &self.t_evaluations;
     }
 
     #[cfg(debug_assertions)]
     pub fn get_transition_degrees(&self) -> Vec<usize> {
-        return [
-            self.decoder.constraint_degrees(), self.stack.constraint_degrees()
-        ].concat();
+        return [self.air.transition_degrees(), self.air.aux_transition_degrees()].concat();
     }
 }
 
@@ -398,7 +336,7 @@ fn group_transition_constraints(degrees: Vec<usize>, trace_length: usize) -> Vec
     let mut result = Vec::new();
     for (degree, constraints) in groups.iter().enumerate() {
         if constraints.len() == 0 { continue; }
-        let constraint_degree = (trace_length - 1) * degree;    
+        let constraint_degree = (trace_length - 1) * degree;
         let incremental_degree = (target_degree - constraint_degree) as u128;
         result.push((incremental_degree, constraints.clone()));
     }
@@ -406,40 +344,97 @@ fn group_transition_constraints(degrees: Vec<usize>, trace_length: usize) -> Vec
     return result;
 }
 
-fn get_boundary_constraint_adjustment_degree(trace_length: usize) -> u128 {
-    let target_degree = get_boundary_constraint_target_degree(trace_length);
+/// Groups assertions by step; groups are sorted by step, and assertions within each group are
+/// kept in the order in which they were made.
+fn group_assertions<E: StarkField>(assertions: &[Assertion<E>], trace_length: usize, last_step: usize) -> Vec<AssertionGroup<E>> {
+    let mut steps: Vec<usize> = assertions.iter().map(|a| a.step).collect();
+    steps.sort_unstable();
+    steps.dedup();
+
+    let trace_root = E::get_root_of_unity(trace_length);
+    let mut result = Vec::with_capacity(steps.len());
+    for step in steps {
+        assert!(step <= last_step, "assertion against step {} is outside of the computation", step);
+        let mut group = AssertionGroup {
+            x           : trace_root.exp(step as u128),
+            registers   : Vec::new(),
+            values      : Vec::new(),
+        };
+        for assertion in assertions.iter().filter(|a| a.step == step) {
+            group.registers.push(assertion.register);
+            group.values.push(assertion.value);
+        }
+        result.push(group);
+    }
+
+    return result;
+}
+
+/// Interpolates periodic columns of a trace of the specified length into polynomials; a column
+/// with cycle length c is interpolated over the domain of c elements.
+pub fn interpolate_periodic_columns<E: StarkField>(columns: Vec<Vec<E>>, trace_length: usize) -> Vec<Vec<E>> {
+    let mut polys = Vec::with_capacity(columns.len());
+    for mut column in columns.into_iter() {
+        let cycle_length = column.len();
+        assert!(cycle_length.is_power_of_two(), "length of a periodic column must be a power of 2");
+        assert!(cycle_length <= trace_length, "length of a periodic column cannot exceed trace length");
+
+        let root = E::get_root_of_unity(cycle_length);
+        let inv_twiddles = fft::get_inv_twiddles(root, cycle_length);
+        polynom::interpolate_fft_twiddles(&mut column, &inv_twiddles, true);
+        polys.push(column);
+    }
+    return polys;
+}
+
+/// Interpolates periodic columns into polynomials, and evaluates these polynomials over the
+/// domain extended by the `extension_factor` and shifted by `domain_offset`. A column with
+/// cycle length c is a polynomial in x^(trace_length / c); so, when the evaluation domain is
+/// shifted by domain_offset, the domain of the column is shifted by the same power of the offset.
+fn extend_periodic_columns<E: StarkField>(columns: Vec<Vec<E>>, trace_length: usize, extension_factor: usize, domain_offset: E)
+    -> (Vec<Vec<E>>, Vec<Vec<E>>)
+{
+    let polys = interpolate_periodic_columns(columns, trace_length);
+    let mut evaluations = Vec::with_capacity(polys.len());
+
+    for column in polys.iter() {
+        let cycle_length = column.len();
+        let domain_size = cycle_length * extension_factor;
+        let domain_root = E::get_root_of_unity(domain_size);
+        let twiddles = fft::get_twiddles(domain_root, domain_size);
+        let column_offset = domain_offset.exp((trace_length / cycle_length) as u128);
+
+        let mut extended_column = vec![E::ZERO; domain_size];
+        extended_column[..cycle_length].copy_from_slice(&column);
+        polynom::shift(&mut extended_column[..cycle_length], column_offset);
+        polynom::eval_fft_twiddles(&mut extended_column, &twiddles, true);
+        evaluations.push(extended_column);
+    }
+
+    return (polys, evaluations);
+}
+
+fn get_boundary_constraint_adjustment_degree(trace_length: usize, blinding_rows: usize) -> u128 {
+    let target_degree = get_boundary_constraint_target_degree(trace_length, blinding_rows);
     let boundary_constraint_degree = trace_length - 1;
     return (target_degree - boundary_constraint_degree) as u128;
 }
 
 /// target degree for boundary constraints is set so that when divided by boundary
 /// constraint divisor (degree 1 polynomial), the degree will be equal to
-/// deg(combination domain) - deg(trace)
-fn get_boundary_constraint_target_degree(trace_length: usize) -> usize {
-    let combination_degree = (MAX_CONSTRAINT_DEGREE - 1) * trace_length;
+/// deg(combination domain) - deg(trace) + blinding_rows
+fn get_boundary_constraint_target_degree(trace_length: usize, blinding_rows: usize) -> usize {
+    let combination_degree = (MAX_CONSTRAINT_DEGREE - 1) * trace_length + blinding_rows;
     let divisor_degree = 1;
     return combination_degree + divisor_degree;
 }
 
-/// target degree for transition constraints is set so when divided transition 
-/// constraint divisor (deg(trace) - 1 polynomial), the degree will be equal to
-/// deg(combination domain) - deg(trace)
+/// target degree for transition constraints is set so when divided transition
+/// constraint divisor (deg(trace) - 1 - blinding_rows polynomial), the degree will be equal
+/// to deg(combination domain) - deg(trace) + blinding_rows; this does not depend on the
+/// number of blinding rows
 fn get_transition_constraint_target_degree(trace_length: usize) -> usize {
     let combination_degree = (MAX_CONSTRAINT_DEGREE - 1) * trace_length;
     let divisor_degree = trace_length - 1;
     return combination_degree + divisor_degree;
 }
-
-fn parse_program_hash(program_hash: &[u8; 32]) -> Vec<u128> {
-    return vec![
-        field::from_bytes(&program_hash[..16]),
-        field::from_bytes(&program_hash[16..]),
-    ];
-}
-
-fn get_boundary_constraint_num(inputs: &[u128], outputs: &[u128]) -> usize {
-    return
-        PROGRAM_DIGEST_SIZE 
-        + inputs.len() + outputs.len()
-        + 1 /* for op_count */;
-}
\ No newline at end of file
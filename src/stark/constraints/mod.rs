@@ -3,10 +3,12 @@ mod decoder;
 mod stack;
 mod constraint_table;
 mod constraint_poly;
+mod processor_air;
+mod checker;
 mod utils;
 
-pub use decoder::{ NUM_STATIC_DECODER_CONSTRAINTS };
-pub use stack::{ NUM_AUX_CONSTRAINTS as NUM_AUX_STACK_CONSTRAINTS };
-pub use evaluator::{ Evaluator as ConstraintEvaluator};
+pub use evaluator::{ Evaluator as ConstraintEvaluator, interpolate_periodic_columns };
 pub use constraint_table::{ ConstraintTable };
-pub use constraint_poly::{ ConstraintPoly };
\ No newline at end of file
+pub use constraint_poly::{ ConstraintPoly };
+pub use processor_air::{ ProcessorAir, ProcessorPublicInputs, SegmentAir, SegmentPublicInputs };
+pub use checker::{ check_trace, ConstraintFailure, CheckError, TraceOp };
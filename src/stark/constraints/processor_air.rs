@@ -0,0 +1,560 @@
+use sp_std::{ convert::TryInto, vec, vec::Vec };
+use alloc::string::String;
+use crate::{
+    math::{ field, f128::BaseElement, FieldElement },
+    processor::{ OpCode, VmState },
+    stark::{ Air, Assertion, FieldExtension, TraceInfo, TraceState, TraceTable },
+    MAX_CONTEXT_DEPTH, MAX_LOOP_DEPTH, MAX_STACK_DEPTH, MIN_TRACE_LENGTH,
+    OP_COUNTER_IDX, SPONGE_RANGE, CF_OP_BITS_RANGE, HD_OP_BITS_RANGE, OP_FLAG_HELPERS_RANGE,
+    PROGRAM_DIGEST_SIZE, HASH_STATE_WIDTH,
+    NUM_RANGE_CHECK_REGISTERS, RANGE_CHECK_TABLE_SIZE, MIN_RANGE_CHECK_TRACE_LENGTH,
+};
+use super::{ decoder::{ Decoder, NUM_PERIODIC_COLUMNS as NUM_DECODER_PERIODIC_COLUMNS }, stack::Stack };
+
+// CONSTANTS
+// ================================================================================================
+
+/// Trace metadata consists of context depth (1 byte), loop depth (1 byte), the number of
+/// executed operations (4 bytes), and a flag indicating whether range checks were performed
+/// (1 byte).
+const TRACE_META_SIZE: usize = 7;
+
+/// Index of the periodic column which holds the range check table; the table follows periodic
+/// columns of the decoder and round constants of the stack. The table contains all byte values
+/// rather than all 16-bit values so that traces with range checks need to be padded only to
+/// 512 steps instead of 2^17 steps; thus, every 16-bit limb is looked up as two bytes.
+const RANGE_CHECK_TABLE_COLUMN: usize = NUM_DECODER_PERIODIC_COLUMNS + 2 * HASH_STATE_WIDTH;
+
+/// Degree of the transition constraint for the running sum of the range check lookup argument.
+const RANGE_CHECK_CONSTRAINT_DEGREE: usize = 4;
+
+/// Degree of the transition constraint which forbids RCACC operation when range checks were
+/// not performed.
+const NO_RANGE_CHECK_CONSTRAINT_DEGREE: usize = 2;
+
+// TYPES AND INTERFACES
+// ================================================================================================
+
+/// AIR of Distaff VM: the execution trace consists of decoder registers followed by user stack
+/// registers and the range check register; transition constraints are defined by the decoder
+/// and by the stack.
+///
+/// If range checks were performed, bytes looked up by RCACC operations are checked against the
+/// table of all byte values with a logarithmic derivative lookup argument: the auxiliary segment
+/// of the trace holds the running sum s, and for a random element α, every step of the trace
+/// adds 1 / (α - a) + 1 / (α - b) for looked up bytes a and b, and subtracts m / (α - t) for
+/// table value t looked up m times; the sum must start and end at 0. Otherwise, RCACC
+/// operation cannot be executed.
+pub struct ProcessorAir {
+    trace_info          : TraceInfo,
+    decoder             : Decoder,
+    stack               : Stack,
+    ctx_depth           : usize,
+    loop_depth          : usize,
+    stack_depth         : usize,
+    range_checks        : bool,
+    boundary            : Boundary,
+    constraint_degrees  : Vec<usize>,
+    aux_degrees         : Vec<usize>,
+}
+
+/// AIR of a single segment of a program execution: transition constraints are the same as for
+/// the entire execution, but the first and the last steps of the trace are asserted against
+/// the states in which the VM enters and exits the segment.
+pub struct SegmentAir(ProcessorAir);
+
+/// Public inputs of a program execution: the hash of the program, the initial state of the
+/// user stack, and the state of the top of the user stack after the program is executed.
+pub struct ProcessorPublicInputs {
+    pub program_hash    : [u8; 32],
+    pub inputs          : Vec<u128>,
+    pub outputs         : Vec<u128>,
+}
+
+/// Public inputs of an execution segment: states of the VM at the first and the last steps of
+/// the segment.
+pub struct SegmentPublicInputs {
+    pub entry           : VmState,
+    pub exit            : VmState,
+}
+
+/// Values against which the first and the last steps of the trace are asserted.
+enum Boundary {
+    Execution {
+        op_count        : u128,
+        program_hash    : [u128; PROGRAM_DIGEST_SIZE],
+        inputs          : Vec<u128>,
+        outputs         : Vec<u128>,
+    },
+    Segment {
+        entry           : VmState,
+        exit            : VmState,
+    },
+}
+
+// PROCESSOR AIR IMPLEMENTATION
+// ================================================================================================
+impl ProcessorAir {
+
+    /// Encodes layout of the execution trace, the number of executed operations, and whether
+    /// range checks were performed into trace metadata.
+    pub fn build_trace_meta(ctx_depth: usize, loop_depth: usize, op_count: u128, range_checks: bool) -> Vec<u8> {
+        let mut meta = Vec::with_capacity(TRACE_META_SIZE);
+        meta.push(ctx_depth as u8);
+        meta.push(loop_depth as u8);
+        meta.extend_from_slice(&(op_count as u32).to_le_bytes());
+        meta.push(range_checks as u8);
+        return meta;
+    }
+
+    /// Decodes context depth, loop depth, the number of executed operations, and whether range
+    /// checks were performed from trace metadata.
+    pub fn parse_trace_meta(meta: &[u8]) -> Result<(usize, usize, u128, bool), String> {
+        if meta.len() != TRACE_META_SIZE {
+            return Err(format!("trace metadata must consist of {} bytes, but was {} bytes",
+                TRACE_META_SIZE, meta.len()));
+        }
+        let op_count = u32::from_le_bytes(meta[2..6].try_into().unwrap());
+        let range_checks = match meta[6] {
+            0 => false,
+            1 => true,
+            _ => return Err(String::from("range check flag in trace metadata must be either 0 or 1")),
+        };
+        return Ok((meta[0] as usize, meta[1] as usize, op_count as u128, range_checks));
+    }
+
+    /// Returns periodic columns of the decoder followed by periodic columns of the stack.
+    pub fn build_periodic_columns() -> Vec<Vec<u128>> {
+        let mut columns = Decoder::get_periodic_columns();
+        columns.append(&mut Stack::get_periodic_columns());
+        return columns;
+    }
+
+    /// Returns the range check table, which is a periodic column containing all byte values.
+    pub fn build_range_check_table() -> Vec<u128> {
+        return (0..RANGE_CHECK_TABLE_SIZE as u128).collect();
+    }
+
+    /// Returns an AIR for an execution trace of the specified shape with the specified values
+    /// of the first and the last steps; returns an error if the trace layout is not valid.
+    fn with_boundary(trace_info: &TraceInfo, boundary: Boundary) -> Result<ProcessorAir, String> {
+        let (ctx_depth, loop_depth, _, range_checks) = ProcessorAir::parse_trace_meta(&trace_info.meta)?;
+
+        // validate trace layout
+        if trace_info.length < MIN_TRACE_LENGTH {
+            return Err(format!("execution trace must consist of at least {} steps", MIN_TRACE_LENGTH));
+        }
+        if range_checks && trace_info.length < MIN_RANGE_CHECK_TRACE_LENGTH {
+            return Err(format!("execution trace with range checks must consist of at least {} steps",
+                MIN_RANGE_CHECK_TRACE_LENGTH));
+        }
+        if ctx_depth > MAX_CONTEXT_DEPTH {
+            return Err(format!("context depth cannot be greater than {}", MAX_CONTEXT_DEPTH));
+        }
+        if loop_depth > MAX_LOOP_DEPTH {
+            return Err(format!("loop depth cannot be greater than {}", MAX_LOOP_DEPTH));
+        }
+        let decoder_width = TraceState::compute_decoder_width(ctx_depth, loop_depth);
+        let stack_depth = trace_info.width.saturating_sub(decoder_width + NUM_RANGE_CHECK_REGISTERS);
+        if stack_depth == 0 || stack_depth > MAX_STACK_DEPTH {
+            return Err(format!("user stack must consist of between 1 and {} registers", MAX_STACK_DEPTH));
+        }
+
+        // when range checks were not performed, an extra constraint forbids RCACC operation
+        let decoder = Decoder::new(ctx_depth, loop_depth);
+        let stack = Stack::new(stack_depth);
+        let mut constraint_degrees = [decoder.constraint_degrees(), stack.constraint_degrees()].concat();
+        let aux_degrees = if range_checks {
+            vec![RANGE_CHECK_CONSTRAINT_DEGREE]
+        }
+        else {
+            constraint_degrees.push(NO_RANGE_CHECK_CONSTRAINT_DEGREE);
+            Vec::new()
+        };
+
+        return Ok(ProcessorAir {
+            trace_info          : trace_info.clone(),
+            decoder             : decoder,
+            stack               : stack,
+            ctx_depth           : ctx_depth,
+            loop_depth          : loop_depth,
+            stack_depth         : stack_depth,
+            range_checks        : range_checks,
+            boundary            : boundary,
+            constraint_degrees  : constraint_degrees,
+            aux_degrees         : aux_degrees,
+        });
+    }
+
+    /// Returns the index of the first user stack register.
+    fn stack_start(&self) -> usize {
+        return self.range_check_register() - self.stack_depth;
+    }
+
+    /// Returns the index of the register which holds multiplicities of range check lookups.
+    fn range_check_register(&self) -> usize {
+        return self.trace_info.width - NUM_RANGE_CHECK_REGISTERS;
+    }
+
+    // RANGE CHECK HELPERS
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns the flag of RCACC operation at the specified trace state; the flag is computed
+    /// from op flag helpers in the same way as flags of all other low-degree operations.
+    fn get_rcacc_flag(state: &TraceState) -> u128 {
+        return state.ld_op_flags()[OpCode::RcAcc.ld_index()];
+    }
+
+    /// Adds range check assertions: the running sum of the lookup argument must be 0 at the
+    /// first and at the last steps of the computation.
+    fn add_range_check_assertions(&self, result: &mut Vec<Assertion<BaseElement>>) {
+        if self.range_checks {
+            let register = self.trace_info.width;
+            result.push(Assertion::new(register, 0, BaseElement::ZERO));
+            result.push(Assertion::new(register, self.trace_info.last_step(), BaseElement::ZERO));
+        }
+    }
+
+    // ASSERTION HELPERS
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns assertions for a trace of an entire program execution.
+    fn get_execution_assertions(&self, op_count: u128, program_hash: &[u128], inputs: &[u128], outputs: &[u128])
+        -> Vec<Assertion<BaseElement>>
+    {
+        let last_step = self.trace_info.last_step();
+        let stack_start = self.stack_start();
+        let mut result = Vec::new();
+
+        // 1 ----- assertions for the first step ---------------------------------------------------
+
+        // op_counter, operation sponge, op bits, context stack, and loop stack must be set to 0s;
+        // op flag helpers are fully defined by op bits, and so, they are not asserted
+        for i in (0..OP_FLAG_HELPERS_RANGE.start).chain(OP_FLAG_HELPERS_RANGE.end..stack_start) {
+            result.push(Assertion::new(i, 0, BaseElement::ZERO));
+        }
+
+        // stack registers must be set to inputs
+        for (i, &value) in inputs.iter().take(self.stack_depth).enumerate() {
+            result.push(Assertion::new(stack_start + i, 0, BaseElement::new(value)));
+        }
+
+        // 2 ----- assertions for the last step ----------------------------------------------------
+
+        // op_counter must be set to the number of executed operations
+        result.push(Assertion::new(OP_COUNTER_IDX, last_step, BaseElement::new(op_count)));
+
+        // operation sponge must contain program hash
+        for (i, &value) in program_hash.iter().enumerate() {
+            result.push(Assertion::new(SPONGE_RANGE.start + i, last_step, BaseElement::new(value)));
+        }
+
+        // op bits must be set to VOID (111) and NOOP (11111, 11)
+        for i in CF_OP_BITS_RANGE.start..HD_OP_BITS_RANGE.end {
+            result.push(Assertion::new(i, last_step, BaseElement::ONE));
+        }
+
+        // context and loop stacks must be empty
+        for i in OP_FLAG_HELPERS_RANGE.end..stack_start {
+            result.push(Assertion::new(i, last_step, BaseElement::ZERO));
+        }
+
+        // stack registers must be set to outputs
+        for (i, &value) in outputs.iter().take(self.stack_depth).enumerate() {
+            result.push(Assertion::new(stack_start + i, last_step, BaseElement::new(value)));
+        }
+
+        return result;
+    }
+
+    /// Returns assertions for a trace of an execution segment; op bits at the first step are
+    /// not asserted because the segment may start with any operation.
+    fn get_segment_assertions(&self, entry: &VmState, exit: &VmState) -> Vec<Assertion<BaseElement>> {
+        let mut result = Vec::new();
+        self.assert_state(&mut result, 0, entry);
+        self.assert_state(&mut result, self.trace_info.last_step(), exit);
+
+        // op bits at the last step must be set to VOID (111) and NOOP (11111, 11)
+        for i in CF_OP_BITS_RANGE.start..HD_OP_BITS_RANGE.end {
+            result.push(Assertion::new(i, self.trace_info.last_step(), BaseElement::ONE));
+        }
+
+        return result;
+    }
+
+    /// Asserts that op_counter, operation sponge, and all stacks at the specified `step` are
+    /// set to the values of the `state`.
+    fn assert_state(&self, result: &mut Vec<Assertion<BaseElement>>, step: usize, state: &VmState) {
+        result.push(Assertion::new(OP_COUNTER_IDX, step, BaseElement::new(state.op_counter)));
+        for (i, &value) in state.sponge.iter().enumerate() {
+            result.push(Assertion::new(SPONGE_RANGE.start + i, step, BaseElement::new(value)));
+        }
+
+        let ctx_start = OP_FLAG_HELPERS_RANGE.end;
+        let loop_start = ctx_start + self.ctx_depth;
+        let stack_start = self.stack_start();
+        let stacks = state.ctx_stack.iter().take(self.ctx_depth).enumerate().map(|(i, v)| (ctx_start + i, v))
+            .chain(state.loop_stack.iter().take(self.loop_depth).enumerate().map(|(i, v)| (loop_start + i, v)))
+            .chain(state.user_stack.iter().take(self.stack_depth).enumerate().map(|(i, v)| (stack_start + i, v)));
+        for (register, &value) in stacks {
+            result.push(Assertion::new(register, step, BaseElement::new(value)));
+        }
+    }
+}
+
+impl Air for ProcessorAir {
+    type BaseElement = BaseElement;
+    type PublicInputs = ProcessorPublicInputs;
+
+    fn new(trace_info: &TraceInfo, pub_inputs: &ProcessorPublicInputs) -> Result<ProcessorAir, String> {
+        let (_, _, op_count, _) = ProcessorAir::parse_trace_meta(&trace_info.meta)?;
+
+        // make sure the number of executed operations was sufficient
+        if op_count < MIN_TRACE_LENGTH as u128 {
+            return Err(String::from("Verification of minimum operation count failed"));
+        }
+
+        let boundary = Boundary::Execution {
+            op_count        : op_count,
+            program_hash    : [
+                field::from_bytes(&pub_inputs.program_hash[..16]),
+                field::from_bytes(&pub_inputs.program_hash[16..]),
+            ],
+            inputs          : pub_inputs.inputs.clone(),
+            outputs         : pub_inputs.outputs.clone(),
+        };
+        let air = ProcessorAir::with_boundary(trace_info, boundary)?;
+
+        // stack registers beyond stack depth are implicitly set to 0s
+        let stack_depth = air.stack_depth;
+        for &value in pub_inputs.inputs.iter().skip(stack_depth).chain(pub_inputs.outputs.iter().skip(stack_depth)) {
+            if value != field::ZERO {
+                return Err(String::from("inputs and outputs must fit into the user stack"));
+            }
+        }
+
+        return Ok(air);
+    }
+
+    /// Decoder and stack constraints are defined only over the base field, and so the VM can
+    /// be proved only without field extensions.
+    fn supports_extension(extension: FieldExtension) -> bool {
+        return extension == FieldExtension::None;
+    }
+
+    fn trace_info(&self) -> &TraceInfo {
+        return &self.trace_info;
+    }
+
+    fn transition_degrees(&self) -> &[usize] {
+        return &self.constraint_degrees;
+    }
+
+    fn evaluate_transition<E: FieldElement<BaseField = BaseElement>>(&self, current: &[E], next: &[E], periodic_values: &[E], result: &mut [E]) {
+        // decoder and stack constraints are defined only over the base field; the prover and the
+        // verifier reject field extensions (see supports_extension()), and so E is always the
+        // base field here
+        debug_assert!(E::EXTENSION_DEGREE == 1, "constraints of Distaff VM cannot be evaluated over a field extension");
+        let current = BaseElement::as_raw_slice(E::as_base_elements(current));
+        let next = BaseElement::as_raw_slice(E::as_base_elements(next));
+        let periodic_values = BaseElement::as_raw_slice(E::as_base_elements(periodic_values));
+
+        let current = TraceState::from_vec(self.ctx_depth, self.loop_depth, self.stack_depth, current);
+        let next = TraceState::from_vec(self.ctx_depth, self.loop_depth, self.stack_depth, next);
+
+        let mut evaluations = vec![field::ZERO; result.len()];
+        let (d_periodic_values, s_periodic_values) = periodic_values.split_at(NUM_DECODER_PERIODIC_COLUMNS);
+        let (d_result, s_result) = evaluations.split_at_mut(self.decoder.constraint_count());
+        let (s_result, rc_result) = s_result.split_at_mut(self.stack.constraint_degrees().len());
+        self.decoder.evaluate(&current, &next, d_periodic_values, d_result);
+        self.stack.evaluate(&current, &next, s_periodic_values, s_result);
+
+        // RCACC operation can be executed only if range checks are enforced
+        if !self.range_checks {
+            rc_result[0] = ProcessorAir::get_rcacc_flag(&current);
+        }
+
+        for (result, &value) in result.iter_mut().zip(evaluations.iter()) {
+            *result = E::from(BaseElement::new(value));
+        }
+    }
+
+    fn get_assertions(&self) -> Vec<Assertion<BaseElement>> {
+        let mut result = match &self.boundary {
+            Boundary::Execution { op_count, program_hash, inputs, outputs } => {
+                self.get_execution_assertions(*op_count, program_hash, inputs, outputs)
+            },
+            Boundary::Segment { entry, exit } => self.get_segment_assertions(entry, exit),
+        };
+        self.add_range_check_assertions(&mut result);
+        return result;
+    }
+
+    fn get_periodic_columns(&self) -> Vec<Vec<BaseElement>> {
+        let mut columns = ProcessorAir::build_periodic_columns();
+        if self.range_checks {
+            columns.push(ProcessorAir::build_range_check_table());
+        }
+        return columns.into_iter().map(BaseElement::from_raw_vec).collect();
+    }
+
+    fn aux_width(&self) -> usize {
+        return if self.range_checks { 1 } else { 0 };
+    }
+
+    fn aux_rand_count(&self) -> usize {
+        return if self.range_checks { 1 } else { 0 };
+    }
+
+    /// Builds the running sum of the range check lookup argument; the sum is computed over
+    /// the steps of the computation, and is set to random values over blinding rows.
+    fn build_aux_registers(&self, trace: &TraceTable<BaseElement>, rand: &[BaseElement]) -> Vec<Vec<BaseElement>> {
+        if !self.range_checks {
+            return Vec::new();
+        }
+
+        // read registers needed to compute the running sum from the trace
+        let stack_start = self.stack_start();
+        let stack = [trace.get_unextended_register(stack_start), trace.get_unextended_register(stack_start + 1)];
+        let multiplicities = trace.get_unextended_register(self.range_check_register());
+        let helpers: Vec<Vec<BaseElement>> = OP_FLAG_HELPERS_RANGE
+            .map(|i| trace.get_unextended_register(i))
+            .collect();
+
+        let alpha = rand[0];
+        let last_step = self.trace_info.last_step();
+        let mut state = TraceState::new(self.ctx_depth, self.loop_depth, self.stack_depth);
+        let mut helper_values = vec![field::ZERO; helpers.len()];
+        let mut result = Vec::with_capacity(self.trace_info.length);
+        result.push(BaseElement::ZERO);
+        for step in 0..last_step {
+            // compute RCACC flag at the current step from op flag helpers
+            for (value, helper) in helper_values.iter_mut().zip(helpers.iter()) {
+                *value = helper[step].into();
+            }
+            state.set_op_helpers(&helper_values);
+            let flag = BaseElement::new(ProcessorAir::get_rcacc_flag(&state));
+
+            // add lookups of the next step, and subtract lookups of the table at this step
+            let table_value = BaseElement::new((step % RANGE_CHECK_TABLE_SIZE) as u128);
+            let lookups = (alpha - stack[0][step + 1]).inv() + (alpha - stack[1][step + 1]).inv();
+            let table = multiplicities[step] / (alpha - table_value);
+            result.push(result[step] + flag * lookups - table);
+        }
+
+        // blinding rows are filled with random values
+        result.append(&mut BaseElement::rand_vector(self.trace_info.length - last_step - 1));
+        return vec![result];
+    }
+
+    fn aux_transition_degrees(&self) -> &[usize] {
+        return &self.aux_degrees;
+    }
+
+    /// Enforces the running sum s of the range check lookup argument:
+    /// (s' - s)(α - a)(α - b)(α - t) = f [(α - b)(α - t) + (α - a)(α - t)] - m (α - a)(α - b)
+    /// where f is the flag of RCACC operation, a and b are bytes looked up in the next state,
+    /// t is the value of the range check table, and m is the multiplicity of t.
+    fn evaluate_aux_transition<E: FieldElement<BaseField = BaseElement>>(&self,
+        current         : &[E],
+        next            : &[E],
+        periodic_values : &[E],
+        rand            : &[E],
+        result          : &mut [E])
+    {
+        debug_assert!(E::EXTENSION_DEGREE == 1, "constraints of Distaff VM cannot be evaluated over a field extension");
+        let raw_current = BaseElement::as_raw_slice(E::as_base_elements(current));
+        let state = TraceState::from_vec(self.ctx_depth, self.loop_depth, self.stack_depth, raw_current);
+        let flag = E::from(BaseElement::new(ProcessorAir::get_rcacc_flag(&state)));
+
+        let stack_start = self.stack_start();
+        let sum_register = self.trace_info.width;
+        let alpha = rand[0];
+        let a = alpha - next[stack_start];
+        let b = alpha - next[stack_start + 1];
+        let t = alpha - periodic_values[RANGE_CHECK_TABLE_COLUMN];
+        let m = current[self.range_check_register()];
+
+        let delta = next[sum_register] - current[sum_register];
+        result[0] = delta * a * b * t - flag * (b * t + a * t) + m * a * b;
+    }
+}
+
+// SEGMENT AIR IMPLEMENTATION
+// ================================================================================================
+impl Air for SegmentAir {
+    type BaseElement = BaseElement;
+    type PublicInputs = SegmentPublicInputs;
+
+    fn new(trace_info: &TraceInfo, pub_inputs: &SegmentPublicInputs) -> Result<SegmentAir, String> {
+        let boundary = Boundary::Segment {
+            entry   : pub_inputs.entry.clone(),
+            exit    : pub_inputs.exit.clone(),
+        };
+        let air = ProcessorAir::with_boundary(trace_info, boundary)?;
+
+        // stack registers beyond the depths of the trace are implicitly set to 0s
+        for state in [&pub_inputs.entry, &pub_inputs.exit].iter() {
+            let overflow = state.ctx_stack.iter().skip(air.ctx_depth)
+                .chain(state.loop_stack.iter().skip(air.loop_depth))
+                .chain(state.user_stack.iter().skip(air.stack_depth));
+            for &value in overflow {
+                if value != field::ZERO {
+                    return Err(String::from("segment states must fit into the stacks of the trace"));
+                }
+            }
+        }
+
+        return Ok(SegmentAir(air));
+    }
+
+    fn supports_extension(extension: FieldExtension) -> bool {
+        return ProcessorAir::supports_extension(extension);
+    }
+
+    fn trace_info(&self) -> &TraceInfo {
+        return self.0.trace_info();
+    }
+
+    fn transition_degrees(&self) -> &[usize] {
+        return self.0.transition_degrees();
+    }
+
+    fn evaluate_transition<E: FieldElement<BaseField = BaseElement>>(&self, current: &[E], next: &[E], periodic_values: &[E], result: &mut [E]) {
+        self.0.evaluate_transition(current, next, periodic_values, result);
+    }
+
+    fn get_assertions(&self) -> Vec<Assertion<BaseElement>> {
+        return self.0.get_assertions();
+    }
+
+    fn get_periodic_columns(&self) -> Vec<Vec<BaseElement>> {
+        return self.0.get_periodic_columns();
+    }
+
+    fn aux_width(&self) -> usize {
+        return self.0.aux_width();
+    }
+
+    fn aux_rand_count(&self) -> usize {
+        return self.0.aux_rand_count();
+    }
+
+    fn build_aux_registers(&self, trace: &TraceTable<BaseElement>, rand: &[BaseElement]) -> Vec<Vec<BaseElement>> {
+        return self.0.build_aux_registers(trace, rand);
+    }
+
+    fn aux_transition_degrees(&self) -> &[usize] {
+        return self.0.aux_transition_degrees();
+    }
+
+    fn evaluate_aux_transition<E: FieldElement<BaseField = BaseElement>>(&self,
+        current         : &[E],
+        next            : &[E],
+        periodic_values : &[E],
+        rand            : &[E],
+        result          : &mut [E])
+    {
+        self.0.evaluate_aux_transition(current, next, periodic_values, rand, result);
+    }
+}
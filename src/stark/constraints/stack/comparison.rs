@@ -131,4 +131,26 @@ pub fn enforce_binacc(result: &mut [u128], old_stack: &[u128], new_stack: &[u128
 
     // registers beyond 2nd register remained the same
     enforce_stack_copy(result, old_stack, new_stack, 4, op_flag);
+}
+
+/// Evaluates constraints for RCACC operation; the limb bytes are not constrained here because
+/// they are checked against the range check table by the lookup argument.
+pub fn enforce_rcacc(result: &mut [u128], old_stack: &[u128], new_stack: &[u128], op_flag: u128)
+{
+    // layout of first 4 registers:
+    // [high byte, low byte, power of 2^16, accumulated value]
+    // limb bytes are located in the next state (not current state)
+    let limb = field::add(field::mul(new_stack[0], 256), new_stack[1]);
+
+    // power of 2^16 register was updated correctly
+    let power_of_two = old_stack[2];
+    let power_of_two_constraint = are_equal(new_stack[2], field::mul(power_of_two, 65536));
+    result.agg_constraint(2, op_flag, power_of_two_constraint);
+
+    // accumulator was updated correctly
+    let acc = field::add(old_stack[3], field::mul(limb, power_of_two));
+    result.agg_constraint(3, op_flag, are_equal(new_stack[3], acc));
+
+    // registers beyond 4th register remained the same
+    enforce_stack_copy(result, old_stack, new_stack, 4, op_flag);
 }
\ No newline at end of file
@@ -1,12 +1,12 @@
 use crate::{
-    math::{ field, polynom },
+    math::field,
     processor::OpCode,
     stark::TraceState,
     utils::hasher::ARK,
-    BASE_CYCLE_LENGTH, HASH_STATE_WIDTH
+    HASH_STATE_WIDTH,
 };
 use super::utils::{
-    are_equal, is_zero, is_binary, binary_not, extend_constants, EvaluationResult,
+    are_equal, is_zero, is_binary, binary_not, EvaluationResult,
     enforce_stack_copy, enforce_left_shift, enforce_right_shift,
 };
 
@@ -26,7 +26,7 @@ use manipulation::{
 };
 
 mod comparison;
-use comparison::{ enforce_assert, enforce_asserteq, enforce_eq, enforce_cmp, enforce_binacc };
+use comparison::{ enforce_assert, enforce_asserteq, enforce_eq, enforce_cmp, enforce_binacc, enforce_rcacc };
 
 mod conditional;
 use conditional::{ enforce_choose, enforce_choose2, enforce_cswap2 };
@@ -39,16 +39,12 @@ use sp_std::{vec, vec::Vec};
 // CONSTANTS
 // ================================================================================================
 pub const NUM_AUX_CONSTRAINTS: usize = 2;
-const AUX_CONSTRAINT_DEGREES: [usize; NUM_AUX_CONSTRAINTS] = [7, 7];
-const STACK_TRANSITION_DEGREE: usize = 7; // degree for all stack register transition constraints
+const AUX_CONSTRAINT_DEGREES: [usize; NUM_AUX_CONSTRAINTS] = [4, 4];
+const STACK_TRANSITION_DEGREE: usize = 4; // degree for all stack register transition constraints
 
 // TYPES AND INTERFACES
 // ================================================================================================
 pub struct Stack {
-    trace_length        : usize,
-    cycle_length        : usize,
-    ark_values          : Vec<[u128; 2 * HASH_STATE_WIDTH]>,
-    ark_polys           : Vec<Vec<u128>>,
     constraint_degrees  : Vec<usize>,
 }
 
@@ -56,22 +52,13 @@ pub struct Stack {
 // ================================================================================================
 impl Stack {
 
-    pub fn new(trace_length: usize, extension_factor: usize, stack_depth: usize) -> Stack 
+    pub fn new(stack_depth: usize) -> Stack 
     {
         // build an array of constraint degrees for the stack
         let mut degrees = Vec::from(&AUX_CONSTRAINT_DEGREES[..]);
         degrees.resize(stack_depth + NUM_AUX_CONSTRAINTS, STACK_TRANSITION_DEGREE);
 
-        // determine extended cycle length
-        let cycle_length = BASE_CYCLE_LENGTH * extension_factor;
-
-        // extend rounds constants by the specified extension factor
-        let (ark_polys, ark_evaluations) = extend_constants(&ARK, extension_factor);
-        let ark_values = transpose_ark_constants(ark_evaluations, cycle_length);
-
         return Stack {
-            trace_length, cycle_length,
-            ark_values, ark_polys,
             constraint_degrees: degrees,
         };
     }
@@ -80,37 +67,22 @@ impl Stack {
         return &self.constraint_degrees;
     }
 
+    /// Returns round constants of the hash function; these are periodic columns with the
+    /// cycle of `BASE_CYCLE_LENGTH` steps.
+    pub fn get_periodic_columns() -> Vec<Vec<u128>> {
+        return ARK.iter().map(|column| column.to_vec()).collect();
+    }
+
     // EVALUATOR FUNCTIONS
     // --------------------------------------------------------------------------------------------
 
-    /// Evaluates stack transition constraints at the specified step of the evaluation domain and
-    /// saves the evaluations into `result`.
-    pub fn evaluate(&self, current: &TraceState, next: &TraceState, step: usize, result: &mut [u128])
+    /// Evaluates stack transition constraints and saves the evaluations into `result`;
+    /// `periodic_values` must contain values of the columns returned by `get_periodic_columns()`
+    /// at the current step.
+    pub fn evaluate(&self, current: &TraceState, next: &TraceState, periodic_values: &[u128], result: &mut [u128])
     {
-        // determine round constants at the specified step
-        let ark = self.ark_values[step % self.cycle_length];
-
         // evaluate transition constraints for the stack
-        enforce_constraints(current, next, &ark, result);
-    }
-
-    /// Evaluates stack transition constraints at the specified x coordinate and saves the
-    /// evaluations into `result`. Unlike the function above, this function can evaluate constraints
-    /// at any out-of-domain point, but it is much slower than the previous function.
-    pub fn evaluate_at(&self, current: &TraceState, next: &TraceState, x: u128, result: &mut [u128])
-    {
-        // map x to the corresponding coordinate in constant cycles
-        let num_cycles = (self.trace_length / BASE_CYCLE_LENGTH) as u128;
-        let x = field::exp(x, num_cycles);
-
-        // determine round constants at the specified x coordinate
-        let mut ark = [field::ZERO; 2 * HASH_STATE_WIDTH];
-        for i in 0..ark.len() {
-            ark[i] = polynom::eval(&self.ark_polys[i], x);
-        }
-
-        // evaluate transition constraints for the stack
-        enforce_constraints(current, next, &ark, result);
+        enforce_constraints(current, next, periodic_values, result);
     }
 }
 
@@ -169,12 +141,16 @@ fn enforce_constraints(current: &TraceState, next: &TraceState, ark: &[u128], re
     // comparison operations
     enforce_eq      (&mut evaluations, aux, old_stack, new_stack, ld_flags[OpCode::Eq.ld_index()]);
     enforce_binacc  (&mut evaluations,      old_stack, new_stack, ld_flags[OpCode::BinAcc.ld_index()]);
+    enforce_rcacc   (&mut evaluations,      old_stack, new_stack, ld_flags[OpCode::RcAcc.ld_index()]);
 
     // conditional selection operations
     enforce_choose  (&mut evaluations, aux, old_stack, new_stack, ld_flags[OpCode::Choose.ld_index()]);
     enforce_choose2 (&mut evaluations, aux, old_stack, new_stack, ld_flags[OpCode::Choose2.ld_index()]);
     enforce_cswap2  (&mut evaluations, aux, old_stack, new_stack, ld_flags[OpCode::CSwap2.ld_index()]);
 
+    // DYNCALL marks the start and the end of a dynamic call and does not change the stack
+    enforce_stack_copy(&mut evaluations, old_stack, new_stack, 0, ld_flags[OpCode::DynCall.ld_index()]);
+
     // 2 ----- enforce constraints for high-degree operations --------------------------------------
     let hd_flags = current.hd_op_flags();
 
@@ -185,25 +161,12 @@ fn enforce_constraints(current: &TraceState, next: &TraceState, ark: &[u128], re
     // 3 ----- enforce constraints for composite operations ---------------------------------------
 
     // BEGIN and NOOP have "composite" opcodes where all 7 opcode bits are set to either 1s or 0s;
-    // thus, the flags for these operations are computed separately by multiplying all opcodes;
-    // this results in flag degree of 7 for each operation, but since both operations enforce the
-    // same constraints (the stack doesn't change), higher degree terms cancel out, and we
-    // end up with overall constraint degree of (6 + 1 = 7) for both operations.
+    // thus, the flags for these operations are computed separately by multiplying ld and hd
+    // flags together; this results in flag degree of 3 for each operation, and overall
+    // constraint degree of (3 + 1 = 4) for both operations.
     enforce_stack_copy(&mut evaluations, old_stack, new_stack, 0, current.begin_flag());
     enforce_stack_copy(&mut evaluations, old_stack, new_stack, 0, current.noop_flag());
     
     // 4 ----- copy evaluations into the result ---------------------------------------------------
     result.copy_from_slice(&evaluations[..result.len()]);
 }
-
-fn transpose_ark_constants(constants: Vec<Vec<u128>>, cycle_length: usize) -> Vec<[u128; 2 * HASH_STATE_WIDTH]>
-{
-    let mut values = Vec::new();
-    for i in 0..cycle_length {
-        values.push([field::ZERO; 2 * HASH_STATE_WIDTH]);
-        for j in 0..(2 * HASH_STATE_WIDTH) {
-            values[i][j] = constants[j][i];
-        }
-    }
-    return values;
-}
\ No newline at end of file
@@ -1,4 +1,5 @@
 use serde::{ Serialize, Deserialize };
+use crate::math::{ FieldElement, f128::BaseElement };
 use sp_std::vec::Vec;
 
 // RE-EXPORTS
@@ -11,21 +12,21 @@ pub use prover::{ reduce, build_proof };
 mod verifier;
 pub use verifier::{ verify };
 
-const MAX_REMAINDER_LENGTH: usize = 256;
-
 // TYPES AND INTERFACES
 // ================================================================================================
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct FriProof {
-    pub layers      : Vec<FriLayer>,
-    pub rem_root    : [u8; 32],
-    pub rem_values  : Vec<u128>,
+#[serde(bound = "E: FieldElement")]
+pub struct FriProof<E: FieldElement = BaseElement> {
+    pub layers      : Vec<FriLayer<E>>,
+    pub remainder   : Vec<E>,       // coefficients of the remainder polynomial, in base field
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct FriLayer {
+#[serde(bound = "E: FieldElement")]
+pub struct FriLayer<E: FieldElement = BaseElement> {
     pub root    : [u8; 32],
-    pub values  : Vec<[u128; 4]>,
+    pub values  : Vec<E>,           // queried rows of the layer, folding_factor values per row;
+                                    // extension field values are flattened into base field values
     pub nodes   : Vec<Vec<[u8; 32]>>,
     pub depth   : u8,
 }
@@ -34,73 +35,145 @@ pub struct FriLayer {
 // ================================================================================================
 #[cfg(test)]
 mod tests {
-    use crate::math::{ field, polynom };
+    use crate::math::{ f128::BaseElement, f64, fft, polynom, QuadExtension, FieldElement, StarkField };
     use crate::stark::{ ProofOptions, utils::compute_query_positions };
 
     #[test]
     fn prove_verify() {
         let degree: usize = 63;
         let domain_size: usize = 512;
-        let root = field::get_root_of_unity(domain_size);
-        let domain = field::get_power_series(root, domain_size);
+        let root = BaseElement::get_root_of_unity(domain_size);
+        let domain = BaseElement::get_power_series(root, domain_size);
         let options = ProofOptions::default();
 
         let evaluations = build_random_poly_evaluations(domain_size, degree);
 
         // generate proof
-        let (fri_trees, fri_values) = super::reduce(&evaluations, &domain, &options);
+        let (fri_trees, fri_values, remainder) = super::reduce(&evaluations, &domain, &options);
         let positions = compute_query_positions(fri_trees[fri_trees.len() - 1].root(), domain_size, &options);
-        let proof = super::build_proof(fri_trees, fri_values, &positions);
+        let proof = super::build_proof(fri_trees, fri_values, remainder, &positions, &options);
 
         // verify proof
-        let sampled_evaluations = positions.iter().map(|&i| evaluations[i]).collect::<Vec<u128>>();
-        let result = super::verify(&proof, &sampled_evaluations, &positions, degree, &options);
+        let sampled_evaluations = positions.iter().map(|&i| evaluations[i]).collect::<Vec<BaseElement>>();
+        let result = super::verify(&proof, &sampled_evaluations, &positions, domain_size, degree, &options);
         assert_eq!(Ok(true), result);
     }
 
+    #[test]
+    fn prove_verify_folding_factors() {
+        let degree: usize = 63;
+        let domain_size: usize = 1024;
+        let root = BaseElement::get_root_of_unity(domain_size);
+        let domain = BaseElement::get_power_series(root, domain_size);
+        let evaluations = build_random_poly_evaluations(domain_size, degree);
+
+        for &(folding_factor, max_remainder_size) in [(2, 64), (4, 256), (8, 128), (16, 256), (16, 1024)].iter() {
+            let options = ProofOptions::default().with_fri(folding_factor, max_remainder_size);
+
+            // generate proof
+            let (fri_trees, fri_values, remainder) = super::reduce(&evaluations, &domain, &options);
+            let remainder_domain_size = domain_size / usize::pow(folding_factor, fri_trees.len() as u32);
+            assert!(remainder_domain_size <= max_remainder_size);
+            assert!(remainder.len() <= remainder_domain_size);
+
+            let positions = compute_query_positions(&[folding_factor as u8; 32], domain_size, &options);
+            let proof = super::build_proof(fri_trees, fri_values, remainder, &positions, &options);
+
+            // verify proof
+            let sampled_evaluations = positions.iter().map(|&i| evaluations[i]).collect::<Vec<BaseElement>>();
+            let result = super::verify(&proof, &sampled_evaluations, &positions, domain_size, degree, &options);
+            assert_eq!(Ok(true), result, "folding factor {}, max remainder size {}", folding_factor, max_remainder_size);
+
+            // a polynomial of a higher degree should fail verification
+            let result = super::verify(&proof, &sampled_evaluations, &positions, domain_size, degree / 2, &options);
+            assert!(result.is_err(), "folding factor {}, max remainder size {}", folding_factor, max_remainder_size);
+        }
+    }
+
     #[test]
     fn verify_fail() {
         let degree: usize = 63;
         let domain_size: usize = 512;
-        let root = field::get_root_of_unity(domain_size);
-        let domain = field::get_power_series(root, domain_size);
+        let root = BaseElement::get_root_of_unity(domain_size);
+        let domain = BaseElement::get_power_series(root, domain_size);
         let options = ProofOptions::default();
 
-        // degree too low 1
+        // degree too low 1; degrees are checked with granularity of the folding factor
         let evaluations = build_random_poly_evaluations(domain_size, degree);
-        let (fri_trees, fri_values) = super::reduce(&evaluations, &domain, &options);
+        let (fri_trees, fri_values, remainder) = super::reduce(&evaluations, &domain, &options);
         let positions = compute_query_positions(fri_trees[fri_trees.len() - 1].root(), domain_size, &options);
-        let proof = super::build_proof(fri_trees, fri_values, &positions);
+        let proof = super::build_proof(fri_trees, fri_values, remainder, &positions, &options);
 
-        let sampled_evaluations = positions.iter().map(|&i| evaluations[i]).collect::<Vec<u128>>();
-        let result = super::verify(&proof, &sampled_evaluations, &positions, degree - 1, &options);
+        let sampled_evaluations = positions.iter().map(|&i| evaluations[i]).collect::<Vec<BaseElement>>();
+        let result = super::verify(&proof, &sampled_evaluations, &positions, domain_size, degree - 4, &options);
         let err_msg = format!("remainder is not a valid degree {} polynomial", 14);
         assert_eq!(Err(err_msg), result);
 
         // degree too low 2
         let evaluations = build_random_poly_evaluations(domain_size, degree + 1);
-        let (fri_trees, fri_values) = super::reduce(&evaluations, &domain, &options);
+        let (fri_trees, fri_values, remainder) = super::reduce(&evaluations, &domain, &options);
         let positions = compute_query_positions(fri_trees[fri_trees.len() - 1].root(), domain_size, &options);
-        let proof = super::build_proof(fri_trees, fri_values, &positions);
+        let proof = super::build_proof(fri_trees, fri_values, remainder, &positions, &options);
 
-        let sampled_evaluations = positions.iter().map(|&i| evaluations[i]).collect::<Vec<u128>>();
-        let result = super::verify(&proof, &sampled_evaluations, &positions, degree, &options);
+        let sampled_evaluations = positions.iter().map(|&i| evaluations[i]).collect::<Vec<BaseElement>>();
+        let result = super::verify(&proof, &sampled_evaluations, &positions, domain_size, degree, &options);
         let err_msg = format!("remainder is not a valid degree {} polynomial", 15);
         assert_eq!(Err(err_msg), result);
 
+        // invalid remainder
+        let mut proof2 = proof.clone();
+        proof2.remainder[0] = proof2.remainder[0] + BaseElement::ONE;
+        let result = super::verify(&proof2, &sampled_evaluations, &positions, domain_size, degree + 1, &options);
+        let err_msg = format!("remainder values are inconsistent with values of the last column");
+        assert_eq!(Err(err_msg), result);
+
         // invalid evaluations
         let sampled_evaluations = sampled_evaluations[1..].to_vec();
-        let result = super::verify(&proof, &sampled_evaluations, &positions, degree, &options);
+        let result = super::verify(&proof, &sampled_evaluations, &positions, domain_size, degree, &options);
         let err_msg = format!("evaluations did not match column value at depth 0");
         assert_eq!(Err(err_msg), result);
     }
 
+    #[test]
+    fn prove_verify_extension() {
+        let degree: usize = 63;
+        let domain_size: usize = 512;
+        let root = f64::BaseElement::get_root_of_unity(domain_size);
+        let domain = f64::BaseElement::get_power_series(root, domain_size);
+        let options = ProofOptions::default();
+
+        let mut evaluations = QuadExtension::<f64::BaseElement>::rand_vector(degree + 1);
+        evaluations.resize(domain_size, QuadExtension::ZERO);
+        polynom::eval_fft_twiddles(&mut evaluations, &build_ext_twiddles(root, domain_size), true);
+
+        // generate proof; values of the proof are in the base field
+        let (fri_trees, fri_values, remainder) = super::reduce(&evaluations, &domain, &options);
+        let positions = compute_query_positions(fri_trees[fri_trees.len() - 1].root(), domain_size, &options);
+        let proof: super::FriProof<f64::BaseElement> = super::build_proof(fri_trees, fri_values, remainder, &positions, &options);
+
+        // verify proof
+        let sampled_evaluations = positions.iter().map(|&i| evaluations[i]).collect::<Vec<_>>();
+        let result = super::verify(&proof, &sampled_evaluations, &positions, domain_size, degree, &options);
+        assert_eq!(Ok(true), result);
+
+        // a remainder which does not consist of whole extension field elements is rejected
+        let mut proof2 = proof.clone();
+        proof2.remainder.pop();
+        let result = super::verify(&proof2, &sampled_evaluations, &positions, domain_size, degree, &options);
+        assert!(result.is_err());
+    }
+
     // TODO: add more tests
 
-    fn build_random_poly_evaluations(domain_size: usize, degree: usize) -> Vec<u128> {
-        let mut evaluations = field::rand_vector(degree + 1);
-        evaluations.resize(domain_size, 0);
+    fn build_random_poly_evaluations(domain_size: usize, degree: usize) -> Vec<BaseElement> {
+        let mut evaluations = BaseElement::rand_vector(degree + 1);
+        evaluations.resize(domain_size, BaseElement::ZERO);
         polynom::eval_fft(&mut evaluations, true);
         return evaluations;
     }
+
+    fn build_ext_twiddles(root: f64::BaseElement, domain_size: usize) -> Vec<QuadExtension<f64::BaseElement>> {
+        let twiddles = fft::get_twiddles(root, domain_size);
+        return twiddles.into_iter().map(QuadExtension::from).collect();
+    }
 }
\ No newline at end of file
@@ -1,96 +1,122 @@
-use sp_std::{mem, vec, vec::Vec};
-use crate::math::{ field, quartic };
+use sp_std::vec::Vec;
+use crate::math::{ polynom, batch, FieldElement, StarkField };
 use crate::crypto::{ MerkleTree };
 use crate::stark::{ ProofOptions };
 
-use super::{ FriProof, FriLayer, utils, MAX_REMAINDER_LENGTH};
+use super::{ FriProof, FriLayer, utils };
 
 // PROVER FUNCTIONS
 // ================================================================================================
 
-pub fn reduce(evaluations: &[u128], domain: &[u128], options: &ProofOptions) -> (Vec<MerkleTree>, Vec<Vec<[u128; 4]>>) {
-    let mut tree_results: Vec<MerkleTree> = Vec::new();
-    let mut value_results: Vec<Vec<[u128; 4]>> = Vec::new();
-
-    // transpose evaluations into a matrix with 4 columns and put its rows into a Merkle tree
-    let mut p_values = quartic::transpose(evaluations, 1);
-    let hashed_values = utils::hash_values(&p_values, options.hash_fn());
-    let mut p_tree = MerkleTree::new(hashed_values, options.hash_fn());
-
-    // reduce the degree by 4 at each iteration until the remaining polynomial is small enough
-    while p_tree.leaves().len() * 4 > MAX_REMAINDER_LENGTH {
-
-        // build polynomials from each row of the polynomial value matrix
-        let depth = tree_results.len() as u32;
-        let xs = quartic::transpose(domain, usize::pow(4, depth));
-        let polys = quartic::interpolate_batch(&xs, &p_values);
-
-        // select a pseudo-random x coordinate and evaluate each row polynomial at that x
-        let special_x = field::prng(*p_tree.root());
-        let column = quartic::evaluate_batch(&polys, special_x);
-
-        // break the column in a polynomial value matrix for the next layer
-        let mut c_values = quartic::transpose(&column, 1);
-
-        // put the resulting matrix into a Merkle tree
-        let hashed_values = utils::hash_values(&c_values, options.hash_fn());
-        let mut c_tree = MerkleTree::new(hashed_values, options.hash_fn());
-
-        // set p_tree = c_tree and p_values = c_values for the next iteration of the loop
-        mem::swap(&mut c_tree, &mut p_tree);
-        mem::swap(&mut c_values, &mut p_values);
-
-        // add p_tree and p_values from this loop (which is now under c_tree and c_values) to the result
-        tree_results.push(c_tree);
-        value_results.push(c_values);
-    }
-
-    // add the tree at the last layer (the remainder)
-    tree_results.push(p_tree);
-    value_results.push(p_values);
-
-    return (tree_results, value_results);
+/// Reduces evaluations of a polynomial over the `domain` to a small remainder; the degree is
+/// reduced by the folding factor specified in `options` at every layer. The domain may be a
+/// coset of a multiplicative subgroup, in which case the domain offset is `domain[0]`. Values
+/// of every layer are returned as a flattened matrix with `folding_factor` columns, and the
+/// remainder is returned as coefficients of the remaining polynomial. The evaluations may be
+/// in an extension of the field over which the domain is defined.
+pub fn reduce<B, E>(evaluations: &[E], domain: &[B], options: &ProofOptions) -> (Vec<MerkleTree>, Vec<Vec<E>>, Vec<E>)
+    where B: StarkField, E: FieldElement<BaseField = B>
+{
+    return match options.fri_folding_factor() {
+        2  => reduce_with::<B, E, 2>(evaluations, domain, options),
+        4  => reduce_with::<B, E, 4>(evaluations, domain, options),
+        8  => reduce_with::<B, E, 8>(evaluations, domain, options),
+        16 => reduce_with::<B, E, 16>(evaluations, domain, options),
+        folding_factor => panic!("FRI folding factor {} is not supported", folding_factor),
+    };
 }
 
-pub fn build_proof(trees: Vec<MerkleTree>, values: Vec<Vec<[u128; 4]>>, positions: &[usize]) -> FriProof {
+/// Builds a FRI proof for the specified query `positions`; values of extension field elements
+/// are recorded in the proof as their base field coefficients.
+pub fn build_proof<E: FieldElement>(trees: Vec<MerkleTree>, values: Vec<Vec<E>>, remainder: Vec<E>, positions: &[usize], options: &ProofOptions) -> FriProof<E::BaseField> {
+    let folding_factor = options.fri_folding_factor();
     let mut positions = positions.to_vec();
-    let mut domain_size = trees[0].leaves().len() * 4;
+    let mut domain_size = values.first().map_or(0, |v| v.len());
 
-    // for all trees, except the last one, record tree root, authentication paths
-    // to row evaluations, and values for row evaluations
+    // for all layers, record tree root, authentication paths to row evaluations,
+    // and values for row evaluations
     let mut layers = Vec::with_capacity(trees.len());
-    for i in 0..(trees.len() - 1) {
+    for (tree, values) in trees.iter().zip(values.iter()) {
         
-        positions = utils::get_augmented_positions(&positions, domain_size);
+        positions = utils::get_augmented_positions(&positions, domain_size, folding_factor);
 
-        let tree = &trees[i];
         let proof = tree.prove_batch(&positions);
         
-        let mut queried_values: Vec<[u128; 4]> = Vec::with_capacity(positions.len());
+        let mut queried_values: Vec<E> = Vec::with_capacity(positions.len() * folding_factor);
         for &position in positions.iter() {
-            queried_values.push(values[i][position]);
+            let row_start = position * folding_factor;
+            queried_values.extend_from_slice(&values[row_start..(row_start + folding_factor)]);
         }
 
         layers.push(FriLayer {
             root    : *tree.root(),
-            values  : queried_values,
+            values  : E::as_base_elements(&queried_values).to_vec(),
             nodes   : proof.nodes,
             depth   : proof.depth
         });
-        domain_size = domain_size / 4;
+        domain_size = domain_size / folding_factor;
     }
 
-    // use the remaining polynomial values directly as proof
-    let last_tree = &trees[trees.len() - 1];
-    let last_values = &values[values.len() - 1];
-    let n = last_values.len();
-    let mut remainder = vec![field::ZERO; n * 4];
-    for i in 0..last_values.len() {
-        remainder[i] = last_values[i][0];
-        remainder[i + n] = last_values[i][1];
-        remainder[i + n * 2] = last_values[i][2];
-        remainder[i + n * 3] = last_values[i][3];
+    // the remainder polynomial is small enough to be included into the proof directly
+    let remainder = E::as_base_elements(&remainder).to_vec();
+    return FriProof { layers, remainder };
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+fn reduce_with<B, E, const N: usize>(evaluations: &[E], domain: &[B], options: &ProofOptions) -> (Vec<MerkleTree>, Vec<Vec<E>>, Vec<E>)
+    where B: StarkField, E: FieldElement<BaseField = B>
+{
+    let domain_offset = domain[0];
+    let mut tree_results: Vec<MerkleTree> = Vec::new();
+    let mut value_results: Vec<Vec<E>> = Vec::new();
+
+    // reduce the degree by N at each iteration until the remaining polynomial is small enough
+    let mut p_evaluations = evaluations.to_vec();
+    while p_evaluations.len() > options.fri_max_remainder_size() {
+
+        // transpose evaluations into a matrix with N columns and put its rows into a Merkle tree
+        let p_values = batch::transpose::<_, N>(&p_evaluations, 1);
+        let hashed_values = utils::hash_values(&p_values, options.hash_fn());
+        let p_tree = MerkleTree::new(hashed_values, options.hash_algorithm());
+
+        // build polynomials from each row of the polynomial value matrix
+        let depth = tree_results.len() as u32;
+        let stride = usize::pow(N, depth);
+        let mut xs = batch::transpose::<_, N>(domain, stride);
+
+        // the domain at this depth is the original domain raised to the power of stride;
+        // so, for a coset, elements taken from the original domain need to be adjusted
+        // by domain_offset^(stride - 1)
+        if domain_offset != B::ONE {
+            let adjustment = domain_offset.exp((stride - 1) as u128);
+            for row in xs.iter_mut() {
+                for x in row.iter_mut() { *x = *x * adjustment; }
+            }
+        }
+        let xs = xs.into_iter().map(|row| row.map(E::from)).collect::<Vec<_>>();
+        let polys = batch::interpolate_batch(&xs, &p_values);
+
+        // select a pseudo-random x coordinate and evaluate each row polynomial at that x;
+        // the results become evaluations for the next layer
+        let special_x = E::prng(*p_tree.root());
+        p_evaluations = batch::evaluate_batch(&polys, special_x);
+
+        tree_results.push(p_tree);
+        value_results.push(batch::flatten(p_values));
     }
 
-    return FriProof { layers, rem_root: *last_tree.root(), rem_values: remainder };
-}
\ No newline at end of file
+    // interpolate the remaining evaluations into a polynomial; the evaluations are over the
+    // original domain raised to the power of N^depth, which is shifted by domain_offset^(N^depth)
+    let stride = usize::pow(N, tree_results.len() as u32);
+    let remainder_offset = domain_offset.exp(stride as u128);
+    let mut remainder = p_evaluations;
+    polynom::interpolate_fft(&mut remainder, true);
+    polynom::shift(&mut remainder, E::from(remainder_offset.inv()));
+
+    // drop zero coefficients so that the length of the remainder reflects its degree
+    remainder.truncate(polynom::degree_of(&remainder) + 1);
+
+    return (tree_results, value_results, remainder);
+}
@@ -1,9 +1,10 @@
 use crate::crypto::{ HashFunction };
+use crate::math::{ FieldElement };
 use crate::utils::{ uninit_vector, as_bytes };
 use sp_std::vec::Vec;
 
-pub fn get_augmented_positions(positions: &[usize], column_length: usize) -> Vec<usize> {
-    let row_length = column_length / 4;
+pub fn get_augmented_positions(positions: &[usize], column_length: usize, folding_factor: usize) -> Vec<usize> {
+    let row_length = column_length / folding_factor;
     let mut result = Vec::new();
     for i in 0..positions.len() {
         let ap = positions[i] % row_length;
@@ -14,10 +15,10 @@ pub fn get_augmented_positions(positions: &[usize], column_length: usize) -> Vec
     return result;
 }
 
-pub fn hash_values(values: &Vec<[u128; 4]>, hash: HashFunction) -> Vec<[u8; 32]> {
+pub fn hash_values<E: FieldElement, const N: usize>(values: &[[E; N]], hash: HashFunction) -> Vec<[u8; 32]> {
     let mut result: Vec<[u8; 32]> = uninit_vector(values.len());
     for i in 0..values.len() {
         hash(as_bytes(&values[i]), &mut result[i]);
     }
     return result;
-}
\ No newline at end of file
+}
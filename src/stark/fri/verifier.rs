@@ -1,5 +1,5 @@
 use sp_std::mem;
-use crate::math::{ field, polynom, quartic };
+use crate::math::{ polynom, batch, FieldElement, StarkField };
 use crate::crypto::{ MerkleTree, BatchMerkleProof };
 use crate::stark::{ ProofOptions };
 
@@ -10,27 +10,48 @@ use alloc::string::String;
 // VERIFIER
 // ================================================================================================
 
-pub fn verify(
-    proof       : &FriProof,
-    evaluations : &[u128],
+/// Verifies that `evaluations` at the specified `positions` are evaluations of a polynomial of
+/// degree at most `max_degree`; the evaluations may be in an extension of the field over which
+/// the evaluation domain is defined.
+pub fn verify<B, E>(
+    proof       : &FriProof<B>,
+    evaluations : &[E],
     positions   : &[usize],
+    domain_size : usize,
     max_degree  : usize,
     options     : &ProofOptions) -> Result<bool, String>
+    where B: StarkField, E: FieldElement<BaseField = B>
 {
+    return match options.fri_folding_factor() {
+        2  => verify_with::<B, E, 2>(proof, evaluations, positions, domain_size, max_degree, options),
+        4  => verify_with::<B, E, 4>(proof, evaluations, positions, domain_size, max_degree, options),
+        8  => verify_with::<B, E, 8>(proof, evaluations, positions, domain_size, max_degree, options),
+        16 => verify_with::<B, E, 16>(proof, evaluations, positions, domain_size, max_degree, options),
+        folding_factor => Err(format!("FRI folding factor {} is not supported", folding_factor)),
+    };
+}
 
-    let domain_size = usize::pow(2, proof.layers[0].depth as u32) * 4;
-    let domain_root = field::get_root_of_unity(domain_size);
+fn verify_with<B, E, const N: usize>(
+    proof       : &FriProof<B>,
+    evaluations : &[E],
+    positions   : &[usize],
+    domain_size : usize,
+    max_degree  : usize,
+    options     : &ProofOptions) -> Result<bool, String>
+    where B: StarkField, E: FieldElement<BaseField = B>
+{
+    let domain_root = B::get_root_of_unity(domain_size);
 
-    // powers of the given root of unity 1, p, p^2, p^3 such that p^4 = 1
-    let quartic_roots = [
-        1u128,
-        field::exp(domain_root, (domain_size / 4) as u128),
-        field::exp(domain_root, (domain_size / 2) as u128),
-        field::exp(domain_root, (domain_size * 3 / 4) as u128),
-    ];
+    // powers of the given root of unity 1, p, p^2, ..., p^(N - 1) such that p^N = 1
+    let folding_root = domain_root.exp((domain_size / N) as u128);
+    let mut folding_roots = [B::ONE; N];
+    for i in 1..N {
+        folding_roots[i] = folding_roots[i - 1] * folding_root;
+    }
 
     // 1 ----- verify the recursive components of the FRI proof -----------------------------------
     let mut domain_root = domain_root;
+    let mut domain_offset: B = options.domain_offset();
     let mut domain_size = domain_size;
     let mut max_degree_plus_1 = max_degree + 1;
     let mut positions = positions.to_vec();
@@ -38,14 +59,22 @@ pub fn verify(
 
     for (depth, layer) in proof.layers.iter().enumerate() {
 
-        let mut augmented_positions = utils::get_augmented_positions(&positions, domain_size);
-        let column_values = get_column_values(&layer.values, &positions, &augmented_positions, domain_size);
+        if layer.values.len() % (N * E::EXTENSION_DEGREE) != 0 {
+            return Err(format!("number of values is not a multiple of folding factor at layer {}", depth));
+        }
+        let layer_values = batch::to_rows::<_, N>(E::slice_from_base_elements(&layer.values).to_vec());
+
+        let mut augmented_positions = utils::get_augmented_positions(&positions, domain_size, N);
+        if layer_values.len() != augmented_positions.len() {
+            return Err(format!("invalid number of values at layer {}", depth));
+        }
+        let column_values = get_column_values(&layer_values, &positions, &augmented_positions, domain_size);
         if evaluations != column_values {
             return Err(format!("evaluations did not match column value at depth {}", depth));
         }
 
         // verify Merkle proof for the layer
-        let merkle_proof = build_layer_merkle_proof(&layer, options);
+        let merkle_proof = build_layer_merkle_proof(&layer, &layer_values, options);
         if !MerkleTree::verify_batch(&layer.root, &augmented_positions, &merkle_proof, options.hash_fn()) {
             return Err(format!("verification of Merkle proof failed at layer {}", depth));
         }
@@ -53,72 +82,49 @@ pub fn verify(
         // build a set of x for each row polynomial
         let mut xs = Vec::with_capacity(augmented_positions.len());
         for &i in augmented_positions.iter() {
-            let xe = field::exp(domain_root, i as u128);
-            xs.push([
-                field::mul(quartic_roots[0], xe),
-                field::mul(quartic_roots[1], xe),
-                field::mul(quartic_roots[2], xe),
-                field::mul(quartic_roots[3], xe)
-            ]);
+            let xe = domain_offset * domain_root.exp(i as u128);
+            let mut row = [E::ZERO; N];
+            for j in 0..N {
+                row[j] = E::from(folding_roots[j] * xe);
+            }
+            xs.push(row);
         }
 
         // interpolate x and y values into row polynomials
-        let row_polys = quartic::interpolate_batch(&xs, &layer.values);
+        let row_polys = batch::interpolate_batch(&xs, &layer_values);
 
         // calculate the pseudo-random x coordinate
-        let special_x = field::prng(layer.root);
+        let special_x = E::prng(layer.root);
 
         // check that when the polynomials are evaluated at x, the result is equal to the corresponding column value
-        evaluations = quartic::evaluate_batch(&row_polys, special_x);
-
-        // update variables for the next iteration of the loop
-        domain_root = field::exp(domain_root, 4);
-        max_degree_plus_1 = max_degree_plus_1 / 4;
-        domain_size = domain_size / 4;
+        evaluations = batch::evaluate_batch(&row_polys, special_x);
+
+        // update variables for the next iteration of the loop; a polynomial of degree d folds
+        // into a polynomial of degree floor(d / N)
+        domain_root = domain_root.exp(N as u128);
+        domain_offset = domain_offset.exp(N as u128);
+        max_degree_plus_1 = (max_degree_plus_1 + N - 1) / N;
+        domain_size = domain_size / N;
         mem::swap(&mut positions, &mut augmented_positions);
     }
 
     // 2 ----- verify the remainder of the FRI proof ----------------------------------------------
-    
-    for (&position, evaluation) in positions.iter().zip(evaluations) {
-        if proof.rem_values[position] != evaluation {
-            return Err(String::from("remainder values are inconsistent with values of the last column"));
-        }
-    }
-
-    // make sure the remainder values satisfy the degree
-    return verify_remainder(&proof.rem_values, max_degree_plus_1, domain_root, options.extension_factor());
-}
 
-fn verify_remainder(remainder: &[u128], max_degree_plus_1: usize, domain_root: u128, extension_factor: usize) -> Result<bool, String> {
-    if max_degree_plus_1 > remainder.len() {
-        return Err(String::from("remainder degree is greater than number of remainder values"));
-    }
-
-    // exclude points which should be skipped during evaluation
-    let mut positions = Vec::new();
-    for i in 0..remainder.len() {
-        if i % extension_factor != 0 {
-            positions.push(i);
-        }
+    if proof.remainder.len() % E::EXTENSION_DEGREE != 0 {
+        return Err(String::from("number of remainder values is not a multiple of extension degree"));
     }
+    let remainder = E::slice_from_base_elements(&proof.remainder);
 
-    // pick a subset of points from the remainder and interpolate them into a polynomial
-    let domain = field::get_power_series(domain_root, remainder.len());
-    let mut xs = Vec::with_capacity(max_degree_plus_1);
-    let mut ys = Vec::with_capacity(max_degree_plus_1);
-    for i in 0..max_degree_plus_1 {
-        let p = positions[i];
-        xs.push(domain[p]);
-        ys.push(remainder[p]);
+    // make sure the remainder satisfies the degree
+    if remainder.len() > max_degree_plus_1 {
+        return Err(format!("remainder is not a valid degree {} polynomial", max_degree_plus_1 - 1));
     }
-    let poly = polynom::interpolate(&xs, &ys);
 
-    // check that polynomial evaluates correctly for all other points in the remainder
-    for i in max_degree_plus_1..positions.len() {
-        let p = positions[i];
-        if polynom::eval(&poly, domain[p]) != remainder[p] {
-            return Err(format!("remainder is not a valid degree {} polynomial", max_degree_plus_1 - 1));
+    // make sure the remainder evaluates to the values of the last column at queried positions
+    for (&position, evaluation) in positions.iter().zip(evaluations) {
+        let x = domain_offset * domain_root.exp(position as u128);
+        if polynom::eval(remainder, E::from(x)) != evaluation {
+            return Err(String::from("remainder values are inconsistent with values of the last column"));
         }
     }
 
@@ -127,8 +133,8 @@ fn verify_remainder(remainder: &[u128], max_degree_plus_1: usize, domain_root: u
 
 // HELPER FUNCTIONS
 // ================================================================================================
-fn get_column_values(values: &Vec<[u128; 4]>, positions: &[usize], augmented_positions: &[usize], column_length: usize) -> Vec<u128> {
-    let row_length = column_length / 4;
+fn get_column_values<E: FieldElement, const N: usize>(values: &[[E; N]], positions: &[usize], augmented_positions: &[usize], column_length: usize) -> Vec<E> {
+    let row_length = column_length / N;
 
     let mut result = Vec::new();
     for position in positions {
@@ -140,44 +146,10 @@ fn get_column_values(values: &Vec<[u128; 4]>, positions: &[usize], augmented_pos
     return result;
 }
 
-fn build_layer_merkle_proof(layer: &FriLayer, options: &ProofOptions) -> BatchMerkleProof {
+fn build_layer_merkle_proof<E: FieldElement, const N: usize>(layer: &FriLayer<E::BaseField>, values: &[[E; N]], options: &ProofOptions) -> BatchMerkleProof {
     return BatchMerkleProof {
-        values  : utils::hash_values(&layer.values, options.hash_fn()),
+        values  : utils::hash_values(values, options.hash_fn()),
         nodes   : layer.nodes.clone(),
         depth   : layer.depth
     };
 }
-
-// TESTS
-// ================================================================================================
-#[cfg(test)]
-mod tests {
-    
-    use crate::math::{ field, polynom };
-
-    #[test]
-    fn verify_remainder() {
-        let degree_plus_1: usize = 32;
-        let root = field::get_root_of_unity(degree_plus_1 * 2);
-        let extension_factor = 16;
-
-        let mut remainder = field::rand_vector(degree_plus_1);
-        remainder.resize(degree_plus_1 * 2, 0);
-        polynom::eval_fft(&mut remainder, true);
-
-        // check against exact degree
-        let result = super::verify_remainder(&remainder, degree_plus_1, root, extension_factor);
-        assert_eq!(Ok(true), result);
-
-        // check against higher degree
-        let result = super::verify_remainder(&remainder, degree_plus_1 + 1, root, extension_factor);
-        assert_eq!(Ok(true), result);
-
-        // check against lower degree
-        let degree_plus_1 = degree_plus_1 - 1;
-        let result = super::verify_remainder(&remainder, degree_plus_1, root, extension_factor);
-        let err_msg = format!("remainder is not a valid degree {} polynomial", degree_plus_1 - 1);
-        assert_eq!(Err(err_msg), result);
-    }
-
-}
\ No newline at end of file
@@ -0,0 +1,196 @@
+use core::time::Duration;
+use sp_std::{ fmt, vec::Vec };
+use crate::{ utils::allocator, processor::ExecutionError };
+use super::FieldExtension;
+
+// TYPES AND INTERFACES
+// ================================================================================================
+
+/// Phases of proof generation in the order in which they are executed.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ProverPhase {
+    TraceExtension,
+    TraceCommitment,
+    ConstraintEvaluation,
+    Composition,
+    FriLayers,
+    ProofOfWork,
+    QueryBuilding,
+}
+
+/// A callback invoked at the start of every prover phase; if the callback returns `false`,
+/// proof generation is aborted.
+pub type ProgressCallback<'a> = &'a mut dyn FnMut(ProverPhase) -> bool;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ProverError {
+    /// Program execution exceeded execution limits, and so no proof was generated.
+    Execution(ExecutionError),
+    /// Proof generation was cancelled by the progress callback before the phase started.
+    Cancelled(ProverPhase),
+    /// The field extension requested by proof options is not defined for the base field of
+    /// the computation.
+    UnsupportedExtension(FieldExtension),
+}
+
+/// Wall time and peak heap allocation of a single prover phase; peak allocation is available
+/// only when `utils::allocator::TrackingAllocator` is installed as the global allocator.
+#[derive(Clone, Debug)]
+pub struct PhaseMetrics {
+    pub phase           : ProverPhase,
+    pub wall_time       : Duration,
+    pub peak_allocation : Option<usize>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ProverMetrics {
+    pub phases          : Vec<PhaseMetrics>,
+}
+
+// PROVER METRICS IMPLEMENTATION
+// ================================================================================================
+impl ProverMetrics {
+
+    /// Returns metrics of the specified phase, if the phase has been executed.
+    pub fn get(&self, phase: ProverPhase) -> Option<&PhaseMetrics> {
+        return self.phases.iter().find(|m| m.phase == phase);
+    }
+
+    /// Returns total wall time of all executed phases.
+    pub fn total_time(&self) -> Duration {
+        return self.phases.iter().map(|m| m.wall_time).sum();
+    }
+}
+
+/// Tracks the currently executing prover phase.
+pub struct PhaseTracker<'a> {
+    metrics         : ProverMetrics,
+    progress        : Option<ProgressCallback<'a>>,
+    current         : Option<(ProverPhase, Timer)>,
+}
+
+impl <'a> PhaseTracker<'a> {
+
+    pub fn new(progress: Option<ProgressCallback<'a>>) -> PhaseTracker<'a> {
+        return PhaseTracker { metrics: ProverMetrics::default(), progress, current: None };
+    }
+
+    /// Closes the current phase and starts the next one, unless the progress callback
+    /// requests cancellation.
+    pub fn start(&mut self, phase: ProverPhase) -> Result<(), ProverError> {
+        self.finish_phase();
+        if let Some(progress) = self.progress.as_mut() {
+            if !progress(phase) {
+                return Err(ProverError::Cancelled(phase));
+            }
+        }
+
+        allocator::reset_peak_allocation();
+        self.current = Some((phase, Timer::start()));
+        return Ok(());
+    }
+
+    /// Closes the current phase and returns metrics for all phases.
+    pub fn finish(mut self) -> ProverMetrics {
+        self.finish_phase();
+        return self.metrics;
+    }
+
+    fn finish_phase(&mut self) {
+        if let Some((phase, timer)) = self.current.take() {
+            self.metrics.phases.push(PhaseMetrics {
+                phase,
+                wall_time       : timer.elapsed(),
+                peak_allocation : allocator::peak_allocation(),
+            });
+        }
+    }
+}
+
+impl From<ExecutionError> for ProverError {
+    fn from(err: ExecutionError) -> ProverError {
+        return ProverError::Execution(err);
+    }
+}
+
+// TIMER
+// ================================================================================================
+
+/// Wall clock timer; std::time::Instant is not available in WebAssembly, and so all phases
+/// are reported to take 0 time there.
+#[cfg(not(target_arch = "wasm32"))]
+struct Timer(std::time::Instant);
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Timer {
+    fn start() -> Timer { return Timer(std::time::Instant::now()); }
+    fn elapsed(&self) -> Duration { return self.0.elapsed(); }
+}
+
+#[cfg(target_arch = "wasm32")]
+struct Timer;
+
+#[cfg(target_arch = "wasm32")]
+impl Timer {
+    fn start() -> Timer { return Timer; }
+    fn elapsed(&self) -> Duration { return Duration::from_secs(0); }
+}
+
+// FORMATTING
+// ================================================================================================
+impl fmt::Display for ProverError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return match self {
+            ProverError::Execution(err) => write!(f, "{}", err),
+            ProverError::Cancelled(phase) => write!(f, "proof generation cancelled before {:?} phase", phase),
+            ProverError::UnsupportedExtension(extension) =>
+                write!(f, "{:?} extension is not supported for the base field of the computation", extension),
+        };
+    }
+}
+
+// TESTS
+// ================================================================================================
+#[cfg(test)]
+mod tests {
+
+    use crate::{ processor, programs::{ assembly, ProgramInputs }, stark::{ self, TraceTable, ProcessorAir, ProcessorPublicInputs }, ProofOptions, ProverConfig };
+    use super::{ ProverPhase, ProverError };
+
+    #[test]
+    fn prove_with_metrics() {
+        let (mut trace, pub_inputs) = build_trace();
+        let options = ProofOptions::default();
+
+        let mut phases = Vec::new();
+        let mut progress = |phase: ProverPhase| { phases.push(phase); true };
+        let (_, metrics) = stark::prove_with_metrics::<ProcessorAir>(&mut trace, &pub_inputs, &options, &ProverConfig::default(), Some(&mut progress)).unwrap();
+
+        let expected = vec![
+            ProverPhase::TraceExtension, ProverPhase::TraceCommitment, ProverPhase::ConstraintEvaluation,
+            ProverPhase::Composition, ProverPhase::FriLayers, ProverPhase::ProofOfWork,
+            ProverPhase::QueryBuilding,
+        ];
+        assert_eq!(expected, phases);
+        assert_eq!(expected, metrics.phases.iter().map(|m| m.phase).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn prove_cancelled() {
+        let (mut trace, pub_inputs) = build_trace();
+        let options = ProofOptions::default();
+
+        let mut progress = |phase: ProverPhase| phase != ProverPhase::FriLayers;
+        let result = stark::prove_with_metrics::<ProcessorAir>(&mut trace, &pub_inputs, &options, &ProverConfig::default(), Some(&mut progress));
+        assert_eq!(Some(ProverError::Cancelled(ProverPhase::FriLayers)), result.err());
+    }
+
+    fn build_trace() -> (TraceTable, ProcessorPublicInputs) {
+        let program = assembly::compile("begin push.3 push.5 add push.3 mul end").unwrap();
+        let inputs = ProgramInputs::none();
+        let (trace, ctx_depth, loop_depth) = processor::execute(&program, &inputs, &Default::default()).unwrap();
+        let trace = TraceTable::new(trace, ctx_depth, loop_depth, ProofOptions::default().extension_factor());
+        let pub_inputs = ProcessorPublicInputs { program_hash: *program.hash(), inputs: vec![], outputs: vec![24] };
+        return (trace, pub_inputs);
+    }
+}
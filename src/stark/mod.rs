@@ -1,3 +1,4 @@
+pub mod air;
 mod trace;
 mod constraints;
 mod options;
@@ -5,22 +6,34 @@ mod prover;
 mod verifier;
 mod proof;
 mod fri;
+mod metrics;
 mod utils;
 
+pub use air::{ Air, Assertion, TraceInfo };
 pub use trace::{ TraceTable, TraceState };
 
 pub use constraints::{
     ConstraintEvaluator,
     ConstraintTable,
-    ConstraintPoly };
+    ConstraintPoly,
+    ProcessorAir,
+    ProcessorPublicInputs,
+    SegmentAir,
+    SegmentPublicInputs,
+    ConstraintFailure,
+    CheckError,
+    TraceOp,
+    check_trace };
 
 pub use utils::{
     ConstraintCoefficients,
-    CompositionCoefficients };
+    CompositionCoefficients,
+    get_composition_degree };
 
-pub use options::ProofOptions;
-pub use proof::{ StarkProof, DeepValues, GenOutput, ProgramAssembly};
-pub use prover::{ prove };
-pub use verifier::{ verify };
+pub use options::{ ProofOptions, ProverConfig, FieldExtension };
+pub use proof::{ StarkProof, SegmentProof, DeepValues, GenOutput, ProgramAssembly};
+pub use prover::{ prove, prove_with_metrics };
+pub use metrics::{ ProverMetrics, PhaseMetrics, ProverPhase, ProverError, ProgressCallback };
+pub use verifier::{ verify, verify_batch, verify_all };
 
-const MAX_CONSTRAINT_DEGREE : usize = 8;
\ No newline at end of file
+pub const MAX_CONSTRAINT_DEGREE : usize = 4;
\ No newline at end of file
@@ -1,12 +1,23 @@
 use serde::{ Serialize, Deserialize };
-use crate::crypto::{ HashFunction, hash };
+use crate::crypto::{ HashFunction, HashAlgorithm };
+use crate::math::StarkField;
 use super::MAX_CONSTRAINT_DEGREE;
 
 // CONSTANTS
 // ================================================================================================
-const DEFAULT_EXTENSION_FACTOR: u8 = (MAX_CONSTRAINT_DEGREE * 4) as u8;
-const DEFAULT_NUM_QUERIES     : u8 = 50;
-const DEFAULT_GRINDING_FACTOR : u8 = 20;
+const DEFAULT_EXTENSION_FACTOR      : u8 = (MAX_CONSTRAINT_DEGREE * 4) as u8;
+const DEFAULT_NUM_QUERIES           : u8 = 50;
+const DEFAULT_GRINDING_FACTOR       : u8 = 20;
+const DEFAULT_FRI_FOLDING_FACTOR    : u8 = 4;
+const DEFAULT_FRI_MAX_REMAINDER_SIZE: u16 = 256;
+
+// offset of the LDE domain coset used in zero-knowledge mode; 3 is not in the 2^40 subgroup,
+// and so the coset does not intersect the trace domain or any of its extensions
+const ZK_DOMAIN_OFFSET              : u128 = 3;
+
+// in zero-knowledge mode, every register reveals its values at query positions and at the two
+// DEEP points; the trace is padded with this many more random rows than values revealed
+const ZK_EXTRA_BLINDING_ROWS        : usize = 2;
 
 // TYPES AND INTERFACES
 // ================================================================================================
@@ -17,9 +28,43 @@ pub struct ProofOptions {
     extension_factor    : u8,   // stored as power of 2
     num_queries         : u8,
     grinding_factor     : u8,
+    zk                  : bool,
+    fri_folding_factor  : u8,
+    fri_remainder_size  : u8,   // stored as power of 2
+    field_extension     : FieldExtension,
+
+    #[serde(with = "hash_serialization")]
+    hash                : HashAlgorithm,
+}
+
+/// Field from which random values used by the protocol (out-of-domain point, coefficients for
+/// random linear combinations, FRI folding challenges) are drawn: either the base field of the
+/// computation, or its quadratic or cubic extension. Extensions are needed for small fields
+/// (e.g. the 64-bit field) to provide the same level of security as the 128-bit field.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FieldExtension {
+    None,
+    Quadratic,
+    Cubic,
+}
 
-    #[serde(with = "hash_fn_serialization")]
-    hash_fn: HashFunction,
+/// Settings which affect how a proof is generated but not the proof itself; proofs generated
+/// with different configurations are byte-for-byte identical.
+///
+/// * `num_threads` is the number of threads used by the prover; it must be a power of 2, and
+///    setting it to 1 (the default) keeps all computations in the calling thread, which is
+///    required for WebAssembly targets.
+/// * `low_memory` trades proving time for memory: the extended execution trace is never held
+///    in memory in full; instead, registers are kept as polynomials and rows of the extended
+///    trace are re-evaluated in segments of `trace_length` rows when the trace is committed to,
+///    when constraints are evaluated, and when queries are answered. This reduces memory taken
+///    by the extended trace by a factor of `extension_factor`, while the work needed to extend
+///    the trace grows by about `MAX_CONSTRAINT_DEGREE / extension_factor` (e.g. by 50% for the
+///    minimum extension factor of 8, and by 25% for the default of 16).
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ProverConfig {
+    pub num_threads     : usize,
+    pub low_memory      : bool,
 }
 
 // PROOF OPTIONS IMPLEMENTATION
@@ -30,10 +75,11 @@ impl ProofOptions {
         extension_factor : usize,
         num_queries      : usize,
         grinding_factor  : u32,
-        hash_fn          : HashFunction) -> ProofOptions
+        hash             : HashAlgorithm) -> ProofOptions
     {
         assert!(extension_factor.is_power_of_two(), "extension_factor must be a power of 2");
-        assert!(extension_factor >= 16, "extension_factor cannot be smaller than 16");
+        assert!(extension_factor >= 2 * MAX_CONSTRAINT_DEGREE,
+            "extension_factor cannot be smaller than {}", 2 * MAX_CONSTRAINT_DEGREE);
         assert!(extension_factor <= 256, "extension_factor cannot be greater than 256");
 
         assert!(num_queries > 0, "num_queries must be greater than 0");
@@ -45,10 +91,45 @@ impl ProofOptions {
             extension_factor    : extension_factor.trailing_zeros() as u8,
             num_queries         : num_queries as u8,
             grinding_factor     : grinding_factor as u8,
-            hash_fn
+            zk                  : false,
+            fri_folding_factor  : DEFAULT_FRI_FOLDING_FACTOR,
+            fri_remainder_size  : DEFAULT_FRI_MAX_REMAINDER_SIZE.trailing_zeros() as u8,
+            field_extension     : FieldExtension::None,
+            hash
         };
     }
 
+    /// Returns options with the specified FRI parameters: at every FRI layer, the degree of the
+    /// polynomial is reduced by `folding_factor`, and layers are built until the number of
+    /// evaluations is not greater than `max_remainder_size`. Higher folding factors result in
+    /// fewer FRI layers, and larger remainders result in fewer layers but more remainder values.
+    pub fn with_fri(mut self, folding_factor: usize, max_remainder_size: usize) -> ProofOptions {
+        assert!([2, 4, 8, 16].contains(&folding_factor), "folding_factor must be 2, 4, 8, or 16");
+
+        assert!(max_remainder_size.is_power_of_two(), "max_remainder_size must be a power of 2");
+        assert!(max_remainder_size >= folding_factor, "max_remainder_size cannot be smaller than folding_factor");
+        assert!(max_remainder_size <= 65536, "max_remainder_size cannot be greater than 65536");
+
+        self.fri_folding_factor = folding_factor as u8;
+        self.fri_remainder_size = max_remainder_size.trailing_zeros() as u8;
+        return self;
+    }
+
+    /// Returns options which generate zero-knowledge proofs: execution trace is padded with
+    /// random blinding rows, trace and constraint polynomials are evaluated over a coset of the
+    /// LDE domain, and the DEEP composition polynomial is masked with a random polynomial.
+    pub fn with_zk(mut self) -> ProofOptions {
+        self.zk = true;
+        return self;
+    }
+
+    /// Returns options which draw random values used by the protocol from the specified
+    /// extension of the base field.
+    pub fn with_field_extension(mut self, field_extension: FieldExtension) -> ProofOptions {
+        self.field_extension = field_extension;
+        return self;
+    }
+
     pub fn extension_factor(&self) -> usize {
         return 1 << (self.extension_factor as usize)
     }
@@ -62,7 +143,37 @@ impl ProofOptions {
     }
 
     pub fn hash_fn(&self) -> HashFunction {
-        return self.hash_fn;
+        return self.hash.hash_fn();
+    }
+
+    pub fn hash_algorithm(&self) -> HashAlgorithm {
+        return self.hash;
+    }
+
+    pub fn fri_folding_factor(&self) -> usize {
+        return self.fri_folding_factor as usize;
+    }
+
+    pub fn fri_max_remainder_size(&self) -> usize {
+        return 1 << (self.fri_remainder_size as usize);
+    }
+
+    pub fn zk(&self) -> bool {
+        return self.zk;
+    }
+
+    pub fn field_extension(&self) -> FieldExtension {
+        return self.field_extension;
+    }
+
+    /// Returns the number of random rows appended to the execution trace.
+    pub fn blinding_rows(&self) -> usize {
+        return if self.zk { self.num_queries() + ZK_EXTRA_BLINDING_ROWS } else { 0 };
+    }
+
+    /// Returns the offset of the coset over which trace and constraint polynomials are evaluated.
+    pub fn domain_offset<E: StarkField>(&self) -> E {
+        return if self.zk { E::from(ZK_DOMAIN_OFFSET) } else { E::ONE };
     }
 
     pub fn security_level(&self, optimistic: bool) -> u32 {
@@ -83,39 +194,88 @@ impl Default for ProofOptions {
 
     fn default() -> ProofOptions {
         return ProofOptions {
-            extension_factor: DEFAULT_EXTENSION_FACTOR.trailing_zeros() as u8,
-            num_queries     : DEFAULT_NUM_QUERIES,
-            grinding_factor : DEFAULT_GRINDING_FACTOR,
-            hash_fn         : hash::blake3,
+            extension_factor    : DEFAULT_EXTENSION_FACTOR.trailing_zeros() as u8,
+            num_queries         : DEFAULT_NUM_QUERIES,
+            grinding_factor     : DEFAULT_GRINDING_FACTOR,
+            zk                  : false,
+            fri_folding_factor  : DEFAULT_FRI_FOLDING_FACTOR,
+            fri_remainder_size  : DEFAULT_FRI_MAX_REMAINDER_SIZE.trailing_zeros() as u8,
+            field_extension     : FieldExtension::None,
+            hash                : HashAlgorithm::Blake3,
         };
     }
 
 }
 
+// FIELD EXTENSION IMPLEMENTATION
+// ================================================================================================
+impl FieldExtension {
+
+    /// Returns the degree of the extension over the base field.
+    pub fn degree(&self) -> usize {
+        return match self {
+            FieldExtension::None        => 1,
+            FieldExtension::Quadratic   => 2,
+            FieldExtension::Cubic       => 3,
+        };
+    }
+}
+
+// PROVER CONFIG IMPLEMENTATION
+// ================================================================================================
+impl ProverConfig {
+
+    pub fn new(num_threads: usize) -> ProverConfig {
+        assert!(num_threads.is_power_of_two(), "num_threads must be a power of 2");
+        return ProverConfig { num_threads, low_memory: false };
+    }
+
+    /// Returns a config which makes the prover re-evaluate the extended execution trace on
+    /// demand instead of keeping it in memory.
+    pub fn with_low_memory(mut self) -> ProverConfig {
+        self.low_memory = true;
+        return self;
+    }
+}
+
+impl Default for ProverConfig {
+
+    fn default() -> ProverConfig {
+        return ProverConfig { num_threads: 1, low_memory: false };
+    }
+}
+
 // HASH FUNCTION SERIALIZATION / DE-SERIALIZATION
 // ================================================================================================
-mod hash_fn_serialization {
+mod hash_serialization {
 
-    use serde::{ Serializer, Deserializer, Deserialize, ser, de };
-    use crate::crypto::{ HashFunction, hash };
+    use serde::{ Serializer, Deserializer, Deserialize, de };
+    use crate::crypto::HashAlgorithm;
 
-    pub fn serialize<S>(hf: &HashFunction, s: S) -> Result<S::Ok, S::Error>
+    pub fn serialize<S>(hash: &HashAlgorithm, s: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer
     {
-        match *hf as usize {
-            f if f == hash::blake3 as usize => s.serialize_u8(0),
-            _ => Err(ser::Error::custom("unsupported hash function"))?
-        }
+        return s.serialize_u8(match hash {
+            HashAlgorithm::Blake3   => 0,
+            HashAlgorithm::Sha3     => 1,
+            HashAlgorithm::Rescue   => 2,
+            HashAlgorithm::Poseidon => 3,
+            HashAlgorithm::Gmimc    => 4,
+        });
     }
     
-    pub fn deserialize<'de, D>(deserializer: D) -> Result<HashFunction, D::Error>
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<HashAlgorithm, D::Error>
     where
         D: Deserializer<'de>
     {
         match Deserialize::deserialize(deserializer)? {
-            0u8 => Ok(hash::blake3),
+            0u8 => Ok(HashAlgorithm::Blake3),
+            1u8 => Ok(HashAlgorithm::Sha3),
+            2u8 => Ok(HashAlgorithm::Rescue),
+            3u8 => Ok(HashAlgorithm::Poseidon),
+            4u8 => Ok(HashAlgorithm::Gmimc),
             _ => Err(de::Error::custom("unsupported hash function"))
         }
     }
-}
\ No newline at end of file
+}
@@ -1,6 +1,8 @@
 use serde::{ Serialize, Deserialize };
 use crate::crypto::{ BatchMerkleProof };
-use crate::stark::{ fri::FriProof, TraceState, ProofOptions };
+use crate::math::{ FieldElement, f128::BaseElement };
+use crate::processor::VmState;
+use crate::stark::{ fri::FriProof, TraceInfo, ProofOptions, utils };
 use crate::utils::{ uninit_vector, as_bytes };
 use sp_std::vec::Vec;
 use alloc::string::String;
@@ -21,68 +23,92 @@ pub struct ProgramAssembly{
 }
 
 
+/// A STARK proof; all values in the proof are elements of the base field. When the proof is
+/// generated using a field extension, values from the extension field (out-of-domain trace
+/// states, constraint evaluations, and FRI values) are recorded as their base field coefficients.
+/// When the trace has an auxiliary segment, the segment is committed to in a separate tree, and
+/// trace evaluations at queried positions consist of main registers followed by auxiliary ones.
 #[derive(Clone, Serialize, Deserialize)]
-pub struct StarkProof {
+#[serde(bound = "E: FieldElement")]
+pub struct StarkProof<E: FieldElement = BaseElement> {
     trace_root          : [u8; 32],
-    trace_info          : TraceInfo,
+    trace_shape         : TraceShape,
     trace_nodes         : Vec<Vec<[u8; 32]>>,
-    trace_evaluations   : Vec<Vec<u128>>,
+    trace_evaluations   : Vec<Vec<E>>,
+    aux_root            : Option<[u8; 32]>,
+    aux_nodes           : Vec<Vec<[u8; 32]>>,
     constraint_root     : [u8; 32],
-    constraint_proof    : BatchMerkleProof,
-    deep_values         : DeepValues,
-    degree_proof        : FriProof,
+    constraint_depth    : u8,
+    constraint_nodes    : Vec<Vec<[u8; 32]>>,
+    constraint_rows     : Vec<Vec<E>>,
+    deep_values         : DeepValues<E>,
+    degree_proof        : FriProof<E>,
     pow_nonce           : u64,
     options             : ProofOptions
 }
 
+/// A proof of a single segment of a program execution together with the states of the VM at
+/// the start and at the end of the segment.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SegmentProof {
+    pub entry           : VmState,
+    pub exit            : VmState,
+    pub proof           : StarkProof,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct DeepValues {
-    pub trace_at_z1     : Vec<u128>,
-    pub trace_at_z2     : Vec<u128>,
+#[serde(bound = "E: FieldElement")]
+pub struct DeepValues<E: FieldElement = BaseElement> {
+    pub trace_at_z1     : Vec<E>,
+    pub trace_at_z2     : Vec<E>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct TraceInfo {
-    pub domain_depth    : u8,
-    pub ctx_depth       : u8,
-    pub loop_depth      : u8,
-    pub stack_depth     : u8,
-    pub op_count        : u32,
+struct TraceShape {
+    domain_depth        : u8,
+    width               : u8,
+    meta                : Vec<u8>,
 }
 
 // STARK PROOF IMPLEMENTATION
 // ================================================================================================
-impl StarkProof {
+impl<E: FieldElement> StarkProof<E> {
     pub fn new(
         trace_root          : &[u8; 32],
         trace_proof         : BatchMerkleProof,
-        trace_evaluations   : Vec<Vec<u128>>,
+        trace_evaluations   : Vec<Vec<E>>,
+        aux_commitment      : Option<(&[u8; 32], BatchMerkleProof)>,
         constraint_root     : &[u8; 32],
         constraint_proof    : BatchMerkleProof,
-        deep_values         : DeepValues,
-        degree_proof        : FriProof,
+        constraint_rows     : Vec<Vec<E>>,
+        deep_values         : DeepValues<E>,
+        degree_proof        : FriProof<E>,
         pow_nonce           : u64,
-        op_count            : u128,
-        ctx_depth           : usize,
-        loop_depth          : usize,
-        stack_depth         : usize,
-        options             : &ProofOptions ) -> StarkProof
+        trace_info          : TraceInfo,
+        options             : &ProofOptions ) -> StarkProof<E>
     {
-        let trace_info = TraceInfo {
+        let trace_shape = TraceShape {
             domain_depth        : trace_proof.depth,
-            ctx_depth           : ctx_depth as u8,
-            loop_depth          : loop_depth as u8,
-            stack_depth         : stack_depth as u8,
-            op_count            : op_count as u32,
+            width               : trace_info.width as u8,
+            meta                : trace_info.meta,
+        };
+
+        let (aux_root, aux_nodes) = match aux_commitment {
+            Some((aux_root, aux_proof)) => (Some(*aux_root), aux_proof.nodes),
+            None => (None, Vec::new()),
         };
 
         return StarkProof {
             trace_root          : *trace_root,
-            trace_info          : trace_info,
+            trace_shape         : trace_shape,
             trace_nodes         : trace_proof.nodes,
             trace_evaluations   : trace_evaluations,
+            aux_root            : aux_root,
+            aux_nodes           : aux_nodes,
             constraint_root     : *constraint_root,
-            constraint_proof    : constraint_proof,
+            constraint_depth    : constraint_proof.depth,
+            constraint_nodes    : constraint_proof.nodes,
+            constraint_rows     : constraint_rows,
             deep_values         : deep_values,
             degree_proof        : degree_proof,
             pow_nonce           : pow_nonce,
@@ -99,37 +125,80 @@ impl StarkProof {
     }
 
     pub fn domain_size(&self) -> usize {
-        return usize::pow(2, self.trace_info.domain_depth as u32);
+        return usize::pow(2, self.trace_shape.domain_depth as u32);
     }
 
+    /// Returns a batch proof for main registers of trace evaluations; trace evaluations must
+    /// contain at least `trace_info().width` values each.
     pub fn trace_proof(&self) -> BatchMerkleProof {
-
-        let hash = self.options.hash_fn();
-        let mut hashed_states = uninit_vector::<[u8; 32]>(self.trace_evaluations.len());
-        for i in 0..self.trace_evaluations.len() {
-            hash(as_bytes(&self.trace_evaluations[i]), &mut hashed_states[i]);
-        }
+        let width = self.trace_shape.width as usize;
+        let hashed_states = self.hash_trace_evaluations(|row| &row[..width]);
 
         return BatchMerkleProof {
             nodes   : self.trace_nodes.clone(),
             values  : hashed_states,
-            depth   : self.trace_info.domain_depth,
+            depth   : self.trace_shape.domain_depth,
          };
     }
 
+    pub fn aux_root(&self) -> Option<&[u8; 32]> {
+        return self.aux_root.as_ref();
+    }
+
+    /// Returns a batch proof for auxiliary registers of trace evaluations; trace evaluations
+    /// must contain at least `trace_info().width` values each.
+    pub fn aux_proof(&self) -> BatchMerkleProof {
+        let width = self.trace_shape.width as usize;
+        let hashed_states = self.hash_trace_evaluations(|row| &row[width..]);
+
+        return BatchMerkleProof {
+            nodes   : self.aux_nodes.clone(),
+            values  : hashed_states,
+            depth   : self.trace_shape.domain_depth,
+        };
+    }
+
+    /// Hashes the specified part of every row of trace evaluations.
+    fn hash_trace_evaluations<'a, F: Fn(&'a [E]) -> &'a [E]>(&'a self, part: F) -> Vec<[u8; 32]> {
+        let hash = self.options.hash_fn();
+        let mut hashed_states = uninit_vector::<[u8; 32]>(self.trace_evaluations.len());
+        for i in 0..self.trace_evaluations.len() {
+            hash(as_bytes(part(&self.trace_evaluations[i])), &mut hashed_states[i]);
+        }
+        return hashed_states;
+    }
+
     pub fn constraint_root(&self) -> &[u8; 32] {
         return &self.constraint_root;
     }
 
     pub fn constraint_proof(&self) -> BatchMerkleProof {
-        return self.constraint_proof.clone();
+
+        let hash = self.options.hash_fn();
+        let mut leaves = uninit_vector::<[u8; 32]>(self.constraint_rows.len());
+        for i in 0..self.constraint_rows.len() {
+            leaves[i] = utils::constraint_row_to_leaf(&self.constraint_rows[i], hash);
+        }
+
+        return BatchMerkleProof {
+            nodes   : self.constraint_nodes.clone(),
+            values  : leaves,
+            depth   : self.constraint_depth,
+        };
+    }
+
+    /// Returns rows of constraint evaluations at queried positions; in zero-knowledge mode,
+    /// a row consists of a constraint and a mask evaluation, otherwise, of 2 constraint
+    /// evaluations at consecutive positions.
+    pub fn constraint_rows(&self) -> &[Vec<E>] {
+        return &self.constraint_rows;
     }
 
-    pub fn degree_proof(&self) -> &FriProof {
+    pub fn degree_proof(&self) -> &FriProof<E> {
         return &self.degree_proof;
     }
 
-    pub fn trace_evaluations(&self) -> &[Vec<u128>] {
+    pub fn trace_evaluations(&self) -> &[Vec<E>] {
         return &self.trace_evaluations;
     }
 
@@ -143,37 +212,23 @@ impl StarkProof {
         return self.domain_size() / self.options.extension_factor();
     }
 
-    pub fn ctx_depth(&self) -> usize {
-        return self.trace_info.ctx_depth as usize;
-    }
-
-    pub fn loop_depth(&self) -> usize {
-        return self.trace_info.loop_depth as usize;
+    /// Returns the shape of the execution trace as claimed by the prover.
+    pub fn trace_info(&self) -> TraceInfo {
+        return TraceInfo {
+            width           : self.trace_shape.width as usize,
+            length          : self.trace_length(),
+            blinding_rows   : self.options.blinding_rows(),
+            meta            : self.trace_shape.meta.clone(),
+        };
     }
 
-    pub fn stack_depth(&self) -> usize {
-        return self.trace_info.stack_depth as usize;
+    // DEEP VALUES
+    // -------------------------------------------------------------------------------------------
+    pub fn trace_at_z1(&self) -> &[E] {
+        return &self.deep_values.trace_at_z1;
     }
 
-    pub fn op_count(&self) -> u128 {
-        return self.trace_info.op_count as u128;
+    pub fn trace_at_z2(&self) -> &[E] {
+        return &self.deep_values.trace_at_z2;
     }
-
-    // DEEP VALUES
-    // -------------------------------------------------------------------------------------------
-    pub fn get_state_at_z1(&self) -> TraceState {
-        return TraceState::from_vec(
-            self.ctx_depth(),
-            self.loop_depth(),
-            self.stack_depth(),
-            &self.deep_values.trace_at_z1);
-    }
-
-    pub fn get_state_at_z2(&self) -> TraceState {
-        return TraceState::from_vec(
-            self.ctx_depth(),
-            self.loop_depth(),
-            self.stack_depth(),
-            &self.deep_values.trace_at_z2);
-    }
-}
\ No newline at end of file
+}
@@ -1,40 +1,127 @@
 use log::debug;
 use crate::{
-    math::{ field, polynom, fft },
-    crypto::MerkleTree,
+    math::{ polynom, fft, parallel, FieldElement, StarkField },
+    crypto::{ MerkleTree, HashFunction },
+    utils::as_bytes,
 };
 use super::{
-    ProofOptions, StarkProof, CompositionCoefficients, DeepValues, fri, utils,
-    trace::{ TraceTable, TraceState },
+    Air, ProofOptions, FieldExtension, ProverConfig, StarkProof, CompositionCoefficients, DeepValues, fri, utils,
+    metrics::{ ProverMetrics, ProverPhase, ProverError, PhaseTracker, ProgressCallback },
+    trace::TraceTable,
     constraints::{ ConstraintTable, ConstraintPoly },
-    MAX_CONSTRAINT_DEGREE,
 };
 
-use sp_std::vec::Vec;
+use sp_std::{vec, vec::Vec};
 // use wasm_bindgen_test::*;
 
 // PROVER FUNCTION
 // ================================================================================================
 
-pub fn prove(trace: &mut TraceTable, inputs: &[u128], outputs: &[u128], options: &ProofOptions) -> StarkProof {
+/// Generates a STARK proof attesting that the `trace` is a valid execution trace of the
+/// computation described by AIR `A` instantiated with `pub_inputs`.
+pub fn prove<A: Air>(trace: &mut TraceTable<A::BaseElement>, pub_inputs: &A::PublicInputs, options: &ProofOptions) -> StarkProof<A::BaseElement> {
+    return match prove_with_metrics::<A>(trace, pub_inputs, options, &ProverConfig::default(), None) {
+        Ok((proof, _)) => proof,
+        Err(err) => panic!("{}", err),
+    };
+}
+
+/// Same as `prove()` but also returns wall time and peak allocation for every phase of proof
+/// generation. If `progress` callback is provided, it is invoked at the start of every phase,
+/// and proof generation is aborted as soon as the callback returns `false`. The work is split
+/// across the number of threads specified by `config`; this does not affect the proof.
+/// 
+/// Returns an error if the field extension specified by `options` is not supported for the
+/// base field of the computation, or by the AIR of the computation.
+pub fn prove_with_metrics<A: Air>(
+    trace       : &mut TraceTable<A::BaseElement>,
+    pub_inputs  : &A::PublicInputs,
+    options     : &ProofOptions,
+    config      : &ProverConfig,
+    progress    : Option<ProgressCallback>) -> Result<(StarkProof<A::BaseElement>, ProverMetrics), ProverError>
+{
+    if !A::supports_extension(options.field_extension()) {
+        return Err(ProverError::UnsupportedExtension(options.field_extension()));
+    }
+
+    return match options.field_extension() {
+        FieldExtension::None => {
+            generate_proof::<A, A::BaseElement>(trace, pub_inputs, options, config, progress)
+        },
+        FieldExtension::Quadratic => {
+            if <A::BaseElement as StarkField>::QuadField::EXTENSION_DEGREE != 2 {
+                return Err(ProverError::UnsupportedExtension(FieldExtension::Quadratic));
+            }
+            generate_proof::<A, <A::BaseElement as StarkField>::QuadField>(trace, pub_inputs, options, config, progress)
+        },
+        FieldExtension::Cubic => {
+            if <A::BaseElement as StarkField>::CubeField::EXTENSION_DEGREE != 3 {
+                return Err(ProverError::UnsupportedExtension(FieldExtension::Cubic));
+            }
+            generate_proof::<A, <A::BaseElement as StarkField>::CubeField>(trace, pub_inputs, options, config, progress)
+        },
+    };
+}
+
+/// Generates a STARK proof with out-of-domain sampling, constraint composition, and FRI
+/// performed over field `E`, which is either the base field of the computation or its
+/// extension.
+fn generate_proof<A: Air, E: FieldElement<BaseField = A::BaseElement>>(
+    trace       : &mut TraceTable<A::BaseElement>,
+    pub_inputs  : &A::PublicInputs,
+    options     : &ProofOptions,
+    config      : &ProverConfig,
+    progress    : Option<ProgressCallback>) -> Result<(StarkProof<A::BaseElement>, ProverMetrics), ProverError>
+{
+    let mut tracker = PhaseTracker::new(progress);
+    let num_threads = config.num_threads;
+
     // 1 ----- extend execution trace -------------------------------------------------------------
+    tracker.start(ProverPhase::TraceExtension)?;
 
     // console_log!("trace is {:?}, inputs is{:?},outputs is {:?}.options is {:?}",serde_json::to_string(&trace).unwrap(),serde_json::to_string(&inputs).unwrap(),serde_json::to_string(&outputs).unwrap(),serde_json::to_string(&options).unwrap());
 
 
+    // in zero-knowledge mode, append random rows to the execution trace
+    if options.zk() {
+        trace.blind(options.blinding_rows());
+    }
+
+    // instantiate AIR of the computation for the shape of the (blinded) trace
+    let air = match A::new(&trace.info(), pub_inputs) {
+        Ok(air) => air,
+        Err(msg) => panic!("execution trace does not match the computation: {}", msg),
+    };
+
     // build LDE domain and LDE twiddles (for FFT evaluation over LDE domain)
-    let lde_root = field::get_root_of_unity(trace.domain_size());
+    let lde_root = A::BaseElement::get_root_of_unity(trace.domain_size());
     // console_log!("lde_root is{:?}",lde_root);
 
     // console_log!("2 paras: lde_root is {:?},trace.domain_size() is {:?}",lde_root,trace.domain_size());
-    let lde_domain = field::get_power_series(lde_root, trace.domain_size());
+    let mut lde_domain = A::BaseElement::get_power_series(lde_root, trace.domain_size());
     // console_log!("lde_domain is{:?}",lde_domain);
 
     let lde_twiddles = twiddles_from_domain(&lde_domain);
     // console_log!("lde_twiddles is{:?}",lde_twiddles);
 
-    // extend the execution trace registers to LDE domain
-    trace.extend(&lde_twiddles);
+    // in zero-knowledge mode, the LDE domain is shifted to a coset; twiddles are not affected
+    // by the shift because polynomials are shifted instead before they are evaluated
+    let domain_offset: A::BaseElement = options.domain_offset();
+    if domain_offset != A::BaseElement::ONE {
+        lde_domain.iter_mut().for_each(|x| *x = *x * domain_offset);
+    }
+
+    // extend the execution trace registers to LDE domain; in low-memory mode, registers are
+    // only interpolated, and the extended trace is evaluated segment by segment when needed
+    if config.low_memory {
+        trace.extend_lazily(domain_offset, num_threads);
+    }
+    else {
+        trace.extend(&lde_twiddles, domain_offset, num_threads);
+    }
+
+    // polynomials over field E are evaluated over the same domain using the same twiddles
+    let twiddles = lde_twiddles.iter().map(|&t| E::from(t)).collect::<Vec<E>>();
     debug!("Extended execution trace from {} to {} steps",
         trace.unextended_length(),
         trace.domain_size());
@@ -42,34 +129,32 @@ pub fn prove(trace: &mut TraceTable, inputs: &[u128], outputs: &[u128], options:
     // console_log!("trace before 2 is {:?}",serde_json::to_string(&trace).unwrap());
 
     // 2 ----- build Merkle tree from the extended execution trace ------------------------------------
-    let trace_tree = trace.build_merkle_tree(options.hash_fn());
-
+    tracker.start(ProverPhase::TraceCommitment)?;
+    let trace_tree = trace.build_merkle_tree(options.hash_algorithm(), num_threads);
+
+    // if the computation has an auxiliary trace segment, build it using random elements drawn
+    // from the root of the trace tree, extend it, and put it into a separate Merkle tree
+    let aux_rand = A::BaseElement::prng_vector(*trace_tree.root(), air.aux_rand_count());
+    let aux_tree = if air.aux_width() > 0 {
+        let aux_registers = air.build_aux_registers(trace, &aux_rand);
+        assert!(aux_registers.len() == air.aux_width(),
+            "expected {} auxiliary registers, but {} were built", air.aux_width(), aux_registers.len());
+        trace.extend_aux(aux_registers, &lde_twiddles);
+        Some(trace.build_aux_merkle_tree(options.hash_algorithm(), num_threads))
+    }
+    else {
+        None
+    };
 
     // 3 ----- evaluate constraints ---------------------------------------------------------------
-    
-    // initialize constraint evaluation table
-    let mut constraints = ConstraintTable::new(&trace, trace_tree.root(), inputs, outputs);
-
-    // allocate space to hold current and next states for constraint evaluations
-    let mut current = TraceState::new(trace.ctx_depth(), trace.loop_depth(), trace.stack_depth());
-    let mut next = TraceState::new(trace.ctx_depth(), trace.loop_depth(), trace.stack_depth());
-
-    // we don't need to evaluate constraints over the entire extended execution trace; we need
-    // to evaluate them over the domain extended to match max constraint degree - thus, we can
-    // skip most trace states for the purposes of constraint evaluation.
-    let stride = trace.extension_factor() / MAX_CONSTRAINT_DEGREE;
-    for i in (0..trace.domain_size()).step_by(stride) {
-        // TODO: this loop should be parallelized and also potentially optimized to avoid copying
-        // next state from the trace table twice
-
-        // copy current and next states from the trace table; next state may wrap around the
-        // execution trace (close to the end of the trace)
-        trace.fill_state(&mut current, i);
-        trace.fill_state(&mut next, (i + trace.extension_factor()) % trace.domain_size());
+    tracker.start(ProverPhase::ConstraintEvaluation)?;
 
-        // evaluate the constraints
-        constraints.evaluate(&current, &next, lde_domain[i], i / stride);
-    }
+    // initialize constraint evaluation table and evaluate constraints over the evaluation domain;
+    // coefficients for combining constraints are derived from the roots of the trace trees
+    let aux_root = aux_tree.as_ref().map(|tree| tree.root());
+    let constraint_seed = utils::get_constraint_seed(trace_tree.root(), aux_root, options.hash_fn());
+    let mut constraints = ConstraintTable::<A, E>::new(air, &trace, &constraint_seed, aux_rand);
+    constraints.evaluate_trace(&trace, &lde_domain, num_threads);
 
     debug!("Evaluated {} constraints over domain of {} elements",
         constraints.constraint_count(),
@@ -83,13 +168,26 @@ pub fn prove(trace: &mut TraceTable, inputs: &[u128], outputs: &[u128], options:
     // 5 ----- build Merkle tree from constraint polynomial evaluations ---------------------------
     
     // evaluate constraint polynomial over the evaluation domain
-    let constraint_evaluations = constraint_poly.eval(&lde_twiddles);
+    let constraint_evaluations = constraint_poly.eval(&twiddles, E::from(domain_offset));
+
+    // in zero-knowledge mode, evaluate a random polynomial of composition degree over the
+    // evaluation domain; this polynomial masks the deep composition polynomial
+    let composition_degree = utils::get_composition_degree(trace.unextended_length(), trace.blinding_rows());
+    let mask_evaluations = if options.zk() {
+        build_mask_evaluations(composition_degree, &twiddles, E::from(domain_offset))
+    }
+    else {
+        Vec::new()
+    };
 
-    // put evaluations into a Merkle tree; 4 evaluations per leaf
-    let constraint_evaluations = evaluations_to_leaves(constraint_evaluations);
-    let constraint_tree = MerkleTree::new(constraint_evaluations, options.hash_fn());
+    // put evaluations into a Merkle tree; 2 evaluations per leaf, or in zero-knowledge mode,
+    // constraint and mask evaluations at the same position in a single leaf
+    let constraint_rows = build_constraint_rows(constraint_evaluations, &mask_evaluations);
+    let constraint_leaves = rows_to_leaves(&constraint_rows, options.hash_fn());
+    let constraint_tree = MerkleTree::new_concurrent(constraint_leaves, options.hash_algorithm(), num_threads);
 
     // 6 ----- build and evaluate deep composition polynomial -------------------------------------
+    tracker.start(ProverPhase::Composition)?;
 
     // combine trace and constraint polynomials into the final deep composition polynomial
     let seed = constraint_tree.root();
@@ -98,35 +196,44 @@ pub fn prove(trace: &mut TraceTable, inputs: &[u128], outputs: &[u128], options:
     // evaluate the composition polynomial over LDE domain
     let mut composed_evaluations = composition_poly;
     debug_assert!(composed_evaluations.capacity() == lde_domain.len(), "invalid composition polynomial capacity");
+    polynom::shift(&mut composed_evaluations, E::from(domain_offset));
     unsafe { composed_evaluations.set_len(composed_evaluations.capacity()); }
-    polynom::eval_fft_twiddles(&mut composed_evaluations, &lde_twiddles, true);
+    polynom::eval_fft_twiddles(&mut composed_evaluations, &twiddles, true);
+
+    // in zero-knowledge mode, mask the composition polynomial
+    if options.zk() {
+        parallel::add_in_place(&mut composed_evaluations, &mask_evaluations, num_threads);
+    }
 
     debug!("Built composition polynomial and evaluated it over domain of {} elements",
         composed_evaluations.len());
 
 
     // 7 ----- compute FRI layers for the composition polynomial ----------------------------------
-    let composition_degree = utils::get_composition_degree(trace.unextended_length());
+    tracker.start(ProverPhase::FriLayers)?;
     debug_assert!(composition_degree == polynom::infer_degree(&composed_evaluations));
-    let (fri_trees, fri_values) = fri::reduce(&composed_evaluations, &lde_domain, options);
+    let (fri_trees, fri_values, fri_remainder) = fri::reduce(&composed_evaluations, &lde_domain, options);
 
 
     // 8 ----- determine query positions -----------------------------------------------------------
+    tracker.start(ProverPhase::ProofOfWork)?;
 
-    // combine all FRI layer roots into a single vector
+    // combine all FRI layer roots and the remainder into a single vector
     let mut fri_roots: Vec<u8> = Vec::new();
     for tree in fri_trees.iter() {
         tree.root().iter().for_each(|&v| fri_roots.push(v));
     }
+    as_bytes(&fri_remainder).iter().for_each(|&v| fri_roots.push(v));
 
     // derive a seed from the combined roots
     let mut seed = [0u8; 32];
     options.hash_fn()(&fri_roots, &mut seed);
 
     // apply proof-of-work to get a new seed
-    let (seed, pow_nonce) = utils::find_pow_nonce(seed, &options);
+    let (seed, pow_nonce) = utils::find_pow_nonce(seed, &options, num_threads);
 
     // generate pseudo-random query positions
+    tracker.start(ProverPhase::QueryBuilding)?;
     // console_log!("seed is {:?},lde_domain.len is {:?}, options is {:?}",seed,lde_domain.len(),serde_json::to_string(&options).unwrap());
 
     let positions = utils::compute_query_positions(&seed, lde_domain.len(), options);
@@ -139,54 +246,81 @@ pub fn prove(trace: &mut TraceTable, inputs: &[u128], outputs: &[u128], options:
     // 9 ----- build proof object -----------------------------------------------------------------
 
     // generate FRI proof
-    let fri_proof = fri::build_proof(fri_trees, fri_values, &positions);
+    let fri_proof = fri::build_proof(fri_trees, fri_values, fri_remainder, &positions, options);
 
     // built a list of trace evaluations at queried positions
     let trace_evaluations = trace.get_register_values_at(&positions);
 
-    // build a list of constraint positions
-    let constraint_positions = utils::map_trace_to_constraint_positions(&positions);
+    // build a list of constraint positions and constraint evaluations at these positions
+    let constraint_positions = utils::map_trace_to_constraint_positions(&positions, options);
+    let constraint_rows = constraint_positions.iter()
+        .map(|&p| E::as_base_elements(&constraint_rows[(p * 2)..(p * 2 + 2)]).to_vec())
+        .collect();
 
     // build the proof object
     let proof = StarkProof::new(
         trace_tree.root(),
         trace_tree.prove_batch(&positions),
         trace_evaluations,
+        aux_tree.as_ref().map(|tree| (tree.root(), tree.prove_batch(&positions))),
         constraint_tree.root(),
         constraint_tree.prove_batch(&constraint_positions),
+        constraint_rows,
         deep_values,
         fri_proof,
         pow_nonce,
-        trace.get_last_state().op_counter(),
-        trace.ctx_depth(),
-        trace.loop_depth(),
-        trace.stack_depth(),
+        trace.info(),
         &options);
 
-    return proof;
+    return Ok((proof, tracker.finish()));
 }
 
 // HELPER FUNCTIONS
 // ================================================================================================
-fn twiddles_from_domain(domain: &[u128]) -> Vec<u128> {
+fn twiddles_from_domain<E: FieldElement>(domain: &[E]) -> Vec<E> {
     let mut twiddles = domain[..(domain.len() / 2)].to_vec();
     fft::permute(&mut twiddles);
     return twiddles;
 }
 
-/// Re-interpret vector of 16-byte values as a vector of 32-byte arrays
-fn evaluations_to_leaves(evaluations: Vec<u128>) -> Vec<[u8; 32]> {
-    assert!(evaluations.len() % 2 == 0, "number of values must be divisible by 2");
-    let mut v = sp_std::mem::ManuallyDrop::new(evaluations);
-    let p = v.as_mut_ptr();
-    let len = v.len() / 2;
-    let cap = v.capacity() / 2;
-    return unsafe { Vec::from_raw_parts(p as *mut [u8; 32], len, cap) };
+/// Converts a flattened matrix of constraint evaluations with 2 values per row into leaves of
+/// constraint Merkle tree.
+fn rows_to_leaves<E: FieldElement>(rows: &[E], hash: HashFunction) -> Vec<[u8; 32]> {
+    assert!(rows.len() % 2 == 0, "number of values must be divisible by 2");
+    return rows.chunks(2).map(|row| utils::constraint_row_to_leaf(row, hash)).collect();
 }
 
-fn build_composition_poly(trace: &TraceTable, constraint_poly: ConstraintPoly, seed: &[u8; 32]) -> (Vec<u128>, DeepValues) {
+/// Arranges constraint evaluations into rows of constraint Merkle tree with 2 values per row;
+/// if mask evaluations are provided, each row contains a constraint and a mask evaluation at
+/// the same position.
+fn build_constraint_rows<E: FieldElement>(constraint_evaluations: Vec<E>, mask_evaluations: &[E]) -> Vec<E> {
+    if mask_evaluations.is_empty() {
+        return constraint_evaluations;
+    }
+
+    let mut evaluations = Vec::with_capacity(constraint_evaluations.len() * 2);
+    for (&constraint, &mask) in constraint_evaluations.iter().zip(mask_evaluations) {
+        evaluations.push(constraint);
+        evaluations.push(mask);
+    }
+    return evaluations;
+}
+
+/// Evaluates a random polynomial of the specified degree over the domain defined by `twiddles`
+/// shifted by `domain_offset`.
+fn build_mask_evaluations<E: FieldElement>(degree: usize, twiddles: &[E], domain_offset: E) -> Vec<E> {
+    let mut evaluations = vec![E::ZERO; twiddles.len() * 2];
+    evaluations[..(degree + 1)].copy_from_slice(&E::rand_vector(degree + 1));
+    polynom::shift(&mut evaluations[..(degree + 1)], domain_offset);
+    polynom::eval_fft_twiddles(&mut evaluations, twiddles, true);
+    return evaluations;
+}
+
+fn build_composition_poly<B, E>(trace: &TraceTable<B>, constraint_poly: ConstraintPoly<E>, seed: &[u8; 32]) -> (Vec<E>, DeepValues<B>)
+    where B: StarkField, E: FieldElement<BaseField = B>
+{
     // pseudo-randomly selection deep point z and coefficients for the composition
-    let z = field::prng(*seed);
+    let z = E::prng(*seed);
     let coefficients = CompositionCoefficients::new(*seed);
 
     // divide out deep point from trace polynomials and merge them into a single polynomial
@@ -195,5 +329,109 @@ fn build_composition_poly(trace: &TraceTable, constraint_poly: ConstraintPoly, s
     // divide out deep point from constraint polynomial and merge it into the result
     constraint_poly.merge_into(&mut result, z, &coefficients);
 
-    return (result, DeepValues { trace_at_z1: s1, trace_at_z2: s2 });
-}
\ No newline at end of file
+    // out-of-domain trace states are recorded in the proof as base field elements
+    let deep_values = DeepValues {
+        trace_at_z1 : E::as_base_elements(&s1).to_vec(),
+        trace_at_z2 : E::as_base_elements(&s2).to_vec(),
+    };
+
+    return (result, deep_values);
+}
+// TESTS
+// ================================================================================================
+#[cfg(test)]
+mod tests {
+
+    use crate::{
+        processor, programs::{ assembly, ProgramInputs },
+        stark::{ TraceTable, ProcessorAir, ProcessorPublicInputs, verify },
+        math::{ fft, f128::BaseElement, StarkField }, ProofOptions, ProverConfig, StarkProof
+    };
+
+    #[test]
+    fn prove_with_threads() {
+        let options = ProofOptions::default();
+        let expected = prove("begin push.3 push.5 add push.3 mul end", &options, 1);
+        for &num_threads in [2, 4, 8].iter() {
+            assert_eq!(expected, prove("begin push.3 push.5 add push.3 mul end", &options, num_threads));
+        }
+    }
+
+    #[test]
+    fn prove_low_memory() {
+        let source = "begin push.3 push.5 add push.3 mul end";
+        let options = ProofOptions::default();
+        let expected = prove(source, &options, 1);
+        for &num_threads in [1, 4].iter() {
+            let config = ProverConfig::new(num_threads).with_low_memory();
+            assert_eq!(expected, prove_with_config(source, &options, &config));
+        }
+
+        // in zero-knowledge mode, the trace is extended over a coset of the LDE domain
+        let program = assembly::compile(source).unwrap();
+        let options = ProofOptions::default().with_zk();
+        let config = ProverConfig::new(2).with_low_memory();
+        let proof: StarkProof = bincode::deserialize(&prove_with_config(source, &options, &config)).unwrap();
+        assert_eq!(Ok(true), verify::<ProcessorAir>(&proof, &pub_inputs(program.hash(), 24)));
+    }
+
+    #[test]
+    fn prove_verify_zk() {
+        let source = "begin push.3 push.5 add push.3 mul end";
+        let program = assembly::compile(source).unwrap();
+        let options = ProofOptions::default().with_zk();
+
+        let proof: StarkProof = bincode::deserialize(&prove(source, &options, 1)).unwrap();
+        assert_eq!(Ok(true), verify::<ProcessorAir>(&proof, &pub_inputs(program.hash(), 24)));
+
+        // proof for wrong outputs should fail
+        assert!(verify::<ProcessorAir>(&proof, &pub_inputs(program.hash(), 25)).is_err());
+
+        // proofs of the same execution are randomized
+        let proof2: StarkProof = bincode::deserialize(&prove(source, &options, 4)).unwrap();
+        assert_eq!(Ok(true), verify::<ProcessorAir>(&proof2, &pub_inputs(program.hash(), 24)));
+        assert_ne!(proof.trace_root(), proof2.trace_root());
+    }
+
+    #[test]
+    fn blinded_trace() {
+        let options = ProofOptions::default().with_zk();
+        let mut trace = build_trace("begin push.3 push.5 add push.3 mul end", &options);
+        let last_state = trace.get_last_state();
+        let trace_length = trace.unextended_length();
+
+        trace.blind(options.blinding_rows());
+        assert_eq!((trace_length + options.blinding_rows()).next_power_of_two(), trace.unextended_length());
+        assert_eq!(last_state.to_vec(), trace.get_last_state().to_vec());
+        assert!(crate::stark::check_trace(&trace, &[], &[24]).is_ok());
+
+        // last state is computed from trace polynomials when trace is extended over a coset
+        let lde_root = BaseElement::get_root_of_unity(trace.domain_size());
+        let twiddles = fft::get_twiddles(lde_root, trace.domain_size());
+        trace.extend(&twiddles, options.domain_offset(), 1);
+        assert_eq!(last_state.to_vec(), trace.get_last_state().to_vec());
+    }
+
+    fn prove(source: &str, options: &ProofOptions, num_threads: usize) -> Vec<u8> {
+        return prove_with_config(source, options, &ProverConfig::new(num_threads));
+    }
+
+    fn prove_with_config(source: &str, options: &ProofOptions, config: &ProverConfig) -> Vec<u8> {
+        let program = assembly::compile(source).unwrap();
+        let mut trace = build_trace(source, options);
+        let pub_inputs = pub_inputs(program.hash(), 24);
+        let (proof, _) = super::prove_with_metrics::<ProcessorAir>(&mut trace, &pub_inputs, options, config, None).unwrap();
+        return bincode::serialize(&proof).unwrap();
+    }
+
+    fn build_trace(source: &str, options: &ProofOptions) -> TraceTable {
+        let program = assembly::compile(source).unwrap();
+        let inputs = ProgramInputs::none();
+        let (trace, ctx_depth, loop_depth) = processor::execute(&program, &inputs, &Default::default()).unwrap();
+        return TraceTable::new(trace, ctx_depth, loop_depth, options.extension_factor());
+    }
+
+    fn pub_inputs(program_hash: &[u8; 32], output: u128) -> ProcessorPublicInputs {
+        return ProcessorPublicInputs { program_hash: *program_hash, inputs: vec![], outputs: vec![output] };
+    }
+}
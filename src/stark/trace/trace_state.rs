@@ -2,18 +2,28 @@ use sp_std::{ fmt, cmp, vec, vec::Vec };
 use crate::{
     math::field,
     OpCode,
+    processor::opcodes::FlowOps,
     PROGRAM_DIGEST_SIZE,
     MIN_STACK_DEPTH, MIN_CONTEXT_DEPTH, MIN_LOOP_DEPTH,
     OP_COUNTER_IDX, SPONGE_WIDTH, SPONGE_RANGE,
     NUM_CF_OPS, NUM_LD_OPS, NUM_HD_OPS,
     NUM_CF_OP_BITS, NUM_LD_OP_BITS, NUM_HD_OP_BITS,
-    CF_OP_BITS_RANGE, LD_OP_BITS_RANGE, HD_OP_BITS_RANGE,
+    NUM_LD_LO_HELPERS, NUM_LD_HI_HELPERS, NUM_OP_FLAG_HELPERS,
+    CF_OP_BITS_RANGE, LD_OP_BITS_RANGE, HD_OP_BITS_RANGE, OP_FLAG_HELPERS_RANGE,
 };
 
 // CONSTANTS
 // ================================================================================================
 const NUM_OP_BITS: usize = NUM_CF_OP_BITS + NUM_LD_OP_BITS + NUM_HD_OP_BITS;
-const NUM_STATIC_DECODER_REGISTERS: usize = 1 + SPONGE_WIDTH + NUM_OP_BITS; // 1 is for op_counter
+const NUM_STATIC_DECODER_REGISTERS: usize = 1 + SPONGE_WIDTH + NUM_OP_BITS + NUM_OP_FLAG_HELPERS; // 1 is for op_counter
+
+const HACC_HELPER_IDX   : usize = 0;
+const VOID_HELPER_IDX   : usize = 1;
+const BEGIN_HELPER_IDX  : usize = 2;
+const TEND_HELPER_IDX   : usize = 3;
+const LD_LO_HELPERS_IDX : usize = 4;
+const LD_HI_HELPERS_IDX : usize = LD_LO_HELPERS_IDX + NUM_LD_LO_HELPERS;
+const HD_HELPERS_IDX    : usize = LD_HI_HELPERS_IDX + NUM_LD_HI_HELPERS;
 
 // TYPES AND INTERFACES
 // ================================================================================================
@@ -24,6 +34,7 @@ pub struct TraceState {
     cf_op_bits  : [u128; NUM_CF_OP_BITS],
     ld_op_bits  : [u128; NUM_LD_OP_BITS],
     hd_op_bits  : [u128; NUM_HD_OP_BITS],
+    op_helpers  : [u128; NUM_OP_FLAG_HELPERS],
     ctx_stack   : Vec<u128>,
     loop_stack  : Vec<u128>,
     user_stack  : Vec<u128>,
@@ -55,6 +66,7 @@ impl TraceState {
             cf_op_bits  : [0; NUM_CF_OP_BITS],
             ld_op_bits  : [0; NUM_LD_OP_BITS],
             hd_op_bits  : [0; NUM_HD_OP_BITS],
+            op_helpers  : [0; NUM_OP_FLAG_HELPERS],
             ctx_stack   : vec![0; cmp::max(ctx_depth, MIN_CONTEXT_DEPTH)],
             loop_stack  : vec![0; cmp::max(loop_depth, MIN_LOOP_DEPTH)],
             user_stack  : vec![0; cmp::max(stack_depth, MIN_STACK_DEPTH)],
@@ -70,7 +82,7 @@ impl TraceState {
         };
     }
 
-    pub fn from_vec(ctx_depth: usize, loop_depth: usize, stack_depth: usize, state: &Vec<u128>) -> TraceState {
+    pub fn from_vec(ctx_depth: usize, loop_depth: usize, stack_depth: usize, state: &[u128]) -> TraceState {
 
         let op_counter = state[OP_COUNTER_IDX];
 
@@ -86,20 +98,23 @@ impl TraceState {
         let mut hd_op_bits = [0; NUM_HD_OP_BITS];
         hd_op_bits.copy_from_slice(&state[HD_OP_BITS_RANGE]);
 
+        let mut op_helpers = [0; NUM_OP_FLAG_HELPERS];
+        op_helpers.copy_from_slice(&state[OP_FLAG_HELPERS_RANGE]);
+
         let mut ctx_stack = vec![0; cmp::max(ctx_depth, MIN_CONTEXT_DEPTH)];
-        let ctx_stack_end = HD_OP_BITS_RANGE.end + ctx_depth;
-        ctx_stack[..ctx_depth].copy_from_slice(&state[HD_OP_BITS_RANGE.end..ctx_stack_end]);
+        let ctx_stack_end = OP_FLAG_HELPERS_RANGE.end + ctx_depth;
+        ctx_stack[..ctx_depth].copy_from_slice(&state[OP_FLAG_HELPERS_RANGE.end..ctx_stack_end]);
 
         let mut loop_stack = vec![0; cmp::max(loop_depth, MIN_LOOP_DEPTH)];
         let loop_stack_end = ctx_stack_end + loop_depth;
         loop_stack[..loop_depth].copy_from_slice(&state[ctx_stack_end..loop_stack_end]);
 
         let mut user_stack = vec![0; cmp::max(stack_depth, MIN_STACK_DEPTH)];
-        user_stack[..stack_depth].copy_from_slice(&state[loop_stack_end..]);
+        user_stack[..stack_depth].copy_from_slice(&state[loop_stack_end..(loop_stack_end + stack_depth)]);
 
         return TraceState {
             op_counter, sponge,
-            cf_op_bits, ld_op_bits, hd_op_bits,
+            cf_op_bits, ld_op_bits, hd_op_bits, op_helpers,
             ctx_stack, loop_stack, user_stack,
             ctx_depth, loop_depth, stack_depth,
             cf_op_flags : [0; NUM_CF_OPS],
@@ -111,6 +126,21 @@ impl TraceState {
         };
     }
 
+    /// Builds a trace state from a vector which omits op flag helper registers; values of the
+    /// helpers are computed from op bits.
+    #[cfg(test)]
+    pub fn from_vec_without_helpers(ctx_depth: usize, loop_depth: usize, stack_depth: usize, state: &[u128]) -> TraceState {
+        let mut full_state = state[..OP_FLAG_HELPERS_RANGE.start].to_vec();
+        full_state.resize(OP_FLAG_HELPERS_RANGE.end, 0);
+        full_state.extend_from_slice(&state[OP_FLAG_HELPERS_RANGE.start..]);
+
+        let mut result = TraceState::from_vec(ctx_depth, loop_depth, stack_depth, &full_state);
+        let mut op_bits = [0; NUM_OP_BITS];
+        op_bits.copy_from_slice(&state[CF_OP_BITS_RANGE.start..HD_OP_BITS_RANGE.end]);
+        result.set_op_bits(op_bits);
+        return result;
+    }
+
     // STATIC FUNCTIONS
     // --------------------------------------------------------------------------------------------
     pub fn compute_decoder_width(ctx_depth: usize, loop_depth: usize) -> usize {
@@ -120,7 +150,7 @@ impl TraceState {
     // PUBLIC ACCESSORS
     // --------------------------------------------------------------------------------------------
     pub fn width(&self) -> usize {
-        return HD_OP_BITS_RANGE.end + self.ctx_depth + self.loop_depth + self.stack_depth;
+        return OP_FLAG_HELPERS_RANGE.end + self.ctx_depth + self.loop_depth + self.stack_depth;
     }
 
     pub fn stack_depth(&self) -> usize {
@@ -173,10 +203,53 @@ impl TraceState {
         return result;
     }
 
+    /// Sets op bits to the provided values and populates op flag helpers with the values
+    /// implied by these bits.
     pub fn set_op_bits(&mut self, bits: [u128; NUM_OP_BITS]) {
         self.cf_op_bits.copy_from_slice(&bits[..3]);
         self.ld_op_bits.copy_from_slice(&bits[3..8]);
         self.hd_op_bits.copy_from_slice(&bits[8..]);
+        self.op_helpers = self.get_expected_op_helpers();
+        self.op_flags_set = false;
+    }
+
+    // OP FLAG HELPERS
+    // --------------------------------------------------------------------------------------------
+    pub fn op_helpers(&self) -> &[u128] {
+        return &self.op_helpers;
+    }
+
+    /// Sets op flag helpers to the provided values; op bits are left unchanged.
+    pub fn set_op_helpers(&mut self, helpers: &[u128]) {
+        self.op_helpers.copy_from_slice(helpers);
+        self.op_flags_set = false;
+    }
+
+    /// Computes values of op flag helpers directly from op bits. The degree of each value is
+    /// 3 for control flow helpers, 2 or 3 for low-degree operation helpers, and 2 for high-degree
+    /// operation helpers; the constraints enforce that helper registers are equal to these values.
+    pub fn get_expected_op_helpers(&self) -> [u128; NUM_OP_FLAG_HELPERS] {
+        let mut result = [0; NUM_OP_FLAG_HELPERS];
+
+        // control flow helpers
+        let cf_flags = get_bit_flags(&self.cf_op_bits);
+        result[HACC_HELPER_IDX] = cf_flags[FlowOps::Hacc.op_index()];
+        result[VOID_HELPER_IDX] = cf_flags[FlowOps::Void.op_index()];
+        result[BEGIN_HELPER_IDX] = cf_flags[FlowOps::Begin.op_index()];
+        result[TEND_HELPER_IDX] = cf_flags[FlowOps::Tend.op_index()];
+
+        // low-degree operation helpers
+        let ld_lo = get_bit_flags(&self.ld_op_bits[..2]);
+        result[LD_LO_HELPERS_IDX..LD_HI_HELPERS_IDX].copy_from_slice(&ld_lo[..NUM_LD_LO_HELPERS]);
+
+        let ld_hi = get_bit_flags(&self.ld_op_bits[2..]);
+        result[LD_HI_HELPERS_IDX..HD_HELPERS_IDX].copy_from_slice(&ld_hi[..NUM_LD_HI_HELPERS]);
+
+        // high-degree operation helpers
+        let hd = get_bit_flags(&self.hd_op_bits);
+        result[HD_HELPERS_IDX..].copy_from_slice(&hd[..NUM_HD_OPS]);
+
+        return result;
     }
 
     // OP FLAGS
@@ -242,35 +315,37 @@ impl TraceState {
         result.extend_from_slice(&self.cf_op_bits);
         result.extend_from_slice(&self.ld_op_bits);
         result.extend_from_slice(&self.hd_op_bits);
+        result.extend_from_slice(&self.op_helpers);
         result.extend_from_slice(&self.ctx_stack[..self.ctx_depth]);
         result.extend_from_slice(&self.loop_stack[..self.loop_depth]);
         result.extend_from_slice(&self.user_stack[..self.stack_depth]);
         return result;
     }
 
-    pub fn update_from_trace(&mut self, trace: &Vec<Vec<u128>>, step: usize) {
+    pub fn update_from_trace<T: Copy + Into<u128>>(&mut self, trace: &[Vec<T>], step: usize) {
 
-        self.op_counter = trace[OP_COUNTER_IDX][step];
+        self.op_counter = trace[OP_COUNTER_IDX][step].into();
 
-        for (i, j) in SPONGE_RANGE.enumerate()     { self.sponge[i] = trace[j][step]; }
-        for (i, j) in CF_OP_BITS_RANGE.enumerate() { self.cf_op_bits[i] = trace[j][step]; }
-        for (i, j) in LD_OP_BITS_RANGE.enumerate() { self.ld_op_bits[i] = trace[j][step]; }
-        for (i, j) in HD_OP_BITS_RANGE.enumerate() { self.hd_op_bits[i] = trace[j][step]; }
+        for (i, j) in SPONGE_RANGE.enumerate()     { self.sponge[i] = trace[j][step].into(); }
+        for (i, j) in CF_OP_BITS_RANGE.enumerate() { self.cf_op_bits[i] = trace[j][step].into(); }
+        for (i, j) in LD_OP_BITS_RANGE.enumerate() { self.ld_op_bits[i] = trace[j][step].into(); }
+        for (i, j) in HD_OP_BITS_RANGE.enumerate() { self.hd_op_bits[i] = trace[j][step].into(); }
+        for (i, j) in OP_FLAG_HELPERS_RANGE.enumerate() { self.op_helpers[i] = trace[j][step].into(); }
 
-        let ctx_stack_start = HD_OP_BITS_RANGE.end;
+        let ctx_stack_start = OP_FLAG_HELPERS_RANGE.end;
         let ctx_stack_end = ctx_stack_start + self.ctx_depth;
         for (i, j) in (ctx_stack_start..ctx_stack_end).enumerate() {
-            self.ctx_stack[i] = trace[j][step];
+            self.ctx_stack[i] = trace[j][step].into();
         }
 
         let loop_stack_end = ctx_stack_end + self.loop_depth;
         for (i, j) in (ctx_stack_end..loop_stack_end).enumerate() {
-            self.loop_stack[i] = trace[j][step];
+            self.loop_stack[i] = trace[j][step].into();
         }
 
         let user_stack_end = loop_stack_end + self.stack_depth;
         for (i, j) in (loop_stack_end..user_stack_end).enumerate() {
-            self.user_stack[i] = trace[j][step];
+            self.user_stack[i] = trace[j][step].into();
         }
         
         self.op_flags_set = false;
@@ -280,49 +355,25 @@ impl TraceState {
     // --------------------------------------------------------------------------------------------
     fn set_op_flags(&mut self) {
 
-        // set control flow flags
-        let not_0 = binary_not(self.cf_op_bits[0]);
-        let not_1 = binary_not(self.cf_op_bits[1]);
-        self.cf_op_flags[0] = field::mul(not_0, not_1);
-        self.cf_op_flags[1] = field::mul(self.cf_op_bits[0], not_1);
-        self.cf_op_flags[2] = field::mul(not_0, self.cf_op_bits[1]);
-        self.cf_op_flags[3] = field::mul(self.cf_op_bits[0], self.cf_op_bits[1]);
-        self.cf_op_flags.copy_within(0..4, 4);
-
-        let not_2 = binary_not(self.cf_op_bits[2]);
-        for i in 0..4 { self.cf_op_flags[i] = field::mul(self.cf_op_flags[i], not_2); }
-        for i in 4..8 { self.cf_op_flags[i] = field::mul(self.cf_op_flags[i], self.cf_op_bits[2]); }
-
-        // set low-degree operation flags
-        let not_0 = binary_not(self.ld_op_bits[0]);
-        let not_1 = binary_not(self.ld_op_bits[1]);
-        self.ld_op_flags[0] = field::mul(not_0, not_1);
-        self.ld_op_flags[1] = field::mul(self.ld_op_bits[0], not_1);
-        self.ld_op_flags[2] = field::mul(not_0, self.cf_op_bits[1]);
-        self.ld_op_flags[3] = field::mul(self.ld_op_bits[0], self.ld_op_bits[1]);
-        self.ld_op_flags.copy_within(0..4, 4);
-
-        let not_2 = binary_not(self.ld_op_bits[2]);
-        for i in 0..4 { self.ld_op_flags[i] = field::mul(self.ld_op_flags[i], not_2); }
-        for i in 4..8 { self.ld_op_flags[i] = field::mul(self.ld_op_flags[i], self.ld_op_bits[2]); }
-        self.ld_op_flags.copy_within(0..8, 8);
-
-        let not_3 = binary_not(self.ld_op_bits[3]);
-        for i in 0..8  { self.ld_op_flags[i] = field::mul(self.ld_op_flags[i], not_3); }
-        for i in 8..16 { self.ld_op_flags[i] = field::mul(self.ld_op_flags[i], self.ld_op_bits[3]); }
-        self.ld_op_flags.copy_within(0..16, 16);
-
-        let not_4 = binary_not(self.ld_op_bits[4]);
-        for i in 0..16  { self.ld_op_flags[i] = field::mul(self.ld_op_flags[i], not_4); }
-        for i in 16..32 { self.ld_op_flags[i] = field::mul(self.ld_op_flags[i], self.ld_op_bits[4]); }
-
-        // set high-degree operation flags
-        let not_0 = binary_not(self.hd_op_bits[0]);
-        let not_1 = binary_not(self.hd_op_bits[1]);
-        self.hd_op_flags[0] = field::mul(not_0, not_1);
-        self.hd_op_flags[1] = field::mul(self.hd_op_bits[0], not_1);
-        self.hd_op_flags[2] = field::mul(not_0, self.hd_op_bits[1]);
-        self.hd_op_flags[3] = field::mul(self.hd_op_bits[0], self.hd_op_bits[1]);
+        // set control flow flags; HACC, VOID, BEGIN, and TEND flags are taken directly from
+        // helper registers to keep the degree of constraints which depend on them low; all
+        // other flags have degree 3
+        self.cf_op_flags = get_bit_flags(&self.cf_op_bits);
+        self.cf_op_flags[FlowOps::Hacc.op_index()] = self.op_helpers[HACC_HELPER_IDX];
+        self.cf_op_flags[FlowOps::Void.op_index()] = self.op_helpers[VOID_HELPER_IDX];
+        self.cf_op_flags[FlowOps::Begin.op_index()] = self.op_helpers[BEGIN_HELPER_IDX];
+        self.cf_op_flags[FlowOps::Tend.op_index()] = self.op_helpers[TEND_HELPER_IDX];
+
+        // set low-degree operation flags; each flag is a product of a helper for the two lower
+        // op bits and a helper for the three upper op bits, and thus, has degree 2
+        let ld_lo = &self.op_helpers[LD_LO_HELPERS_IDX..LD_HI_HELPERS_IDX];
+        let ld_hi = &self.op_helpers[LD_HI_HELPERS_IDX..HD_HELPERS_IDX];
+        for i in 0..NUM_LD_OPS {
+            self.ld_op_flags[i] = field::mul(ld_lo[i % NUM_LD_LO_HELPERS], ld_hi[i / NUM_LD_LO_HELPERS]);
+        }
+
+        // set high-degree operation flags; these are just values of helper registers
+        self.hd_op_flags.copy_from_slice(&self.op_helpers[HD_HELPERS_IDX..]);
 
         // compute flag for BEGIN operation which is just 0000000; the below is equivalent
         // to multiplying binary inverses of all op bits together.
@@ -352,12 +403,13 @@ impl TraceState {
 
 impl fmt::Debug for TraceState {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "[{:>4}] {:>32X?} {:?} {:?} {:?} {:>32X?} {:>32X?} {:?}",
+        write!(f, "[{:>4}] {:>32X?} {:?} {:?} {:?} {:?} {:>32X?} {:>32X?} {:?}",
             self.op_counter,
             self.sponge, 
             self.cf_op_bits,
             self.ld_op_bits,
             self.hd_op_bits,
+            self.op_helpers,
             self.ctx_stack,
             self.loop_stack,
             self.user_stack
@@ -367,12 +419,13 @@ impl fmt::Debug for TraceState {
 
 impl fmt::Display for TraceState {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "[{:>4}] {:>16X?} {:?} {:?} {:?} {:>16X?} {:>16X?} {:?}",
+        write!(f, "[{:>4}] {:>16X?} {:?} {:?} {:?} {:?} {:>16X?} {:>16X?} {:?}",
             self.op_counter,
             self.sponge.iter().map(|x| x >> 64).collect::<Vec<u128>>(),
             self.cf_op_bits,
             self.ld_op_bits,
             self.hd_op_bits,
+            self.op_helpers,
             self.ctx_stack.iter().map(|x| x >> 64).collect::<Vec<u128>>(),
             self.loop_stack.iter().map(|x| x >> 64).collect::<Vec<u128>>(),
             &self.user_stack[..self.stack_depth]
@@ -387,6 +440,23 @@ fn binary_not(v: u128) -> u128 {
     return field::sub(field::ONE, v);
 }
 
+/// Computes flags for all combinations of the provided bits (up to 3 bits); flag at index i
+/// is set to 1 when the bits encode value i, and the degree of each flag is equal to the
+/// number of bits.
+fn get_bit_flags(bits: &[u128]) -> [u128; 8] {
+    let mut flags = [0; 8];
+    flags[0] = field::ONE;
+    for (i, &bit) in bits.iter().enumerate() {
+        let n = 1 << i;
+        let not_bit = binary_not(bit);
+        for j in 0..n {
+            flags[j + n] = field::mul(flags[j], bit);
+            flags[j] = field::mul(flags[j], not_bit);
+        }
+    }
+    return flags;
+}
+
 // TESTS
 // ================================================================================================
 #[cfg(test)]
@@ -399,7 +469,8 @@ mod tests {
 
         // empty context and loop stacks
         let state = TraceState::from_vec(0, 0, 2, &vec![
-            101,  1, 2, 3, 4,  5, 6, 7,  8, 9, 10, 11, 12,  13, 14,  15, 16
+            101,  1, 2, 3, 4,  5, 6, 7,  8, 9, 10, 11, 12,  13, 14,
+            15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32, 33, 34,  35, 36
         ]);
 
         assert_eq!(101, state.op_counter());
@@ -407,18 +478,23 @@ mod tests {
         assert_eq!([5, 6, 7], state.cf_op_bits());
         assert_eq!([8, 9, 10, 11, 12], state.ld_op_bits());
         assert_eq!([13, 14], state.hd_op_bits());
+        assert_eq!([
+            15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32, 33, 34
+        ], state.op_helpers());
         assert_eq!([0], state.ctx_stack());
         assert_eq!([0], state.loop_stack());
-        assert_eq!([15, 16, 0, 0, 0, 0, 0, 0], state.user_stack());
-        assert_eq!(17, state.width());
+        assert_eq!([35, 36, 0, 0, 0, 0, 0, 0], state.user_stack());
+        assert_eq!(37, state.width());
         assert_eq!(2, state.stack_depth());
         assert_eq!(vec![
-            101, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16
+            101, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14,
+            15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32, 33, 34, 35, 36
         ], state.to_vec());
 
         // 1 item on context stack, empty loop stack
         let state = TraceState::from_vec(1, 0, 2, &vec![
-            101,  1, 2, 3, 4,  5, 6, 7,  8, 9, 10, 11, 12,  13, 14,  15,  16, 17
+            101,  1, 2, 3, 4,  5, 6, 7,  8, 9, 10, 11, 12,  13, 14,
+            15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32, 33, 34,  35,  36, 37
         ]);
 
         assert_eq!(101, state.op_counter());
@@ -426,19 +502,21 @@ mod tests {
         assert_eq!([5, 6, 7], state.cf_op_bits());
         assert_eq!([8, 9, 10, 11, 12], state.ld_op_bits());
         assert_eq!([13, 14], state.hd_op_bits());
-        assert_eq!([15], state.ctx_stack());
+        assert_eq!([35], state.ctx_stack());
         assert_eq!([0], state.loop_stack());
-        assert_eq!([16, 17, 0, 0, 0, 0, 0, 0], state.user_stack());
-        assert_eq!(18, state.width());
+        assert_eq!([36, 37, 0, 0, 0, 0, 0, 0], state.user_stack());
+        assert_eq!(38, state.width());
         assert_eq!(2, state.stack_depth());
         assert_eq!(vec![
-            101, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17
+            101, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14,
+            15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32, 33, 34, 35, 36, 37
         ], state.to_vec());
 
         // non-empty loop stack
         let state = TraceState::from_vec(2, 1, 9, &vec![
-            101,  1, 2, 3, 4,  5, 6, 7,  8, 9, 10, 11, 12,  13, 14,  15, 16,  17,
-            18, 19, 20, 21, 22, 23, 24, 25, 26,
+            101,  1, 2, 3, 4,  5, 6, 7,  8, 9, 10, 11, 12,  13, 14,
+            15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32, 33, 34,  35, 36,  37,
+            38, 39, 40, 41, 42, 43, 44, 45, 46,
         ]);
 
         assert_eq!(101, state.op_counter());
@@ -446,21 +524,23 @@ mod tests {
         assert_eq!([5, 6, 7], state.cf_op_bits());
         assert_eq!([8, 9, 10, 11, 12], state.ld_op_bits());
         assert_eq!([13, 14], state.hd_op_bits());
-        assert_eq!([15, 16], state.ctx_stack());
-        assert_eq!([17], state.loop_stack());
-        assert_eq!([18, 19, 20, 21, 22, 23, 24, 25, 26], state.user_stack());
-        assert_eq!(27, state.width());
+        assert_eq!([35, 36], state.ctx_stack());
+        assert_eq!([37], state.loop_stack());
+        assert_eq!([38, 39, 40, 41, 42, 43, 44, 45, 46], state.user_stack());
+        assert_eq!(47, state.width());
         assert_eq!(9, state.stack_depth());
         assert_eq!(vec![
-            101, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17,
-            18, 19, 20, 21, 22, 23, 24, 25, 26,
+            101, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14,
+            15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32, 33, 34, 35, 36, 37,
+            38, 39, 40, 41, 42, 43, 44, 45, 46,
         ], state.to_vec());
     }
 
     #[test]
     fn update_from_trace() {
-        let data = vec![
-            101,  1, 2, 3, 4,  5, 6, 7,  8, 9, 10, 11, 12,  13, 14,  15, 16,  17,  18, 19, 20
+        let data: Vec<u128> = vec![
+            101,  1, 2, 3, 4,  5, 6, 7,  8, 9, 10, 11, 12,  13, 14,
+            15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32, 33, 34,  35, 36,  37,  38, 39, 40
         ];
         let mut trace = Vec::with_capacity(data.len());
         for i in 0..data.len() {
@@ -476,10 +556,11 @@ mod tests {
         assert_eq!([0, 0, 0], state.cf_op_bits());
         assert_eq!([0, 0, 0, 0, 0], state.ld_op_bits());
         assert_eq!([0, 0], state.hd_op_bits());
+        assert_eq!([0; 20], state.op_helpers());
         assert_eq!([0, 0], state.ctx_stack());
         assert_eq!([0], state.loop_stack());
         assert_eq!([0, 0, 0, 0, 0, 0, 0, 0], state.user_stack());
-        assert_eq!(21, state.width());
+        assert_eq!(41, state.width());
         assert_eq!(3, state.stack_depth());
 
         // second row
@@ -490,10 +571,13 @@ mod tests {
         assert_eq!([5, 6, 7], state.cf_op_bits());
         assert_eq!([8, 9, 10, 11, 12], state.ld_op_bits());
         assert_eq!([13, 14], state.hd_op_bits());
-        assert_eq!([15, 16], state.ctx_stack());
-        assert_eq!([17], state.loop_stack());
-        assert_eq!([18, 19, 20, 0, 0, 0, 0, 0], state.user_stack());
-        assert_eq!(21, state.width());
+        assert_eq!([
+            15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32, 33, 34
+        ], state.op_helpers());
+        assert_eq!([35, 36], state.ctx_stack());
+        assert_eq!([37], state.loop_stack());
+        assert_eq!([38, 39, 40, 0, 0, 0, 0, 0], state.user_stack());
+        assert_eq!(41, state.width());
         assert_eq!(3, state.stack_depth());
     }
 
@@ -501,7 +585,7 @@ mod tests {
     fn op_flags() {
 
         // all zeros
-        let state = TraceState::from_vec(1, 0, 2, &vec![
+        let state = TraceState::from_vec_without_helpers(1, 0, 2, &vec![
             101,  1, 2, 3, 4,  0, 0, 0,  0, 0, 0, 0, 0,  0, 0,  15, 16, 17
         ]);
 
@@ -515,7 +599,7 @@ mod tests {
         assert_eq!(0, state.noop_flag());
 
         // all ones
-        let state = TraceState::from_vec(1, 0, 2, &vec![
+        let state = TraceState::from_vec_without_helpers(1, 0, 2, &vec![
             101,  1, 2, 3, 4,  1, 1, 1,  1, 1, 1, 1, 1,  1, 1,  15, 16, 17
         ]);
 
@@ -529,7 +613,7 @@ mod tests {
         assert_eq!(1, state.noop_flag());
 
         // mixed 1
-        let state = TraceState::from_vec(1, 0, 2, &vec![
+        let state = TraceState::from_vec_without_helpers(1, 0, 2, &vec![
             101,  1, 2, 3, 4,  1, 0, 0,  1, 0, 0, 0, 0,  1, 0,  15, 16, 17
         ]);
 
@@ -543,7 +627,7 @@ mod tests {
         assert_eq!(0, state.noop_flag());
 
         // mixed 2
-        let state = TraceState::from_vec(1, 0, 2, &vec![
+        let state = TraceState::from_vec_without_helpers(1, 0, 2, &vec![
             101, 1, 2, 3, 4, 1, 1, 0, 1, 1, 0, 0, 0, 0, 1, 15, 16, 17
         ]);
 
@@ -553,26 +637,40 @@ mod tests {
             0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
         ], state.ld_op_flags());
         assert_eq!([0, 0, 1, 0], state.hd_op_flags());
+
+        // EQ operation
+        let state = TraceState::from_vec_without_helpers(1, 0, 2, &vec![
+            101,  1, 2, 3, 4,  0, 0, 0,  0, 1, 0, 0, 0,  1, 1,  15, 16, 17
+        ]);
+
+        assert_eq!([1, 0, 0, 0, 0, 0, 0, 0], state.cf_op_flags());
+        assert_eq!([
+            0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        ], state.ld_op_flags());
+        assert_eq!([0, 0, 0, 1], state.hd_op_flags());
+        assert_eq!(0, state.begin_flag());
+        assert_eq!(0, state.noop_flag());
     }
 
     #[test]
     fn op_code() {
-        let state = TraceState::from_vec(1, 0, 2, &vec![
+        let state = TraceState::from_vec_without_helpers(1, 0, 2, &vec![
             101,  1, 2, 3, 4,  1, 1, 1,  0, 0, 0, 0, 0,  0, 0,  15, 16, 17
         ]);
         assert_eq!(0, state.op_code());
 
-        let state = TraceState::from_vec(1, 0, 2, &vec![
+        let state = TraceState::from_vec_without_helpers(1, 0, 2, &vec![
             101,  1, 2, 3, 4,  1, 1, 1,  1, 1, 1, 1, 1,  1, 1,  15, 16, 17
         ]);
         assert_eq!(127, state.op_code());
 
-        let state = TraceState::from_vec(1, 0, 2, &vec![
+        let state = TraceState::from_vec_without_helpers(1, 0, 2, &vec![
             101,  1, 2, 3, 4,  1, 1, 1,  1, 1, 1, 1, 1,  1, 0,  15, 16, 17
         ]);
         assert_eq!(63, state.op_code());
 
-        let state = TraceState::from_vec(1, 0, 2, &vec![
+        let state = TraceState::from_vec_without_helpers(1, 0, 2, &vec![
             101,  1, 2, 3, 4,  1, 1, 1,  1, 0, 0, 0, 0,  1, 1,  15, 16, 17
         ]);
         assert_eq!(97, state.op_code());
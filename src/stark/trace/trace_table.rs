@@ -1,35 +1,33 @@
-use crate::math::{ field, fft, polynom, parallel };
-use crate::crypto::{ MerkleTree, HashFunction };
-use crate::stark::{ CompositionCoefficients, utils };
-use crate::utils::{ uninit_vector, filled_vector, as_bytes };
+use crate::math::{ f128::BaseElement, fft, polynom, parallel, FieldElement, StarkField };
+use crate::crypto::{ MerkleTree, HashAlgorithm };
+use crate::stark::{ CompositionCoefficients, ProcessorAir, TraceInfo, utils };
+use crate::utils::{ uninit_vector, filled_vector };
 use super::{ TraceState };
-use sp_std::{vec, vec::Vec};
+use sp_std::{vec, vec::Vec, ops::Range};
+use crossbeam_utils::thread;
 use serde::{Serialize, Deserialize};
 
 // TYPES AND INTERFACES
 // ================================================================================================
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct TraceTable {
-    registers       : Vec<Vec<u128>>,
-    polys           : Vec<Vec<u128>>,
-    ctx_depth       : usize,
-    loop_depth      : usize,
-    stack_depth     : usize,
+#[serde(bound = "E: StarkField")]
+pub struct TraceTable<E: StarkField = BaseElement> {
+    registers       : Vec<Vec<E>>,
+    polys           : Vec<Vec<E>>,
+    meta            : Vec<u8>,
     trace_length    : usize,
     extension_factor: usize,
+    blinding_rows   : usize,
+    aux_width       : usize,
+    domain_offset   : E,
 }
 
-// TRACE TABLE IMPLEMENTATION
+// DISTAFF TRACE TABLE IMPLEMENTATION
 // ================================================================================================
-impl TraceTable {
-    /// Returns a trace table constructed from the specified register traces.
+impl TraceTable<BaseElement> {
+    /// Returns a trace table constructed from register traces of a Distaff program execution.
     pub fn new(registers: Vec<Vec<u128>>, ctx_depth: usize, loop_depth: usize, extension_factor: usize) -> TraceTable
     {
-        // validate extension factor
-        assert!(extension_factor.is_power_of_two(), "trace extension factor must be a power of 2");
-        assert!(extension_factor >= crate::MIN_EXTENSION_FACTOR,
-            "extension factor must be at least {}", crate::MIN_EXTENSION_FACTOR);
-
         // validate context depth
         assert!(ctx_depth <= crate::MAX_CONTEXT_DEPTH,
             "context depth cannot be greater than {}", crate::MAX_CONTEXT_DEPTH);
@@ -38,12 +36,84 @@ impl TraceTable {
         assert!(loop_depth <= crate::MAX_LOOP_DEPTH,
             "loop depth cannot be greater than {}", crate::MAX_LOOP_DEPTH);
 
-        // compute stack depth
+        // make sure there is at least one stack register followed by the range check register
         let decoder_width = TraceState::compute_decoder_width(ctx_depth, loop_depth);
-        assert!(registers.len() > decoder_width, "user stack must consist of at least one register");
-        let stack_depth = registers.len() - decoder_width;
+        assert!(registers.len() > decoder_width + crate::NUM_RANGE_CHECK_REGISTERS,
+            "user stack must consist of at least one register");
+
+        // the number of executed operations is the value of op_counter at the last step, and
+        // range checks were performed if any lookups were counted in the range check register
+        let op_count = registers[crate::OP_COUNTER_IDX].last().copied().unwrap_or(0);
+        let range_checks = registers[registers.len() - 1].iter().any(|&count| count != 0);
+        let meta = ProcessorAir::build_trace_meta(ctx_depth, loop_depth, op_count, range_checks);
+        let registers = registers.into_iter().map(BaseElement::from_raw_vec).collect();
+        return TraceTable::with_meta(registers, meta, extension_factor);
+    }
+
+    /// Returns state of the trace table at the specified `step`.
+    pub fn get_state(&self, step: usize) -> TraceState {
+        let mut result = TraceState::new(self.ctx_depth(), self.loop_depth(), self.stack_depth());
+        self.fill_state(&mut result, step);
+        return result;
+    }
+
+    /// Returns state of the trace table at the last step of program execution; blinding rows
+    /// are not considered part of the execution.
+    pub fn get_last_state(&self) -> TraceState {
+        let last_step = self.unextended_length() - self.blinding_rows - 1;
+        if !self.is_extended() {
+            return self.get_state(last_step);
+        }
+        else if self.domain_offset == BaseElement::ONE && !self.is_lazy() {
+            return self.get_state(last_step * self.extension_factor());
+        }
+
+        // when the trace is extended over a coset, the last step is not in the evaluation
+        // domain, and when the trace is extended lazily, extended registers are not kept in
+        // memory; in both cases, the state must be computed from trace polynomials
+        let g = BaseElement::get_root_of_unity(self.unextended_length());
+        let state = self.eval_polys_at(g.exp(last_step as u128));
+        let state = BaseElement::as_raw_slice(&state);
+        return TraceState::from_vec(self.ctx_depth(), self.loop_depth(), self.stack_depth(), state);
+    }
+
+    /// Copies trace table state at the specified `step` to the passed in `state` object.
+    pub fn fill_state(&self, state: &mut TraceState, step: usize) {
+        state.update_from_trace(&self.registers, step);
+    }
+
+    /// Returns the number of registers used by context stack.
+    pub fn ctx_depth(&self) -> usize {
+        return self.meta[0] as usize;
+    }
+
+    /// Returns the number of registers used by loop stack.
+    pub fn loop_depth(&self) -> usize {
+        return self.meta[1] as usize;
+    }
+
+    /// Returns the number of registers used by user stack.
+    pub fn stack_depth(&self) -> usize {
+        let decoder_width = TraceState::compute_decoder_width(self.ctx_depth(), self.loop_depth());
+        return self.main_width() - decoder_width - crate::NUM_RANGE_CHECK_REGISTERS;
+    }
+}
+
+// TRACE TABLE IMPLEMENTATION
+// ================================================================================================
+impl<E: StarkField> TraceTable<E> {
+
+    /// Returns a trace table constructed from the specified register traces of an arbitrary
+    /// computation; `meta` is passed to the AIR of the computation as a part of `TraceInfo`.
+    pub fn with_meta(registers: Vec<Vec<E>>, meta: Vec<u8>, extension_factor: usize) -> TraceTable<E>
+    {
+        // validate extension factor
+        assert!(extension_factor.is_power_of_two(), "trace extension factor must be a power of 2");
+        assert!(extension_factor >= crate::MIN_EXTENSION_FACTOR,
+            "extension factor must be at least {}", crate::MIN_EXTENSION_FACTOR);
 
         // validate register traces
+        assert!(registers.len() > 0, "execution trace must consist of at least one register");
         assert!(registers.len() < crate::MAX_REGISTER_COUNT,
             "execution trace cannot have more than {} registers", crate::MAX_REGISTER_COUNT);
         let trace_length = registers[0].len();
@@ -54,33 +124,27 @@ impl TraceTable {
 
         let polys = Vec::with_capacity(registers.len());
         return TraceTable {
-            registers, polys,
-            ctx_depth, loop_depth, stack_depth,
-            trace_length, extension_factor
+            registers, polys, meta,
+            trace_length, extension_factor,
+            blinding_rows: 0, aux_width: 0, domain_offset: E::ONE
         };
     }
 
-    /// Returns state of the trace table at the specified `step`.
-    pub fn get_state(&self, step: usize) -> TraceState {
-        let mut result = TraceState::new(self.ctx_depth, self.loop_depth, self.stack_depth);
-        self.fill_state(&mut result, step);
-        return result;
-    }
-
-    /// Returns state of the trace table at the last step.
-    pub fn get_last_state(&self) -> TraceState {
-        let last_step = if self.is_extended() {
-            self.domain_size() - self.extension_factor()
-        }
-        else {
-            self.unextended_length() - 1
+    /// Returns the shape of the trace table before it is extended.
+    pub fn info(&self) -> TraceInfo {
+        return TraceInfo {
+            width           : self.main_width(),
+            length          : self.unextended_length(),
+            blinding_rows   : self.blinding_rows,
+            meta            : self.meta.clone(),
         };
-        return self.get_state(last_step);
     }
 
-    /// Copies trace table state at the specified `step` to the passed in `state` object.
-    pub fn fill_state(&self, state: &mut TraceState, step: usize) {
-        state.update_from_trace(&self.registers, step);
+    /// Copies values of all registers at the specified `step` into the passed in `row`.
+    pub fn fill_row(&self, row: &mut [E], step: usize) {
+        for (value, register) in row.iter_mut().zip(self.registers.iter()) {
+            *value = register[step];
+        }
     }
 
     /// Returns the number of states in the un-extended trace table.
@@ -93,108 +157,312 @@ impl TraceTable {
         return self.trace_length * self.extension_factor;
     }
 
+    /// Returns the number of random rows appended to the execution trace.
+    pub fn blinding_rows(&self) -> usize {
+        return self.blinding_rows;
+    }
+
+    /// Returns the offset of the coset over which the trace table has been extended.
+    pub fn domain_offset(&self) -> E {
+        return self.domain_offset;
+    }
+
     /// Returns `extension_factor` for the trace table.
     pub fn extension_factor(&self) -> usize {
         return self.extension_factor;
     }
 
-    /// Returns the number of registers in the trace table.
+    /// Returns the number of registers in the trace table, including auxiliary registers.
     pub fn register_count(&self) -> usize {
         return self.registers.len();
     }
 
-    /// Returns the number of registers used by context stack.
-    pub fn ctx_depth(&self) -> usize {
-        return self.ctx_depth;
+    /// Returns the number of main registers in the trace table.
+    pub fn main_width(&self) -> usize {
+        return self.registers.len() - self.aux_width;
     }
 
-    /// Returns the number of registers used by loop stack.
-    pub fn loop_depth(&self) -> usize {
-        return self.loop_depth;
+    /// Returns the number of auxiliary registers in the trace table.
+    pub fn aux_width(&self) -> usize {
+        return self.aux_width;
     }
 
-    /// Returns the number of registers used by user stack.
-    pub fn stack_depth(&self) -> usize {
-        return self.stack_depth;
+    /// Returns values of the register at the specified `index` over the un-extended trace; if
+    /// the trace table has been extended, the values are computed from the register polynomial.
+    pub fn get_unextended_register(&self, index: usize) -> Vec<E> {
+        if !self.is_extended() {
+            return self.registers[index].clone();
+        }
+        else if self.domain_offset == E::ONE && !self.is_lazy() {
+            return self.registers[index].iter().step_by(self.extension_factor).copied().collect();
+        }
+
+        let mut values = self.polys[index].clone();
+        polynom::eval_fft(&mut values, true);
+        return values;
     }
 
     /// Returns polynomial of the register at the specified `index`; can be called only
     /// after the trace table has been extended.
     #[cfg(test)]
-    pub fn get_register_poly(&self, index: usize) -> &[u128] {
+    pub fn get_register_poly(&self, index: usize) -> &[E] {
         assert!(self.is_extended(), "trace table has not been extended yet");
         return &self.polys[index];
     }
 
-    /// Returns values of all registers at the specified `positions`.
-    pub fn get_register_values_at(&self, positions: &[usize]) -> Vec<Vec<u128>> {
+    /// Returns values of all registers at the specified `positions`; if the trace table has
+    /// been extended lazily, the values are computed from trace polynomials.
+    pub fn get_register_values_at(&self, positions: &[usize]) -> Vec<Vec<E>> {
         let mut result = Vec::with_capacity(positions.len());
-        for &i in positions.iter() {
-            let row = self.registers.iter().map(|r| r[i]).collect();
-            result.push(row);
+        if self.is_lazy() {
+            let root = E::get_root_of_unity(self.domain_size());
+            for &i in positions.iter() {
+                let x = self.domain_offset * root.exp(i as u128);
+                let row = self.polys.iter().map(|p| polynom::eval(p, x)).collect();
+                result.push(row);
+            }
+        }
+        else {
+            for &i in positions.iter() {
+                let row = self.registers.iter().map(|r| r[i]).collect();
+                result.push(row);
+            }
         }
         return result;
     }
 
     /// Returns `true` if the trace table has been extended.
     pub fn is_extended(&self) -> bool {
-        return self.registers[0].len() > self.trace_length;
+        return !self.polys.is_empty();
+    }
+
+    /// Returns `true` if the trace table has been extended lazily, meaning that only trace
+    /// polynomials are kept in memory.
+    pub fn is_lazy(&self) -> bool {
+        return self.is_extended() && self.registers[0].is_empty();
+    }
+
+    /// Appends `num_rows` rows of random values to the trace table, padding the trace with
+    /// copies of the last state so that its length remains a power of 2. After this, the
+    /// execution trace ends `num_rows + 1` steps before the end of the trace table. A trace
+    /// table can be blinded only once, and only before it is extended.
+    pub fn blind(&mut self, num_rows: usize) {
+        assert!(!self.is_extended(), "trace table has already been extended");
+        assert!(self.blinding_rows == 0, "trace table has already been blinded");
+        if num_rows == 0 { return; }
+
+        let trace_length = (self.trace_length + num_rows).next_power_of_two();
+        for register in self.registers.iter_mut() {
+            let last_value = register[self.trace_length - 1];
+            register.resize(trace_length - num_rows, last_value);
+            register.append(&mut E::rand_vector(num_rows));
+        }
+        self.trace_length = trace_length;
+        self.blinding_rows = num_rows;
     }
 
     /// Extends all registers of the trace table by the `extension_factor` specified during
-    /// trace table construction. A trace table can be extended only once.
-    pub fn extend(&mut self, twiddles: &[u128]) {
+    /// trace table construction; the registers are evaluated over the LDE domain shifted by
+    /// `domain_offset`. A trace table can be extended only once. Registers are distributed
+    /// across `num_threads` threads, and each register is extended in one thread.
+    pub fn extend(&mut self, twiddles: &[E], domain_offset: E, num_threads: usize) {
         assert!(!self.is_extended(), "trace table has already been extended");
         assert!(twiddles.len() * 2 == self.domain_size(), "invalid number of twiddles");
+        assert!(num_threads > 0, "number of threads must be greater than 0");
 
         // build inverse twiddles needed for FFT interpolation
-        let root = field::get_root_of_unity(self.unextended_length());
+        let root = E::get_root_of_unity(self.unextended_length());
         let inv_twiddles = fft::get_inv_twiddles(root, self.unextended_length());
         
         // move register traces into polys
         sp_std::mem::swap(&mut self.registers, &mut self.polys);
+        self.registers = vec![Vec::new(); self.polys.len()];
 
         // extend all registers
+        self.domain_offset = domain_offset;
         let domain_size = self.domain_size();
-        for poly in self.polys.iter_mut() {
-
-            // interpolate register trace into a polynomial
-            polynom::interpolate_fft_twiddles(poly, &inv_twiddles, true);
-            
-            // allocate space to hold extended evaluations and copy the polynomial into it
-            let mut register = vec![field::ZERO; domain_size];
-            register[..poly.len()].copy_from_slice(&poly);
-            
-            // evaluate the polynomial over extended domain
-            polynom::eval_fft_twiddles(&mut register, &twiddles, true);
-            self.registers.push(register);
+        if num_threads == 1 {
+            for (poly, register) in self.polys.iter_mut().zip(self.registers.iter_mut()) {
+                *register = extend_register(poly, &inv_twiddles, twiddles, domain_offset, domain_size);
+            }
+        }
+        else {
+            let batch_size = (self.polys.len() + num_threads - 1) / num_threads;
+            let inv_twiddles = &inv_twiddles;
+            thread::scope(|s| {
+                let batches = self.polys.chunks_mut(batch_size).zip(self.registers.chunks_mut(batch_size));
+                for (polys, registers) in batches {
+                    s.spawn(move |_| {
+                        for (poly, register) in polys.iter_mut().zip(registers.iter_mut()) {
+                            *register = extend_register(poly, inv_twiddles, twiddles, domain_offset, domain_size);
+                        }
+                    });
+                }
+            }).unwrap();
+        }
+    }
+
+    /// Appends auxiliary `registers` to an extended trace table; the registers are extended in
+    /// the same way as the main registers of the table (i.e., over the same domain, and lazily
+    /// if the main registers have been extended lazily). Auxiliary registers can be added only
+    /// once, and `twiddles` are ignored if the trace table has been extended lazily.
+    pub fn extend_aux(&mut self, registers: Vec<Vec<E>>, twiddles: &[E]) {
+        assert!(self.is_extended(), "trace table has not been extended yet");
+        assert!(self.aux_width == 0, "auxiliary registers have already been added");
+        assert!(self.registers.len() + registers.len() < crate::MAX_REGISTER_COUNT,
+            "execution trace cannot have more than {} registers", crate::MAX_REGISTER_COUNT);
+
+        // build inverse twiddles needed for FFT interpolation
+        let root = E::get_root_of_unity(self.unextended_length());
+        let inv_twiddles = fft::get_inv_twiddles(root, self.unextended_length());
+
+        let is_lazy = self.is_lazy();
+        let domain_size = self.domain_size();
+        self.aux_width = registers.len();
+        for mut poly in registers.into_iter() {
+            assert!(poly.len() == self.trace_length, "all register traces must have the same length");
+            if is_lazy {
+                polynom::interpolate_fft_twiddles(&mut poly, &inv_twiddles, true);
+                self.registers.push(Vec::new());
+            }
+            else {
+                assert!(twiddles.len() * 2 == domain_size, "invalid number of twiddles");
+                let register = extend_register(&mut poly, &inv_twiddles, twiddles, self.domain_offset, domain_size);
+                self.registers.push(register);
+            }
+            self.polys.push(poly);
+        }
+    }
+
+    /// Interpolates all registers of the trace table into polynomials but, unlike `extend()`,
+    /// does not evaluate them over the LDE domain. Instead, rows of the extended trace are
+    /// evaluated segment by segment whenever they are needed (see `eval_segment()`), and so
+    /// the extended trace never has to be held in memory in full. The LDE domain is shifted
+    /// by `domain_offset`. A trace table can be extended only once.
+    pub fn extend_lazily(&mut self, domain_offset: E, num_threads: usize) {
+        assert!(!self.is_extended(), "trace table has already been extended");
+        assert!(num_threads > 0, "number of threads must be greater than 0");
+
+        // build inverse twiddles needed for FFT interpolation
+        let root = E::get_root_of_unity(self.unextended_length());
+        let inv_twiddles = fft::get_inv_twiddles(root, self.unextended_length());
+
+        // move register traces into polys; extended registers are left empty
+        sp_std::mem::swap(&mut self.registers, &mut self.polys);
+        self.registers = vec![Vec::new(); self.polys.len()];
+        self.domain_offset = domain_offset;
+
+        // interpolate all registers
+        if num_threads == 1 {
+            for poly in self.polys.iter_mut() {
+                polynom::interpolate_fft_twiddles(poly, &inv_twiddles, true);
+            }
+        }
+        else {
+            let batch_size = (self.polys.len() + num_threads - 1) / num_threads;
+            let inv_twiddles = &inv_twiddles;
+            thread::scope(|s| {
+                for polys in self.polys.chunks_mut(batch_size) {
+                    s.spawn(move |_| {
+                        for poly in polys.iter_mut() {
+                            polynom::interpolate_fft_twiddles(poly, inv_twiddles, true);
+                        }
+                    });
+                }
+            }).unwrap();
         }
     }
 
-    /// Puts the trace table into a Merkle tree such that each state of the table becomes
-    /// a distinct leaf in the tree; all registers at a given step are hashed together to
-    /// form a single leaf value.
-    pub fn build_merkle_tree(&self, hash: HashFunction) -> MerkleTree {
-        let mut trace_state = vec![field::ZERO; self.register_count()];
+    /// Evaluates trace polynomials over the segment of the extended trace at the specified
+    /// `index` and saves the results into `segment`, one vector per register. The extended
+    /// trace consists of `extension_factor` segments, and segment `index` holds every
+    /// `extension_factor`-th row of the extended trace starting with row `index`; that is,
+    /// row `i` of the segment is row `index + i * extension_factor` of the extended trace.
+    /// Registers are distributed across `num_threads` threads.
+    pub fn eval_segment(&self, index: usize, segment: &mut [Vec<E>], num_threads: usize) {
+        assert!(segment.len() == self.register_count(), "segment must have a vector for every register");
+        self.eval_register_segments(index, &self.polys, segment, num_threads);
+    }
+
+    /// Evaluates the specified trace polynomials over the segment of the extended trace at the
+    /// specified `index`; see `eval_segment()` for details.
+    fn eval_register_segments(&self, index: usize, polys: &[Vec<E>], segment: &mut [Vec<E>], num_threads: usize) {
+        assert!(self.is_extended(), "trace table has not been extended yet");
+        assert!(index < self.extension_factor, "segment index must be smaller than extension factor");
+        assert!(num_threads > 0, "number of threads must be greater than 0");
+
+        // a segment is the trace domain shifted by domain_offset * lde_root^index
+        let trace_length = self.unextended_length();
+        let twiddles = fft::get_twiddles(E::get_root_of_unity(trace_length), trace_length);
+        let lde_root = E::get_root_of_unity(self.domain_size());
+        let offset = self.domain_offset * lde_root.exp(index as u128);
+
+        if num_threads == 1 {
+            for (poly, register) in polys.iter().zip(segment.iter_mut()) {
+                eval_register_segment(poly, &twiddles, offset, register);
+            }
+        }
+        else {
+            let batch_size = (polys.len() + num_threads - 1) / num_threads;
+            let twiddles = &twiddles;
+            thread::scope(|s| {
+                let batches = polys.chunks(batch_size).zip(segment.chunks_mut(batch_size));
+                for (polys, registers) in batches {
+                    s.spawn(move |_| {
+                        for (poly, register) in polys.iter().zip(registers.iter_mut()) {
+                            eval_register_segment(poly, twiddles, offset, register);
+                        }
+                    });
+                }
+            }).unwrap();
+        }
+    }
+
+    /// Puts main registers of the trace table into a Merkle tree such that each state of the
+    /// table becomes a distinct leaf in the tree; all main registers at a given step are hashed
+    /// together to form a single leaf value. Leaves and nodes of the tree are hashed in
+    /// `num_threads` threads. If the trace table has been extended lazily, the extended trace
+    /// is evaluated and hashed one segment at a time.
+    pub fn build_merkle_tree(&self, hash: HashAlgorithm, num_threads: usize) -> MerkleTree {
+        return self.build_registers_merkle_tree(0..self.main_width(), hash, num_threads);
+    }
+
+    /// Puts auxiliary registers of the trace table into a Merkle tree in the same way as
+    /// `build_merkle_tree()` does for main registers.
+    pub fn build_aux_merkle_tree(&self, hash: HashAlgorithm, num_threads: usize) -> MerkleTree {
+        assert!(self.aux_width > 0, "trace table does not have auxiliary registers");
+        return self.build_registers_merkle_tree(self.main_width()..self.register_count(), hash, num_threads);
+    }
+
+    /// Puts the specified range of registers into a Merkle tree with one leaf per state.
+    fn build_registers_merkle_tree(&self, range: Range<usize>, hash: HashAlgorithm, num_threads: usize) -> MerkleTree {
+        assert!(num_threads > 0, "number of threads must be greater than 0");
         let mut hashed_states = uninit_vector::<[u8; 32]>(self.domain_size());
-        // TODO: this loop should be parallelized
-        for i in 0..self.domain_size() {
-            for j in 0..trace_state.len() {
-                trace_state[j] = self.registers[j][i];
+        if self.is_lazy() {
+            let mut segment = vec![Vec::new(); range.len()];
+            let mut hashed_rows = uninit_vector::<[u8; 32]>(self.unextended_length());
+            for index in 0..self.extension_factor {
+                self.eval_register_segments(index, &self.polys[range.clone()], &mut segment, num_threads);
+                hash_rows(&segment, &mut hashed_rows, hash, num_threads);
+                for (i, &row_hash) in hashed_rows.iter().enumerate() {
+                    hashed_states[index + i * self.extension_factor] = row_hash;
+                }
             }
-            hash(as_bytes(&trace_state), &mut hashed_states[i]);
         }
-        return MerkleTree::new(hashed_states, hash);
+        else {
+            hash_rows(&self.registers[range], &mut hashed_states, hash, num_threads);
+        }
+        return MerkleTree::new_concurrent(hashed_states, hash, num_threads);
     }
 
-    /// Evaluates trace polynomials at the specified point `z`; can be called only after
-    /// the trace table has been extended
-    pub fn eval_polys_at(&self, z: u128) -> Vec<u128> {
+    /// Evaluates trace polynomials at the specified point `z`; the point may be in an extension
+    /// of the trace field. Can be called only after the trace table has been extended
+    pub fn eval_polys_at<F: FieldElement<BaseField = E>>(&self, z: F) -> Vec<F> {
         assert!(self.is_extended(), "trace table has not been extended yet");
 
         let mut result = Vec::new();
         for poly in self.polys.iter() {
-            result.push(polynom::eval(poly, z));
+            result.push(polynom::eval_ext(poly, z));
         }
         return result;
     }
@@ -206,34 +474,38 @@ impl TraceTable {
     /// T2_i(x) = (T_i(x) - T_i(z * g)) / (x - z * g) are computed for all i and combined
     /// together into a single polynomial using a pseudo-random linear combination;
     /// 3. Then the degree of the polynomial is adjusted to match the specified degree
-    pub fn get_composition_poly(&self, z: u128, cc: &CompositionCoefficients) -> (Vec<u128>, Vec<u128>, Vec<u128>) {
-
+    /// 
+    /// The deep point z and the coefficients may be in an extension of the trace field, in
+    /// which case the composition polynomial is computed over that extension.
+    pub fn get_composition_poly<F>(&self, z: F, cc: &CompositionCoefficients<F>) -> (Vec<F>, Vec<F>, Vec<F>)
+        where F: FieldElement<BaseField = E>
+    {
         let trace_length = self.unextended_length();
         assert!(self.is_extended(), "trace table has not been extended yet");
         
-        let g = field::get_root_of_unity(trace_length);
-        let next_z = field::mul(z, g);
+        let g = E::get_root_of_unity(trace_length);
+        let next_z = z.mul_base(g);
 
         // compute state of registers at deep points z and z * g
         let trace_state1 = self.eval_polys_at(z);
         let trace_state2 = self.eval_polys_at(next_z);
 
-        let mut t1_composition = vec![field::ZERO; trace_length];
-        let mut t2_composition = vec![field::ZERO; trace_length];
+        let mut t1_composition = vec![F::ZERO; trace_length];
+        let mut t2_composition = vec![F::ZERO; trace_length];
 
         // combine trace polynomials into 2 composition polynomials T1(x) and T2(x)
         for i in 0..self.polys.len() {
             // compute T1(x) = (T(x) - T(z)), multiply it by a pseudo-random coefficient,
             // and add the result into composition polynomial
-            parallel::mul_acc(&mut t1_composition, &self.polys[i], cc.trace1[i], 1);
-            let adjusted_tz = field::mul(trace_state1[i], cc.trace1[i]);
-            t1_composition[0] = field::sub(t1_composition[0], adjusted_tz);
+            mul_acc_base(&mut t1_composition, &self.polys[i], cc.trace1[i]);
+            let adjusted_tz = trace_state1[i] * cc.trace1[i];
+            t1_composition[0] -= adjusted_tz;
 
             // compute T2(x) = (T(x) - T(z * g)), multiply it by a pseudo-random
             // coefficient, and add the result into composition polynomial
-            parallel::mul_acc(&mut t2_composition, &self.polys[i], cc.trace2[i], 1);
-            let adjusted_tz = field::mul(trace_state2[i], cc.trace2[i]);
-            t2_composition[0] = field::sub(t2_composition[0], adjusted_tz);
+            mul_acc_base(&mut t2_composition, &self.polys[i], cc.trace2[i]);
+            let adjusted_tz = trace_state2[i] * cc.trace2[i];
+            t2_composition[0] -= adjusted_tz;
         }
 
         // divide the two composition polynomials by (x - z) and (x - z * g)
@@ -244,9 +516,9 @@ impl TraceTable {
 
         // adjust the degree of the polynomial to match the degree parameter by computing
         // C(x) = T(x) * k_1 + T(x) * x^incremental_degree * k_2
-        let poly_size = utils::get_composition_degree(trace_length).next_power_of_two();
-        let mut composition_poly = filled_vector(poly_size, self.domain_size(), field::ZERO);
-        let incremental_degree = utils::get_incremental_trace_degree(trace_length);
+        let poly_size = utils::get_composition_degree(trace_length, self.blinding_rows).next_power_of_two();
+        let mut composition_poly = filled_vector(poly_size, self.domain_size(), F::ZERO);
+        let incremental_degree = utils::get_incremental_trace_degree(trace_length, self.blinding_rows);
         // this is equivalent to T(x) * k_1
         parallel::mul_acc(
             &mut composition_poly[..trace_length],
@@ -264,16 +536,68 @@ impl TraceTable {
     }
 }
 
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Computes a[i] + b[i] * c for all i and saves the result into a; values of `b` are in the
+/// base field of `c`.
+fn mul_acc_base<F: FieldElement>(a: &mut [F], b: &[F::BaseField], c: F) {
+    for (a, &b) in a.iter_mut().zip(b) {
+        *a += c.mul_base(b);
+    }
+}
+
+/// Hashes all rows of the specified `registers` into the provided `hashed_rows` slice; rows
+/// are split into `num_threads` contiguous batches, and each batch is hashed in one thread.
+fn hash_rows<E: StarkField>(registers: &[Vec<E>], hashed_rows: &mut [[u8; 32]], hash: HashAlgorithm, num_threads: usize) {
+    if num_threads == 1 {
+        hash.hash_rows(registers, 0, hashed_rows);
+    }
+    else {
+        let batch_size = (hashed_rows.len() + num_threads - 1) / num_threads;
+        thread::scope(|s| {
+            for (i, batch) in hashed_rows.chunks_mut(batch_size).enumerate() {
+                s.spawn(move |_| hash.hash_rows(registers, i * batch_size, batch));
+            }
+        }).unwrap();
+    }
+}
+
+/// Evaluates polynomial `poly` over the trace domain shifted by `offset` and saves the
+/// results into `register`.
+fn eval_register_segment<E: StarkField>(poly: &[E], twiddles: &[E], offset: E, register: &mut Vec<E>) {
+    register.clear();
+    register.extend_from_slice(poly);
+    polynom::shift(register, offset);
+    polynom::eval_fft_twiddles(register, twiddles, true);
+}
+
+/// Interpolates register trace into a polynomial (in place), and returns evaluations of the
+/// polynomial over the extended domain shifted by `domain_offset`.
+fn extend_register<E: StarkField>(poly: &mut [E], inv_twiddles: &[E], twiddles: &[E], domain_offset: E, domain_size: usize) -> Vec<E> {
+    // interpolate register trace into a polynomial
+    polynom::interpolate_fft_twiddles(poly, inv_twiddles, true);
+
+    // allocate space to hold extended evaluations and copy the polynomial into it
+    let mut register = vec![E::ZERO; domain_size];
+    register[..poly.len()].copy_from_slice(&poly);
+    polynom::shift(&mut register[..poly.len()], domain_offset);
+
+    // evaluate the polynomial over extended domain
+    polynom::eval_fft_twiddles(&mut register, twiddles, true);
+    return register;
+}
+
 // TESTS
 // ================================================================================================
 
 #[cfg(test)]
 mod tests {
 
-    use sp_std::collections::HashMap;
+    use hashbrown::HashMap;
     use crate::{
-        math::{ field, polynom, parallel, fft },
-        crypto::hash::blake3,
+        math::{ f128::BaseElement, polynom, parallel, fft, FieldElement, StarkField },
+        crypto::HashAlgorithm,
         programs::{ Program, ProgramInputs, blocks::{ ProgramBlock, Span, Group } },
         processor::{ execute, OpCode },
         stark::{ TraceTable, CompositionCoefficients, utils::get_composition_degree }
@@ -284,35 +608,35 @@ mod tests {
     #[test]
     fn eval_polys_at() {
         let mut trace = build_trace_table();
-        let lde_root = field::get_root_of_unity(trace.domain_size());
-        trace.extend(&fft::get_twiddles(lde_root, trace.domain_size()));
+        let lde_root = BaseElement::get_root_of_unity(trace.domain_size());
+        trace.extend(&fft::get_twiddles(lde_root, trace.domain_size()), BaseElement::ONE, 1);
 
-        let g = field::get_root_of_unity(trace.unextended_length());
+        let g = BaseElement::get_root_of_unity(trace.unextended_length());
 
         let v1 = trace.eval_polys_at(g);
         let s1 = trace.get_state(1 * EXT_FACTOR);
-        assert_eq!(v1, s1.to_vec());
+        assert_eq!(BaseElement::into_raw_vec(v1)[..s1.width()], s1.to_vec()[..]);
 
-        let v2 = trace.eval_polys_at(field::exp(g, 2));
+        let v2 = trace.eval_polys_at(g.exp(2));
         let s2 = trace.get_state(2 * EXT_FACTOR);
-        assert_eq!(v2, s2.to_vec());
+        assert_eq!(BaseElement::into_raw_vec(v2)[..s2.width()], s2.to_vec()[..]);
     }
 
     #[test]
     fn get_composition_poly() {
 
         let mut trace = build_trace_table();
-        let lde_root = field::get_root_of_unity(trace.domain_size());
-        trace.extend(&fft::get_twiddles(lde_root, trace.domain_size()));
+        let lde_root = BaseElement::get_root_of_unity(trace.domain_size());
+        trace.extend(&fft::get_twiddles(lde_root, trace.domain_size()), BaseElement::ONE, 1);
 
         // compute trace composition polynomial
-        let t_tree = trace.build_merkle_tree(blake3);
-        let z = field::prng(*t_tree.root());
+        let t_tree = trace.build_merkle_tree(HashAlgorithm::Blake3, 1);
+        let z = BaseElement::prng(*t_tree.root());
         let cc = CompositionCoefficients::new(*t_tree.root());
-        let target_degree =  get_composition_degree(trace.unextended_length());
+        let target_degree =  get_composition_degree(trace.unextended_length(), 0);
 
-        let g = field::get_root_of_unity(trace.unextended_length());
-        let zg = field::mul(z, g);
+        let g = BaseElement::get_root_of_unity(trace.unextended_length());
+        let zg = z * g;
 
         let (composition_poly, ..) = trace.get_composition_poly(z, &cc);
         let mut actual_evaluations = composition_poly.clone();
@@ -321,10 +645,10 @@ mod tests {
 
         // compute expected evaluations
         let domain_size = target_degree.next_power_of_two();
-        let domain_root = field::get_root_of_unity(domain_size);
-        let domain = field::get_power_series(domain_root, domain_size);
+        let domain_root = BaseElement::get_root_of_unity(domain_size);
+        let domain = BaseElement::get_power_series(domain_root, domain_size);
 
-        let mut expected_evaluations = vec![0; domain_size];
+        let mut expected_evaluations = vec![BaseElement::ZERO; domain_size];
 
         let tz = trace.eval_polys_at(z);
         let tzg = trace.eval_polys_at(zg);
@@ -332,21 +656,21 @@ mod tests {
         for i in 0..trace.register_count() {
             // add T1(x) to expected evaluations
             let mut trace_poly = trace.get_register_poly(i).to_vec();
-            trace_poly.resize(domain_size, 0);
+            trace_poly.resize(domain_size, BaseElement::ZERO);
             polynom::eval_fft(&mut trace_poly, true);
             parallel::sub_const_in_place(&mut trace_poly, tz[i], 1);
             for j in 0..trace_poly.len() {
-                trace_poly[j] = field::div(trace_poly[j], field::sub(domain[j], z));
+                trace_poly[j] = trace_poly[j] / (domain[j] - z);
             }
             parallel::mul_acc(&mut expected_evaluations, &trace_poly, cc.trace1[i], 1);
 
             // add T2(x) to expected evaluations
             let mut trace_poly = trace.get_register_poly(i).to_vec();
-            trace_poly.resize(domain_size, 0);
+            trace_poly.resize(domain_size, BaseElement::ZERO);
             polynom::eval_fft(&mut trace_poly, true);
             parallel::sub_const_in_place(&mut trace_poly, tzg[i], 1);
             for j in 0..trace_poly.len() {
-                trace_poly[j] = field::div(trace_poly[j], field::sub(domain[j], zg));
+                trace_poly[j] = trace_poly[j] / (domain[j] - zg);
             }
             parallel::mul_acc(&mut expected_evaluations, &trace_poly, cc.trace2[i], 1);
         }
@@ -355,11 +679,11 @@ mod tests {
         let incremental_degree = target_degree - (trace.unextended_length() - 2);
         for i in 0..domain.len() {
             let y = expected_evaluations[i];
-            let y1 = field::mul(y, cc.t1_degree);
+            let y1 = y * cc.t1_degree;
 
-            let xp = field::exp(domain[i], incremental_degree as u128);
-            let y2 = field::mul(field::mul(y, xp), cc.t2_degree);
-            expected_evaluations[i] = field::add(y1, y2);
+            let xp = domain[i].exp(incremental_degree as u128);
+            let y2 = y * xp * cc.t2_degree;
+            expected_evaluations[i] = y1 + y2;
         }
 
         assert_eq!(expected_evaluations, actual_evaluations);
@@ -376,7 +700,7 @@ mod tests {
             ProgramBlock::Span(Span::new(instructions, HashMap::new()))
         ]));
         let inputs = ProgramInputs::from_public(&[1, 0]);
-        let (trace, ctx_depth, loop_depth) = execute(&program, &inputs);
+        let (trace, ctx_depth, loop_depth) = execute(&program, &inputs, &Default::default()).unwrap();
         return TraceTable::new(trace, ctx_depth, loop_depth, EXT_FACTOR);
     }
 }
\ No newline at end of file
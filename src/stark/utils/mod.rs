@@ -6,8 +6,8 @@ use rand::{Rng};
 // use crate::stark::trace::trace_state::fmt::string::lossy::char::methods::unicode::N;
 
 use super::{ ProofOptions, MAX_CONSTRAINT_DEGREE };
+use crate::{ crypto::HashFunction, math::FieldElement, utils::as_bytes };
 use sp_std::vec::Vec;
-use wasm_bindgen_test::console_log;
 
 // RE-EXPORTS
 // ================================================================================================
@@ -17,23 +17,23 @@ pub use coefficients::{ ConstraintCoefficients, CompositionCoefficients };
 mod proof_of_work;
 pub use proof_of_work::{ find_pow_nonce, verify_pow_nonce };
 
-pub fn get_composition_degree(trace_length: usize) -> usize {
-    return (MAX_CONSTRAINT_DEGREE - 1) * trace_length - 1;
+/// Returns degree of the composition polynomial; transition constraints are not enforced over
+/// the blinding rows of the trace, and so every blinding row increases the degree by 1.
+pub fn get_composition_degree(trace_length: usize, blinding_rows: usize) -> usize {
+    return (MAX_CONSTRAINT_DEGREE - 1) * trace_length + blinding_rows - 1;
 }
 
 // PUBLIC FUNCTIONS
 // ================================================================================================
 
-pub fn get_incremental_trace_degree(trace_length: usize) -> usize {
-    let composition_degree = get_composition_degree(trace_length);
+pub fn get_incremental_trace_degree(trace_length: usize, blinding_rows: usize) -> usize {
+    let composition_degree = get_composition_degree(trace_length, blinding_rows);
     return composition_degree - (trace_length - 2);
 }
 
 pub fn compute_query_positions(seed: &[u8; 32], domain_size: usize, options: &ProofOptions) -> Vec<usize> {
     let range = Uniform::from(0..domain_size);
     log::debug!(target:"starks-verifier","seeeeed is {:?}",seed);
-    println!("seeed is {:?},domainsize is {:?}",seed,domain_size);
-    console_log!("seeed is {:?},domainsize is {:?}",seed,domain_size);
 
     let mut index_iter = StdRng::from_seed(*seed).sample_iter(range);
     let num_queries = options.num_queries();
@@ -61,11 +61,43 @@ pub fn compute_query_positions(seed: &[u8; 32], domain_size: usize, options: &Pr
     return result;
 }
 
-pub fn map_trace_to_constraint_positions(positions: &[usize]) -> Vec<usize> {
+/// Maps trace positions to positions of constraint tree leaves; a leaf contains evaluations at
+/// 2 consecutive positions, or, in zero-knowledge mode, constraint and mask evaluations at a
+/// single position.
+pub fn map_trace_to_constraint_positions(positions: &[usize], options: &ProofOptions) -> Vec<usize> {
+    let values_per_leaf = if options.zk() { 1 } else { 2 };
     let mut result = Vec::with_capacity(positions.len());
     for &position in positions.iter() {
-        let cp = position / 2;
+        let cp = position / values_per_leaf;
         if !result.contains(&cp) { result.push(cp); }
     }
     return result;
-}
\ No newline at end of file
+}
+/// Converts a row of constraint evaluations into a leaf of constraint Merkle tree; a row which
+/// fits into 32 bytes is used as a leaf directly (padded with zeros), and a longer row (e.g.
+/// a row of extension field elements) is hashed into a leaf.
+pub fn constraint_row_to_leaf<E: FieldElement>(row: &[E], hash: HashFunction) -> [u8; 32] {
+    let bytes = as_bytes(row);
+    let mut leaf = [0u8; 32];
+    if bytes.len() <= 32 {
+        leaf[..bytes.len()].copy_from_slice(bytes);
+    }
+    else {
+        hash(bytes, &mut leaf);
+    }
+    return leaf;
+}
+
+/// Returns the seed from which coefficients for combining constraints are derived; when the
+/// trace has an auxiliary segment, the seed commits to both the main and the auxiliary
+/// segments of the trace, otherwise it is just the root of the trace tree.
+pub fn get_constraint_seed(trace_root: &[u8; 32], aux_root: Option<&[u8; 32]>, hash: HashFunction) -> [u8; 32] {
+    return match aux_root {
+        Some(aux_root) => {
+            let mut seed = [0u8; 32];
+            hash(&[&trace_root[..], &aux_root[..]].concat(), &mut seed);
+            seed
+        },
+        None => *trace_root,
+    };
+}
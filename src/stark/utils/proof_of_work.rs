@@ -1,35 +1,42 @@
-use sp_std::slice;
+use core::sync::atomic::{ AtomicU64, Ordering };
 use alloc::string::String;
+use crossbeam_utils::thread;
+use crate::crypto::HashFunction;
 use crate::stark::{ ProofOptions };
 
-pub fn find_pow_nonce(seed: [u8; 32], options: &ProofOptions) -> ([u8; 32], u64) {
+// CONSTANTS
+// ================================================================================================
 
-    let hash = options.hash_fn();
-    let grinding_factor = options.grinding_factor();
+/// Number of consecutive nonces a thread claims at a time when grinding in multiple threads.
+const NONCE_BATCH_SIZE: u64 = 1024;
 
-    // copy seed into inputs and also get a reference to inputs as to an array of bytes
-    let mut inputs = [0u64; 8];
-    let input_bytes: &mut [u8] = unsafe {
-        slice::from_raw_parts_mut(inputs.as_ptr() as *mut u8, inputs.len() * 8)
-    };
-    input_bytes[0..32].copy_from_slice(&seed);
+// PUBLIC FUNCTIONS
+// ================================================================================================
 
-    // create buffer to hold outputs and also get a reference to it as to any array of bytes
-    let output = [0u64; 4];
-    let mut output_bytes = unsafe {
-        slice::from_raw_parts_mut(output.as_ptr() as *mut u8, output.len() * 8)
-    };
+/// Finds the smallest nonce (starting with 1) which, when hashed together with the seed,
+/// produces a hash with at least `grinding_factor` trailing zeros. When `num_threads` is
+/// greater than 1, nonces are checked in batches by multiple threads; the result is the same
+/// as for a single thread.
+pub fn find_pow_nonce(seed: [u8; 32], options: &ProofOptions, num_threads: usize) -> ([u8; 32], u64) {
 
-    loop {
-        inputs[4] += 1;
-        hash(&input_bytes, &mut output_bytes);
-        if output[0].trailing_zeros() >= grinding_factor { break; }
+    let hash = options.hash_fn();
+    let grinding_factor = options.grinding_factor();
+    if num_threads > 1 {
+        let nonce = find_pow_nonce_concurrent(seed, hash, grinding_factor, num_threads);
+        let result = verify_pow_nonce(seed, nonce, options).unwrap();
+        return (result, nonce);
     }
 
-    let mut result = [0; 32];
-    result.copy_from_slice(output_bytes);
+    // check nonces one by one starting with 1
+    let mut input_bytes = build_pow_inputs(seed);
+    let mut output_bytes = [0u8; 32];
+    let mut nonce = 0;
+    loop {
+        nonce += 1;
+        if hash_pow_nonce(hash, &mut input_bytes, nonce, &mut output_bytes) >= grinding_factor { break; }
+    }
 
-    return (result, inputs[4]);
+    return (output_bytes, nonce);
 }
 
 pub fn verify_pow_nonce(seed: [u8; 32], nonce: u64, options: &ProofOptions) -> Result<[u8; 32], String> {
@@ -37,22 +44,82 @@ pub fn verify_pow_nonce(seed: [u8; 32], nonce: u64, options: &ProofOptions) -> R
     let hash = options.hash_fn();
 
     // append nonce to seed for hashing
-    let mut input_bytes = [0; 64];
+    let mut input_bytes = build_pow_inputs(seed);
+    let mut output_bytes = [0u8; 32];
+    if hash_pow_nonce(hash, &mut input_bytes, nonce, &mut output_bytes) < options.grinding_factor() {
+        return Err(String::from("seed proof-of-work verification failed"));
+    }
+
+    return Ok(output_bytes);
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Threads claim batches of nonces in increasing order and record the smallest valid nonce
+/// found so far; a thread stops once the next unclaimed batch starts above that nonce. Since
+/// every batch below the result has been fully checked, the result is the smallest valid nonce.
+fn find_pow_nonce_concurrent(seed: [u8; 32], hash: HashFunction, grinding_factor: u32, num_threads: usize) -> u64 {
+    let next_batch = AtomicU64::new(0);
+    let best_nonce = AtomicU64::new(u64::MAX);
+
+    thread::scope(|s| {
+        for _ in 0..num_threads {
+            s.spawn(|_| {
+                let mut input_bytes = build_pow_inputs(seed);
+                let mut output_bytes = [0u8; 32];
+
+                loop {
+                    let batch_start = next_batch.fetch_add(1, Ordering::Relaxed) * NONCE_BATCH_SIZE + 1;
+                    if batch_start > best_nonce.load(Ordering::Relaxed) { break; }
+
+                    for nonce in batch_start..(batch_start + NONCE_BATCH_SIZE) {
+                        if hash_pow_nonce(hash, &mut input_bytes, nonce, &mut output_bytes) >= grinding_factor {
+                            best_nonce.fetch_min(nonce, Ordering::Relaxed);
+                            break;
+                        }
+                    }
+                }
+            });
+        }
+    }).unwrap();
+
+    return best_nonce.load(Ordering::Relaxed);
+}
+
+/// Returns a hash input which consists of the seed followed by 32 bytes reserved for the nonce.
+fn build_pow_inputs(seed: [u8; 32]) -> [u8; 64] {
+    let mut input_bytes = [0u8; 64];
     input_bytes[0..32].copy_from_slice(&seed);
+    return input_bytes;
+}
+
+/// Writes the nonce into the hash input, hashes it into `output_bytes`, and returns the number
+/// of trailing zeros in the first 8 bytes of the hash (read as a little-endian integer).
+fn hash_pow_nonce(hash: HashFunction, input_bytes: &mut [u8; 64], nonce: u64, output_bytes: &mut [u8; 32]) -> u32 {
     input_bytes[32..40].copy_from_slice(&nonce.to_le_bytes());
+    hash(input_bytes, output_bytes);
+    let mut first_word = [0u8; 8];
+    first_word.copy_from_slice(&output_bytes[..8]);
+    return u64::from_le_bytes(first_word).trailing_zeros();
+}
 
-    let output = [0u64; 4];
-    let mut output_bytes: &mut [u8] = unsafe {
-        slice::from_raw_parts_mut(output.as_ptr() as *mut u8, output.len() * 8)
-    };
+// TESTS
+// ================================================================================================
+#[cfg(test)]
+mod tests {
 
-    hash(&input_bytes, &mut output_bytes);
-    if output[0].trailing_zeros() < options.grinding_factor() {
-        return Err(String::from("seed proof-of-work verification failed"));
-    }
+    use crate::{ crypto::HashAlgorithm, stark::ProofOptions };
 
-    let mut result = [0; 32];
-    result.copy_from_slice(output_bytes);
+    #[test]
+    fn find_pow_nonce_concurrent() {
+        let options = ProofOptions::new(32, 4, 12, HashAlgorithm::Blake3);
+        let seed = [7u8; 32];
 
-    return Ok(result);
-}
\ No newline at end of file
+        let expected = super::find_pow_nonce(seed, &options, 1);
+        for &num_threads in [2, 4, 8].iter() {
+            assert_eq!(expected, super::find_pow_nonce(seed, &options, num_threads));
+        }
+        assert_eq!(Ok(expected.0), super::verify_pow_nonce(seed, expected.1, &options));
+    }
+}
@@ -1,17 +1,110 @@
 use crate::{
-    math::field,
+    math::{ FieldElement, StarkField },
     crypto::{ MerkleTree },
-    MIN_TRACE_LENGTH
+    utils::as_bytes,
+    MAX_REGISTER_COUNT,
+};
+use super::{
+    Air, TraceInfo, StarkProof, FieldExtension, ConstraintEvaluator, CompositionCoefficients, fri, utils,
+    constraints::interpolate_periodic_columns,
 };
-use super::{ StarkProof, TraceState, ConstraintEvaluator, CompositionCoefficients, fri, utils };
 use alloc::string::String;
-use sp_std::{vec, vec::Vec};
+use sp_std::{vec, vec::Vec, sync::atomic::{ AtomicBool, Ordering }};
+use crossbeam_utils::thread;
+
+// TYPES AND INTERFACES
+// ================================================================================================
 
+/// Values needed by the verifier which are the same for all proofs of a computation with the
+/// same trace shape, LDE domain, LDE domain offset, and periodic columns; when a batch of proofs
+/// is verified, these values are computed once for every distinct combination of these
+/// parameters.
+struct VerifierContext<B: StarkField> {
+    trace_info      : TraceInfo,
+    domain_size     : usize,
+    domain_offset   : B,
+    lde_root        : B,
+    trace_root      : B,
+    exceptions      : Vec<B>,
+    periodic_columns: Vec<Vec<B>>,
+    periodic_polys  : Vec<Vec<B>>,
+}
 
-// VERIFIER FUNCTION
+// VERIFIER FUNCTIONS
 // ================================================================================================
 
-pub fn verify(program_hash: &[u8; 32], inputs: &[u128], outputs: &[u128], proof: &StarkProof) -> Result<bool, String>
+/// Verifies that the `proof` attests to a valid execution of the computation described by AIR
+/// `A` instantiated with `pub_inputs`.
+pub fn verify<A: Air>(proof: &StarkProof<A::BaseElement>, pub_inputs: &A::PublicInputs) -> Result<bool, String>
+{
+    return verify_with_context::<A>(proof, pub_inputs, &[]);
+}
+
+/// Verifies a batch of proofs, each against its own public inputs, and returns a result for
+/// every proof in the order in which the proofs appear in the batch. Values which are the same
+/// for proofs with the same trace shape and proof options (domain roots, periodic column
+/// polynomials etc.) are computed once per batch. Proofs are distributed across `num_threads`
+/// threads such that each thread verifies a contiguous range of proofs.
+pub fn verify_batch<A: Air>(proofs: &[(&StarkProof<A::BaseElement>, A::PublicInputs)], num_threads: usize) -> Vec<Result<bool, String>>
+    where A::PublicInputs: Sync
+{
+    return run_batch::<A>(proofs, num_threads, false).into_iter()
+        .map(|result| result.expect("every proof in the batch must be verified"))
+        .collect();
+}
+
+/// Returns `Ok(true)` if all proofs in the batch are valid; otherwise, returns an error which
+/// identifies an invalid proof. Unlike `verify_batch()`, this stops verifying the batch as soon
+/// as an invalid proof is found.
+pub fn verify_all<A: Air>(proofs: &[(&StarkProof<A::BaseElement>, A::PublicInputs)], num_threads: usize) -> Result<bool, String>
+    where A::PublicInputs: Sync
+{
+    for (i, result) in run_batch::<A>(proofs, num_threads, true).into_iter().enumerate() {
+        match result {
+            Some(Ok(true)) | None => continue,
+            Some(Ok(false)) => return Ok(false),
+            Some(Err(msg)) => return Err(format!("verification of proof {} failed: {}", i, msg)),
+        }
+    }
+    return Ok(true);
+}
+
+/// Verifies the `proof` using pre-computed values from one of the `contexts`; if none of the
+/// contexts matches the proof, the values are computed for the proof.
+fn verify_with_context<A: Air>(
+    proof       : &StarkProof<A::BaseElement>,
+    pub_inputs  : &A::PublicInputs,
+    contexts    : &[VerifierContext<A::BaseElement>]) -> Result<bool, String>
+{
+    if !A::supports_extension(proof.options().field_extension()) {
+        return Err(String::from("field extension of the proof is not supported by the computation"));
+    }
+
+    return match proof.options().field_extension() {
+        FieldExtension::None => {
+            verify_proof::<A, A::BaseElement>(proof, pub_inputs, contexts)
+        },
+        FieldExtension::Quadratic => {
+            if <A::BaseElement as StarkField>::QuadField::EXTENSION_DEGREE != 2 {
+                return Err(String::from("quadratic extension is not supported for the base field of the computation"));
+            }
+            verify_proof::<A, <A::BaseElement as StarkField>::QuadField>(proof, pub_inputs, contexts)
+        },
+        FieldExtension::Cubic => {
+            if <A::BaseElement as StarkField>::CubeField::EXTENSION_DEGREE != 3 {
+                return Err(String::from("cubic extension is not supported for the base field of the computation"));
+            }
+            verify_proof::<A, <A::BaseElement as StarkField>::CubeField>(proof, pub_inputs, contexts)
+        },
+    };
+}
+
+/// Verifies the `proof` with out-of-domain sampling, constraint composition, and FRI performed
+/// over field `E`, which is either the base field of the computation or its extension.
+fn verify_proof<A: Air, E: FieldElement<BaseField = A::BaseElement>>(
+    proof       : &StarkProof<A::BaseElement>,
+    pub_inputs  : &A::PublicInputs,
+    contexts    : &[VerifierContext<A::BaseElement>]) -> Result<bool, String>
 {
     let options = proof.options();
     let hash_fn = options.hash_fn();
@@ -21,7 +114,7 @@ pub fn verify(program_hash: &[u8; 32], inputs: &[u128], outputs: &[u128], proof:
     for layer in degree_proof.layers.iter() {
         layer.root.iter().for_each(|&v| fri_roots.push(v));
     }
-    degree_proof.rem_root.iter().for_each(|&v| fri_roots.push(v));
+    as_bytes(&degree_proof.remainder).iter().for_each(|&v| fri_roots.push(v));
 
     let mut seed = [0u8; 32];
     hash_fn(&fri_roots, &mut seed);
@@ -31,11 +124,37 @@ pub fn verify(program_hash: &[u8; 32], inputs: &[u128], outputs: &[u128], proof:
     };
 
     let t_positions = utils::compute_query_positions(&seed, proof.domain_size(), options);
-    let c_positions = utils::map_trace_to_constraint_positions(&t_positions);
+    let c_positions = utils::map_trace_to_constraint_positions(&t_positions, options);
+
+    // 2 ----- Instantiate AIR of the computation for the claimed trace shape ----------------------
+    let trace_info = proof.trace_info();
+    let air = build_air::<A>(&trace_info, pub_inputs)?;
+
+    // use one of the provided contexts if it was built for proofs like this one
+    let local_context;
+    let context = match contexts.iter().find(|context| context.matches(&air, proof)) {
+        Some(context) => context,
+        None => {
+            local_context = VerifierContext::new(&air, proof);
+            &local_context
+        }
+    };
+
+    // make sure all trace values in the proof match the width of the trace (including the
+    // auxiliary segment); out-of-domain trace states consist of elements of field E
+    let width = trace_info.width + air.aux_width();
+    let width_mismatch = proof.trace_evaluations().iter().any(|r| r.len() != width)
+        || proof.trace_at_z1().len() != width * E::EXTENSION_DEGREE
+        || proof.trace_at_z2().len() != width * E::EXTENSION_DEGREE;
+    if width_mismatch {
+        return Err(String::from("number of trace values does not match trace width"));
+    }
+    let trace_at_z1 = E::slice_from_base_elements(proof.trace_at_z1());
+    let trace_at_z2 = E::slice_from_base_elements(proof.trace_at_z2());
 
-    // 2 ----- Verify number of operations in the program -----------------------------------------
-    if proof.op_count() < MIN_TRACE_LENGTH as u128 {
-        return Err(String::from("Verification of minimum operation count failed"));
+    // make sure every row of constraint evaluations consists of 2 elements of field E
+    if proof.constraint_rows().iter().any(|r| r.len() != 2 * E::EXTENSION_DEGREE) {
+        return Err(String::from("number of constraint values does not match field extension"));
     }
 
     // 3 ----- Verify trace and constraint Merkle proofs ------------------------------------------
@@ -43,19 +162,40 @@ pub fn verify(program_hash: &[u8; 32], inputs: &[u128], outputs: &[u128], proof:
         return Err(String::from("verification of trace Merkle proof failed"));
     }
 
+    // the auxiliary segment is committed to only if the trace has one
+    let aux_root = match (air.aux_width(), proof.aux_root()) {
+        (0, None) => None,
+        (0, Some(_)) => return Err(String::from("proof commits to an unexpected auxiliary trace segment")),
+        (_, None) => return Err(String::from("proof does not commit to the auxiliary trace segment")),
+        (_, Some(aux_root)) => {
+            if !MerkleTree::verify_batch(aux_root, &t_positions, &proof.aux_proof(), hash_fn) {
+                return Err(String::from("verification of auxiliary trace Merkle proof failed"));
+            }
+            Some(aux_root)
+        },
+    };
+
     if !MerkleTree::verify_batch(proof.constraint_root(), &c_positions, &proof.constraint_proof(), hash_fn) {
         return Err(String::from("verification of constraint Merkle proof failed"));
     }
 
     // 4 ----- Compute constraint evaluations at DEEP point z -------------------------------------
     // derive DEEP point z from the root of the constraint tree
-    let z = field::prng(*proof.constraint_root());
+    let z = E::prng(*proof.constraint_root());
+
+    // derive random elements of the auxiliary segment from the root of the trace tree, and
+    // coefficients for combining constraints from the roots of both trace trees
+    let aux_rand = A::BaseElement::prng_vector(*proof.trace_root(), air.aux_rand_count());
+    let seed = utils::get_constraint_seed(proof.trace_root(), aux_root, hash_fn);
 
     // evaluate constraints at z
+    let evaluator = ConstraintEvaluator::<A, E>::with_periodic_polys(
+        air, &seed, aux_rand, context.domain_offset, context.periodic_polys.clone());
     let constraint_evaluation_at_z = evaluate_constraints(
-        ConstraintEvaluator::from_proof(proof, program_hash, inputs, outputs),
-        proof.get_state_at_z1(),
-        proof.get_state_at_z2(),
+        evaluator,
+        &context.exceptions,
+        trace_at_z1,
+        trace_at_z2,
         z
     );
 
@@ -64,72 +204,175 @@ pub fn verify(program_hash: &[u8; 32], inputs: &[u128], outputs: &[u128], proof:
     let coefficients = CompositionCoefficients::new(*proof.constraint_root());
 
     // compute composition values separately for trace and constraints, and then add them together
-    let t_composition = compose_registers(&proof, &t_positions, z, &coefficients);
-    let c_composition = compose_constraints(&proof, &t_positions, &c_positions, z, constraint_evaluation_at_z, &coefficients);
-    let evaluations = t_composition.iter().zip(c_composition).map(|(&t, c)| field::add(t, c)).collect::<Vec<u128>>();
+    let t_composition = compose_registers(&proof, context, &t_positions, trace_at_z1, trace_at_z2, z, &coefficients);
+    let c_composition = compose_constraints(&proof, context, &t_positions, &c_positions, z, constraint_evaluation_at_z, &coefficients);
+    let evaluations = t_composition.iter().zip(c_composition).map(|(&t, c)| t + c).collect::<Vec<_>>();
     
     // 6 ----- Verify low-degree proof -------------------------------------------------------------
-    let max_degree = utils::get_composition_degree(proof.trace_length());
-    return match fri::verify(&degree_proof, &evaluations, &t_positions, max_degree, options) {
+    let max_degree = utils::get_composition_degree(proof.trace_length(), options.blinding_rows());
+    return match fri::verify(&degree_proof, &evaluations, &t_positions, proof.domain_size(), max_degree, options) {
         Ok(result) => Ok(result),
         Err(msg) => Err(format!("verification of low-degree proof failed: {}", msg))
     }
 }
 
+// VERIFIER CONTEXT IMPLEMENTATION
+// ================================================================================================
+impl<B: StarkField> VerifierContext<B> {
+
+    /// Returns a context for proofs of the computation described by the `air` which have the
+    /// same shape and proof options as the specified `proof`.
+    fn new<A: Air<BaseElement = B>>(air: &A, proof: &StarkProof<B>) -> VerifierContext<B> {
+        let trace_info = air.trace_info().clone();
+        let trace_root = B::get_root_of_unity(trace_info.length);
+
+        // transition constraints are not enforced at the last step of the computation and
+        // at all blinding rows which follow it
+        let exceptions = (trace_info.last_step()..trace_info.length)
+            .map(|i| trace_root.exp(i as u128))
+            .collect();
+
+        let periodic_columns = air.get_periodic_columns();
+        let periodic_polys = interpolate_periodic_columns(periodic_columns.clone(), trace_info.length);
+        return VerifierContext {
+            domain_size     : proof.domain_size(),
+            domain_offset   : proof.options().domain_offset(),
+            lde_root        : B::get_root_of_unity(proof.domain_size()),
+            trace_root, trace_info, exceptions, periodic_columns, periodic_polys,
+        };
+    }
+
+    /// Returns `true` if this context can be used to verify the specified `proof` against the
+    /// `air`; periodic columns are compared by value since they may depend on public inputs.
+    fn matches<A: Air<BaseElement = B>>(&self, air: &A, proof: &StarkProof<B>) -> bool {
+        return self.domain_size == proof.domain_size()
+            && self.domain_offset == proof.options().domain_offset()
+            && self.trace_info == proof.trace_info()
+            && self.periodic_columns == air.get_periodic_columns();
+    }
+}
+
 // HELPER FUNCTIONS
 // ================================================================================================
-fn evaluate_constraints(evaluator: ConstraintEvaluator, state1: TraceState, state2: TraceState, x: u128) -> u128 {
-    let (i_value, f_value) = evaluator.evaluate_boundaries(&state1, x);
-    let t_value = evaluator.evaluate_transition_at(&state1, &state2, x);
 
-    // Z(x) = x - 1
-    let z = field::sub(x, field::ONE);
-    let mut result = field::div(i_value, z);
+/// Verifies all proofs in the batch and returns a result for every proof; if `fail_fast` is
+/// set, proofs which have not been verified by the time an invalid proof is found are skipped,
+/// and their results are set to `None`.
+fn run_batch<A: Air>(proofs: &[(&StarkProof<A::BaseElement>, A::PublicInputs)], num_threads: usize, fail_fast: bool)
+    -> Vec<Option<Result<bool, String>>>
+    where A::PublicInputs: Sync
+{
+    assert!(num_threads > 0, "number of threads must be greater than 0");
+
+    // build a context for every distinct combination of trace shape, proof options, and
+    // periodic columns; if AIR cannot be instantiated for a proof, the proof will fail
+    // verification on its own
+    let mut contexts: Vec<VerifierContext<A::BaseElement>> = Vec::new();
+    for (proof, pub_inputs) in proofs.iter() {
+        if let Ok(air) = build_air::<A>(&proof.trace_info(), pub_inputs) {
+            if contexts.iter().any(|context| context.matches(&air, proof)) { continue; }
+            contexts.push(VerifierContext::new(&air, proof));
+        }
+    }
+
+    let failed = AtomicBool::new(false);
+    let contexts = &contexts;
+    let failed = &failed;
+    let verify_range = move |first_proof: usize, results: &mut [Option<Result<bool, String>>]| {
+        for (i, result) in results.iter_mut().enumerate() {
+            if fail_fast && failed.load(Ordering::Relaxed) { return; }
+            let (proof, pub_inputs) = &proofs[first_proof + i];
+            let verdict = verify_with_context::<A>(proof, pub_inputs, contexts);
+            if verdict != Ok(true) {
+                failed.store(true, Ordering::Relaxed);
+            }
+            *result = Some(verdict);
+        }
+    };
+
+    let mut results = vec![None; proofs.len()];
+    if num_threads == 1 || proofs.len() < 2 {
+        verify_range(0, &mut results);
+    }
+    else {
+        let batch_size = (proofs.len() + num_threads - 1) / num_threads;
+        let verify_range = &verify_range;
+        thread::scope(|s| {
+            for (i, batch) in results.chunks_mut(batch_size).enumerate() {
+                s.spawn(move |_| verify_range(i * batch_size, batch));
+            }
+        }).unwrap();
+    }
+    return results;
+}
+
+/// Instantiates AIR `A` for an execution trace of the specified shape; returns an error if
+/// the shape is not valid.
+fn build_air<A: Air>(trace_info: &TraceInfo, pub_inputs: &A::PublicInputs) -> Result<A, String> {
+    if trace_info.width == 0 || trace_info.width >= MAX_REGISTER_COUNT {
+        return Err(format!("execution trace must consist of between 1 and {} registers", MAX_REGISTER_COUNT - 1));
+    }
+    if trace_info.length <= trace_info.blinding_rows {
+        return Err(String::from("execution trace is too short"));
+    }
+    return A::new(trace_info, pub_inputs);
+}
+
+fn evaluate_constraints<A, E>(evaluator: ConstraintEvaluator<A, E>, exceptions: &[A::BaseElement], state1: &[E], state2: &[E], x: E) -> E
+    where A: Air, E: FieldElement<BaseField = A::BaseElement>
+{
+    let mut b_values = vec![E::ZERO; evaluator.assertion_step_count()];
+    evaluator.evaluate_boundaries(state1, x, &mut b_values);
+    let t_value = evaluator.evaluate_transition_at(state1, state2, x);
 
-    // Z(x) = x - x_at_last_step
-    let z = field::sub(x, evaluator.get_x_at_last_step());
-    result = field::add(result, field::div(f_value, z));
+    // Z(x) = x - x_at_step, for every step against which assertions are made
+    let mut result = E::ZERO;
+    for (&b_value, x_at_step) in b_values.iter().zip(evaluator.get_assertion_points()) {
+        result = result + b_value / (x - E::from(x_at_step));
+    }
 
-    // Z(x) = (x^steps - 1) / (x - x_at_last_step)
-    let z = field::div(field::sub(field::exp(x, evaluator.trace_length() as u128), field::ONE), z);
-    result = field::add(result, field::div(t_value, z));
+    // Z(x) = (x^steps - 1) / ((x - x_at_last_step) * ... * (x - x_at_blinding_rows))
+    let mut z = x.exp(evaluator.trace_length() as u128) - E::ONE;
+    for &exception in exceptions.iter() {
+        z = z / (x - E::from(exception));
+    }
+    result = result + t_value / z;
 
     return result;
 }
 
-fn compose_registers(proof: &StarkProof, positions: &[usize], z: u128, cc: &CompositionCoefficients) -> Vec<u128>
+fn compose_registers<B, E>(proof: &StarkProof<B>, context: &VerifierContext<B>, positions: &[usize], trace_at_z1: &[E], trace_at_z2: &[E], z: E, cc: &CompositionCoefficients<E>) -> Vec<E>
+    where B: StarkField, E: FieldElement<BaseField = B>
 {    
-    let lde_root = field::get_root_of_unity(proof.domain_size());
-    let trace_root = field::get_root_of_unity(proof.trace_length());
-    let next_z = field::mul(z, trace_root);
+    let options = proof.options();
+    let next_z = z.mul_base(context.trace_root);
 
-    let trace_at_z1 = proof.get_state_at_z1().to_vec();
-    let trace_at_z2 = proof.get_state_at_z2().to_vec();
     let evaluations = proof.trace_evaluations();
 
-    let incremental_degree = utils::get_incremental_trace_degree(proof.trace_length()) as u128;
+    let incremental_degree = utils::get_incremental_trace_degree(proof.trace_length(), options.blinding_rows()) as u128;
 
     let mut result = Vec::with_capacity(evaluations.len());
     for (registers, &position) in evaluations.into_iter().zip(positions) {
-        let x = field::exp(lde_root, position as u128);
+        let x = E::from(context.domain_offset * context.lde_root.exp(position as u128));
         
-        let mut composition = field::ZERO;
+        let mut composition = E::ZERO;
         for (i, &value) in registers.iter().enumerate() {
+            let value = E::from(value);
             // compute T1(x) = (T(x) - T(z)) / (x - z)
-            let t1 = field::div(field::sub(value, trace_at_z1[i]), field::sub(x, z));
+            let t1 = (value - trace_at_z1[i]) / (x - z);
             // multiply it by a pseudo-random coefficient, and combine with result
-            composition = field::add(composition, field::mul(t1, cc.trace1[i]));
+            composition = composition + t1 * cc.trace1[i];
 
             // compute T2(x) = (T(x) - T(z * g)) / (x - z * g)
-            let t2 = field::div(field::sub(value, trace_at_z2[i]), field::sub(x, next_z));
+            let t2 = (value - trace_at_z2[i]) / (x - next_z);
             // multiply it by a pseudo-random coefficient, and combine with result
-            composition = field::add(composition, field::mul(t2, cc.trace2[i]));
+            composition = composition + t2 * cc.trace2[i];
         }
 
         // raise the degree to match composition degree
-        let xp = field::exp(x, incremental_degree);
-        let adj_composition = field::mul(field::mul(composition, xp), cc.t2_degree);
-        composition = field::add(field::mul(composition, cc.t1_degree), adj_composition);
+        let xp = x.exp(incremental_degree);
+        let adj_composition = composition * xp * cc.t2_degree;
+        composition = composition * cc.t1_degree + adj_composition;
 
         result.push(composition);
     }
@@ -137,28 +380,39 @@ fn compose_registers(proof: &StarkProof, positions: &[usize], z: u128, cc: &Comp
     return result;
 }
 
-fn compose_constraints(proof: &StarkProof, t_positions: &[usize], c_positions: &[usize], z: u128, evaluation_at_z: u128, cc: &CompositionCoefficients) -> Vec<u128> {
-    // build constraint evaluation values from the leaves of constraint Merkle proof
-    let mut evaluations: Vec<u128> = Vec::with_capacity(t_positions.len());
-    let leaves = proof.constraint_proof().values;
+fn compose_constraints<B, E>(proof: &StarkProof<B>, context: &VerifierContext<B>, t_positions: &[usize], c_positions: &[usize], z: E, evaluation_at_z: E, cc: &CompositionCoefficients<E>) -> Vec<E>
+    where B: StarkField, E: FieldElement<BaseField = B>
+{
+    // build constraint evaluation values from the rows of constraint evaluations; in
+    // zero-knowledge mode, each row also contains an evaluation of the mask polynomial
+    let options = proof.options();
+    let mut evaluations: Vec<E> = Vec::with_capacity(t_positions.len());
+    let mut masks: Vec<E> = Vec::with_capacity(t_positions.len());
+    let rows = proof.constraint_rows();
     for &position in t_positions.iter() {
-        let leaf_idx = c_positions.iter().position(|&v| v == position / 2).unwrap();
-        let element_start = (position % 2) * 16;
-        let element_bytes = &leaves[leaf_idx][element_start..(element_start + 16)];
-        evaluations.push(field::from_bytes(element_bytes));
+        if options.zk() {
+            let row_idx = c_positions.iter().position(|&v| v == position).unwrap();
+            let row = E::slice_from_base_elements(&rows[row_idx]);
+            evaluations.push(row[0]);
+            masks.push(row[1]);
+        }
+        else {
+            let row_idx = c_positions.iter().position(|&v| v == position / 2).unwrap();
+            let row = E::slice_from_base_elements(&rows[row_idx]);
+            evaluations.push(row[position % 2]);
+            masks.push(E::ZERO);
+        }
     }
 
-    let lde_root = field::get_root_of_unity(proof.domain_size());
-
     // divide out deep point from the evaluations
     let mut result = Vec::with_capacity(evaluations.len());
-    for (evaluation, &position) in evaluations.into_iter().zip(t_positions) {
-        let x = field::exp(lde_root, position as u128);
+    for ((evaluation, mask), &position) in evaluations.into_iter().zip(masks).zip(t_positions) {
+        let x = E::from(context.domain_offset * context.lde_root.exp(position as u128));
 
         // compute C(x) = (P(x) - P(z)) / (x - z)
-        let composition = field::div(field::sub(evaluation, evaluation_at_z), field::sub(x, z));
-        // multiply by pseudo-random coefficient for linear combination
-        result.push(field::mul(composition, cc.constraints));
+        let composition = (evaluation - evaluation_at_z) / (x - z);
+        // multiply by pseudo-random coefficient for linear combination, and add the mask
+        result.push(composition * cc.constraints + mask);
     }
 
     return result;
@@ -1,7 +1,7 @@
-use crate::{ ProofOptions, math::field };
+use crate::{ ProofOptions, ExecutionLimits, assembly, math::field };
 use super::{
-    build_program, OpCode,
-    super::{ execute, verify, ProgramInputs }
+    build_program, execute, OpCode,
+    super::{ execute_segments, verify, verify_segments, ProgramInputs }
 };
 
 #[test]
@@ -104,6 +104,100 @@ fn binacc_operation() {
     assert_eq!(Ok(true), result);
 }
 
+#[test]
+fn rcacc_operation() {
+
+    let a: u128 = field::rand() >> 32;
+
+    // build inputs: 16-bit limbs of the value, least significant limb first
+    let inputs_a: Vec<u128> = (0..6).map(|i| (a >> (16 * i)) & 0xFFFF).collect();
+
+    // build the program
+    let mut instructions = vec![OpCode::Begin];
+    for _ in 0..6 { instructions.push(OpCode::RcAcc); }
+    instructions.push(OpCode::Drop);
+    instructions.push(OpCode::Drop);
+    instructions.push(OpCode::Drop);
+    while instructions.len() < 15 { instructions.push(OpCode::Noop); }
+
+    let program = build_program(instructions, &[]);
+
+    let options = ProofOptions::default();
+    let inputs = ProgramInputs::new(&[0, 0, 1, 0, a], &inputs_a, &[]);
+    let num_outputs = 2;
+
+    let expected_result = vec![a, a];
+
+    // execute the program and make sure results are correct
+    let (outputs, proof) = execute(&program, &inputs, num_outputs, &options);
+    assert_eq!(expected_result, outputs);
+
+    // verify execution proof
+    let result = verify(program.hash(), inputs.get_public_inputs(), &outputs, &proof);
+    assert_eq!(Ok(true), result);
+}
+
+#[test]
+fn range_check_operations() {
+    let p63 = field::exp(2, 63);
+    let p64 = field::exp(2, 64);
+    let p125 = field::exp(2, 125);
+    let cases: [(&str, &[u128], u128); 16] = [
+        ("rc.64",    &[p64 - 1],      1),
+        ("rc.64",    &[p64],          0),
+        ("rc.40",    &[p63],          0),
+        ("lt.64",    &[5, p63],       1),
+        ("lt.64",    &[p63, 5],       0),
+        ("lt.64",    &[7, 7],         0),
+        ("gt.64",    &[p63, 5],       1),
+        ("gt.64",    &[7, 7],         0),
+        ("lt.50",    &[5, 1 << 49],   1),
+        ("lt.50",    &[1 << 49, 5],   0),
+        ("gt.50",    &[1 << 49, 5],   1),
+        ("lt.126",   &[p125, p125 + 1], 1),
+        ("lt.126",   &[p125 + 1, p125], 0),
+        ("gt.126",   &[5, p125],      0),
+        ("isodd.64", &[p63 + 3],      1),
+        ("isodd.40", &[1 << 39],      0),
+    ];
+
+    let options = ProofOptions::default();
+    for &(op, values, expected) in cases.iter() {
+        let program = assembly::compile(&format!("begin {} end", op)).unwrap();
+        let inputs = ProgramInputs::from_public(values);
+
+        let (outputs, proof) = execute(&program, &inputs, 1, &options);
+        assert_eq!(vec![expected], outputs, "{} of {:?}", op, values);
+
+        let result = verify(program.hash(), inputs.get_public_inputs(), &outputs, &proof);
+        assert_eq!(Ok(true), result, "{} of {:?}", op, values);
+    }
+
+    // range checks can be proven in zero-knowledge mode as well
+    let program = assembly::compile("begin lt.64 end").unwrap();
+    let inputs = ProgramInputs::from_public(&[5, p63]);
+    let (outputs, proof) = execute(&program, &inputs, 1, &ProofOptions::default().with_zk());
+    assert_eq!(vec![1], outputs);
+
+    let result = verify(program.hash(), inputs.get_public_inputs(), &outputs, &proof);
+    assert_eq!(Ok(true), result);
+}
+
+#[test]
+fn range_check_segments() {
+    let program = assembly::compile("begin rc.64 swap lt.64 add read while.true dup rc.32 mul read end end").unwrap();
+    let inputs = ProgramInputs::new(&[5, 3, 7], &[1, 1, 0], &[]);
+    let options = ProofOptions::default();
+
+    let (outputs, _) = execute(&program, &inputs, 1, &options);
+    let (segment_outputs, segments) = execute_segments(&program, &inputs, 1, 32, &options, &ExecutionLimits::default(), 1).unwrap();
+    assert_eq!(outputs, segment_outputs);
+    assert!(segments.len() > 1);
+
+    let result = verify_segments(program.hash(), inputs.get_public_inputs(), &outputs, &segments);
+    assert_eq!(Ok(true), result);
+}
+
 // HELPER FUNCTIONS
 // ================================================================================================
 fn build_inputs_for_cmp(a: u128, b: u128, size: usize) -> (Vec<u128>, Vec<u128>) {
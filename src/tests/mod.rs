@@ -1,6 +1,6 @@
-use sp_std::collections::HashMap;
+use hashbrown::HashMap;
 use crate::{
-    ProofOptions, Program, ProgramInputs, OpCode, OpHint,
+    ProofOptions, Program, ProgramInputs, StarkProof, GenOutput, ProgramLibrary, MerkleStore, LamportKey, OpCode, OpHint, ExecutionLimits, ExecutionError, CheckError, ProverConfig, ProverError, FieldExtension, assembly,
     blocks::{ ProgramBlock, Span, Group },
     math::field, utils::hasher
 };
@@ -21,7 +21,66 @@ fn execute_verify() {
     let inputs = ProgramInputs::from_public(&[1, 0]);
     let num_outputs = 1;
 
-    let (outputs, proof) = super::execute(&program, &inputs, num_outputs, &options);
+    let (outputs, proof) = execute(&program, &inputs, num_outputs, &options);
+    assert_eq!(outputs, [3]);
+
+    let result = super::verify(program.hash(), inputs.get_public_inputs(), &outputs, &proof);
+    assert_eq!(Ok(true), result);
+}
+
+#[test]
+fn execute_verify_fri_options() {
+    let program = build_program(vec![
+        OpCode::Begin, OpCode::Swap, OpCode::Dup2, OpCode::Drop,
+        OpCode::Add,   OpCode::Swap, OpCode::Dup2, OpCode::Drop,
+        OpCode::Add,   OpCode::Swap, OpCode::Dup2, OpCode::Drop,
+        OpCode::Add,   OpCode::Noop, OpCode::Noop,
+    ], &[]);
+
+    let inputs = ProgramInputs::from_public(&[1, 0]);
+    let num_outputs = 1;
+
+    for &(folding_factor, max_remainder_size) in [(2, 32), (8, 64), (16, 256)].iter() {
+        let options = ProofOptions::default().with_fri(folding_factor, max_remainder_size);
+        let (outputs, proof) = execute(&program, &inputs, num_outputs, &options);
+        assert_eq!(outputs, [3]);
+
+        let result = super::verify(program.hash(), inputs.get_public_inputs(), &outputs, &proof);
+        assert_eq!(Ok(true), result);
+    }
+}
+
+#[test]
+fn execute_field_extension() {
+    let program = assembly::compile("begin push.3 add end").unwrap();
+    let inputs = ProgramInputs::from_public(&[1]);
+    let config = ProverConfig::default();
+    let limits = ExecutionLimits::default();
+
+    // VM constraints are defined only over the base field
+    for &extension in [FieldExtension::Quadratic, FieldExtension::Cubic].iter() {
+        let options = ProofOptions::default().with_field_extension(extension);
+        let result = super::execute_with_metrics(&program, &inputs, 1, &options, &config, &limits, None);
+        assert_eq!(Some(ProverError::UnsupportedExtension(extension)), result.err());
+    }
+}
+
+#[test]
+fn execute_verify_min_extension_factor() {
+    let program = build_program(vec![
+        OpCode::Begin, OpCode::Swap, OpCode::Dup2, OpCode::Drop,
+        OpCode::Add,   OpCode::Swap, OpCode::Dup2, OpCode::Drop,
+        OpCode::Add,   OpCode::Swap, OpCode::Dup2, OpCode::Drop,
+        OpCode::Add,   OpCode::Noop, OpCode::Noop,
+    ], &[]);
+
+    let default_options = ProofOptions::default();
+    let options = ProofOptions::new(8, default_options.num_queries(),
+        default_options.grinding_factor(), default_options.hash_algorithm());
+    let inputs = ProgramInputs::from_public(&[1, 0]);
+    let num_outputs = 1;
+
+    let (outputs, proof) = execute(&program, &inputs, num_outputs, &options);
     assert_eq!(outputs, [3]);
 
     let result = super::verify(program.hash(), inputs.get_public_inputs(), &outputs, &proof);
@@ -41,7 +100,7 @@ fn execute_verify_fail() {
     let inputs = ProgramInputs::from_public(&[1, 0]);
     let num_outputs = 1;
 
-    let (outputs, proof) = super::execute(&program, &inputs, num_outputs, &options);
+    let (outputs, proof) = execute(&program, &inputs, num_outputs, &options);
     assert_eq!(outputs, [3]);
 
     // wrong inputs
@@ -62,6 +121,255 @@ fn execute_verify_fail() {
     assert_eq!(Err(err_msg), result);
 }
 
+#[test]
+fn execute_verify_batch() {
+    let program = build_program(vec![
+        OpCode::Begin, OpCode::Swap, OpCode::Dup2, OpCode::Drop,
+        OpCode::Add,   OpCode::Swap, OpCode::Dup2, OpCode::Drop,
+        OpCode::Add,   OpCode::Swap, OpCode::Dup2, OpCode::Drop,
+        OpCode::Add,   OpCode::Noop, OpCode::Noop,
+    ], &[]);
+
+    let options = ProofOptions::default();
+    let inputs1 = ProgramInputs::from_public(&[1, 0]);
+    let inputs2 = ProgramInputs::from_public(&[2, 1]);
+    let (outputs1, proof1) = execute(&program, &inputs1, 1, &options);
+    let (outputs2, proof2) = execute(&program, &inputs2, 1, &options);
+    let zk_options = ProofOptions::default().with_zk();
+    let (outputs3, proof3) = execute(&program, &inputs1, 1, &zk_options);
+
+    let batch = vec![
+        (program.hash(), inputs1.get_public_inputs(), &outputs1[..], &proof1),
+        (program.hash(), inputs2.get_public_inputs(), &outputs2[..], &proof2),
+        (program.hash(), inputs1.get_public_inputs(), &outputs3[..], &proof3),
+        (program.hash(), inputs2.get_public_inputs(), &outputs2[..], &proof1),
+    ];
+
+    // the last entry of the batch is invalid because the proof is for different inputs
+    let err_msg = format!("verification of low-degree proof failed: evaluations did not match column value at depth 0");
+    for &num_threads in [1, 2, 4].iter() {
+        let results = super::verify_batch(&batch, num_threads);
+        assert_eq!(vec![Ok(true), Ok(true), Ok(true), Err(err_msg.clone())], results);
+
+        assert_eq!(Ok(true), super::verify_all(&batch[..3], num_threads));
+        assert_eq!(Err(format!("verification of proof 3 failed: {}", err_msg)), super::verify_all(&batch, num_threads));
+    }
+}
+
+#[test]
+fn execute_verify_segments() {
+    let program = assembly::compile("begin mul read while.true dup mul read end end").unwrap();
+    let inputs = ProgramInputs::new(&[5, 3], &[1, 1, 1, 1, 1, 0], &[]);
+    let options = ProofOptions::default();
+
+    let (outputs, _) = execute(&program, &inputs, 1, &options);
+    let (segment_outputs, segments) = super::execute_segments(&program, &inputs, 1, 32, &options, &ExecutionLimits::default(), 1).unwrap();
+    assert_eq!(outputs, segment_outputs);
+    assert_eq!(4, segments.len());
+
+    // segments proved concurrently have the same proofs as segments proved one by one
+    for &num_threads in [2, 3].iter() {
+        let (_, concurrent) = super::execute_segments(&program, &inputs, 1, 32, &options, &ExecutionLimits::default(), num_threads).unwrap();
+        assert_eq!(bincode::serialize(&segments).unwrap(), bincode::serialize(&concurrent).unwrap());
+    }
+
+    let result = super::verify_segments(program.hash(), inputs.get_public_inputs(), &outputs, &segments);
+    assert_eq!(Ok(true), result);
+
+    // segments must be chained in the order of execution
+    let mut reordered = segments.clone();
+    reordered.swap(1, 2);
+    let result = super::verify_segments(program.hash(), inputs.get_public_inputs(), &outputs, &reordered);
+    assert_eq!(Err(String::from("segment 1 does not start in the state in which segment 0 ends")), result);
+
+    // states of a segment are bound to its proof
+    let mut tampered = segments.clone();
+    tampered[1].exit.user_stack[0] = 7;
+    tampered[2].entry.user_stack[0] = 7;
+    assert!(super::verify_segments(program.hash(), inputs.get_public_inputs(), &outputs, &tampered).is_err());
+
+    // the last segment must end with the outputs
+    let result = super::verify_segments(program.hash(), inputs.get_public_inputs(), &[7], &segments);
+    assert_eq!(Err(String::from("the last segment does not end with the specified outputs")), result);
+}
+
+#[test]
+fn execute_verify_hidden_branch() {
+    let program = assembly::compile("begin read if.true push.3 add else read mul push.9 end end").unwrap();
+    let program = hide_false_branch(&program);
+    let options = ProofOptions::default();
+
+    // only the true branch is needed to execute the program and to prove the execution
+    let inputs = ProgramInputs::new(&[5], &[1], &[]);
+    let (outputs, proof) = execute(&program, &inputs, 1, &options);
+    assert_eq!(outputs, [8]);
+
+    let result = super::verify(program.hash(), inputs.get_public_inputs(), &outputs, &proof);
+    assert_eq!(Ok(true), result);
+}
+
+#[test]
+fn execute_hidden_branch() {
+    let program = assembly::compile("begin read if.true push.3 add else read mul push.9 end end").unwrap();
+    let false_branch_hash = match &program.root().body()[1] {
+        ProgramBlock::Switch(block) => block.false_branch_hash(),
+        _ => panic!("expected a Switch block"),
+    };
+
+    let program = hide_false_branch(&program);
+    let inputs = ProgramInputs::new(&[5], &[0, 2], &[]);
+    let result = super::execute_with_limits(&program, &inputs, 1, &ProofOptions::default(), &ExecutionLimits::default());
+    assert_eq!(Err(ExecutionError::HiddenBlock(false_branch_hash)), result);
+}
+
+#[test]
+fn check_execution() {
+    let program = assembly::compile("begin read if.true push.3 add else read mul push.9 end end").unwrap();
+    let inputs = ProgramInputs::new(&[5], &[1], &[]);
+    assert_eq!(Ok(()), super::check_execution(&program, &inputs, &[8]));
+
+    // unexpected outputs fail boundary constraints
+    match super::check_execution(&program, &inputs, &[9]) {
+        Err(CheckError::Constraints(failures)) => assert!(failures.iter().all(|f| f.group == "stack::boundary")),
+        result => panic!("expected constraint failures, but got {:?}", result),
+    }
+
+    // execution errors are returned instead of constraint failures
+    let program = hide_false_branch(&program);
+    let inputs = ProgramInputs::new(&[5], &[0, 2], &[]);
+    match super::check_execution(&program, &inputs, &[19]) {
+        Err(CheckError::Execution(ExecutionError::HiddenBlock(_))) => (),
+        result => panic!("expected execution error, but got {:?}", result),
+    }
+}
+
+#[test]
+fn execute_verify_dyncall() {
+    let mut library = ProgramLibrary::new();
+    let double = library.add(build_callee("drop push.2 mul"));
+    let triple = library.add(build_callee("drop push.3 mul"));
+    let program = assembly::compile("begin read dyncall push.1 add end").unwrap();
+    let options = ProofOptions::default();
+
+    // the callee is selected by the hash read from the secret input tape, and the program
+    // hash is computed with the call site bound to this callee
+    let bound = program.bind_calls(&[triple]);
+    let inputs = ProgramInputs::new(&[5], &[triple], &[]).with_library(library.clone());
+    let (outputs, proof) = execute(&bound, &inputs, 1, &options);
+    assert_eq!(outputs, [16]);
+
+    let result = super::verify(bound.hash(), inputs.get_public_inputs(), &outputs, &proof);
+    assert_eq!(Ok(true), result);
+
+    // the proof is not valid for the unbound program or for a program bound to another callee
+    let result = super::verify(program.hash(), inputs.get_public_inputs(), &outputs, &proof);
+    assert!(result.is_err());
+
+    let other = program.bind_calls(&[double]);
+    let result = super::verify(other.hash(), inputs.get_public_inputs(), &outputs, &proof);
+    assert!(result.is_err());
+
+    // the bound program cannot call a different block, and unbound call sites cannot be executed
+    let inputs = ProgramInputs::new(&[5], &[double], &[]).with_library(library);
+    let result = super::execute_with_limits(&bound, &inputs, 1, &options, &ExecutionLimits::default());
+    assert_eq!(Err(ExecutionError::UnboundCallee(double)), result);
+
+    let result = super::execute_with_limits(&program, &inputs, 1, &options, &ExecutionLimits::default());
+    assert_eq!(Err(ExecutionError::UnboundCallee(double)), result);
+
+    // but the same program bound to a different callee can be executed and verified
+    let (outputs, proof) = execute(&other, &inputs, 1, &options);
+    assert_eq!(outputs, [11]);
+
+    let result = super::verify(other.hash(), inputs.get_public_inputs(), &outputs, &proof);
+    assert_eq!(Ok(true), result);
+}
+
+#[test]
+fn execute_dyncall_unknown_callee() {
+    let mut library = ProgramLibrary::new();
+    let triple = library.add(build_callee("drop push.3 mul"));
+
+    let program = assembly::compile("begin read dyncall push.1 add end").unwrap();
+    let program = program.bind_calls(&[triple + 1]);
+    let inputs = ProgramInputs::new(&[5], &[triple + 1], &[]).with_library(library);
+    let result = super::execute_with_limits(&program, &inputs, 1, &ProofOptions::default(), &ExecutionLimits::default());
+    assert_eq!(Err(ExecutionError::UnknownCallee(triple + 1)), result);
+}
+
+#[test]
+fn execute_verify_mupdate() {
+    let mut store = MerkleStore::from_leaves(5, &[[1, 2], [3, 4], [5, 6]]);
+    let program = assembly::compile("begin mupdate.5 end").unwrap();
+    let options = ProofOptions::default();
+
+    // replace leaf 1 with [7, 8] and prove both the old and the new root
+    let old_root = store.root();
+    let inputs = ProgramInputs::new(&[4, 3, 8, 7, 1], &[], &[]).with_pmpath(&store, 1);
+    store.update(1, [7, 8]);
+    let new_root = store.root();
+
+    let (outputs, proof) = execute(&program, &inputs, 4, &options);
+    assert_eq!(outputs, [old_root[1], old_root[0], new_root[1], new_root[0]]);
+
+    let result = super::verify(program.hash(), inputs.get_public_inputs(), &outputs, &proof);
+    assert_eq!(Ok(true), result);
+}
+
+#[test]
+fn execute_verify_hashtape() {
+    let options = ProofOptions::default();
+    let values: Vec<u128> = (1..10).collect();
+
+    // hashing the same values with hashtape.n and hashtape.many results in the same digest
+    for n in [1, 3, 4, 5, 8, 9].iter() {
+        let mut expected_hash = hasher::digest_many(&values[..*n]);
+        expected_hash.reverse();
+
+        let program = assembly::compile(&format!("begin hashtape.{} end", n)).unwrap();
+        let inputs = ProgramInputs::new(&[], &values[..*n], &[]);
+        let (outputs, proof) = execute(&program, &inputs, 2, &options);
+        assert_eq!(expected_hash, outputs);
+
+        let result = super::verify(program.hash(), inputs.get_public_inputs(), &outputs, &proof);
+        assert_eq!(Ok(true), result);
+
+        let program = assembly::compile("begin hashtape.many end").unwrap();
+        let inputs = ProgramInputs::none().with_hashtape(&values[..*n]);
+        let (outputs, proof) = execute(&program, &inputs, 2, &options);
+        assert_eq!(expected_hash, outputs);
+
+        let result = super::verify(program.hash(), inputs.get_public_inputs(), &outputs, &proof);
+        assert_eq!(Ok(true), result);
+    }
+
+    // hashtape.many can also hash an empty sequence of values
+    let mut expected_hash = hasher::digest_many(&[]);
+    expected_hash.reverse();
+
+    let program = assembly::compile("begin hashtape.many end").unwrap();
+    let inputs = ProgramInputs::none().with_hashtape(&[]);
+    let (outputs, _) = execute(&program, &inputs, 2, &options);
+    assert_eq!(expected_hash, outputs);
+}
+
+#[test]
+fn execute_verify_lamport() {
+    let key = LamportKey::new([11, 13], 32);
+    let pk = key.public_key_hash();
+    let signature = key.sign(0xCAFEBABE);
+
+    let program = assembly::compile("begin lamport.32 end").unwrap();
+    let inputs = ProgramInputs::from_public(&[0xCAFEBABE, pk[1], pk[0], 7]).with_lamport(&signature);
+    let options = ProofOptions::default();
+
+    let (outputs, proof) = execute(&program, &inputs, 1, &options);
+    assert_eq!(outputs, [7]);
+
+    let result = super::verify(program.hash(), inputs.get_public_inputs(), &outputs, &proof);
+    assert_eq!(Ok(true), result);
+}
+
 #[test]
 fn stack_manipulation() {
     let program = build_program(vec![
@@ -79,7 +387,7 @@ fn stack_manipulation() {
     let inputs = ProgramInputs::from_public(&[7, 6, 5, 4, 3, 2, 1, 0]);
     let num_outputs = 8;
 
-    let (outputs, proof) = super::execute(&program, &inputs, num_outputs, &options);
+    let (outputs, proof) = execute(&program, &inputs, num_outputs, &options);
     assert_eq!(outputs, [46, 19, 4, 11, 0, 11, 0, 6]);
 
     let result = super::verify(program.hash(), inputs.get_public_inputs(), &outputs, &proof);
@@ -100,7 +408,7 @@ fn conditional_operations() {
     let inputs = ProgramInputs::from_public(&[3, 4, 1, 5, 0, 6, 7, 8]);
     let num_outputs = 8;
 
-    let (outputs, proof) = super::execute(&program, &inputs, num_outputs, &options);
+    let (outputs, proof) = execute(&program, &inputs, num_outputs, &options);
     assert_eq!(outputs, [5, 6, 7, 8, 0, 0, 0, 0]);
 
     let result = super::verify(program.hash(), inputs.get_public_inputs(), &outputs, &proof);
@@ -122,7 +430,7 @@ fn conditional_operations() {
     let inputs = ProgramInputs::from_public(&[5, 6, 1, 0, 7, 8, 0, 0]);
     let num_outputs = 8;
 
-    let (outputs, proof) = super::execute(&program, &inputs, num_outputs, &options);
+    let (outputs, proof) = execute(&program, &inputs, num_outputs, &options);
     assert_eq!(outputs, [7, 8, 0, 0, 0, 0, 0, 0]);
 
     let result = super::verify(program.hash(), inputs.get_public_inputs(), &outputs, &proof);
@@ -140,7 +448,7 @@ fn conditional_operations() {
     let inputs = ProgramInputs::from_public(&[3, 4, 1, 2, 1, 0, 5, 6]);
     let num_outputs = 8;
 
-    let (outputs, proof) = super::execute(&program, &inputs, num_outputs, &options);
+    let (outputs, proof) = execute(&program, &inputs, num_outputs, &options);
     assert_eq!(outputs, [3, 4, 5, 6, 1, 2, 0, 0]);
 
     let result = super::verify(program.hash(), inputs.get_public_inputs(), &outputs, &proof);
@@ -161,7 +469,7 @@ fn selection_operations_panic() {
     let inputs = ProgramInputs::from_public(&[3, 4, 2, 5, 0, 6, 7, 8]);
     let num_outputs = 8;
 
-    super::execute(&program, &inputs, num_outputs, &options);
+    execute(&program, &inputs, num_outputs, &options);
 }
 
 #[test]
@@ -179,7 +487,7 @@ fn math_operations() {
 
     let expected_result = vec![field::ONE, field::neg(field::inv(65))];
 
-    let (outputs, proof) = super::execute(&program, &inputs, num_outputs, &options);
+    let (outputs, proof) = execute(&program, &inputs, num_outputs, &options);
     assert_eq!(expected_result, outputs);
 
     let result = super::verify(program.hash(), inputs.get_public_inputs(), &outputs, &proof);
@@ -201,7 +509,7 @@ fn bool_operations() {
 
     let expected_result = vec![field::ONE];
 
-    let (outputs, proof) = super::execute(&program, &inputs, num_outputs, &options);
+    let (outputs, proof) = execute(&program, &inputs, num_outputs, &options);
     assert_eq!(expected_result, outputs);
 
     let result = super::verify(program.hash(), inputs.get_public_inputs(), &outputs, &proof);
@@ -230,7 +538,7 @@ fn hash_operations() {
     let inputs = ProgramInputs::from_public(&[0, 0, 4, 3, 2, 1]);
     let num_outputs = 2;
 
-    let (outputs, proof) = super::execute(&program, &inputs, num_outputs, &options);
+    let (outputs, proof) = execute(&program, &inputs, num_outputs, &options);
     assert_eq!(expected_hash, outputs);
 
     let result = super::verify(program.hash(), inputs.get_public_inputs(), &outputs, &proof);
@@ -261,7 +569,7 @@ fn hash_operations() {
     let inputs = ProgramInputs::from_public(&[0, 0, 4, 3, 2, 1]);
     let num_outputs = 2;
 
-    let (outputs, proof) = super::execute(&program, &inputs, num_outputs, &options);
+    let (outputs, proof) = execute(&program, &inputs, num_outputs, &options);
     assert_eq!(expected_hash, outputs);
 
     let result = super::verify(program.hash(), inputs.get_public_inputs(), &outputs, &proof);
@@ -281,7 +589,7 @@ fn read_operations() {
     let inputs = ProgramInputs::new(&[1], &[2, 3], &[4]);
     let num_outputs = 5;
 
-    let (outputs, proof) = super::execute(&program, &inputs, num_outputs, &options);
+    let (outputs, proof) = execute(&program, &inputs, num_outputs, &options);
     assert_eq!(vec![5, 4, 3, 2, 1], outputs);
 
     let result = super::verify(program.hash(), inputs.get_public_inputs(), &outputs, &proof);
@@ -303,7 +611,7 @@ fn assert_operations() {
 
     let expected_result = vec![0, 0];
 
-    let (outputs, proof) = super::execute(&program, &inputs, num_outputs, &options);
+    let (outputs, proof) = execute(&program, &inputs, num_outputs, &options);
     assert_eq!(expected_result, outputs);
 
     let result = super::verify(program.hash(), inputs.get_public_inputs(), &outputs, &proof);
@@ -314,6 +622,34 @@ fn assert_operations() {
 
 // HELPER FUNCTIONS
 // ================================================================================================
+/// Compiles the `source` into a nested block which can be added to a program library.
+fn build_callee(source: &str) -> Group {
+    let program = assembly::compile(&format!("begin block {} end end", source)).unwrap();
+    return match &program.root().body()[1] {
+        ProgramBlock::Group(block) => block.clone(),
+        _ => unreachable!(),
+    };
+}
+
+/// Replaces false branches of all top-level Switch blocks of the `program` with proxies.
+fn hide_false_branch(program: &Program) -> Program {
+    let body = program.root().body().iter().map(|block| match block {
+        ProgramBlock::Switch(block) => ProgramBlock::Switch(block.hide_false_branch()),
+        _ => block.clone(),
+    }).collect();
+
+    let hidden = Program::new(Group::new(body));
+    assert_eq!(program.hash(), hidden.hash());
+    return hidden;
+}
+
+/// Executes the program and returns its outputs together with the deserialized proof.
+fn execute(program: &Program, inputs: &ProgramInputs, num_outputs: usize, options: &ProofOptions) -> (Vec<u128>, StarkProof) {
+    let output: GenOutput = serde_json::from_str(&super::execute(program, inputs, num_outputs, options)).unwrap();
+    let proof_bytes: Vec<u8> = serde_json::from_str(&output.stark_proof).unwrap();
+    return (output.stark_output, bincode::deserialize(&proof_bytes).unwrap());
+}
+
 fn build_program(instructions: Vec<OpCode>, push_values: &[u128]) -> Program {
 
     // build hint map for PUSh operations
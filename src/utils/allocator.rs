@@ -0,0 +1,83 @@
+use std::alloc::{ GlobalAlloc, Layout, System };
+use std::sync::atomic::{ AtomicBool, AtomicUsize, Ordering };
+
+// GLOBAL STATE
+// ================================================================================================
+static INSTALLED: AtomicBool = AtomicBool::new(false);
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+// TRACKING ALLOCATOR
+// ================================================================================================
+
+/// A wrapper around the system allocator which keeps track of the current and the peak number
+/// of allocated bytes. To enable allocation metrics in the prover, install it in the binary:
+///
+/// ```ignore
+/// #[global_allocator]
+/// static ALLOCATOR: distaff::utils::allocator::TrackingAllocator = distaff::utils::allocator::TrackingAllocator;
+/// ```
+pub struct TrackingAllocator;
+
+unsafe impl GlobalAlloc for TrackingAllocator {
+
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            record_alloc(layout.size());
+        }
+        return ptr;
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        CURRENT.fetch_sub(layout.size(), Ordering::Relaxed);
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            record_alloc(layout.size());
+        }
+        return ptr;
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = System.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            CURRENT.fetch_sub(layout.size(), Ordering::Relaxed);
+            record_alloc(new_size);
+        }
+        return new_ptr;
+    }
+}
+
+// PUBLIC FUNCTIONS
+// ================================================================================================
+
+/// Returns the number of bytes currently allocated, or None if `TrackingAllocator` is not
+/// installed as the global allocator.
+pub fn current_allocation() -> Option<usize> {
+    if !INSTALLED.load(Ordering::Relaxed) { return None; }
+    return Some(CURRENT.load(Ordering::Relaxed));
+}
+
+/// Returns the largest number of bytes allocated at the same time since the last call to
+/// `reset_peak_allocation()`, or None if `TrackingAllocator` is not installed.
+pub fn peak_allocation() -> Option<usize> {
+    if !INSTALLED.load(Ordering::Relaxed) { return None; }
+    return Some(PEAK.load(Ordering::Relaxed));
+}
+
+/// Resets the peak allocation to the number of bytes currently allocated.
+pub fn reset_peak_allocation() {
+    PEAK.store(CURRENT.load(Ordering::Relaxed), Ordering::Relaxed);
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+fn record_alloc(size: usize) {
+    INSTALLED.store(true, Ordering::Relaxed);
+    let current = CURRENT.fetch_add(size, Ordering::Relaxed) + size;
+    PEAK.fetch_max(current, Ordering::Relaxed);
+}
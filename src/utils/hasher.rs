@@ -16,16 +16,41 @@ pub fn digest(values: &[u128]) -> Vec<u128> {
 
     let mut state = [field::ZERO; STATE_WIDTH];
     state[..values.len()].copy_from_slice(values);
-    state.reverse();
+    apply_permutation(&mut state);
+    return state[..DIGEST_SIZE].to_vec();
+}
 
-    for i in 0..NUM_ROUNDS {
-        apply_round(&mut state, i);
+/// Computes a digest of any number of values in the same way as `hashtape.n` and `hashtape.many`
+/// instructions. The values are padded with 1 followed by 0's to a multiple of 4, and every chunk
+/// of 4 values is hashed together with the digest of all preceding chunks (starting with [0, 0]).
+/// The result is hashed once more with 1 in the last element of the state; `digest()` always
+/// leaves this element set to 0, and thus, the two functions never hash the same state.
+pub fn digest_many(values: &[u128]) -> Vec<u128> {
+    let mut values = values.to_vec();
+    values.push(field::ONE);
+    values.resize((values.len() + STATE_RATE - 1) / STATE_RATE * STATE_RATE, field::ZERO);
+
+    let mut state = [field::ZERO; STATE_WIDTH];
+    for chunk in values.chunks(STATE_RATE) {
+        state[DIGEST_SIZE..].copy_from_slice(chunk);
+        apply_permutation(&mut state);
     }
 
-    state.reverse();
+    state[DIGEST_SIZE..].copy_from_slice(&[field::ZERO, field::ZERO, field::ZERO, field::ONE]);
+    apply_permutation(&mut state);
     return state[..DIGEST_SIZE].to_vec();
 }
 
+/// Applies all rounds of Rescue permutation to the `state`; the state is reversed for the
+/// duration of the permutation to match the order in which it is kept on the stack of the VM.
+fn apply_permutation(state: &mut [u128; STATE_WIDTH]) {
+    state.reverse();
+    for i in 0..NUM_ROUNDS {
+        apply_round(state, i);
+    }
+    state.reverse();
+}
+
 pub fn apply_round(state: &mut [u128], step: usize) {
     
     let ark_idx = step % CYCLE_LENGTH;
@@ -4,6 +4,7 @@ use sp_std::{ mem, slice, ops::Range, vec::Vec };
 // ================================================================================================
 pub mod hasher;
 pub mod sponge;
+pub mod allocator;
 use sp_std::vec;
 // use wasm_bindgen_test::console_log;
 
@@ -23,9 +24,9 @@ pub fn filled_vector<T: Copy>(length: usize, capacity: usize, value: T) -> Vec<T
 }
 
 #[cfg(test)]
-pub fn remove_leading_zeros(values: &[u128]) -> Vec<u128> {
+pub fn remove_leading_zeros<E: crate::math::FieldElement>(values: &[E]) -> Vec<E> {
     for i in (0..values.len()).rev() {
-        if values[i] != 0 {
+        if values[i] != E::ZERO {
             return values[0..(i + 1)].to_vec();
         }
     }